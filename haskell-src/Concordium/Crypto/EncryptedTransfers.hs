@@ -43,6 +43,7 @@ module Concordium.Crypto.EncryptedTransfers (
 
 import Data.Serialize
 import Data.Word
+import Data.Int
 import Data.Aeson
 import Foreign.Ptr
 import Data.ByteString.Short
@@ -329,7 +330,7 @@ foreign import ccall safe "verify_encrypted_transfer"
      -> EncryptedAmountAggIndex -- ^ Index up to which amounts have been aggregated
      -> Ptr CChar -- ^ Pointer to the proof
      -> Word64 -- ^ Length of the proof
-     -> IO Word8 -- ^ Return either 0 if proof checking failed, or non-zero in case of success.
+     -> IO Int32 -- ^ Return 1 in case of success, or a negative value identifying why proof checking failed.
 
 verifyEncryptedTransferProof ::
   -- |Global context with parameters
@@ -364,7 +365,7 @@ verifyEncryptedTransferProof gc receiverPK senderPK initialAmount transferData =
                        idx
                        proof_ptr
                        proof_len
-               return (res /= 0)
+               return (res == 1)
   where AccountEncryptionKey receiverPK' = receiverPK
         AccountEncryptionKey senderPK' = senderPK
 
@@ -479,7 +480,7 @@ foreign import ccall safe "verify_sec_to_pub_transfer"
      -> EncryptedAmountAggIndex -- ^ Index up to which amounts have been aggregated
      -> Ptr CChar -- ^ Pointer to the proof
      -> Word64
-     -> IO Word8 -- ^ Return either 0 if proof checking failed, or non-zero in case of success.
+     -> IO Int32 -- ^ Return 1 in case of success, or a negative value identifying why proof checking failed.
 
 verifySecretToPublicTransferProof ::
   -- |Global context with parameters
@@ -509,7 +510,7 @@ verifySecretToPublicTransferProof gc senderPK initialAmount transferData = unsaf
                      idx
                      proof_ptr
                      proof_len
-             return (res /= 0)
+             return (res == 1)
   where AccountEncryptionKey senderPK' = senderPK
 
 -- |Decrypt an encrypted amount.
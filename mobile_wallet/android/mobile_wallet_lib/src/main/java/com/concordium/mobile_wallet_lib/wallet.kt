@@ -3,6 +3,7 @@ package com.concordium.mobile_wallet_lib
 external fun create_id_request_and_private_data(input: String) : ReturnValue
 external fun create_credential(input: String) : ReturnValue
 external fun generate_accounts(input: String) : ReturnValue
+external fun derive_keys_from_seed_phrase(input: String) : ReturnValue
 external fun create_transfer(input: String) : ReturnValue
 external fun create_encrypted_transfer(input: String) : ReturnValue
 external fun create_pub_to_sec_transfer(input: String) : ReturnValue
@@ -35,6 +35,13 @@ char *combine_encrypted_amounts(const char *input_ptr_1, const char *input_ptr_2
  */
 char *generate_accounts(const char *input_ptr, uint8_t *success);
 
+/**
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *derive_keys_from_seed_phrase(const char *input_ptr, uint8_t *success);
+
 /**
  * # Safety
  * The input pointer must point to a null-terminated buffer, otherwise this
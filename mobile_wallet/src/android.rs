@@ -4,7 +4,7 @@
 use crate::{
     check_account_address, combine_encrypted_amounts, create_credential, create_encrypted_transfer,
     create_id_request_and_private_data, create_pub_to_sec_transfer, create_sec_to_pub_transfer,
-    create_transfer, decrypt_encrypted_amount, generate_accounts,
+    create_transfer, decrypt_encrypted_amount, derive_keys_from_seed_phrase, generate_accounts,
 };
 use jni::{
     objects::{JClass, JString, JValue},
@@ -140,6 +140,48 @@ pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_generate
     }
 }
 
+#[no_mangle]
+/// The JNI wrapper for the `derive_keys_from_seed_phrase` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_derive_1keys_1from_1seed_1phrase(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                &env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = derive_keys_from_seed_phrase(input_str.as_ptr(), &mut success);
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    match cstr_res.to_str() {
+        Ok(str_ref) => wrap_return_tuple(&env, success, str_ref),
+        Err(e) => wrap_return_tuple(
+            &env,
+            127,
+            &format!("Could not read CString from crypto library {:?}", e),
+        ),
+    }
+}
+
 #[no_mangle]
 /// The JNI wrapper for the `create_transfer` method.
 /// The `input` parameter must be a properly initalized `java.lang.String` that
@@ -11,6 +11,7 @@ use ed25519_dalek::Signer;
 use either::Either::{Left, Right};
 use encrypted_transfers::encrypt_amount_with_fixed_randomness;
 use id::{account_holder, constants::AttributeKind, secret_sharing::Threshold, types::*};
+use key_derivation::ConcordiumHdWallet;
 use pairing::bls12_381::{Bls12, G1};
 use rand::thread_rng;
 use serde_json::{from_str, from_value, to_string, Value};
@@ -407,11 +408,18 @@ fn create_credential_aux(input: &str) -> anyhow::Result<String> {
     let new_or_existing = Left(expiry);
 
     // The mobile wallet can only create new accounts, which means new credential
-    // data will be generated.
+    // data will be generated. If the wallet has a seed phrase the signing key is
+    // derived from it, otherwise it is random.
     let cred_data = {
         let mut keys = std::collections::BTreeMap::new();
-        let mut csprng = thread_rng();
-        keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
+        let key = match v.get("seedPhrase") {
+            Some(_) => {
+                let (wallet, ip_index, identity_index) = get_wallet(&v)?;
+                wallet.get_account_signing_key(ip_index, identity_index, acc_num)?
+            }
+            None => KeyPair::generate(&mut thread_rng()),
+        };
+        keys.insert(KeyIndex(0), key);
 
         CredentialData {
             keys,
@@ -434,6 +442,7 @@ fn create_credential_aux(input: &str) -> anyhow::Result<String> {
         valid_to: id_object.alist.valid_to,
         created_at: id_object.alist.created_at,
         policy_vec,
+        statements: Vec::new(),
         _phantom: Default::default(),
     };
 
@@ -514,6 +523,50 @@ fn generate_accounts_aux(input: &str) -> anyhow::Result<String> {
     Ok(to_string(&response)?)
 }
 
+/// Read the wallet from the fields `seedPhrase` and, optionally, `passphrase`
+/// of the input, together with the indices `identityProviderIndex` and
+/// `identityIndex` of the identity.
+fn get_wallet(v: &Value) -> anyhow::Result<(ConcordiumHdWallet, IpIdentity, u32)> {
+    let seed_phrase: String = try_get(v, "seedPhrase")?;
+    let passphrase: String = try_get(v, "passphrase").unwrap_or_default();
+    let ip_index = try_get(v, "identityProviderIndex")?;
+    let identity_index = try_get(v, "identityIndex")?;
+    Ok((
+        ConcordiumHdWallet::from_mnemonic(&seed_phrase, &passphrase),
+        ip_index,
+        identity_index,
+    ))
+}
+
+/// Derive the secrets of an identity and the keys of one of its accounts from
+/// the seed phrase of the wallet.
+fn derive_keys_from_seed_phrase_aux(input: &str) -> anyhow::Result<String> {
+    let v: Value = from_str(input)?;
+    let (wallet, ip_index, identity_index) = get_wallet(&v)?;
+    let acc_num: u8 = try_get(&v, "accountNumber")?;
+    let tags: Vec<AttributeTag> = try_get(&v, "revealedAttributes").unwrap_or_default();
+
+    let mut keys = std::collections::BTreeMap::new();
+    keys.insert(
+        KeyIndex(0),
+        wallet.get_account_signing_key(ip_index, identity_index, acc_num)?,
+    );
+    let cred_data = CredentialData {
+        keys,
+        threshold: SignatureThreshold(1),
+    };
+
+    let randomness = wallet.get_commitments_randomness(ip_index, identity_index, acc_num, tags)?;
+
+    let response = json!({
+        "accountKeys": AccountKeys::from(cred_data),
+        "idCredSec": wallet.get_id_cred_sec(ip_index, identity_index)?,
+        "prfKey": wallet.get_prf_key(ip_index, identity_index)?,
+        "commitmentsRandomness": randomness,
+    });
+    Ok(to_string(&response)?)
+}
+
 /// Embed the precomputed table for decryption.
 /// It is unfortunate that this is pure bytes, but not enough of data is marked
 /// as const, and in any case a HashMap relies on an allocator, so will never be
@@ -734,6 +787,20 @@ make_wrapper!(
     /// function will fail in unspecified ways.
     => generate_accounts -> generate_accounts_aux);
 
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => derive_keys_from_seed_phrase -> derive_keys_from_seed_phrase_aux);
+
 /// Take pointers to a NUL-terminated UTF8-string and return a u64.
 ///
 /// In case of failure to decode the input the function will
@@ -1,16 +1,14 @@
 use clap::AppSettings;
 use client_server_helpers::*;
-use crypto_common::*;
+use crypto_common::{keystore::EncryptedKeyFile, *};
 use crypto_common_derive::*;
-use curve_arithmetic::{Curve, Value};
+use curve_arithmetic::Curve;
 use dodis_yampolskiy_prf as prf;
-use elgamal::{decrypt_from_chunks_given_generator, Message};
+use elgamal::BabyStepGiantStep;
 use id::{anonymity_revoker::*, constants::ArCurve, types::*};
+use rand::thread_rng;
 use serde_json::json;
-use std::{
-    convert::TryFrom,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -53,14 +51,21 @@ struct CombinePrf {
         long = "ar-record",
         help = "File with the JSON encoded anonymity revocation record."
     )]
-    ar_record: PathBuf,
+    ar_record:          PathBuf,
     #[structopt(
         long = "shares",
         help = "Files with the JSON encoded decrypted shares."
     )]
-    shares:    Vec<PathBuf>,
+    shares:             Vec<PathBuf>,
+    #[structopt(
+        long = "ars",
+        help = "File with a list of anonymity revokers, used to check the decrypted shares."
+    )]
+    anonymity_revokers: PathBuf,
+    #[structopt(long = "global-context", help = "File with global context.")]
+    global_context:     PathBuf,
     #[structopt(long = "out", help = "File to output the decryption to.")]
-    out:       PathBuf,
+    out:                PathBuf,
 }
 
 #[derive(StructOpt)]
@@ -69,14 +74,19 @@ struct Combine {
         long = "credential",
         help = "File with the JSON encoded credential or credential values."
     )]
-    credential: PathBuf,
+    credential:         PathBuf,
     #[structopt(
         long = "shares",
         help = "Files with the JSON encoded decrypted shares."
     )]
-    shares:     Vec<PathBuf>,
+    shares:             Vec<PathBuf>,
+    #[structopt(
+        long = "ars",
+        help = "File with a list of anonymity revokers, used to check the decrypted shares."
+    )]
+    anonymity_revokers: PathBuf,
     #[structopt(long = "out", help = "File to output the decryption to.")]
-    out:        PathBuf,
+    out:                PathBuf,
 }
 
 #[derive(StructOpt)]
@@ -253,16 +263,17 @@ fn handle_compute_regids(rid: ComputeRegIds) -> Result<(), String> {
     Ok(())
 }
 
-// Try to read ArData, either from encrypted or a plaintext file.
+// Try to read ArData, either from an encrypted (keystore or legacy) or a
+// plaintext file.
 fn decrypt_ar_data(fname: &Path) -> Result<ArData<ArCurve>, String> {
     let data = succeed_or_die!(std::fs::read(fname), e => "Could not read anonymity revoker secret keys due to {}");
     match serde_json::from_slice(&data) {
         Ok(v) => Ok(v),
         Err(_) => {
             // try to decrypt
-            let parsed = succeed_or_die!(serde_json::from_slice(&data), e => "Could not parse encrypted file {}");
+            let parsed: EncryptedKeyFile = succeed_or_die!(serde_json::from_slice(&data), e => "Could not parse encrypted file {}");
             let pass = succeed_or_die!(rpassword::read_password_from_tty(Some("Enter password to decrypt AR credentials: ")), e => "Could not read password {}.");
-            let decrypted = succeed_or_die!(crypto_common::encryption::decrypt(&pass.into(), &parsed), e =>  "Could not decrypt AR credentials. Most likely the password you provided is incorrect {}.");
+            let decrypted = succeed_or_die!(parsed.decrypt(&pass.into()), e =>  "Could not decrypt AR credentials. Most likely the password you provided is incorrect {}.");
             serde_json::from_slice(&decrypted).map_err(|_| {
                 "Could not decrypt AR credentials. Most likely the password you provided is \
                  incorrect."
@@ -293,13 +304,12 @@ fn handle_decrypt_id(dcr: Decrypt) -> Result<(), String> {
         ar_data.get(&ar.public_ar_info.ar_identity),
         "Supplied AR is not part of the credential."
     );
-    let m = ar
-        .ar_secret_key
-        .decrypt(&single_ar_data.enc_id_cred_pub_share);
-    let share = ChainArDecryptedData {
-        ar_identity:       ar.public_ar_info.ar_identity,
-        id_cred_pub_share: m,
-    };
+    let share = decrypt_id_cred_pub_share(
+        ar.public_ar_info.ar_identity,
+        &ar.ar_secret_key,
+        single_ar_data,
+        &mut thread_rng(),
+    );
     match write_json_to_file(&dcr.out, &share) {
         Ok(_) => println!("Wrote decryption to {}", dcr.out.display()),
         Err(e) => {
@@ -331,17 +341,15 @@ fn handle_decrypt_prf(dcr: DecryptPrf) -> Result<(), String> {
         ar_data.get(&ar.public_ar_info.ar_identity),
         "Given AR is not part of the credential."
     );
-    let m = decrypt_from_chunks_given_generator(
+    let table = BabyStepGiantStep::new(global_context.encryption_in_exponent_generator(), 1 << 16);
+    let share = decrypt_prf_key_share(
+        &global_context,
+        ar.public_ar_info.ar_identity,
         &ar.ar_secret_key,
-        &single_ar_data.enc_prf_key_share,
-        &global_context.encryption_in_exponent_generator(),
-        1 << 16,
-        CHUNK_SIZE,
+        single_ar_data,
+        &table,
+        &mut thread_rng(),
     );
-    let share = IpArDecryptedData {
-        ar_identity:   ar.public_ar_info.ar_identity,
-        prf_key_share: m,
-    };
     match write_json_to_file(&dcr.out, &share) {
         Ok(_) => println!("Wrote decryption to {}.", dcr.out.display()),
         Err(e) => {
@@ -363,26 +371,10 @@ fn handle_combine_id(cmb: Combine) -> Result<(), String> {
         }
         AccountCredentialValues::Normal { cdi } => cdi,
     };
-    let revocation_threshold = credential.threshold;
-
-    let shares_values: Vec<_> = cmb.shares;
-
-    let number_of_ars = shares_values.len();
-    let number_of_ars =
-        u8::try_from(number_of_ars).expect("Number of anonymity revokers should not exceed 2^8-1");
-    if number_of_ars < revocation_threshold.into() {
-        return Err(format!(
-            "Insufficient number of anonymity revokers ({}). Threshold is {}.",
-            number_of_ars, revocation_threshold
-        ));
-    }
-
-    let mut ar_decrypted_data_vec: Vec<ChainArDecryptedData<ExampleCurve>> =
-        Vec::with_capacity(shares_values.len());
-    let mut shares: Vec<(ArIdentity, Message<ExampleCurve>)> =
-        Vec::with_capacity(shares_values.len());
+    let ars = succeed_or_die!(read_anonymity_revokers(cmb.anonymity_revokers), e => "Could not read anonymity revokers due to {}");
 
-    for share_value in shares_values.iter() {
+    let mut shares: Vec<ChainArDecryptedData<ExampleCurve>> = Vec::with_capacity(cmb.shares.len());
+    for share_value in cmb.shares.iter() {
         let decrypted = read_json_from_file(&share_value).map_err(|e| {
             format!(
                 "Could not read from ar file {}, error: {}",
@@ -390,27 +382,17 @@ fn handle_combine_id(cmb: Combine) -> Result<(), String> {
                 e
             )
         })?;
-        ar_decrypted_data_vec.push(decrypted);
-    }
-
-    let mut ar_identities = Vec::with_capacity(ar_decrypted_data_vec.len());
-
-    for ar_decrypted_data in ar_decrypted_data_vec {
-        let ar_id = ar_decrypted_data.ar_identity;
-        ar_identities.push(ar_id);
-        shares.push((ar_id, ar_decrypted_data.id_cred_pub_share));
-    }
-    ar_identities.sort();
-    ar_identities.dedup();
-    if ar_identities.len() < shares.len() {
-        return Err(
-            "No duplicates among the anonymity revokers identities nor share numbers are allowed."
-                .to_owned(),
-        );
+        shares.push(decrypted);
     }
 
-    let id_cred_pub = reveal_id_cred_pub(&shares);
-    let id_cred_pub_string = base16_encode_string(&id_cred_pub);
+    let revealed = succeed_or_die!(reveal_id_cred_pub_verified(
+        &ars.anonymity_revokers,
+        &credential.ar_data,
+        credential.threshold,
+        &shares,
+    ), e => "Could not reveal idCredPub: {}");
+    report_invalid_shares(&revealed.invalid);
+    let id_cred_pub_string = base16_encode_string(&revealed.value);
 
     let json = json!({ "idCredPub": id_cred_pub_string });
     match write_json_to_file(&cmb.out, &json) {
@@ -428,27 +410,17 @@ fn handle_combine_prf(cmb: CombinePrf) -> Result<(), String> {
     if ar_record.version != VERSION_0 {
         return Err("The version of the ArRecord should be 0.".to_owned());
     }
+    let ar_record = ar_record.value;
 
-    let revocation_threshold = ar_record.value.threshold;
-
-    let shares_values: Vec<_> = cmb.shares;
-
-    let number_of_ars = shares_values.len();
-    let number_of_ars =
-        u8::try_from(number_of_ars).expect("Number of anonymity revokers should not exceed 2^8-1");
-    if number_of_ars < revocation_threshold.into() {
-        return Err(format!(
-            "Insufficient number of anonymity revokers ({}). Threshold is {}.",
-            number_of_ars, revocation_threshold
-        ));
+    let global_context: Versioned<GlobalContext<ExampleCurve>> = succeed_or_die!(read_json_from_file(cmb.global_context), e => "Could not read global context due to {}");
+    if global_context.version != VERSION_0 {
+        return Err("The version of the GlobalContext should be 0.".to_owned());
     }
+    let global_context = global_context.value;
+    let ars = succeed_or_die!(read_anonymity_revokers(cmb.anonymity_revokers), e => "Could not read anonymity revokers due to {}");
 
-    let mut ar_decrypted_data_vec: Vec<IpArDecryptedData<ExampleCurve>> =
-        Vec::with_capacity(shares_values.len());
-    let mut shares: Vec<(ArIdentity, Value<ExampleCurve>)> =
-        Vec::with_capacity(shares_values.len());
-
-    for share_value in shares_values.iter() {
+    let mut shares: Vec<IpArDecryptedData<ExampleCurve>> = Vec::with_capacity(cmb.shares.len());
+    for share_value in cmb.shares.iter() {
         match read_json_from_file(&share_value) {
             Err(y) => {
                 return Err(format!(
@@ -457,28 +429,19 @@ fn handle_combine_prf(cmb: CombinePrf) -> Result<(), String> {
                     y
                 ));
             }
-            Ok(val) => ar_decrypted_data_vec.push(val),
+            Ok(val) => shares.push(val),
         }
     }
 
-    let mut ar_identities = Vec::with_capacity(ar_decrypted_data_vec.len());
-
-    for ar_decrypted_data in ar_decrypted_data_vec {
-        let ar_id = ar_decrypted_data.ar_identity;
-        ar_identities.push(ar_id);
-        shares.push((ar_id, ar_decrypted_data.prf_key_share));
-    }
-    ar_identities.sort();
-    ar_identities.dedup();
-    if ar_identities.len() < shares.len() {
-        return Err(
-            "No duplicates among the anonymity revokers identities nor share numbers are allowed."
-                .to_owned(),
-        );
-    }
-
-    let prf_key = reveal_prf_key(&shares);
-    let prf_key_string = base16_encode_string(&prf_key);
+    let revealed = succeed_or_die!(reveal_prf_key_verified(
+        &global_context,
+        &ars.anonymity_revokers,
+        &ar_record.ar_data,
+        ar_record.threshold,
+        &shares,
+    ), e => "Could not reveal the PRF key: {}");
+    report_invalid_shares(&revealed.invalid);
+    let prf_key_string = base16_encode_string(&revealed.value);
     let json = json!({ "prfKey": prf_key_string });
     match write_json_to_file(&cmb.out, &json) {
         Ok(_) => println!("Wrote PRF key to {}.", cmb.out.display()),
@@ -488,3 +451,15 @@ fn handle_combine_prf(cmb: CombinePrf) -> Result<(), String> {
     }
     Ok(())
 }
+
+/// Warn about anonymity revokers whose shares were not used because their
+/// proof of correct decryption is invalid.
+fn report_invalid_shares(invalid: &[ArIdentity]) {
+    for ar in invalid {
+        eprintln!(
+            "WARNING: The share of anonymity revoker {} is not a correct decryption and was not \
+             used.",
+            ar
+        );
+    }
+}
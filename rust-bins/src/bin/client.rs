@@ -16,6 +16,7 @@ use id::{
     secret_sharing::*,
     types::*,
 };
+use key_derivation::{recovery::*, ConcordiumHdWallet};
 use pairing::bls12_381::{Bls12, G1};
 use rand::*;
 use serde_json::{json, to_value};
@@ -416,6 +417,39 @@ struct MakeAccount {
     out:         PathBuf,
 }
 
+#[derive(StructOpt)]
+/// Recover the accounts of a wallet whose keys are derived from a BIP39 seed
+/// phrase.
+struct RecoverAccounts {
+    #[structopt(
+        long = "seed-phrase",
+        help = "File with the seed phrase. If not given the phrase is read from the terminal."
+    )]
+    seed_phrase:        Option<PathBuf>,
+    #[structopt(long = "ips", help = "File with a list of identity providers.", default_value = IDENTITY_PROVIDERS)]
+    identity_providers: PathBuf,
+    #[structopt(
+        long = "global",
+        help = "File with global parameters.",
+        default_value = "database/global.json"
+    )]
+    global:             PathBuf,
+    #[structopt(
+        long = "reg-ids",
+        help = "File with a JSON list of the registration IDs of all credentials on the chain."
+    )]
+    reg_ids:            PathBuf,
+    #[structopt(
+        long = "gap-limit",
+        help = "Number of consecutive unused identities, or credentials, after which to stop \
+                searching.",
+        default_value = "20"
+    )]
+    gap_limit:          u32,
+    #[structopt(long = "out", help = "File to write the recovered accounts to.")]
+    out:                Option<PathBuf>,
+}
+
 // This is the type of credentials that is output by the desktop wallet for
 // genesis creation.
 #[derive(SerdeDeserialize)]
@@ -473,6 +507,12 @@ enum IdClient {
         about = "Create a genesis account from credentials and possibly baker information."
     )]
     MakeAccount(MakeAccount),
+    #[structopt(
+        name = "recover-accounts",
+        about = "Find the accounts created by a wallet from its seed phrase, together with their \
+                 keys."
+    )]
+    RecoverAccounts(RecoverAccounts),
 }
 
 fn main() {
@@ -492,6 +532,7 @@ fn main() {
         ExtendIpList(eil) => handle_extend_ip_list(eil),
         VerifyCredential(vcred) => handle_verify_credential(vcred),
         MakeAccount(macc) => handle_make_account(macc),
+        RecoverAccounts(racc) => handle_recover_accounts(racc),
     }
 }
 
@@ -558,6 +599,81 @@ fn handle_make_account(macc: MakeAccount) {
     }
 }
 
+fn handle_recover_accounts(racc: RecoverAccounts) {
+    let seed_phrase = match racc.seed_phrase {
+        Some(path) => match std::fs::read_to_string(path) {
+            Ok(phrase) => phrase,
+            Err(e) => {
+                eprintln!("Could not read the seed phrase: {}", e);
+                return;
+            }
+        },
+        // Read the seed phrase without echoing it, since it is secret.
+        None => match rpassword::read_password_from_tty(Some("Enter the seed phrase: ")) {
+            Ok(phrase) => phrase,
+            Err(e) => {
+                eprintln!("Could not read the seed phrase: {}", e);
+                return;
+            }
+        },
+    };
+    // Normalize the whitespace between the words.
+    let seed_phrase = seed_phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    let wallet = ConcordiumHdWallet::from_mnemonic(&seed_phrase, "");
+
+    let global_ctx = {
+        if let Some(gc) = read_global_context(racc.global) {
+            gc
+        } else {
+            eprintln!("Cannot read global context information database. Terminating.");
+            return;
+        }
+    };
+
+    let ips = match read_identity_providers(racc.identity_providers) {
+        Ok(ips) => ips,
+        Err(e) => {
+            eprintln!("Could not read the identity providers: {}", e);
+            return;
+        }
+    };
+    let ip_identities: Vec<IpIdentity> = ips.identity_providers.keys().copied().collect();
+
+    let mut lookup: KnownRegIds = match read_json_from_file(racc.reg_ids) {
+        Ok(lookup) => lookup,
+        Err(e) => {
+            eprintln!("Could not read the registration IDs: {}", e);
+            return;
+        }
+    };
+
+    let accounts = match recover_accounts(
+        &wallet,
+        &global_ctx,
+        &ip_identities,
+        racc.gap_limit,
+        &mut lookup,
+    ) {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            eprintln!("Could not recover accounts: {}", e);
+            return;
+        }
+    };
+    eprintln!("Recovered {} accounts.", accounts.len());
+    if let Some(filepath) = racc.out {
+        match write_json_to_file(filepath, &accounts) {
+            Ok(()) => println!("Wrote recovered accounts to file."),
+            Err(_) => {
+                eprintln!("Could not write to file. The recovered accounts are");
+                output_json(&accounts);
+            }
+        }
+    } else {
+        output_json(&accounts)
+    }
+}
+
 fn handle_verify_credential(vcred: VerifyCredential) {
     let ip_info = match read_ip_info(vcred.ip_info) {
         Ok(v) => v,
@@ -770,6 +886,7 @@ fn handle_create_credential(cc: CreateCredential) {
         valid_to:   id_object.alist.valid_to,
         created_at: id_object.alist.created_at,
         policy_vec: revealed_attributes,
+        statements: Vec::new(),
         _phantom:   Default::default(),
     };
 
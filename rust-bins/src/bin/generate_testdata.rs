@@ -176,6 +176,7 @@ fn main() {
             tree.insert(AttributeTag::from(1u8), AttributeKind::from(31));
             tree
         },
+        statements: Vec::new(),
         _phantom: Default::default(),
     };
     {
@@ -3,7 +3,7 @@ use clap::AppSettings;
 use client_server_helpers::*;
 use crypto_common::{
     base16_encode_string,
-    encryption::{encrypt, Password},
+    encryption::{encrypt_with, Cipher, KeyDerivationParameters, Password, NUM_ROUNDS},
     types::{Amount, KeyIndex},
     *,
 };
@@ -212,6 +212,7 @@ fn main() -> std::io::Result<()> {
             valid_to:   attributes.valid_to,
             created_at: attributes.created_at,
             policy_vec: BTreeMap::<_, ExampleAttribute>::new(),
+            statements: Vec::new(),
             _phantom:   Default::default(),
         };
 
@@ -374,10 +375,21 @@ fn main() -> std::io::Result<()> {
                     });
 
                     // If the password is provided then encrypt, otherwise output in plaintext.
+                    // The node reads these files, and it only supports AES-256 in CBC mode
+                    // with PBKDF2, so the file is not a keystore.
                     let baker_credentials_out = if let Some(pass) = baker_keys_password.as_ref() {
                         let plaintext = serde_json::to_vec(&baker_data_json)
                             .expect("Cannot convert to JSON, should not happen.");
-                        let encrypted = encrypt(pass, &plaintext, &mut csprng);
+                        let encrypted = encrypt_with(
+                            pass,
+                            &plaintext,
+                            Cipher::Aes256Cbc,
+                            KeyDerivationParameters::Pbkdf2Sha256 {
+                                iterations: NUM_ROUNDS,
+                            },
+                            &mut csprng,
+                        )
+                        .expect("PBKDF2 parameters are valid.");
                         serde_json::to_value(&encrypted).expect("JSON serialization must succeed.")
                     } else {
                         baker_data_json
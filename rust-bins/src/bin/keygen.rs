@@ -1,4 +1,3 @@
-use bitvec::prelude::*;
 use clap::AppSettings;
 use client_server_helpers::*;
 use crossterm::{
@@ -6,16 +5,18 @@ use crossterm::{
     terminal::{Clear, ClearType},
 };
 use crypto_common::*;
-use curve_arithmetic::Curve;
+use curve_arithmetic::{
+    bls12_381::{Bls12, Fr, G1, G2},
+    Curve,
+};
 use dialoguer::{Confirm, Input};
 use elgamal::{PublicKey, SecretKey};
 use hkdf::HkdfExtract;
-use hmac::{Hmac, Mac, NewMac};
 use id::types::*;
+use key_derivation::{bip39::*, ExtendedKey};
 use keygen_bls::keygen_bls;
-use pairing::bls12_381::{Bls12, Fr, G1, G2};
 use rand::Rng;
-use sha2::{Digest, Sha256, Sha512};
+use sha2::{Digest, Sha256};
 use std::{
     collections::HashMap,
     fs::{self, File},
@@ -24,17 +25,6 @@ use std::{
 };
 use structopt::StructOpt;
 
-const BIP39_ENGLISH: &str = include_str!("data/BIP39English.txt");
-
-/// List of BIP39 words. There is a test that checks that this list has correct
-/// length, so there is no need to check when using this in the tool.
-fn bip39_words() -> impl Iterator<Item = &'static str> { BIP39_ENGLISH.split_whitespace() }
-
-/// Inverse mapping to the implicit mapping in bip39_words. Maps word to its
-/// index in the list. This allows to quickly test membership and convert words
-/// to their index.
-fn bip39_map() -> HashMap<&'static str, usize> { bip39_words().zip(0..).collect() }
-
 #[derive(StructOpt)]
 struct KeygenIp {
     #[structopt(long = "rand-input", help = "File with randomness.")]
@@ -209,8 +199,11 @@ macro_rules! succeed_or_die {
     };
 }
 
+/// Output the data, either in plaintext or, if a password is given, in a
+/// keystore of the given key type.
 fn output_possibly_encrypted<X: SerdeSerialize>(
     fname: &Path,
+    key_type: &str,
     data: &X,
 ) -> Result<(), std::io::Error> {
     let pass = ask_for_password_confirm(
@@ -222,9 +215,13 @@ fn output_possibly_encrypted<X: SerdeSerialize>(
         write_json_to_file(fname, data)
     } else {
         let plaintext = serde_json::to_vec(data).expect("JSON serialization does not fail.");
-        let encrypted =
-            crypto_common::encryption::encrypt(&pass.into(), &plaintext, &mut rand::thread_rng());
-        write_json_to_file(fname, &encrypted)
+        let keystore = crypto_common::keystore::Keystore::new(
+            &pass.into(),
+            key_type,
+            &plaintext,
+            &mut rand::thread_rng(),
+        );
+        write_json_to_file(fname, &keystore.versioned())
     }
 }
 
@@ -391,7 +388,7 @@ fn handle_generate_ar_keys(kgar: KeygenAr) -> Result<(), String> {
                 .expect("Output file not provided."),
         )
     });
-    match output_possibly_encrypted(&out_file, &ar_data) {
+    match output_possibly_encrypted(&out_file, "anonymity-revoker", &ar_data) {
         Ok(_) => println!("Wrote private keys to {}.", out_file.display()),
         Err(e) => {
             return Err(format!(
@@ -445,7 +442,7 @@ fn handle_generate_ip_keys(kgip: KeygenIp) -> Result<(), String> {
         ip_cdi_secret_key,
     };
     let versioned_ip_info_public = Versioned::new(VERSION_0, full_info.public_ip_info.clone());
-    match output_possibly_encrypted(&kgip.out, &full_info) {
+    match output_possibly_encrypted(&kgip.out, "identity-provider", &full_info) {
         Ok(_) => println!("Wrote private to {}.", kgip.out.display()),
         Err(e) => {
             return Err(format!(
@@ -530,17 +527,9 @@ pub fn generate_ps_sk(n: u32, ikm: &[u8]) -> Result<ps_sig::SecretKey<Bls12>, hk
 
 /// This function is an implementation of the procedure described in https://github.com/satoshilabs/slips/blob/master/slip-0010.md
 /// It produces 32 random bytes given a seed, which is exactly a secret key for
-/// the ed25519_dalek.
-pub fn keygen_ed(seed: &[u8]) -> [u8; 32] {
-    let mut mac =
-        Hmac::<Sha512>::new_from_slice(b"ed25519 seed").expect("HMAC can take key of any size");
-    mac.update(&seed);
-    let result = mac.finalize();
-    let code_bytes = result.into_bytes();
-    let mut il = [0u8; 32];
-    il.clone_from_slice(&code_bytes[0..32]);
-    il
-}
+/// the ed25519_dalek. This is the master key of the derivation tree that
+/// [ConcordiumHdWallet](key_derivation::ConcordiumHdWallet) uses.
+pub fn keygen_ed(seed: &[u8]) -> [u8; 32] { ExtendedKey::master(seed).key }
 
 /// It generates a ed25519_dalek secret key given a seed, using the `keygen_ed`
 /// above.
@@ -634,100 +623,6 @@ pub fn read_words_from_terminal(
     Ok(word_list)
 }
 
-/// Verify whether the given vector of words constitutes a valid BIP39 sentence.
-pub fn verify_bip39(word_vec: &[String], bip_word_map: &HashMap<&str, usize>) -> bool {
-    // check that word_vec contains allowed number of words
-    match word_vec.len() {
-        12 | 15 | 18 | 21 | 24 => (),
-        _ => return false,
-    };
-
-    // convert word vector to bits
-    let mut bit_vec = BitVec::<Msb0, u8>::new();
-    for word in word_vec {
-        match bip_word_map.get(word.as_str()) {
-            Some(idx) => {
-                let word_bits = BitVec::<Msb0, u16>::from_element(*idx as u16);
-                // There are 2048 words in the BIP39 list, which can be represented using 11
-                // bits. Thus, the first 5 bits of word_bits are 0. Remove those leading zeros
-                // and add the remaining ones to bit_bec.
-                bit_vec.extend_from_bitslice(&word_bits[5..]);
-            }
-            None => return false, // not valid if it contains invalid word
-        };
-    }
-
-    // Valid sentence consists of initial entropy of length ent_len plus
-    // checksum of length ent_len/32. Hence, ent_len * 33/32 = bit_vec.len().
-    // Note that bit_vec.len() is always a multiple of 33 because 11 bits
-    // are added for each word and all allowed word counts are multiples of 3.
-    let ent_len = 32 * bit_vec.len() / 33;
-
-    // split bits after ent_len off. These correspond to the checksum.
-    let checksum = bit_vec.split_off(ent_len);
-
-    // checksum is supposed to be first cs_len bits of SHA256(entropy)
-    let hash = Sha256::digest(&bit_vec.into_vec());
-
-    // convert hash from byte vector to bit vector
-    let hash_bits = BitVec::<Msb0, u8>::from_slice(&hash).unwrap();
-
-    // sentence is valid if checksum equals fist ent_len/32 bits of hash
-    checksum == hash_bits[0..ent_len / 32]
-}
-
-/// Convert given byte array to valid BIP39 sentence.
-/// Bytes must contain {16, 20, 24, 28, 32} bytes corresponding to
-/// {128, 160, 192, 224, 256} bits.
-/// This uses the method described at https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki
-pub fn bytes_to_bip39(bytes: &[u8], bip_word_list: &[&str]) -> Result<Vec<String>, String> {
-    let ent_len = 8 * bytes.len(); // input is called entropy in BIP39
-    match ent_len {
-        128 | 160 | 192 | 224 | 256 => (),
-        _ => {
-            return Err(
-                "The number of bytes to be converted to a BIP39 sentence must be in {16, 20, 24, \
-                 28, 32}."
-                    .to_string(),
-            )
-        }
-    };
-
-    // checksum length is ent_len / 32
-    let cs_len = ent_len / 32;
-
-    // checksum is first cs_len bits of SHA256(bytes)
-    // first compute hash of bytes
-    let hash = Sha256::digest(bytes);
-
-    // convert hash from byte vector to bit vector
-    let hash_bits = succeed_or_die!(
-        BitVec::<Msb0, u8>::from_slice(&hash),
-        e => "Failed to convert hash to bit vector because {}"
-    );
-
-    // convert input bytes from byte vector to bit vector
-    let mut random_bits = succeed_or_die!(
-        BitVec::<Msb0, u8>::from_slice(&bytes),
-        e => "Failed to convert hash to bit vector because {}"
-    );
-
-    // append the first cs_len bits of hash_bits to the end of random_bits
-    for i in 0..cs_len {
-        random_bits.push(hash_bits[i]);
-    }
-
-    // go over random_bits in chunks of 11 bits and convert those to words
-    let mut vec = Vec::<String>::new();
-    let random_iter = random_bits.chunks(11);
-    for chunk in random_iter {
-        let idx = chunk.iter().fold(0, |acc, b| acc << 1 | *b as usize); // convert chunk to integer
-        vec.push(bip_word_list[idx].to_string());
-    }
-
-    Ok(vec)
-}
-
 /// Rerandomize given list of words using system randomness and HKDF extractor.
 /// The input can be an arbitrary slice of strings.
 /// The output is a valid BIP39 sentence with 24 words.
@@ -783,132 +678,4 @@ mod tests {
             hex::decode("171cb88b1b3c1db25add599712e36245d75bc65a1a5c9e18d76f9f2b1eab4012")
         );
     }
-
-    /// Test correct generation of BIP39 sentences.
-    /// Values are taken from https://github.com/trezor/python-mnemonic/blob/master/vectors.json
-    #[test]
-    pub fn test_bip39_generation() {
-        let bip39_vec: Vec<_> = bip39_words().collect();
-        assert_eq!(bip39_vec.len(), 2048);
-
-        assert_eq!(
-            bytes_to_bip39(
-                &hex::decode("00000000000000000000000000000000").unwrap(),
-                &bip39_vec
-            )
-            .unwrap()
-            .join(" "),
-            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
-             abandon about",
-        );
-        assert_eq!(
-            bytes_to_bip39(
-                &hex::decode("7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f").unwrap(),
-                &bip39_vec
-            )
-            .unwrap()
-            .join(" "),
-            "legal winner thank year wave sausage worth useful legal winner thank yellow",
-        );
-        assert_eq!(
-            bytes_to_bip39(
-                &hex::decode("000000000000000000000000000000000000000000000000").unwrap(),
-                &bip39_vec
-            )
-            .unwrap()
-            .join(" "),
-            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
-             abandon abandon abandon abandon abandon abandon abandon agent",
-        );
-        assert_eq!(
-            bytes_to_bip39(
-                &hex::decode("0000000000000000000000000000000000000000000000000000000000000000")
-                    .unwrap(),
-                &bip39_vec
-            )
-            .unwrap()
-            .join(" "),
-            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
-             abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
-             abandon abandon abandon art",
-        );
-        assert_eq!(
-            bytes_to_bip39(
-                &hex::decode("f585c11aec520db57dd353c69554b21a89b20fb0650966fa0a9d6f74fd989d8f")
-                    .unwrap(),
-                &bip39_vec
-            )
-            .unwrap()
-            .join(" "),
-            "void come effort suffer camp survey warrior heavy shoot primary clutch crush open \
-             amazing screen patrol group space point ten exist slush involve unfold",
-        );
-    }
-
-    /// Test BIP39 verification.
-    #[test]
-    pub fn test_bip39_verification() {
-        let bip39_vec: Vec<_> = bip39_words().collect();
-        assert_eq!(bip39_vec.len(), 2048);
-        let mut bip39_map = HashMap::new();
-        for (i, word) in bip39_vec.iter().enumerate() {
-            bip39_map.insert(*word, i);
-        }
-
-        let valid_list = vec![
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "art".to_string(),
-        ];
-        let invalid_list = vec![
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-            "abandon".to_string(),
-        ];
-
-        assert!(verify_bip39(&valid_list, &bip39_map));
-        assert!(!verify_bip39(&invalid_list, &bip39_map));
-    }
 }
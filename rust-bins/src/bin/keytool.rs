@@ -0,0 +1,233 @@
+//! Management of keystore files, see [crypto_common::keystore]. The tool can
+//! change the password of a key, re-encrypt it with different parameters, and
+//! show its metadata. Decrypted keys are never output.
+
+use anyhow::Context;
+use clap::AppSettings;
+use client_server_helpers::*;
+use crypto_common::{
+    encryption::{
+        Cipher, EncryptedData, EncryptionMethod, KeyDerivationMethod, KeyDerivationParameters,
+        Password, ARGON2_MEMORY_COST, ARGON2_PARALLELISM, ARGON2_TIME_COST,
+    },
+    keystore::Keystore,
+    Versioned,
+};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+
+fn parse_cipher(s: &str) -> Result<Cipher, String> {
+    match s {
+        "aes-256-gcm" => Ok(Cipher::Aes256Gcm),
+        "chacha20-poly1305" => Ok(Cipher::ChaCha20Poly1305),
+        "aes-256-cbc" => Ok(Cipher::Aes256Cbc),
+        _ => Err(format!("Unsupported cipher {}.", s)),
+    }
+}
+
+#[derive(StructOpt)]
+struct ConfigInspect {
+    #[structopt(long = "in", help = "Keystore file to inspect.")]
+    input: PathBuf,
+}
+
+#[derive(StructOpt)]
+struct ConfigChangePassword {
+    #[structopt(long = "in", help = "Keystore file with the key.")]
+    input:  PathBuf,
+    #[structopt(long = "out", help = "File to write the updated keystore to.")]
+    output: PathBuf,
+}
+
+#[derive(StructOpt)]
+struct ConfigReencrypt {
+    #[structopt(long = "in", help = "Keystore file with the key.")]
+    input:       PathBuf,
+    #[structopt(long = "out", help = "File to write the updated keystore to.")]
+    output:      PathBuf,
+    #[structopt(
+        long = "cipher",
+        help = "Cipher to encrypt with, one of aes-256-gcm, chacha20-poly1305 and aes-256-cbc.",
+        default_value = "aes-256-gcm",
+        parse(try_from_str = parse_cipher)
+    )]
+    cipher:      Cipher,
+    #[structopt(
+        long = "pbkdf2-iterations",
+        help = "Derive the key with PBKDF2 with this many iterations instead of with Argon2id.",
+        conflicts_with_all = &["memory-cost", "time-cost", "parallelism"]
+    )]
+    iterations:  Option<u32>,
+    #[structopt(
+        name = "memory-cost",
+        long = "memory-cost",
+        help = "Memory used by Argon2id, in KiB."
+    )]
+    memory_cost: Option<u32>,
+    #[structopt(
+        name = "time-cost",
+        long = "time-cost",
+        help = "Number of passes of Argon2id."
+    )]
+    time_cost:   Option<u32>,
+    #[structopt(
+        name = "parallelism",
+        long = "parallelism",
+        help = "Number of lanes of Argon2id."
+    )]
+    parallelism: Option<u32>,
+}
+
+#[derive(StructOpt)]
+struct ConfigImport {
+    #[structopt(
+        long = "in",
+        help = "File with an encrypted key, as output by the encrypt command of the utils tool."
+    )]
+    input:    PathBuf,
+    #[structopt(long = "key-type", help = "Type of the key, e.g., anonymity-revoker.")]
+    key_type: String,
+    #[structopt(long = "out", help = "File to write the keystore to.")]
+    output:   PathBuf,
+}
+
+#[derive(StructOpt)]
+#[structopt(
+    about = "Manage encrypted key files.",
+    author = "Concordium",
+    version = "0.0"
+)]
+enum KeyTool {
+    #[structopt(name = "inspect", about = "Show the metadata of a keystore file.")]
+    Inspect(ConfigInspect),
+    #[structopt(
+        name = "change-password",
+        about = "Change the password of a keystore file, keeping the encryption parameters."
+    )]
+    ChangePassword(ConfigChangePassword),
+    #[structopt(
+        name = "reencrypt",
+        about = "Re-encrypt a keystore file with new encryption parameters, keeping the password."
+    )]
+    Reencrypt(ConfigReencrypt),
+    #[structopt(
+        name = "import",
+        about = "Wrap an encrypted key file without metadata in a keystore, without decrypting it."
+    )]
+    Import(ConfigImport),
+}
+
+fn main() -> anyhow::Result<()> {
+    let app = KeyTool::clap()
+        .setting(AppSettings::ArgRequiredElseHelp)
+        .global_setting(AppSettings::ColoredHelp);
+    let matches = app.get_matches();
+    let tool = KeyTool::from_clap(&matches);
+    match tool {
+        KeyTool::Inspect(cfg) => handle_inspect(cfg),
+        KeyTool::ChangePassword(cfg) => handle_change_password(cfg),
+        KeyTool::Reencrypt(cfg) => handle_reencrypt(cfg),
+        KeyTool::Import(cfg) => handle_import(cfg),
+    }
+}
+
+fn read_keystore(path: &Path) -> anyhow::Result<Keystore> {
+    let versioned: Versioned<Keystore> =
+        read_json_from_file(path).context("Could not read the keystore file.")?;
+    Ok(Keystore::from_versioned(versioned)?)
+}
+
+fn write_keystore(path: &Path, keystore: Keystore) -> anyhow::Result<()> {
+    eprintln!("Writing keystore to {}", path.to_string_lossy());
+    write_json_to_file(path, &keystore.versioned())?;
+    Ok(())
+}
+
+fn read_password(prompt: &str) -> anyhow::Result<Password> {
+    let pass = rpassword::read_password_from_tty(Some(prompt))?;
+    Ok(pass.into())
+}
+
+fn handle_inspect(cfg: ConfigInspect) -> anyhow::Result<()> {
+    let keystore = read_keystore(&cfg.input)?;
+    let created = chrono::NaiveDateTime::from_timestamp_opt(keystore.created_at() as i64, 0)
+        .map_or_else(|| keystore.created_at().to_string(), |t| t.to_string());
+    let cipher = match keystore.metadata().encryption_method() {
+        EncryptionMethod::Aes256 { .. } => "AES-256-CBC",
+        EncryptionMethod::Aes256Gcm { .. } => "AES-256-GCM",
+        EncryptionMethod::ChaCha20Poly1305 { .. } => "ChaCha20-Poly1305",
+    };
+    let key_derivation = match keystore.metadata().key_derivation_method() {
+        KeyDerivationMethod::Pbkdf2Sha256 { iterations, .. } => json!({
+            "method": "PBKDF2WithHmacSHA256",
+            "iterations": iterations,
+        }),
+        KeyDerivationMethod::Argon2id {
+            memory_cost,
+            time_cost,
+            parallelism,
+            ..
+        } => json!({
+            "method": "Argon2id",
+            "memoryCost": memory_cost,
+            "timeCost": time_cost,
+            "parallelism": parallelism,
+        }),
+    };
+    output_json(&json!({
+        "keyId": keystore.key_id(),
+        "keyType": keystore.key_type(),
+        "createdAt": created,
+        "cipher": cipher,
+        "keyDerivation": key_derivation,
+    }));
+    Ok(())
+}
+
+fn handle_change_password(cfg: ConfigChangePassword) -> anyhow::Result<()> {
+    let keystore = read_keystore(&cfg.input)?;
+    let old_pass = read_password("Enter the current password: ")?;
+    let new_pass: Password = ask_for_password_confirm("Enter the new password: ", false)?.into();
+    let metadata = keystore.metadata();
+    let updated = keystore
+        .reencrypt(
+            &old_pass,
+            &new_pass,
+            metadata.encryption_method().into(),
+            metadata.key_derivation_method().into(),
+            &mut rand::thread_rng(),
+        )
+        .context("Could not change the password.")?;
+    write_keystore(&cfg.output, updated)
+}
+
+fn handle_reencrypt(cfg: ConfigReencrypt) -> anyhow::Result<()> {
+    let keystore = read_keystore(&cfg.input)?;
+    let key_derivation = match cfg.iterations {
+        Some(iterations) => KeyDerivationParameters::Pbkdf2Sha256 { iterations },
+        None => KeyDerivationParameters::Argon2id {
+            memory_cost: cfg.memory_cost.unwrap_or(ARGON2_MEMORY_COST),
+            time_cost:   cfg.time_cost.unwrap_or(ARGON2_TIME_COST),
+            parallelism: cfg.parallelism.unwrap_or(ARGON2_PARALLELISM),
+        },
+    };
+    let pass = read_password("Enter the password: ")?;
+    let updated = keystore
+        .reencrypt(
+            &pass,
+            &pass,
+            cfg.cipher,
+            key_derivation,
+            &mut rand::thread_rng(),
+        )
+        .context("Could not re-encrypt the key.")?;
+    write_keystore(&cfg.output, updated)
+}
+
+fn handle_import(cfg: ConfigImport) -> anyhow::Result<()> {
+    let encrypted: EncryptedData =
+        read_json_from_file(&cfg.input).context("Could not read the encrypted key.")?;
+    let keystore = Keystore::from_encrypted(&cfg.key_type, encrypted, &mut rand::thread_rng());
+    write_keystore(&cfg.output, keystore)
+}
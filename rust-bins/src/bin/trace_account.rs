@@ -16,7 +16,7 @@ use client_server_helpers::read_json_from_file;
 use crypto_common::{types::Amount, *};
 use id::types::*;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
 type EncryptedAmount = encrypted_transfers::types::EncryptedAmount<id::constants::ArCurve>;
@@ -246,21 +246,35 @@ struct Trace {
         help = "File with cryptographic parameters.",
         default_value = "global.json"
     )]
-    global: PathBuf,
+    global:     PathBuf,
     #[structopt(
         long = "out",
         help = "File to output the account trace to. If not provided the data is printed to \
                 stdout."
     )]
-    out:    Option<PathBuf>,
+    out:        Option<PathBuf>,
+    #[structopt(
+        long = "table",
+        help = "File with a precomputed table for decrypting encrypted amounts. If the file does \
+                not exist the table is computed and written to it, so that later runs can reuse \
+                it."
+    )]
+    table:      Option<PathBuf>,
+    #[structopt(
+        long = "table-size",
+        help = "Size of the table for decrypting encrypted amounts, if it has to be computed. \
+                Larger tables make decryption faster, at the cost of memory.",
+        default_value = "65536"
+    )]
+    table_size: u64,
     #[structopt(
         long = "source",
         help = "URL to the wallet-proxy instance.",
         default_value = "https://wallet-proxy.eu.staging.concordium.com"
     )]
-    source: url::Url,
+    source:     url::Url,
     #[structopt(subcommand)]
-    mode:   Mode,
+    mode:       Mode,
 }
 
 fn main() {
@@ -284,7 +298,19 @@ fn main() {
                 return;
             }
         };
-    let table = elgamal::BabyStepGiantStep::new(global.encryption_in_exponent_generator(), 1 << 16);
+    let table: Box<dyn elgamal::DiscreteLog<id::constants::ArCurve>> = match &tr.table {
+        Some(table_file) => match load_or_compute_table(&global, table_file, tr.table_size) {
+            Ok(table) => Box::new(table),
+            Err(e) => {
+                eprintln!("Could not load the decryption table: {}", e);
+                return;
+            }
+        },
+        None => Box::new(elgamal::BabyStepGiantStep::new(
+            global.encryption_in_exponent_generator(),
+            tr.table_size,
+        )),
+    };
     let mut writer: Box<dyn std::io::Write> = if let Some(file) = tr.out {
         Box::new(std::fs::File::create(file).expect("Cannot create output file"))
     } else {
@@ -300,7 +326,7 @@ fn main() {
                 }
             };
             for input in inputs.iter() {
-                trace_single_account(&table, &tr.source, input, &mut writer);
+                trace_single_account(&*table, &tr.source, input, &mut writer);
                 writeln!(writer, "\n\n").expect("Could not write.");
             }
         }
@@ -330,13 +356,38 @@ fn main() {
                     encryption_secret_key: None,
                 },
             };
-            trace_single_account(&table, &tr.source, &input, &mut writer)
+            trace_single_account(&*table, &tr.source, &input, &mut writer)
         }
     };
 }
 
+/// Load the table for decrypting encrypted amounts from the given file. If the
+/// file does not exist the table of the given size is computed and stored in
+/// the file.
+fn load_or_compute_table(
+    global: &GlobalContext<id::constants::ArCurve>,
+    table_file: &Path,
+    table_size: u64,
+) -> anyhow::Result<elgamal::BabyStepGiantStepFile<id::constants::ArCurve>> {
+    let base = global.encryption_in_exponent_generator();
+    if table_file.exists() {
+        let data = std::fs::read(table_file).context("Could not read the table file.")?;
+        let table = elgamal::BabyStepGiantStepFile::from_bytes(data)?;
+        anyhow::ensure!(
+            table.base() == base,
+            "The table is for a different generator than the one in the cryptographic parameters."
+        );
+        Ok(table)
+    } else {
+        let mut data = Vec::new();
+        elgamal::write_bsgs_table(base, table_size, &mut data)?;
+        std::fs::write(table_file, &data).context("Could not write the table file.")?;
+        elgamal::BabyStepGiantStepFile::from_bytes(data)
+    }
+}
+
 fn trace_single_account(
-    table: &elgamal::BabyStepGiantStep<id::constants::ArCurve>,
+    table: &dyn elgamal::DiscreteLog<id::constants::ArCurve>,
     source: &url::Url,
     input: &RetrievalInput,
     writer: &mut impl std::io::Write,
@@ -482,12 +533,12 @@ fn trace_single_account(
                             if tx.origin.origin_type == OriginType::Own {
                                 if let Some(sk) = &sk {
                                     let before = encrypted_transfers::decrypt_amount(
-                                        &table,
+                                        table,
                                         &sk,
                                         &et.input_encrypted_amount,
                                     );
                                     let after = encrypted_transfers::decrypt_amount(
-                                        &table,
+                                        table,
                                         &sk,
                                         &et.new_self_encrypted_amount,
                                     );
@@ -523,7 +574,7 @@ fn trace_single_account(
                             } else if tx.origin.origin_type == OriginType::Account {
                                 if let Some(sk) = &sk {
                                     let amount = encrypted_transfers::decrypt_amount(
-                                        &table,
+                                        table,
                                         &sk,
                                         &et.encrypted_amount,
                                     );
@@ -1,10 +1,15 @@
 //! Some command line auxiliary utilities.
 //! At the moment we have encryption and decryption in the formats used by other
-//! parts of the Concordium project.
+//! parts of the Concordium project. Decryption accepts both keystore files and
+//! encrypted files without metadata.
 
 use anyhow::Context;
 use clap::AppSettings;
 use client_server_helpers::*;
+use crypto_common::{
+    encryption::{encrypt_with, Cipher, KeyDerivationParameters, NUM_ROUNDS},
+    keystore::EncryptedKeyFile,
+};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
@@ -55,8 +60,17 @@ fn main() -> anyhow::Result<()> {
 fn handle_encrypt(cfg: ConfigEncrypt) -> anyhow::Result<()> {
     let data = std::fs::read(&cfg.input).context("Cannot read input file.")?;
     let pass = ask_for_password_confirm("Enter password to encrypt with: ", false)?;
-    let encrypted =
-        crypto_common::encryption::encrypt(&pass.into(), &data, &mut rand::thread_rng());
+    // Use AES-256 in CBC mode with PBKDF2, since other tools, e.g., the node,
+    // only support this format.
+    let encrypted = encrypt_with(
+        &pass.into(),
+        &data,
+        Cipher::Aes256Cbc,
+        KeyDerivationParameters::Pbkdf2Sha256 {
+            iterations: NUM_ROUNDS,
+        },
+        &mut rand::thread_rng(),
+    )?;
     eprintln!("Writing output to {}", cfg.output.to_string_lossy());
     write_json_to_file(&cfg.output, &encrypted)?;
     Ok(())
@@ -64,9 +78,9 @@ fn handle_encrypt(cfg: ConfigEncrypt) -> anyhow::Result<()> {
 
 fn handle_decrypt(cfg: ConfigDecrypt) -> anyhow::Result<()> {
     let data = std::fs::read(&cfg.input).context("Cannot read input file.")?;
-    let parsed_data = serde_json::from_slice(&data)?;
+    let parsed_data: EncryptedKeyFile = serde_json::from_slice(&data)?;
     let pass = rpassword::read_password_from_tty(Some("Enter password to decrypt with: "))?;
-    let plaintext = match crypto_common::encryption::decrypt(&pass.into(), &parsed_data) {
+    let plaintext = match parsed_data.decrypt(&pass.into()) {
         Ok(pt) => pt,
         Err(_) => anyhow::bail!("Could not decrypt."),
     };
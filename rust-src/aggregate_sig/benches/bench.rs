@@ -1,7 +1,7 @@
 use aggregate_sig::*;
 
 use criterion::*;
-use pairing::bls12_381::Bls12;
+use curve_arithmetic::bls12_381::Bls12;
 use rand::{thread_rng, Rng};
 
 macro_rules! rand_m_of_length {
@@ -117,6 +117,46 @@ fn bench_verify_aggregate_sig_trusted_keys(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_verify_batch(c: &mut Criterion) {
+    let mut csprng = thread_rng();
+
+    let mut group = c.benchmark_group("verify_batch");
+
+    for &n in [10, 50, 100, 200].iter() {
+        let (sks, pks) = get_sks_pks!(n, csprng);
+        let ms: Vec<_> = n_rand_ms_of_length!(n, 1000, csprng);
+        let sigs: Vec<Signature<Bls12>> = sks
+            .iter()
+            .zip(ms.iter())
+            .map(|(sk, m)| sk.sign(m))
+            .collect();
+        let same_m_sigs: Vec<Signature<Bls12>> = sks.iter().map(|sk| sk.sign(&ms[0])).collect();
+
+        let batch: Vec<(&[u8], PublicKey<Bls12>, Signature<Bls12>)> = ms
+            .iter()
+            .zip(pks.iter().zip(sigs.iter()))
+            .map(|(m, (pk, sig))| (m.as_slice(), *pk, *sig))
+            .collect();
+        let same_m_batch: Vec<(&[u8], PublicKey<Bls12>, Signature<Bls12>)> = pks
+            .iter()
+            .zip(same_m_sigs.iter())
+            .map(|(pk, sig)| (ms[0].as_slice(), *pk, *sig))
+            .collect();
+
+        group.bench_function(format!("verify_individually_{}", n), |b| {
+            b.iter(|| batch.iter().all(|(m, pk, sig)| pk.verify(m, *sig)))
+        });
+        group.bench_function(format!("verify_batch_distinct_messages_{}", n), |b| {
+            b.iter(|| verify_batch(&batch, &mut csprng))
+        });
+        group.bench_function(format!("verify_batch_same_message_{}", n), |b| {
+            b.iter(|| verify_batch(&same_m_batch, &mut csprng))
+        });
+    }
+
+    group.finish();
+}
+
 // to bench has_duplicates, expose it in aggregate_sig.rs by making it public
 //
 // fn bench_has_duplicates(c: &mut Criterion) {
@@ -141,10 +181,12 @@ criterion_group!(
     verify_aggregate_trusted_keys,
     bench_verify_aggregate_sig_trusted_keys
 );
+criterion_group!(batch_verify, bench_verify_batch);
 // criterion_group!(has_dups, bench_has_duplicates);
 criterion_main!(
     // sign_and_verify,
     // aggregate,
     // verify_aggregate,
-    verify_aggregate_trusted_keys
+    verify_aggregate_trusted_keys,
+    batch_verify
 );
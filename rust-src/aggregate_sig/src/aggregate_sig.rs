@@ -1,6 +1,6 @@
 use crypto_common::*;
 use crypto_common_derive::*;
-use curve_arithmetic::{Curve, Pairing, Value};
+use curve_arithmetic::{multiexp, Curve, Pairing, Value};
 use ff::Field;
 use generic_array::GenericArray;
 use id::sigma_protocols::{common::*, dlog::*};
@@ -8,6 +8,7 @@ use rand::Rng;
 use random_oracle::RandomOracle;
 use rayon::iter::*;
 use sha2::{Digest, Sha512};
+use std::collections::BTreeMap;
 
 /// Size of the aggregate signature public key in bytes.
 pub const PUBLIC_KEY_SIZE: usize = 96;
@@ -21,7 +22,7 @@ pub const SIGNATURE_SIZE: usize = 48;
 /// EQUALITY IS NOT CONSTANT TIME!!! Do not use in production
 /// the trait is implemented only for testing purposes
 #[derive(Debug, Eq, Serialize)]
-pub struct SecretKey<P: Pairing>(P::ScalarField);
+pub struct SecretKey<P: Pairing>(pub(crate) P::ScalarField);
 
 impl<P: Pairing> SecretKey<P> {
     pub fn generate<R: Rng>(rng: &mut R) -> SecretKey<P> { SecretKey(P::generate_scalar(rng)) }
@@ -62,7 +63,7 @@ impl<P: Pairing> PartialEq for SecretKey<P> {
 
 /// A Public Key is a point on the second curve of the pairing
 #[derive(Debug, Eq, Serialize, SerdeBase16Serialize)]
-pub struct PublicKey<P: Pairing>(P::G2);
+pub struct PublicKey<P: Pairing>(pub(crate) P::G2);
 
 impl<P: Pairing> PublicKey<P> {
     /// Derived from a secret key sk by exponentiating the generator of G2 with
@@ -108,7 +109,7 @@ impl<P: Pairing> PartialEq for PublicKey<P> {
 }
 
 #[derive(Debug, Eq, Serialize)]
-pub struct Signature<P: Pairing>(P::G1);
+pub struct Signature<P: Pairing>(pub(crate) P::G1);
 
 impl<P: Pairing> Signature<P> {
     /// Aggregates this signatures with the given signature.
@@ -206,6 +207,112 @@ pub fn verify_aggregate_sig_trusted_keys<P: Pairing>(
     )
 }
 
+/// Verifies a batch of independent signatures `(m_i, PK_i, sig_i)` for
+/// i=1..n. With random scalars r_i drawn from `csprng` this checks
+///     `pairing(sum_i r_i * sig_i, g_2) == product_m pairing(g1_hash(m),
+/// sum_{i: m_i = m} r_i * PK_i)`, which takes one pairing more than the
+/// number of distinct messages instead of two pairings per signature.
+/// Returns `true` if all signatures are valid, except with negligible
+/// probability, and `false` otherwise. The empty batch is valid.
+///
+/// The Miller loops of all the pairings are computed together, and there is a
+/// single final exponentiation.
+pub fn verify_batch<P: Pairing, R: Rng>(
+    batch: &[(&[u8], PublicKey<P>, Signature<P>)],
+    csprng: &mut R,
+) -> bool {
+    if batch.is_empty() {
+        return true;
+    }
+    let mut sig_sum = P::G1::zero_point();
+    let mut keys_by_message: BTreeMap<&[u8], P::G2> = BTreeMap::new();
+    for (m, pk, sig) in batch {
+        let r = P::generate_non_zero_scalar(csprng);
+        sig_sum = sig_sum.plus_point(&sig.0.mul_by_scalar(&r));
+        let key = keys_by_message.entry(*m).or_insert_with(P::G2::zero_point);
+        *key = key.plus_point(&pk.0.mul_by_scalar(&r));
+    }
+    let mut g1s = Vec::with_capacity(keys_by_message.len() + 1);
+    let mut g2s = Vec::with_capacity(keys_by_message.len() + 1);
+    g1s.push(P::g1_prepare(&sig_sum.inverse_point()));
+    g2s.push(P::g2_prepare(&P::G2::one_point()));
+    for (m, key) in keys_by_message.iter() {
+        g1s.push(P::g1_prepare(&P::G1::hash_to_group(m)));
+        g2s.push(P::g2_prepare(key));
+    }
+    let pairs: Vec<_> = g1s.iter().zip(g2s.iter()).collect();
+    match P::final_exponentiation(&P::miller_loop(pairs.iter())) {
+        Some(product) => product == <P::TargetField as Field>::one(),
+        None => false,
+    }
+}
+
+/// The weights of the keys in a BDN aggregate, see
+/// <https://eprint.iacr.org/2018/483>. The weight of each key is a hash of the
+/// key together with the whole set of keys. The set is hashed in the order of
+/// the serialized keys, so the weights do not depend on the order in which the
+/// keys are given.
+fn bdn_coefficients<P: Pairing>(pks: &[PublicKey<P>]) -> Vec<P::ScalarField> {
+    let mut key_set: Vec<Vec<u8>> = pks.iter().map(to_bytes).collect();
+    key_set.sort_unstable();
+    let mut ro = RandomOracle::domain("BDNAggregateSignature");
+    ro.extend_from("keys", key_set.iter());
+    pks.iter()
+        .map(|pk| {
+            let mut ro = ro.split();
+            ro.append_message("key", pk);
+            ro.result_to_scalar::<P::G1>()
+        })
+        .collect()
+}
+
+/// Aggregates signatures on the same message in the BDN mode. The signature
+/// `sigs[i]` must be made with the secret key of `pks[i]`, and each signature
+/// is weighted by the coefficient of its key. The result verifies with
+/// [verify_aggregate_sig_bdn] under the same keys. Returns `None` if the
+/// number of keys and signatures differ.
+pub fn aggregate_sig_bdn<P: Pairing>(
+    pks: &[PublicKey<P>],
+    sigs: &[Signature<P>],
+) -> Option<Signature<P>> {
+    if pks.len() != sigs.len() {
+        return None;
+    }
+    let coefficients = bdn_coefficients(pks);
+    let points: Vec<P::G1> = sigs.iter().map(|sig| sig.0).collect();
+    Some(Signature(multiexp(&points, &coefficients)))
+}
+
+/// The aggregate public key of the BDN mode, i.e., the sum of the keys each
+/// weighted by its coefficient. A BDN aggregate signature on `m` under `pks`
+/// is an ordinary signature on `m` under this key.
+pub fn aggregate_public_key_bdn<P: Pairing>(pks: &[PublicKey<P>]) -> PublicKey<P> {
+    let coefficients = bdn_coefficients(pks);
+    let points: Vec<P::G2> = pks.iter().map(|pk| pk.0).collect();
+    PublicKey(multiexp(&points, &coefficients))
+}
+
+/// Verifies a BDN aggregate signature, produced by [aggregate_sig_bdn], on the
+/// same message m under keys PK_i for i=1..n by checking
+/// pairing(sig, g_2) == pairing(g1_hash(m), sum_{i=0}^n (a_i * PK_i)), where
+/// a_i is the coefficient of PK_i.
+///
+/// Since the coefficients depend on the whole set of keys, an adversary cannot
+/// choose its key as a function of the other keys to cancel them out. So,
+/// unlike [verify_aggregate_sig_trusted_keys], this is secure without checking
+/// proofs of possession of the secret keys.
+pub fn verify_aggregate_sig_bdn<P: Pairing>(
+    m: &[u8],
+    pks: &[PublicKey<P>],
+    signature: Signature<P>,
+) -> bool {
+    // verifying against the empty set of signers always fails
+    if pks.is_empty() {
+        return false;
+    }
+    aggregate_public_key_bdn(pks).verify(m, signature)
+}
+
 // Checks for duplicates in a list of messages
 // This is not very efficient - the sorting algorithm can exit as soon as it
 // encounters an equality and report that a duplicate indeed exists.
@@ -227,7 +334,7 @@ fn hash_message(m: &[u8]) -> GenericArray<u8, <Sha512 as Digest>::OutputSize> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use pairing::bls12_381::Bls12;
+    use curve_arithmetic::bls12_381::Bls12;
     use rand::{rngs::StdRng, thread_rng, SeedableRng};
     use std::convert::TryFrom;
 
@@ -352,6 +459,99 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_verify_aggregate_sig_bdn() {
+        let mut rng: StdRng = SeedableRng::from_rng(thread_rng()).unwrap();
+        for _ in 0..TEST_ITERATIONS {
+            let (sks, pks) = get_sks_pks::<Bls12>(50, &mut rng);
+            let m: [u8; 32] = rng.gen::<[u8; 32]>();
+            let sigs: Vec<Signature<Bls12>> = sks.iter().map(|sk| sk.sign(&m)).collect();
+            let agg_sig = aggregate_sig_bdn(&pks, &sigs).expect("Lengths match.");
+
+            assert!(verify_aggregate_sig_bdn(&m, &pks, agg_sig));
+            // The plain aggregate is not a valid BDN aggregate.
+            let plain_sig = sigs
+                .iter()
+                .skip(1)
+                .fold(sigs[0], |acc, x| acc.aggregate(*x));
+            assert!(!verify_aggregate_sig_bdn(&m, &pks, plain_sig));
+
+            // The order of the keys does not matter.
+            let mut pks_rev = pks.clone();
+            pks_rev.reverse();
+            assert!(verify_aggregate_sig_bdn(&m, &pks_rev, agg_sig));
+
+            // test changing message makes verification fail
+            let m_alt: [u8; 32] = rng.gen::<[u8; 32]>();
+            assert!(!verify_aggregate_sig_bdn(&m_alt, &pks, agg_sig));
+
+            // test that removing a public key makes verification fail
+            assert!(!verify_aggregate_sig_bdn(&m, &pks[1..], agg_sig));
+            assert!(!verify_aggregate_sig_bdn(&m, &[], agg_sig));
+            assert!(aggregate_sig_bdn(&pks[1..], &sigs).is_none());
+        }
+    }
+
+    #[test]
+    fn test_rogue_key_bdn() {
+        let mut rng: StdRng = SeedableRng::from_rng(thread_rng()).unwrap();
+        let honest_sk = SecretKey::<Bls12>::generate(&mut rng);
+        let honest_pk = PublicKey::from_secret(&honest_sk);
+        // The adversary picks its key as g_2^x - honest_pk, so that the sum of
+        // the two keys is g_2^x, for which it knows the secret key.
+        let x = SecretKey::<Bls12>::generate(&mut rng);
+        let rogue_pk = PublicKey(PublicKey::from_secret(&x).0.minus_point(&honest_pk.0));
+        let m: [u8; 32] = rng.gen::<[u8; 32]>();
+        let forged = x.sign(&m);
+        let pks = [honest_pk, rogue_pk];
+        assert!(verify_aggregate_sig_trusted_keys(&m, &pks, forged));
+        assert!(!verify_aggregate_sig_bdn(&m, &pks, forged));
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut rng: StdRng = SeedableRng::from_rng(thread_rng()).unwrap();
+        for _ in 0..TEST_ITERATIONS {
+            let (sks, pks) = get_sks_pks::<Bls12>(20, &mut rng);
+            // Half of the signers sign the same message.
+            let mut ms = get_random_messages(20, &mut rng);
+            let m0 = ms[0];
+            for m in ms.iter_mut().take(10) {
+                *m = m0;
+            }
+            let sigs: Vec<Signature<Bls12>> = sks
+                .iter()
+                .zip(ms.iter())
+                .map(|(sk, m)| sk.sign(m))
+                .collect();
+            let mut batch: Vec<(&[u8], PublicKey<Bls12>, Signature<Bls12>)> = ms
+                .iter()
+                .zip(pks.iter())
+                .zip(sigs.iter())
+                .map(|((m, pk), sig)| (&m[..], *pk, *sig))
+                .collect();
+            assert!(verify_batch(&batch, &mut rng));
+            assert!(verify_batch::<Bls12, _>(&[], &mut rng));
+
+            // A signature by the wrong key makes verification fail.
+            batch[3].2 = sigs[4];
+            assert!(!verify_batch(&batch, &mut rng));
+            batch[3].2 = sigs[3];
+
+            // As does a signature on the wrong message.
+            batch[15].0 = &ms[16];
+            assert!(!verify_batch(&batch, &mut rng));
+            batch[15].0 = &ms[15];
+
+            // Swapping the signatures of two signers of the same message
+            // preserves the sum of signatures, but not the random combination.
+            batch.swap(1, 2);
+            batch[1].1 = pks[1];
+            batch[2].1 = pks[2];
+            assert!(!verify_batch(&batch, &mut rng));
+        }
+    }
+
     #[test]
     fn test_verification_empty_signers() {
         let mut rng: StdRng = SeedableRng::from_rng(thread_rng()).unwrap();
@@ -1,8 +1,8 @@
 use crate::*;
 use crypto_common::*;
+use curve_arithmetic::bls12_381::Bls12;
 use ffi_helpers::*;
 use id::sigma_protocols::dlog;
-use pairing::bls12_381::Bls12;
 use rand::{rngs::StdRng, thread_rng, SeedableRng};
 use random_oracle::RandomOracle;
 use std::{cmp::Ordering, slice};
@@ -135,6 +135,96 @@ pub extern "C" fn bls_verify_aggregate(
     u8::from(verify_aggregate_sig_trusted_keys(&m_bytes, &pks, *sig))
 }
 
+/// Verify a batch of `len` independent signatures. The signature at position
+/// `i` must be on the message of length `ms_lens[i]` pointed to by `ms_ptr[i]`,
+/// by the key at position `i`. Returns 1 if all the signatures are valid, and
+/// 0 otherwise.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn bls_verify_batch(
+    ms_ptr: *const *const u8,
+    ms_lens: *const size_t,
+    pks_ptr: *const *mut PublicKey<Bls12>,
+    sigs_ptr: *const *mut Signature<Bls12>,
+    len: size_t,
+) -> u8 {
+    if len == 0 {
+        return 1;
+    }
+    let ms_: &[*const u8] = unsafe { slice::from_raw_parts(ms_ptr, len) };
+    let ms_lens_: &[size_t] = unsafe { slice::from_raw_parts(ms_lens, len) };
+    let pks_: &[*mut PublicKey<Bls12>] = unsafe { slice::from_raw_parts(pks_ptr, len) };
+    let sigs_: &[*mut Signature<Bls12>] = unsafe { slice::from_raw_parts(sigs_ptr, len) };
+    let batch: Vec<(&[u8], PublicKey<Bls12>, Signature<Bls12>)> = ms_
+        .iter()
+        .zip(ms_lens_.iter())
+        .zip(pks_.iter().zip(sigs_.iter()))
+        .map(|((m_ptr, m_len), (pk, sig))| {
+            let m_len = *m_len as usize;
+            (
+                slice_from_c_bytes!(*m_ptr, m_len),
+                *from_ptr!(*pk),
+                *from_ptr!(*sig),
+            )
+        })
+        .collect();
+    u8::from(verify_batch(&batch, &mut thread_rng()))
+}
+
+/// Aggregate signatures on the same message in the BDN mode. The signature at
+/// position `i` must be by the key at position `i`. Returns a null pointer if
+/// the number of keys and signatures differ.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn bls_aggregate_bdn(
+    pks_ptr: *const *mut PublicKey<Bls12>,
+    pks_len: size_t,
+    sigs_ptr: *const *mut Signature<Bls12>,
+    sigs_len: size_t,
+) -> *mut Signature<Bls12> {
+    let pks_: &[*mut PublicKey<Bls12>] = if pks_len == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(pks_ptr, pks_len) }
+    };
+    let sigs_: &[*mut Signature<Bls12>] = if sigs_len == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(sigs_ptr, sigs_len) }
+    };
+    let pks: Vec<PublicKey<Bls12>> = pks_.iter().map(|pk| *from_ptr!(*pk)).collect();
+    let sigs: Vec<Signature<Bls12>> = sigs_.iter().map(|sig| *from_ptr!(*sig)).collect();
+    match aggregate_sig_bdn(&pks, &sigs) {
+        Some(sig) => Box::into_raw(Box::new(sig)),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Verify a BDN aggregate signature on a single message. Unlike
+/// `bls_verify_aggregate` this does not require the keys to have been checked
+/// with a proof of possession.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn bls_verify_aggregate_bdn(
+    m_ptr: *const u8,
+    m_len: size_t,
+    pks_ptr: *const *mut PublicKey<Bls12>,
+    pks_len: size_t,
+    sig_ptr: *mut Signature<Bls12>,
+) -> u8 {
+    let m_len = m_len as usize;
+    let m_bytes = slice_from_c_bytes!(m_ptr, m_len);
+
+    let pks_: &[*mut PublicKey<Bls12>] = if pks_len == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(pks_ptr, pks_len) }
+    };
+    let pks: Vec<PublicKey<Bls12>> = pks_.iter().map(|pk| *from_ptr!(*pk)).collect();
+    let sig = from_ptr!(sig_ptr);
+    u8::from(verify_aggregate_sig_bdn(&m_bytes, &pks, *sig))
+}
+
 // Only used for adding a dummy proof to the genesis block
 #[no_mangle]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
@@ -222,6 +312,65 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_verify_aggregate_bdn_ffi() {
+        let mut rng: StdRng = SeedableRng::from_rng(thread_rng()).unwrap();
+
+        for _ in 0..10 {
+            let m = rng.gen::<[u8; 32]>();
+            let sk1 = SecretKey::<Bls12>::generate(&mut rng);
+            let sk2 = SecretKey::<Bls12>::generate(&mut rng);
+            let mut pk1 = PublicKey::<Bls12>::from_secret(&sk1);
+            let mut pk2 = PublicKey::<Bls12>::from_secret(&sk2);
+            let mut sig1 = sk1.sign(&m);
+            let mut sig2 = sk2.sign(&m);
+
+            let pks_ptr: *const *mut PublicKey<Bls12> =
+                &[&mut pk1 as *mut _, &mut pk2 as *mut _] as *const *mut _;
+            let sigs_ptr: *const *mut Signature<Bls12> =
+                &[&mut sig1 as *mut _, &mut sig2 as *mut _] as *const *mut _;
+            let sig_ptr = bls_aggregate_bdn(pks_ptr, 2, sigs_ptr, 2);
+            assert!(!sig_ptr.is_null());
+            assert!(bls_aggregate_bdn(pks_ptr, 2, sigs_ptr, 1).is_null());
+
+            let m_ptr: *const u8 = &m as *const _;
+            assert_eq!(bls_verify_aggregate_bdn(m_ptr, 32, pks_ptr, 2, sig_ptr), 1);
+            assert_eq!(bls_verify_aggregate_bdn(m_ptr, 32, pks_ptr, 1, sig_ptr), 0);
+            bls_free_sig(sig_ptr);
+        }
+    }
+
+    #[test]
+    fn test_verify_batch_ffi() {
+        let mut rng: StdRng = SeedableRng::from_rng(thread_rng()).unwrap();
+
+        for _ in 0..10 {
+            let m1 = rng.gen::<[u8; 32]>();
+            let m2 = rng.gen::<[u8; 17]>();
+            let sk1 = SecretKey::<Bls12>::generate(&mut rng);
+            let sk2 = SecretKey::<Bls12>::generate(&mut rng);
+            let mut pk1 = PublicKey::<Bls12>::from_secret(&sk1);
+            let mut pk2 = PublicKey::<Bls12>::from_secret(&sk2);
+            let mut sig1 = sk1.sign(&m1);
+            let mut sig2 = sk2.sign(&m2);
+
+            let ms_ptr: *const *const u8 = &[m1.as_ptr(), m2.as_ptr()] as *const *const _;
+            let pks_ptr: *const *mut PublicKey<Bls12> =
+                &[&mut pk1 as *mut _, &mut pk2 as *mut _] as *const *mut _;
+            let sigs_ptr: *const *mut Signature<Bls12> =
+                &[&mut sig1 as *mut _, &mut sig2 as *mut _] as *const *mut _;
+            let lens_ptr: *const size_t = &[32, 17] as *const _;
+            assert_eq!(bls_verify_batch(ms_ptr, lens_ptr, pks_ptr, sigs_ptr, 2), 1);
+            assert_eq!(bls_verify_batch(ms_ptr, lens_ptr, pks_ptr, sigs_ptr, 0), 1);
+
+            let wrong_lens_ptr: *const size_t = &[32, 16] as *const _;
+            assert_eq!(
+                bls_verify_batch(ms_ptr, wrong_lens_ptr, pks_ptr, sigs_ptr, 2),
+                0
+            );
+        }
+    }
+
     #[test]
     fn test_eq() {
         for _i in 0..10 {
@@ -1,5 +1,6 @@
 //! Implementation of aggregate signatures specified in <https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-bls-signature-04>
 mod aggregate_sig;
 mod ffi;
+pub mod threshold;
 
 pub use crate::aggregate_sig::*;
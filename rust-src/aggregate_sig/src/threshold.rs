@@ -0,0 +1,635 @@
+//! Threshold BLS signatures with distributed key generation.
+//!
+//! The key generation is the joint Feldman protocol of Pedersen, with the
+//! complaint handling of Gennaro, Jarecki, Krawczyk and Rabin, "Secure
+//! Distributed Key Generation for Discrete-Log Based Cryptosystems". Each of
+//! the `n` participants acts as a dealer of a random polynomial of degree `t -
+//! 1` and publishes commitments to its coefficients in G2. The group secret key
+//! is the sum of the constant terms of the qualified dealers. Nobody learns it,
+//! but every participant ends up with a share of it, namely the sum of the
+//! evaluations of the qualified dealers' polynomials at the participant's
+//! index.
+//!
+//! A message is signed by each participant signing it with its share, and any
+//! `t` of the resulting partial signatures can be combined by Lagrange
+//! interpolation into a [Signature] that verifies under the group [PublicKey]
+//! with [PublicKey::verify].
+//!
+//! The protocol runs in three rounds.
+//! 1. Each participant creates a [DkgParticipant] which produces a
+//!    [DkgCommitments] message to broadcast, and a [DkgShare] to send to each
+//!    other participant over a confidential and authenticated channel.
+//! 2. Each participant processes the messages it received, and broadcasts a
+//!    [DkgComplaint] against each dealer that sent an invalid share or no share
+//!    at all.
+//! 3. Each participant accused by a complaint broadcasts a [DkgJustification]
+//!    revealing the disputed share. Dealers that fail to justify themselves are
+//!    disqualified.
+//!
+//! After that [DkgParticipant::finish] produces the participant's
+//! [SecretKeyShare], together with the [ThresholdPublicKey] which is the same
+//! for all honest participants.
+use crate::aggregate_sig::*;
+use crypto_common::*;
+use crypto_common_derive::*;
+use curve_arithmetic::{multiexp, Curve, Pairing, Value};
+use ff::Field;
+use id::secret_sharing::{reveal_in_group, share, Threshold};
+use rand::Rng;
+use random_oracle::RandomOracle;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Index of a participant in the key generation. Participants are numbered
+/// from 1 since shares are evaluations of polynomials at the index, and the
+/// evaluation at 0 is the secret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serial)]
+pub struct ParticipantIndex(pub u8);
+
+impl Deserial for ParticipantIndex {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let x: u8 = source.get()?;
+        if x >= 1 {
+            Ok(ParticipantIndex(x))
+        } else {
+            anyhow::bail!("Participant index must be at least 1.")
+        }
+    }
+}
+
+impl From<ParticipantIndex> for u64 {
+    fn from(x: ParticipantIndex) -> Self { u64::from(x.0) }
+}
+
+/// The number of participants `n`, and the number `t` of partial signatures
+/// that are needed to produce a signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serial)]
+pub struct DkgParameters {
+    num_participants: u8,
+    threshold:        Threshold,
+}
+
+impl Deserial for DkgParameters {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let num_participants: u8 = source.get()?;
+        let threshold: Threshold = source.get()?;
+        match DkgParameters::new(num_participants, threshold) {
+            Some(parameters) => Ok(parameters),
+            None => anyhow::bail!("Threshold must not exceed the number of participants."),
+        }
+    }
+}
+
+impl DkgParameters {
+    /// Construct parameters, checking that `1 <= t <= n`.
+    pub fn new(num_participants: u8, threshold: Threshold) -> Option<Self> {
+        if threshold.0 >= 1 && threshold.0 <= num_participants {
+            Some(DkgParameters {
+                num_participants,
+                threshold,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn num_participants(&self) -> u8 { self.num_participants }
+
+    pub fn threshold(&self) -> Threshold { self.threshold }
+
+    /// All participant indices, 1 to n.
+    pub fn participants(&self) -> impl Iterator<Item = ParticipantIndex> {
+        (1..=self.num_participants).map(ParticipantIndex)
+    }
+
+    fn is_participant(&self, index: ParticipantIndex) -> bool {
+        index.0 >= 1 && index.0 <= self.num_participants
+    }
+}
+
+/// The message each dealer broadcasts in the first round: commitments to the
+/// `t` coefficients of its polynomial, constant term first, together with a
+/// proof of knowledge of the constant term.
+#[derive(Debug, Clone, Serialize)]
+pub struct DkgCommitments<P: Pairing> {
+    pub dealer:       ParticipantIndex,
+    #[size_length = 1]
+    pub coefficients: Vec<P::G2>,
+    pub proof:        Proof<P>,
+}
+
+/// The message a dealer sends privately to each other participant in the
+/// first round: the evaluation of its polynomial at the recipient's index.
+#[derive(Debug, Clone, Serialize)]
+pub struct DkgShare<P: Pairing> {
+    pub dealer:    ParticipantIndex,
+    pub recipient: ParticipantIndex,
+    pub share:     Value<P::G2>,
+}
+
+/// A complaint broadcast in the second round by a participant that did not
+/// receive a valid share from a dealer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct DkgComplaint {
+    pub accuser: ParticipantIndex,
+    pub accused: ParticipantIndex,
+}
+
+/// The response of an accused dealer in the third round: the disputed share,
+/// now revealed to everybody.
+#[derive(Debug, Clone, Serialize)]
+pub struct DkgJustification<P: Pairing> {
+    pub share: DkgShare<P>,
+}
+
+/// The state of one participant during the key generation.
+pub struct DkgParticipant<P: Pairing> {
+    index:        ParticipantIndex,
+    parameters:   DkgParameters,
+    /// The shares this participant dealt, kept to answer complaints.
+    dealt:        Vec<DkgShare<P>>,
+    /// Commitments of the dealers, including this participant's own.
+    commitments:  BTreeMap<ParticipantIndex, Vec<P::G2>>,
+    /// Valid shares received, including this participant's own.
+    shares:       BTreeMap<ParticipantIndex, Value<P::G2>>,
+    /// Complaints that have not been answered with a valid justification.
+    complaints:   BTreeSet<DkgComplaint>,
+    /// Dealers that are known to have cheated.
+    disqualified: BTreeSet<ParticipantIndex>,
+}
+
+/// The random oracle binding the proof of knowledge of a dealer's constant
+/// term to the dealer and the parameters.
+fn dealer_ro(parameters: &DkgParameters, dealer: ParticipantIndex) -> RandomOracle {
+    let mut ro = RandomOracle::domain("ThresholdBLSDKG");
+    ro.append_message("parameters", parameters);
+    ro.append_message("dealer", &dealer);
+    ro
+}
+
+/// Evaluate the committed polynomial at the given index, i.e., compute
+/// `sum_k C_k * index^k`.
+fn evaluate_commitments<C: Curve>(coefficients: &[C], index: ParticipantIndex) -> C {
+    let x = C::scalar_from_u64(index.into());
+    let mut powers = Vec::with_capacity(coefficients.len());
+    let mut power = C::Scalar::one();
+    for _ in 0..coefficients.len() {
+        powers.push(power);
+        power.mul_assign(&x);
+    }
+    multiexp(coefficients, &powers)
+}
+
+impl<P: Pairing> DkgParticipant<P> {
+    /// Start the key generation as the participant with the given index. This
+    /// returns the state together with the commitments to broadcast and the
+    /// shares to send to each of the other participants.
+    ///
+    /// The index must be between 1 and the number of participants.
+    pub fn new<R: Rng>(
+        parameters: DkgParameters,
+        index: ParticipantIndex,
+        csprng: &mut R,
+    ) -> (Self, DkgCommitments<P>, Vec<DkgShare<P>>) {
+        assert!(parameters.is_participant(index));
+        let secret = SecretKey::<P>::generate(csprng);
+        let sharing = share::<P::G2, _, _, _>(
+            &secret.0,
+            (1..=parameters.num_participants).map(u64::from),
+            parameters.threshold,
+            csprng,
+        );
+        let mut coefficients = Vec::with_capacity(parameters.threshold.into());
+        coefficients.push(PublicKey::from_secret(&secret).0);
+        for a in sharing.coefficients.iter() {
+            coefficients.push(P::G2::one_point().mul_by_scalar(a));
+        }
+        let proof = secret.prove(csprng, &mut dealer_ro(&parameters, index));
+        let commitments = DkgCommitments {
+            dealer: index,
+            coefficients,
+            proof,
+        };
+        let dealt = parameters
+            .participants()
+            .zip(sharing.shares.into_iter())
+            .map(|(recipient, share)| DkgShare {
+                dealer: index,
+                recipient,
+                share,
+            })
+            .collect::<Vec<_>>();
+        let mut participant = DkgParticipant {
+            index,
+            parameters,
+            dealt,
+            commitments: BTreeMap::new(),
+            shares: BTreeMap::new(),
+            complaints: BTreeSet::new(),
+            disqualified: BTreeSet::new(),
+        };
+        participant.receive_commitments(&commitments);
+        let own_share = participant.dealt[usize::from(index.0) - 1].clone();
+        participant.receive_share(&own_share);
+        let to_send = participant
+            .dealt
+            .iter()
+            .filter(|s| s.recipient != index)
+            .cloned()
+            .collect();
+        (participant, commitments, to_send)
+    }
+
+    pub fn index(&self) -> ParticipantIndex { self.index }
+
+    /// Process the commitments broadcast by a dealer. A dealer whose
+    /// commitments are malformed, or come with an invalid proof of knowledge,
+    /// is disqualified. Commitments are only processed once per dealer, so
+    /// any later commitments of a dealer that was already accepted or
+    /// disqualified are rejected. Returns whether the commitments were
+    /// accepted.
+    pub fn receive_commitments(&mut self, msg: &DkgCommitments<P>) -> bool {
+        if !self.parameters.is_participant(msg.dealer)
+            || self.commitments.contains_key(&msg.dealer)
+            || self.disqualified.contains(&msg.dealer)
+        {
+            return false;
+        }
+        let valid = msg.coefficients.len() == usize::from(self.parameters.threshold)
+            && PublicKey::<P>(msg.coefficients[0])
+                .check_proof(&mut dealer_ro(&self.parameters, msg.dealer), &msg.proof);
+        if valid {
+            self.commitments
+                .insert(msg.dealer, msg.coefficients.clone());
+        } else {
+            self.disqualified.insert(msg.dealer);
+        }
+        valid
+    }
+
+    /// Process a share sent privately to this participant. This must be
+    /// called after the dealer's commitments were received. Returns whether
+    /// the share is consistent with the commitments. Invalid shares are
+    /// ignored, and will lead to a complaint in [DkgParticipant::complaints].
+    pub fn receive_share(&mut self, msg: &DkgShare<P>) -> bool {
+        if msg.recipient != self.index || !self.is_valid_share(msg) {
+            return false;
+        }
+        self.shares.insert(msg.dealer, msg.share.clone());
+        true
+    }
+
+    fn is_valid_share(&self, msg: &DkgShare<P>) -> bool {
+        match self.commitments.get(&msg.dealer) {
+            Some(coefficients) => {
+                P::G2::one_point().mul_by_scalar(&msg.share)
+                    == evaluate_commitments(coefficients, msg.recipient)
+            }
+            None => false,
+        }
+    }
+
+    /// The complaints to broadcast in the second round, one for each dealer
+    /// that is not already disqualified and from which no valid share was
+    /// received.
+    pub fn complaints(&self) -> Vec<DkgComplaint> {
+        self.parameters
+            .participants()
+            .filter(|dealer| {
+                !self.disqualified.contains(dealer) && !self.shares.contains_key(dealer)
+            })
+            .map(|accused| DkgComplaint {
+                accuser: self.index,
+                accused,
+            })
+            .collect()
+    }
+
+    /// Process a complaint broadcast by any participant, including this one.
+    /// If the complaint is against this participant, the justification to
+    /// broadcast is returned.
+    pub fn receive_complaint(&mut self, complaint: &DkgComplaint) -> Option<DkgJustification<P>> {
+        if !self.parameters.is_participant(complaint.accuser)
+            || !self.parameters.is_participant(complaint.accused)
+        {
+            return None;
+        }
+        self.complaints.insert(*complaint);
+        if complaint.accused == self.index {
+            let share = self.dealt[usize::from(complaint.accuser.0) - 1].clone();
+            Some(DkgJustification { share })
+        } else {
+            None
+        }
+    }
+
+    /// Process a justification broadcast by an accused dealer. A valid
+    /// justification resolves the complaint, and if this participant is the
+    /// accuser it adopts the revealed share. An invalid justification
+    /// disqualifies the dealer.
+    pub fn receive_justification(&mut self, justification: &DkgJustification<P>) {
+        let share = &justification.share;
+        let complaint = DkgComplaint {
+            accuser: share.recipient,
+            accused: share.dealer,
+        };
+        if !self.complaints.contains(&complaint) {
+            return;
+        }
+        if self.is_valid_share(share) {
+            self.complaints.remove(&complaint);
+            if share.recipient == self.index {
+                self.shares.insert(share.dealer, share.share.clone());
+            }
+        } else {
+            self.disqualified.insert(share.dealer);
+        }
+    }
+
+    /// The dealers that are qualified at this point: those that sent valid
+    /// commitments and have no unresolved complaints against them.
+    pub fn qualified(&self) -> Vec<ParticipantIndex> {
+        self.commitments
+            .keys()
+            .filter(|dealer| {
+                !self.disqualified.contains(dealer)
+                    && !self.complaints.iter().any(|c| c.accused == **dealer)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Conclude the key generation. This should be called once all
+    /// justifications have been processed, and any complaint that is still
+    /// unresolved disqualifies the accused dealer.
+    ///
+    /// Returns `None` if no dealer is qualified, or if this participant lacks
+    /// a valid share from a qualified dealer, which only happens if it did not
+    /// broadcast its own complaints.
+    pub fn finish(self) -> Option<(SecretKeyShare<P>, ThresholdPublicKey<P>)> {
+        let qualified = self.qualified();
+        if qualified.is_empty() {
+            return None;
+        }
+        let t = usize::from(self.parameters.threshold);
+        let mut combined = vec![P::G2::zero_point(); t];
+        let mut secret = P::ScalarField::zero();
+        for dealer in qualified.iter() {
+            for (c, d) in combined.iter_mut().zip(self.commitments[dealer].iter()) {
+                *c = c.plus_point(d);
+            }
+            // Every qualified dealer has sent this participant a valid share,
+            // unless the participant failed to complain about it.
+            secret.add_assign(self.shares.get(dealer)?);
+        }
+        let key_shares = self
+            .parameters
+            .participants()
+            .map(|i| PublicKey(evaluate_commitments(&combined, i)))
+            .collect();
+        let public_key = ThresholdPublicKey {
+            parameters: self.parameters,
+            public_key: PublicKey(combined[0]),
+            key_shares,
+        };
+        let secret_share = SecretKeyShare {
+            index:  self.index,
+            secret: SecretKey(secret),
+        };
+        Some((secret_share, public_key))
+    }
+}
+
+/// A participant's share of the group secret key.
+#[derive(Debug, Serialize)]
+pub struct SecretKeyShare<P: Pairing> {
+    pub index:  ParticipantIndex,
+    pub secret: SecretKey<P>,
+}
+
+impl<P: Pairing> SecretKeyShare<P> {
+    /// Produce a partial signature on the message.
+    pub fn sign(&self, m: &[u8]) -> PartialSignature<P> {
+        PartialSignature {
+            index:     self.index,
+            signature: self.secret.sign(m),
+        }
+    }
+}
+
+/// A signature on a message by one participant, using its key share.
+#[derive(Debug, Clone, Serialize)]
+pub struct PartialSignature<P: Pairing> {
+    pub index:     ParticipantIndex,
+    pub signature: Signature<P>,
+}
+
+/// The public outcome of the key generation: the group public key, and the
+/// public keys corresponding to each participant's key share, which are used
+/// to check partial signatures.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThresholdPublicKey<P: Pairing> {
+    pub parameters: DkgParameters,
+    pub public_key: PublicKey<P>,
+    /// The key of participant `i` is at position `i - 1`.
+    #[size_length = 1]
+    pub key_shares: Vec<PublicKey<P>>,
+}
+
+impl<P: Pairing> ThresholdPublicKey<P> {
+    /// Check a partial signature against the signer's key share.
+    pub fn verify_partial(&self, m: &[u8], partial: &PartialSignature<P>) -> bool {
+        self.parameters.is_participant(partial.index)
+            && self.key_shares[usize::from(partial.index.0) - 1].verify(m, partial.signature)
+    }
+}
+
+/// Combine partial signatures by different participants into a signature that
+/// verifies under the group public key. The first `threshold` partial
+/// signatures with distinct indices are used, and `None` is returned if there
+/// are fewer than that. The partial signatures are not checked, so a single
+/// invalid one results in an invalid signature. Use
+/// [ThresholdPublicKey::verify_partial] to filter them first.
+pub fn combine_partial_signatures<P: Pairing>(
+    threshold: Threshold,
+    partials: &[PartialSignature<P>],
+) -> Option<Signature<P>> {
+    let t = usize::from(threshold);
+    let mut seen = BTreeSet::new();
+    let points = partials
+        .iter()
+        .filter(|p| seen.insert(p.index))
+        .take(t)
+        .map(|p| (p.index, p.signature.0))
+        .collect::<Vec<_>>();
+    if points.len() < t {
+        return None;
+    }
+    Some(Signature(reveal_in_group(&points)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use curve_arithmetic::bls12_381::Bls12;
+    use rand::{rngs::StdRng, thread_rng, SeedableRng};
+
+    /// Run the key generation among `n` participants in-process. Each
+    /// participant in `cheaters` deals an invalid share to participant 1, and
+    /// if it is also in `silent` it does not justify itself when accused.
+    fn run_dkg(
+        parameters: DkgParameters,
+        cheaters: &[ParticipantIndex],
+        silent: &[ParticipantIndex],
+        rng: &mut StdRng,
+    ) -> Vec<(SecretKeyShare<Bls12>, ThresholdPublicKey<Bls12>)> {
+        let mut participants = Vec::new();
+        let mut broadcasts = Vec::new();
+        let mut private = Vec::new();
+        for i in parameters.participants() {
+            let (participant, commitments, shares) =
+                DkgParticipant::<Bls12>::new(parameters, i, rng);
+            participants.push(participant);
+            // Check that the messages survive serialization.
+            broadcasts.push(serialize_deserialize(&commitments).unwrap());
+            for mut share in shares {
+                if cheaters.contains(&share.dealer) && share.recipient == ParticipantIndex(1) {
+                    share.share = Value::generate(rng);
+                }
+                private.push(serialize_deserialize(&share).unwrap());
+            }
+        }
+
+        // Round 1: distribute commitments and shares.
+        for p in participants.iter_mut() {
+            for c in broadcasts.iter() {
+                if c.dealer != p.index() {
+                    assert!(p.receive_commitments(c));
+                }
+            }
+            for s in private.iter().filter(|s| s.recipient == p.index()) {
+                let valid = p.receive_share(s);
+                assert_eq!(valid, !cheaters.contains(&s.dealer) || s.recipient.0 != 1);
+            }
+        }
+
+        // Round 2: complaints.
+        let complaints = participants
+            .iter()
+            .flat_map(|p| p.complaints())
+            .collect::<Vec<_>>();
+        assert_eq!(complaints.len(), cheaters.len());
+
+        // Round 3: justifications.
+        let mut justifications = Vec::new();
+        for p in participants.iter_mut() {
+            for c in complaints.iter() {
+                if let Some(j) = p.receive_complaint(c) {
+                    if !silent.contains(&p.index()) {
+                        justifications.push(serialize_deserialize(&j).unwrap());
+                    }
+                }
+            }
+        }
+        for p in participants.iter_mut() {
+            for j in justifications.iter() {
+                p.receive_justification(j);
+            }
+        }
+
+        participants
+            .into_iter()
+            .map(|p| p.finish().expect("Some dealer is qualified."))
+            .collect()
+    }
+
+    fn sign_and_combine(
+        outputs: &[(SecretKeyShare<Bls12>, ThresholdPublicKey<Bls12>)],
+        signers: &[usize],
+        m: &[u8],
+    ) -> Option<Signature<Bls12>> {
+        let gpk = &outputs[0].1;
+        let partials = signers
+            .iter()
+            .map(|&i| outputs[i].0.sign(m))
+            .collect::<Vec<_>>();
+        for partial in partials.iter() {
+            assert!(gpk.verify_partial(m, partial));
+        }
+        combine_partial_signatures(gpk.parameters.threshold(), &partials)
+    }
+
+    #[test]
+    fn test_threshold_sign() {
+        let mut rng: StdRng = SeedableRng::from_rng(thread_rng()).unwrap();
+        let parameters = DkgParameters::new(5, Threshold(3)).unwrap();
+        let outputs = run_dkg(parameters, &[], &[], &mut rng);
+
+        // All participants agree on the public outcome.
+        let gpk = &outputs[0].1;
+        for (share, pk) in outputs.iter() {
+            assert_eq!(pk.public_key, gpk.public_key);
+            assert_eq!(pk.key_shares, gpk.key_shares);
+            assert_eq!(
+                PublicKey::from_secret(&share.secret),
+                gpk.key_shares[usize::from(share.index.0) - 1]
+            );
+        }
+
+        let m = b"finalization record";
+        for signers in [[0, 1, 2], [4, 2, 0], [1, 3, 4]].iter() {
+            let sig = sign_and_combine(&outputs, signers, m).expect("Enough partial signatures.");
+            assert!(gpk.public_key.verify(m, sig));
+            assert!(!gpk.public_key.verify(b"another message", sig));
+        }
+        // Any set of signers gives the same signature.
+        assert_eq!(
+            sign_and_combine(&outputs, &[0, 1, 2], m),
+            sign_and_combine(&outputs, &[2, 3, 4], m)
+        );
+        // Too few distinct signers.
+        assert!(sign_and_combine(&outputs, &[0, 1], m).is_none());
+        assert!(sign_and_combine(&outputs, &[0, 1, 1], m).is_none());
+    }
+
+    #[test]
+    fn test_dkg_complaints() {
+        let mut rng: StdRng = SeedableRng::from_rng(thread_rng()).unwrap();
+        let parameters = DkgParameters::new(5, Threshold(3)).unwrap();
+        let cheaters = [ParticipantIndex(2), ParticipantIndex(4)];
+
+        // Dealer 2 justifies itself and stays qualified, dealer 4 does not and
+        // is disqualified.
+        let outputs = run_dkg(parameters, &cheaters, &[ParticipantIndex(4)], &mut rng);
+        let gpk = &outputs[0].1;
+        for (_, pk) in outputs.iter() {
+            assert_eq!(pk.public_key, gpk.public_key);
+        }
+        let m = b"finalization record";
+        let sig = sign_and_combine(&outputs, &[0, 3, 4], m).expect("Enough partial signatures.");
+        assert!(gpk.public_key.verify(m, sig));
+    }
+
+    #[test]
+    fn test_dkg_rejects_invalid_commitments() {
+        let mut rng: StdRng = SeedableRng::from_rng(thread_rng()).unwrap();
+        let parameters = DkgParameters::new(3, Threshold(2)).unwrap();
+        let (mut p1, _, _) =
+            DkgParticipant::<Bls12>::new(parameters, ParticipantIndex(1), &mut rng);
+        let (_, c2, _) = DkgParticipant::<Bls12>::new(parameters, ParticipantIndex(2), &mut rng);
+        let (_, c3, _) = DkgParticipant::<Bls12>::new(parameters, ParticipantIndex(3), &mut rng);
+
+        // A proof of knowledge is bound to the dealer.
+        let mut c3_as_2 = c3.clone();
+        c3_as_2.dealer = ParticipantIndex(2);
+        assert!(!p1.receive_commitments(&c3_as_2));
+        // Commitments are only processed once per dealer, so dealer 2 stays
+        // disqualified.
+        assert!(!p1.receive_commitments(&c2));
+        assert!(p1.receive_commitments(&c3));
+        assert_eq!(p1.qualified(), vec![
+            ParticipantIndex(1),
+            ParticipantIndex(3)
+        ]);
+        assert!(DkgParameters::new(3, Threshold(4)).is_none());
+    }
+}
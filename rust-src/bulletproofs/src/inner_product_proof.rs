@@ -139,18 +139,8 @@ pub fn prove_inner_product_with_scalars<C: Curve>(
         };
 
         let G_scalars = [u_j_inv, u_j];
-        let mut H_scalars = [u_j, u_j_inv];
-
-        for i in 0..a_lo.len() {
-            let a_lo = &a_vec[..n / 2];
-            let a_hi = &a_vec[n / 2..];
-            let G_lo = &G_vec[..n / 2];
-            let G_hi = &G_vec[n / 2..];
-            let b_lo = &b_vec[..n / 2];
-            let b_hi = &b_vec[n / 2..];
-            let H_lo = &H_vec[..n / 2];
-            let H_hi = &H_vec[n / 2..];
-            let a_lo_len = a_lo.len();
+        let half = n / 2;
+        let folded = map_indices(half, |i| {
             // Calculating new a vector:
             let mut a_lo_u_j = a_lo[i];
             a_lo_u_j.mul_assign(&u_j);
@@ -158,9 +148,8 @@ pub fn prove_inner_product_with_scalars<C: Curve>(
             let mut u_j_inv_a_hi = a_hi[i];
             u_j_inv_a_hi.mul_assign(&u_j_inv);
 
-            let mut sum = a_lo_u_j;
-            sum.add_assign(&u_j_inv_a_hi);
-            a_vec[i] = sum;
+            let mut new_a = a_lo_u_j;
+            new_a.add_assign(&u_j_inv_a_hi);
 
             // Calculating new b vector:
             let mut b_lo_u_j_inv = b_lo[i];
@@ -169,26 +158,28 @@ pub fn prove_inner_product_with_scalars<C: Curve>(
             let mut u_j_b_hi = b_hi[i];
             u_j_b_hi.mul_assign(&u_j);
 
-            let mut sum = b_lo_u_j_inv;
-            sum.add_assign(&u_j_b_hi);
-            b_vec[i] = sum;
+            let mut new_b = b_lo_u_j_inv;
+            new_b.add_assign(&u_j_b_hi);
 
             // Calculating new G vector:
             let G_points = [G_lo[i], G_hi[i]];
-            let sum = multiexp(&G_points, &G_scalars);
-            G_vec[i] = sum;
+            let new_G = multiexp(&G_points, &G_scalars);
 
             // Calculating new H vector:
             let H_points = [H_lo[i], H_hi[i]];
+            let mut H_scalars = [u_j, u_j_inv];
             if j == 0 {
-                let mut u_j = u_j;
-                let mut u_j_inv = u_j_inv;
-                u_j.mul_assign(&H_prime_scalars[i]);
-                u_j_inv.mul_assign(&H_prime_scalars[i + a_lo_len]);
-                H_scalars = [u_j, u_j_inv];
+                H_scalars[0].mul_assign(&H_prime_scalars[i]);
+                H_scalars[1].mul_assign(&H_prime_scalars[i + half]);
             }
-            let sum = multiexp(&H_points, &H_scalars);
-            H_vec[i] = sum;
+            let new_H = multiexp(&H_points, &H_scalars);
+            (new_a, new_b, new_G, new_H)
+        });
+        for (i, (new_a, new_b, new_G, new_H)) in folded.into_iter().enumerate() {
+            a_vec[i] = new_a;
+            b_vec[i] = new_b;
+            G_vec[i] = new_G;
+            H_vec[i] = new_H;
         }
         n /= 2;
     }
@@ -199,6 +190,18 @@ pub fn prove_inner_product_with_scalars<C: Curve>(
     Some(InnerProductProof { lr_vec: L_R, a, b })
 }
 
+/// Compute `f(0), ..., f(n-1)`. With the `parallel` feature the values are
+/// computed in parallel. This is used for folding the vectors in each round of
+/// the inner product proof, where each entry is computed independently.
+#[cfg(feature = "parallel")]
+fn map_indices<T: Send, F: Fn(usize) -> T + Send + Sync>(n: usize, f: F) -> Vec<T> {
+    use rayon::prelude::*;
+    (0..n).into_par_iter().map(f).collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn map_indices<T, F: Fn(usize) -> T>(n: usize, f: F) -> Vec<T> { (0..n).map(f).collect() }
+
 /// This struct contains vectors of scalars that are needed for verification.
 /// Both u_sq and u_inv_sq have to be of equal length k, and s has to be of
 /// length 2^k.
@@ -429,38 +429,95 @@ pub enum VerificationError {
     NotEnoughGenerators,
 }
 
-/// This function verifies a range proof, i.e. a proof of knowledge
-/// of value v_1, v_2, ..., v_m that are all in [0, 2^n) that are consistent
-/// with commitments V_i to v_i. The arguments are
-/// - n - the number n such that each v_i is claimed to be in [0, 2^n) by the
-///   prover
-/// - commitments - commitments V_i to each v_i
-/// - proof - the range proof
-/// - gens - generators containing vectors G and H both of length nm
-/// - v_keys - commitment keys B and B_tilde
-///
-/// This function is more efficient than the naive_verify since it
-/// unfolds what the inner product proof verifier does using the verification
-/// scalars.
+/// A linear combination of points that must equal the zero point for a range
+/// proof to be valid. The scalars of the points that are the same for all
+/// proofs, i.e., the commitment key and the generators, are kept separately so
+/// that combining the checks of many proofs does not grow the
+/// multi-exponentiation for these points.
+#[allow(non_snake_case)]
+struct ZeroCheck<C: Curve> {
+    B_scalar:       C::Scalar,
+    B_tilde_scalar: C::Scalar,
+    G_scalars:      Vec<C::Scalar>,
+    H_scalars:      Vec<C::Scalar>,
+    points:         Vec<C>,
+    scalars:        Vec<C::Scalar>,
+}
+
+impl<C: Curve> ZeroCheck<C> {
+    fn new() -> Self {
+        ZeroCheck {
+            B_scalar:       C::Scalar::zero(),
+            B_tilde_scalar: C::Scalar::zero(),
+            G_scalars:      Vec::new(),
+            H_scalars:      Vec::new(),
+            points:         Vec::new(),
+            scalars:        Vec::new(),
+        }
+    }
+
+    /// Add `factor` times the other linear combination to this one.
+    fn add_scaled(&mut self, other: Self, factor: &C::Scalar) {
+        let scale = |mut s: C::Scalar| {
+            s.mul_assign(factor);
+            s
+        };
+        self.B_scalar.add_assign(&scale(other.B_scalar));
+        self.B_tilde_scalar.add_assign(&scale(other.B_tilde_scalar));
+        add_scaled_vec(&mut self.G_scalars, other.G_scalars, factor);
+        add_scaled_vec(&mut self.H_scalars, other.H_scalars, factor);
+        self.points.extend(other.points);
+        self.scalars.extend(other.scalars.into_iter().map(scale));
+    }
+
+    /// Evaluate the linear combination, and check that it is the zero point.
+    fn is_zero(&self, gens: &Generators<C>, v_keys: &CommitmentKey<C>) -> bool {
+        let n = 2 + self.G_scalars.len() + self.H_scalars.len() + self.points.len();
+        let mut points = Vec::with_capacity(n);
+        let mut scalars = Vec::with_capacity(n);
+        points.push(v_keys.g);
+        scalars.push(self.B_scalar);
+        points.push(v_keys.h);
+        scalars.push(self.B_tilde_scalar);
+        points.extend(gens.G_H.iter().take(self.G_scalars.len()).map(|gh| gh.0));
+        scalars.extend_from_slice(&self.G_scalars);
+        points.extend(gens.G_H.iter().take(self.H_scalars.len()).map(|gh| gh.1));
+        scalars.extend_from_slice(&self.H_scalars);
+        points.extend_from_slice(&self.points);
+        scalars.extend_from_slice(&self.scalars);
+        multiexp(&points, &scalars).is_zero_point()
+    }
+}
+
+/// Add `factor` times `other` to `acc` pointwise, extending `acc` with zeros if
+/// it is shorter.
+fn add_scaled_vec<F: Field>(acc: &mut Vec<F>, other: Vec<F>, factor: &F) {
+    if acc.len() < other.len() {
+        acc.resize(other.len(), F::zero());
+    }
+    for (a, mut s) in acc.iter_mut().zip(other) {
+        s.mul_assign(factor);
+        a.add_assign(&s);
+    }
+}
+
+/// Compute the two linear combinations of points that must be zero for the
+/// range proof to be valid. This updates the transcript in the same way as the
+/// prover does. See [verify_efficient] for the meaning of the arguments.
 #[allow(non_snake_case)]
-#[allow(clippy::too_many_arguments)]
 #[allow(clippy::many_single_char_names)]
-pub fn verify_efficient<C: Curve>(
+fn verification_equations<C: Curve>(
     transcript: &mut RandomOracle,
     n: u8,
     commitments: &[Commitment<C>],
     proof: &RangeProof<C>,
     gens: &Generators<C>,
-    v_keys: &CommitmentKey<C>,
-) -> Result<(), VerificationError> {
+) -> Result<(ZeroCheck<C>, ZeroCheck<C>), VerificationError> {
     let m = commitments.len();
     let nm = usize::from(n) * m;
     if gens.G_H.len() < nm {
         return Err(VerificationError::NotEnoughGenerators);
     }
-    let (G, H): (Vec<_>, Vec<_>) = gens.G_H.iter().take(nm).cloned().unzip();
-    let B = v_keys.g;
-    let B_tilde = v_keys.h;
     for V in commitments {
         transcript.append_message(b"Vj", &V.0);
     }
@@ -484,7 +541,6 @@ pub fn verify_efficient<C: Curve>(
     let x: C::Scalar = transcript.challenge_scalar::<C, _>(b"x");
     let mut x2 = x;
     x2.mul_assign(&x);
-    // println!("verifier's x = {:?}", x);
     transcript.append_message(b"tx", &tx);
     transcript.append_message(b"tx_tilde", &tx_tilde);
     transcript.append_message(b"e_tilde", &e_tilde);
@@ -492,7 +548,7 @@ pub fn verify_efficient<C: Curve>(
     // Calculate delta(x,y):
     let mut ip_1_y_nm = C::Scalar::zero();
     let mut yi = C::Scalar::one();
-    for _ in 0..G.len() {
+    for _ in 0..nm {
         ip_1_y_nm.add_assign(&yi);
         yi.mul_assign(&y);
     }
@@ -514,37 +570,37 @@ pub fn verify_efficient<C: Curve>(
     delta_yz.mul_assign(&ip_1_y_nm);
     delta_yz.sub_assign(&sum);
 
-    // LHS of check equation 1:
-    let LHS = B
-        .mul_by_scalar(&tx)
-        .plus_point(&B_tilde.mul_by_scalar(&tx_tilde));
-    let mut RHS = {
-        let mut zj2 = z2;
-        let mut powers = Vec::with_capacity(m);
-        for _ in 0..m {
-            powers.push(zj2);
-            zj2.mul_assign(&z);
-        }
-        multiexp::<C, Commitment<C>>(commitments, &powers)
-    };
-
-    RHS = RHS.plus_point(&multiexp(&[B, T_1, T_2], &[delta_yz, x, x2]));
-
-    let first = LHS.minus_point(&RHS).is_zero_point();
-    if !first {
-        // Terminate early to avoid wasted effort.
-        return Err(VerificationError::First);
+    // First check: B^tx B_tilde^tx_tilde = prod_j V_j^(z^(j+2)) B^delta T_1^x
+    // T_2^(x^2)
+    let mut first = ZeroCheck::new();
+    first.B_scalar = tx;
+    first.B_scalar.sub_assign(&delta_yz);
+    first.B_tilde_scalar = tx_tilde;
+    let mut minus_zj2 = z2;
+    minus_zj2.negate();
+    for V in commitments {
+        first.points.push(V.0);
+        first.scalars.push(minus_zj2);
+        minus_zj2.mul_assign(&z);
     }
-
+    let mut minus_x = x;
+    minus_x.negate();
+    let mut minus_x2 = x2;
+    minus_x2.negate();
+    first.points.push(T_1);
+    first.scalars.push(minus_x);
+    first.points.push(T_2);
+    first.scalars.push(minus_x2);
+
+    // Second check, the unfolded inner product proof.
     let ip_proof = &proof.ip_proof;
-    let mut H_scalars: Vec<C::Scalar> = Vec::with_capacity(G.len());
+    let mut H_scalars: Vec<C::Scalar> = Vec::with_capacity(nm);
     let mut y_i = C::Scalar::one();
     let z_2_m = z_vec(z, 2, m);
-    let verification_scalars = verify_scalars(transcript, G.len(), &ip_proof);
-    if verification_scalars.is_none() {
-        return Err(VerificationError::DivisionError);
-    }
-    let verification_scalars = verification_scalars.unwrap();
+    let verification_scalars = match verify_scalars(transcript, nm, &ip_proof) {
+        Some(scalars) => scalars,
+        None => return Err(VerificationError::DivisionError),
+    };
     let (u_sq, u_inv_sq, s) = (
         verification_scalars.u_sq,
         verification_scalars.u_inv_sq,
@@ -552,7 +608,6 @@ pub fn verify_efficient<C: Curve>(
     );
     let a = ip_proof.a;
     let b = ip_proof.b;
-    let (L, R): (Vec<_>, Vec<_>) = ip_proof.lr_vec.iter().cloned().unzip();
     let mut s_inv = s;
     s_inv.reverse();
     let y_inv = match y.inverse() {
@@ -560,7 +615,7 @@ pub fn verify_efficient<C: Curve>(
         None => return Err(VerificationError::DivisionError),
     };
     let two_n: Vec<C::Scalar> = two_n_vec(n);
-    for i in 0..G.len() {
+    for i in 0..nm {
         let j = i / usize::from(n);
         let mut H_scalar = two_n[i % usize::from(n)];
         H_scalar.mul_assign(&z_2_m[j]);
@@ -574,20 +629,14 @@ pub fn verify_efficient<C: Curve>(
     }
     s_inv.reverse();
     let s = s_inv;
-    let H_term = multiexp(&H, &H_scalars); // Expensive!
-    let A_term = A;
-    let S_term = S.mul_by_scalar(&x);
     let mut B_scalar = tx;
     let mut ab = a;
     ab.mul_assign(&b);
     B_scalar.sub_assign(&ab);
     B_scalar.mul_assign(&w);
-    let B_term = B.mul_by_scalar(&B_scalar);
     let mut minus_e_tilde = e_tilde;
     minus_e_tilde.negate();
-    let B_tilde_scalar = minus_e_tilde;
-    let B_tilde_term = B_tilde.mul_by_scalar(&B_tilde_scalar);
-    let mut G_scalars = Vec::with_capacity(G.len());
+    let mut G_scalars = Vec::with_capacity(nm);
     for si in s {
         let mut G_scalar = z;
         G_scalar.negate();
@@ -596,27 +645,133 @@ pub fn verify_efficient<C: Curve>(
         G_scalar.sub_assign(&sa);
         G_scalars.push(G_scalar);
     }
-    let G_term = multiexp(&G, &G_scalars); // Expensive!
-    let L_term = multiexp(&L, &u_sq); // Expensive!
-    let R_term = multiexp(&R, &u_inv_sq); // Expensive!
-
-    let sum = A_term
-        .plus_point(&S_term)
-        .plus_point(&B_term)
-        .plus_point(&B_tilde_term)
-        .plus_point(&G_term)
-        .plus_point(&H_term)
-        .plus_point(&L_term)
-        .plus_point(&R_term);
-
-    let second = sum.is_zero_point();
-    if first && second {
+    let mut second = ZeroCheck::new();
+    second.B_scalar = B_scalar;
+    second.B_tilde_scalar = minus_e_tilde;
+    second.G_scalars = G_scalars;
+    second.H_scalars = H_scalars;
+    second.points.push(A);
+    second.scalars.push(C::Scalar::one());
+    second.points.push(S);
+    second.scalars.push(x);
+    for ((L, R), (u_sq, u_inv_sq)) in ip_proof.lr_vec.iter().zip(u_sq.iter().zip(u_inv_sq.iter())) {
+        second.points.push(*L);
+        second.scalars.push(*u_sq);
+        second.points.push(*R);
+        second.scalars.push(*u_inv_sq);
+    }
+    Ok((first, second))
+}
+
+/// This function verifies a range proof, i.e. a proof of knowledge
+/// of value v_1, v_2, ..., v_m that are all in [0, 2^n) that are consistent
+/// with commitments V_i to v_i. The arguments are
+/// - n - the number n such that each v_i is claimed to be in [0, 2^n) by the
+///   prover
+/// - commitments - commitments V_i to each v_i
+/// - proof - the range proof
+/// - gens - generators containing vectors G and H both of length nm
+/// - v_keys - commitment keys B and B_tilde
+///
+/// This function is more efficient than the naive_verify since it
+/// unfolds what the inner product proof verifier does using the verification
+/// scalars.
+pub fn verify_efficient<C: Curve>(
+    transcript: &mut RandomOracle,
+    n: u8,
+    commitments: &[Commitment<C>],
+    proof: &RangeProof<C>,
+    gens: &Generators<C>,
+    v_keys: &CommitmentKey<C>,
+) -> Result<(), VerificationError> {
+    let (first, second) = verification_equations(transcript, n, commitments, proof, gens)?;
+    if !first.is_zero(gens, v_keys) {
+        return Err(VerificationError::First);
+    }
+    if !second.is_zero(gens, v_keys) {
+        return Err(VerificationError::Second);
+    }
+    Ok(())
+}
+
+/// The verification equations of a single range proof, to be added to a
+/// [RangeProofBatch]. Computing them updates the transcript, which is the part
+/// of the verification that must be done in order, so the equations can be
+/// computed independently for many proofs and added to a batch afterwards.
+pub struct RangeProofEquations<C: Curve> {
+    first:  ZeroCheck<C>,
+    second: ZeroCheck<C>,
+}
+
+impl<C: Curve> RangeProofEquations<C> {
+    /// Compute the equations of the proof. The arguments, and the way the
+    /// transcript is updated, are the same as for [verify_efficient]. An error
+    /// is returned if the proof is malformed, in which case it is invalid.
+    pub fn new(
+        transcript: &mut RandomOracle,
+        n: u8,
+        commitments: &[Commitment<C>],
+        proof: &RangeProof<C>,
+        gens: &Generators<C>,
+    ) -> Result<Self, VerificationError> {
+        let (first, second) = verification_equations(transcript, n, commitments, proof, gens)?;
+        Ok(RangeProofEquations { first, second })
+    }
+}
+
+/// Verification of many range proofs at once. The equations of each proof
+/// are combined with random weights into a single multi-exponentiation, which
+/// is zero (except with negligible probability) only if all the proofs are
+/// valid. All the proofs must be with respect to the same generators and
+/// commitment key.
+///
+/// If the combined check fails it does not tell which proof is invalid, so the
+/// proofs must then be verified individually to find out.
+pub struct RangeProofBatch<'a, C: Curve> {
+    gens:   &'a Generators<C>,
+    v_keys: &'a CommitmentKey<C>,
+    check:  ZeroCheck<C>,
+}
+
+impl<'a, C: Curve> RangeProofBatch<'a, C> {
+    /// Start an empty batch.
+    pub fn new(gens: &'a Generators<C>, v_keys: &'a CommitmentKey<C>) -> Self {
+        RangeProofBatch {
+            gens,
+            v_keys,
+            check: ZeroCheck::new(),
+        }
+    }
+
+    /// Add a proof to the batch. The arguments, and the way the transcript is
+    /// updated, are the same as for [verify_efficient]. An error is returned
+    /// if the proof could not be added, in which case it is invalid, and the
+    /// batch is unchanged.
+    pub fn add<R: Rng>(
+        &mut self,
+        csprng: &mut R,
+        transcript: &mut RandomOracle,
+        n: u8,
+        commitments: &[Commitment<C>],
+        proof: &RangeProof<C>,
+    ) -> Result<(), VerificationError> {
+        let equations = RangeProofEquations::new(transcript, n, commitments, proof, self.gens)?;
+        self.add_equations(csprng, equations);
         Ok(())
-    } else {
-        // We know now the second check failed since we would have terminated
-        // early if the first one had failed.
-        Err(VerificationError::Second)
     }
+
+    /// Add the equations of a proof that were computed separately, e.g., in
+    /// parallel with other work. The equations must have been computed with
+    /// the generators of this batch.
+    pub fn add_equations<R: Rng>(&mut self, csprng: &mut R, equations: RangeProofEquations<C>) {
+        self.check
+            .add_scaled(equations.first, &C::generate_non_zero_scalar(csprng));
+        self.check
+            .add_scaled(equations.second, &C::generate_non_zero_scalar(csprng));
+    }
+
+    /// Check all the proofs in the batch at once. An empty batch is valid.
+    pub fn verify(&self) -> bool { self.check.is_zero(self.gens, self.v_keys) }
 }
 
 /// For proving that a <= b for integers a,b
@@ -669,6 +824,7 @@ pub fn verify_less_than_or_equal<C: Curve>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use curve_arithmetic::ristretto::RistrettoPoint;
     use pairing::bls12_381::G1;
 
     /// This function produces a proof that will satisfy the verifier's first
@@ -1398,4 +1554,97 @@ mod tests {
         );
         assert!(!b2);
     }
+
+    #[allow(non_snake_case)]
+    #[test]
+    fn test_batch_verification() {
+        let rng = &mut thread_rng();
+        let n = 32;
+        let gens = Generators::generate(usize::from(n) * 4, rng);
+        let keys = CommitmentKey::<SomeCurve>::generate(rng);
+
+        let mut batch = RangeProofBatch::new(&gens, &keys);
+        assert!(batch.verify(), "The empty batch should verify.");
+        // Proofs with different numbers of values use different numbers of
+        // generators.
+        for m in 1..=4u8 {
+            let v_vec: Vec<u64> = (0..m).map(|_| rng.gen_range(0, 1 << n)).collect();
+            let randomness: Vec<_> = (0..m).map(|_| Randomness::generate(rng)).collect();
+            let commitments: Vec<_> = v_vec
+                .iter()
+                .zip(randomness.iter())
+                .map(|(&v, r)| keys.hide(&Value::new(SomeCurve::scalar_from_u64(v)), r))
+                .collect();
+            let mut transcript = RandomOracle::empty();
+            let proof = prove(
+                &mut transcript,
+                rng,
+                n,
+                m,
+                &v_vec,
+                &gens,
+                &keys,
+                &randomness,
+            )
+            .expect("Proving should succeed.");
+            let mut transcript = RandomOracle::empty();
+            assert_eq!(
+                batch.add(rng, &mut transcript, n, &commitments, &proof),
+                Ok(())
+            );
+        }
+        assert!(batch.verify(), "A batch of valid proofs should verify.");
+
+        let (G, H): (Vec<_>, Vec<_>) = gens.G_H.iter().take(usize::from(n) * 2).cloned().unzip();
+        let mut transcript = RandomOracle::empty();
+        let (commitments, proof) =
+            cheat_prove(n, 2, vec![7, 4], G, H, keys.g, keys.h, rng, &mut transcript);
+        let proof = proof.expect("The cheating prover should produce a proof.");
+        let mut transcript = RandomOracle::empty();
+        assert_eq!(
+            batch.add(rng, &mut transcript, n, &commitments, &proof),
+            Ok(())
+        );
+        assert!(
+            !batch.verify(),
+            "A batch with an invalid proof should fail."
+        );
+    }
+
+    #[test]
+    fn test_prove_ristretto() {
+        let rng = &mut thread_rng();
+        let n = 32;
+        let m = 4;
+        let gens = Generators::<RistrettoPoint>::generate(usize::from(n) * usize::from(m), rng);
+        let keys = CommitmentKey::<RistrettoPoint>::generate(rng);
+        let v_vec: Vec<u64> = (0..m).map(|_| rng.gen_range(0, 1 << n)).collect();
+        let randomness: Vec<_> = (0..m).map(|_| Randomness::generate(rng)).collect();
+        let commitments: Vec<_> = v_vec
+            .iter()
+            .zip(randomness.iter())
+            .map(|(&v, r)| keys.hide(&Value::new(RistrettoPoint::scalar_from_u64(v)), r))
+            .collect();
+        let mut transcript = RandomOracle::empty();
+        let proof = prove(
+            &mut transcript,
+            rng,
+            n,
+            m,
+            &v_vec,
+            &gens,
+            &keys,
+            &randomness,
+        )
+        .expect("Proving should succeed.");
+        let mut transcript = RandomOracle::empty();
+        assert!(verify_efficient(&mut transcript, n, &commitments, &proof, &gens, &keys).is_ok());
+        let mut swapped = commitments.clone();
+        swapped.swap(0, 1);
+        let mut transcript = RandomOracle::empty();
+        assert!(
+            verify_efficient(&mut transcript, n, &swapped, &proof, &gens, &keys).is_err(),
+            "The proof should not verify for other commitments."
+        );
+    }
 }
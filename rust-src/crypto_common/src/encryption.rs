@@ -1,22 +1,36 @@
 use crate::{SerdeDeserialize, SerdeSerialize};
 use aes::Aes256;
+use aes_gcm::{
+    aead::{generic_array::GenericArray, Aead, NewAead},
+    Aes256Gcm,
+};
 use block_modes::{block_padding::Pkcs7, BlockMode, Cbc};
+use chacha20poly1305::ChaCha20Poly1305;
 use hmac::Hmac;
 use rand::Rng;
 use serde::{Deserializer, Serializer};
 use std::{convert::TryInto, str::FromStr};
 use thiserror::Error;
+use zeroize::{Zeroize, Zeroizing};
 
-type Cipher = Cbc<Aes256, Pkcs7>;
+type AesCbc = Cbc<Aes256, Pkcs7>;
 
 /// AES block size in bytes
 pub const AES_BLOCK_SIZE: usize = 16;
 
+/// Size of the nonces of the AEAD ciphers in bytes.
+pub const AEAD_NONCE_SIZE: usize = 12;
+
 /// A wrapper to make it less likely to abuse passwords.
 pub struct Password {
     password: String,
 }
 
+/// Overwrite the password with zeros when it goes out of scope.
+impl Drop for Password {
+    fn drop(&mut self) { self.password.zeroize() }
+}
+
 impl From<String> for Password {
     fn from(password: String) -> Self { Password { password } }
 }
@@ -41,62 +55,111 @@ fn from_base64<'de, D: Deserializer<'de>, X: From<Vec<u8>>>(des: D) -> Result<X,
     Ok(X::from(decoded))
 }
 
-/// This is needed before Rust 1.48 due to lacking TryFrom instances for Vec.
-fn from_base64_array<'de, D: Deserializer<'de>>(des: D) -> Result<[u8; AES_BLOCK_SIZE], D::Error> {
+fn from_base64_array<'de, D: Deserializer<'de>, const N: usize>(
+    des: D,
+) -> Result<[u8; N], D::Error> {
     use serde::de::Error;
-    let data: Box<[u8]> = from_base64(des)?;
-    let arr: Box<[u8; AES_BLOCK_SIZE]> = data
-        .try_into()
-        .map_err(|_| Error::custom("Data of incorrect length."))?;
-    Ok(*arr)
+    let data: Vec<u8> = from_base64(des)?;
+    data.try_into()
+        .map_err(|_| Error::custom("Data of incorrect length."))
 }
 
-#[derive(SerdeSerialize, SerdeDeserialize)]
-/// Supported encryption methods.
+#[derive(Debug, Clone, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
+#[serde(tag = "encryptionMethod")]
+/// Supported encryption methods, together with their parameters.
 pub enum EncryptionMethod {
     #[serde(rename = "AES-256")]
-    Aes256,
+    /// AES-256 in CBC mode with PKCS7 padding. This is not authenticated, so
+    /// decryption with the wrong password or of modified data can produce
+    /// garbage. It is supported for compatibility with existing wallet
+    /// exports.
+    Aes256 {
+        #[serde(
+            rename = "initializationVector",
+            serialize_with = "as_base64",
+            deserialize_with = "from_base64_array"
+        )]
+        initialization_vector: [u8; AES_BLOCK_SIZE],
+    },
+    #[serde(rename = "AES-256-GCM")]
+    /// AES-256 in GCM mode.
+    Aes256Gcm {
+        #[serde(
+            rename = "nonce",
+            serialize_with = "as_base64",
+            deserialize_with = "from_base64_array"
+        )]
+        nonce: [u8; AEAD_NONCE_SIZE],
+    },
+    #[serde(rename = "ChaCha20-Poly1305")]
+    /// ChaCha20-Poly1305 as specified in RFC 8439.
+    ChaCha20Poly1305 {
+        #[serde(
+            rename = "nonce",
+            serialize_with = "as_base64",
+            deserialize_with = "from_base64_array"
+        )]
+        nonce: [u8; AEAD_NONCE_SIZE],
+    },
 }
 
-#[derive(SerdeSerialize, SerdeDeserialize)]
-/// Supported key derivation methods.
+#[derive(Debug, Clone, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
+#[serde(tag = "keyDerivationMethod")]
+/// Supported key derivation methods, together with their parameters.
 pub enum KeyDerivationMethod {
     #[serde(rename = "PBKDF2WithHmacSHA256")]
-    Pbkdf2Sha256,
+    Pbkdf2Sha256 {
+        #[serde(rename = "iterations")]
+        /// Number of iterations for the key derivation function.
+        iterations: u32,
+        #[serde(
+            rename = "salt",
+            serialize_with = "as_base64",
+            deserialize_with = "from_base64"
+        )]
+        /// Salt used for the key derivation process.
+        salt:       Vec<u8>,
+    },
+    #[serde(rename = "Argon2id")]
+    Argon2id {
+        #[serde(rename = "memoryCost")]
+        /// Memory used, in KiB.
+        memory_cost: u32,
+        #[serde(rename = "timeCost")]
+        /// Number of passes over the memory.
+        time_cost:   u32,
+        #[serde(rename = "parallelism")]
+        /// Number of lanes.
+        parallelism: u32,
+        #[serde(
+            rename = "salt",
+            serialize_with = "as_base64",
+            deserialize_with = "from_base64"
+        )]
+        /// Salt used for the key derivation process.
+        salt:        Vec<u8>,
+    },
 }
 
-#[derive(SerdeSerialize, SerdeDeserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
 /// Metadata that enables decryption of some encrypted data provided an
 /// a password is provided.
-// This type is defined to be compatible with exports in the wallet, but
-// it is not the best as it is. The encryption and key derivation methods
-// themselves define other fields, such as the number of iterations and salt.
-// A better modelling would be for this to be an enumeration.
+// The fields of both methods are flattened into the metadata object. This is
+// how the wallet exports, which only use AES-256 and PBKDF2, are laid out.
 pub struct EncryptionMetadata {
-    #[serde(rename = "encryptionMethod")]
+    #[serde(flatten)]
     encryption_method:     EncryptionMethod,
-    #[serde(rename = "keyDerivationMethod")]
+    #[serde(flatten)]
     key_derivation_method: KeyDerivationMethod,
-    #[serde(rename = "iterations")]
-    /// Number of iterations for the key derivation function.
-    iterations:            u32,
-    #[serde(
-        rename = "salt",
-        serialize_with = "as_base64",
-        deserialize_with = "from_base64"
-    )]
-    /// Salt used for the key derivation process.
-    salt:                  Vec<u8>,
-    #[serde(
-        rename = "initializationVector",
-        serialize_with = "as_base64",
-        deserialize_with = "from_base64_array"
-    )]
-    /// Initialization vector for AES CBC mode encryption.
-    initialization_vector: [u8; AES_BLOCK_SIZE],
-}
-
-#[derive(SerdeSerialize, SerdeDeserialize)]
+}
+
+impl EncryptionMetadata {
+    pub fn encryption_method(&self) -> &EncryptionMethod { &self.encryption_method }
+
+    pub fn key_derivation_method(&self) -> &KeyDerivationMethod { &self.key_derivation_method }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
 #[serde(transparent)]
 /// A wrapper around a byte array to represent a ciphertext. JSON encodings are
 /// in base64.
@@ -105,7 +168,7 @@ pub struct CipherText {
     ct: Vec<u8>,
 }
 
-#[derive(SerdeSerialize, SerdeDeserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
 /// Ciphertext together with metadata describing the encryption method.
 pub struct EncryptedData {
     #[serde(rename = "metadata")]
@@ -114,95 +177,416 @@ pub struct EncryptedData {
     cipher_text: CipherText,
 }
 
-/// The number of rounds of the key derivation function to use for hashing the
-/// password.
+impl EncryptedData {
+    pub fn metadata(&self) -> &EncryptionMetadata { &self.metadata }
+}
+
+/// The number of rounds of PBKDF2 to use for hashing the password.
 pub const NUM_ROUNDS: u32 = 100000;
 
+/// Memory cost of Argon2id in KiB used by [encrypt]. This and the other
+/// Argon2id parameters are the second recommended option of RFC 9106.
+pub const ARGON2_MEMORY_COST: u32 = 64 * 1024;
+
+/// Number of passes of Argon2id used by [encrypt].
+pub const ARGON2_TIME_COST: u32 = 3;
+
+/// Number of lanes of Argon2id used by [encrypt].
+pub const ARGON2_PARALLELISM: u32 = 4;
+
+/// The ciphers that data can be encrypted with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cipher {
+    /// AES-256 in CBC mode. Only use this if the data must be readable by
+    /// tools that do not support the authenticated ciphers.
+    Aes256Cbc,
+    Aes256Gcm,
+    ChaCha20Poly1305,
+}
+
+/// The parameters of key derivation chosen when encrypting. The salt is always
+/// sampled freshly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyDerivationParameters {
+    Pbkdf2Sha256 {
+        iterations: u32,
+    },
+    Argon2id {
+        memory_cost: u32,
+        time_cost:   u32,
+        parallelism: u32,
+    },
+}
+
+impl Default for KeyDerivationParameters {
+    fn default() -> Self {
+        KeyDerivationParameters::Argon2id {
+            memory_cost: ARGON2_MEMORY_COST,
+            time_cost:   ARGON2_TIME_COST,
+            parallelism: ARGON2_PARALLELISM,
+        }
+    }
+}
+
+impl From<&EncryptionMethod> for Cipher {
+    fn from(method: &EncryptionMethod) -> Self {
+        match method {
+            EncryptionMethod::Aes256 { .. } => Cipher::Aes256Cbc,
+            EncryptionMethod::Aes256Gcm { .. } => Cipher::Aes256Gcm,
+            EncryptionMethod::ChaCha20Poly1305 { .. } => Cipher::ChaCha20Poly1305,
+        }
+    }
+}
+
+impl From<&KeyDerivationMethod> for KeyDerivationParameters {
+    fn from(method: &KeyDerivationMethod) -> Self {
+        match *method {
+            KeyDerivationMethod::Pbkdf2Sha256 { iterations, .. } => {
+                KeyDerivationParameters::Pbkdf2Sha256 { iterations }
+            }
+            KeyDerivationMethod::Argon2id {
+                memory_cost,
+                time_cost,
+                parallelism,
+                ..
+            } => KeyDerivationParameters::Argon2id {
+                memory_cost,
+                time_cost,
+                parallelism,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Error)]
+/// Errors that can occur during encryption.
+pub enum EncryptionError {
+    #[error("Invalid parameters for key derivation.")]
+    KeyDerivation,
+    #[error("Encryption error.")]
+    Aead,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Error)]
+/// Errors that can occur during decryption.
+pub enum DecryptionError {
+    /// Error during AES decryption.
+    #[error("Decryption error.")]
+    BlockMode,
+    /// The parameters of the key derivation in the metadata are invalid.
+    #[error("Invalid parameters for key derivation.")]
+    KeyDerivation,
+    /// The authentication tag did not match. Either the password is wrong or
+    /// the data has been modified.
+    #[error("Decryption failed. The password is incorrect or the data is corrupted.")]
+    Authentication,
+}
+
+/// Derive a 256 bit key from the password. The key is cleared when dropped.
+fn derive_key(pass: &Password, method: &KeyDerivationMethod) -> Option<Zeroizing<[u8; 32]>> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    match method {
+        KeyDerivationMethod::Pbkdf2Sha256 { iterations, salt } => {
+            pbkdf2::pbkdf2::<Hmac<sha2::Sha256>>(
+                pass.password.as_bytes(),
+                salt,
+                *iterations,
+                &mut key[..],
+            );
+        }
+        KeyDerivationMethod::Argon2id {
+            memory_cost,
+            time_cost,
+            parallelism,
+            salt,
+        } => {
+            let config = argon2::Config {
+                variant:     argon2::Variant::Argon2id,
+                version:     argon2::Version::Version13,
+                mem_cost:    *memory_cost,
+                time_cost:   *time_cost,
+                lanes:       *parallelism,
+                thread_mode: argon2::ThreadMode::Sequential,
+                secret:      &[],
+                ad:          &[],
+                hash_length: 32,
+            };
+            let hash =
+                Zeroizing::new(argon2::hash_raw(pass.password.as_bytes(), salt, &config).ok()?);
+            key.copy_from_slice(&hash);
+        }
+    }
+    Some(key)
+}
+
 /// Encrypt the given plaintext using the provided password.
-/// This uses the AES256 cipher using PBKDF2SHA256 key derivation function
-/// using a randomly sampled salt.
-/// The number of rounds of the key derivation function is defined by the
-/// `NUM_ROUNDS` constant.
+/// This uses AES-256 in GCM mode with a key derived from the password by
+/// Argon2id with a randomly sampled salt. The parameters of Argon2id are given
+/// by the `ARGON2_*` constants.
 pub fn encrypt<A: AsRef<[u8]>, R: Rng>(
     pass: &Password,
     plaintext: &A,
     csprng: &mut R,
 ) -> EncryptedData {
-    // Derive the key for AES.
-    // The key will be 256 bits, we are using sha256.
-    let mut key = [0u8; 32];
+    encrypt_with(
+        pass,
+        plaintext,
+        Cipher::Aes256Gcm,
+        KeyDerivationParameters::default(),
+        csprng,
+    )
+    .expect("The default parameters are valid.")
+}
+
+/// Encrypt the given plaintext using the provided password with the given
+/// cipher and key derivation function. The salt, and the initialization vector
+/// or nonce, are sampled randomly.
+pub fn encrypt_with<A: AsRef<[u8]>, R: Rng>(
+    pass: &Password,
+    plaintext: &A,
+    cipher: Cipher,
+    key_derivation: KeyDerivationParameters,
+    csprng: &mut R,
+) -> Result<EncryptedData, EncryptionError> {
     // We generate a random salt, 16 bytes, as recommended by NIST.
     let salt: [u8; 16] = csprng.gen();
-    // generate the key and store it in the `key` array
-    pbkdf2::pbkdf2::<Hmac<sha2::Sha256>>(pass.password.as_bytes(), &salt, NUM_ROUNDS, &mut key);
-
-    // generate the initial block for the CBC AES mode.
-    // The initialization vector must correspond to AES256 block size, which is 128
-    // bits (16 bytes)
-    let initialization_vector: [u8; AES_BLOCK_SIZE] = csprng.gen();
-    // Construct the cipher.
-    let cipher = Cipher::new_fix((&key).into(), (&initialization_vector).into());
-    let cipher_text = CipherText {
-        ct: cipher.encrypt_vec(plaintext.as_ref()),
+    let key_derivation_method = match key_derivation {
+        KeyDerivationParameters::Pbkdf2Sha256 { iterations } => KeyDerivationMethod::Pbkdf2Sha256 {
+            iterations,
+            salt: salt.into(),
+        },
+        KeyDerivationParameters::Argon2id {
+            memory_cost,
+            time_cost,
+            parallelism,
+        } => KeyDerivationMethod::Argon2id {
+            memory_cost,
+            time_cost,
+            parallelism,
+            salt: salt.into(),
+        },
     };
-
-    let metadata = EncryptionMetadata {
-        encryption_method: EncryptionMethod::Aes256,
-        key_derivation_method: KeyDerivationMethod::Pbkdf2Sha256,
-        iterations: NUM_ROUNDS,
-        salt: salt.into(),
-        initialization_vector,
+    let key = derive_key(pass, &key_derivation_method).ok_or(EncryptionError::KeyDerivation)?;
+    let plaintext = plaintext.as_ref();
+    let (encryption_method, ct) = match cipher {
+        Cipher::Aes256Cbc => {
+            // The initialization vector must correspond to AES256 block size,
+            // which is 128 bits (16 bytes)
+            let initialization_vector: [u8; AES_BLOCK_SIZE] = csprng.gen();
+            let cipher = AesCbc::new_fix((&*key).into(), (&initialization_vector).into());
+            (
+                EncryptionMethod::Aes256 {
+                    initialization_vector,
+                },
+                cipher.encrypt_vec(plaintext),
+            )
+        }
+        Cipher::Aes256Gcm => {
+            let nonce: [u8; AEAD_NONCE_SIZE] = csprng.gen();
+            let cipher = Aes256Gcm::new(GenericArray::from_slice(&key[..]));
+            let ct = cipher
+                .encrypt(GenericArray::from_slice(&nonce), plaintext)
+                .map_err(|_| EncryptionError::Aead)?;
+            (EncryptionMethod::Aes256Gcm { nonce }, ct)
+        }
+        Cipher::ChaCha20Poly1305 => {
+            let nonce: [u8; AEAD_NONCE_SIZE] = csprng.gen();
+            let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(&key[..]));
+            let ct = cipher
+                .encrypt(GenericArray::from_slice(&nonce), plaintext)
+                .map_err(|_| EncryptionError::Aead)?;
+            (EncryptionMethod::ChaCha20Poly1305 { nonce }, ct)
+        }
     };
-    EncryptedData {
-        metadata,
-        cipher_text,
-    }
+    Ok(EncryptedData {
+        metadata:    EncryptionMetadata {
+            encryption_method,
+            key_derivation_method,
+        },
+        cipher_text: CipherText { ct },
+    })
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Error)]
-/// Errors that can occur during AES decryption.
-pub enum DecryptionError {
-    /// Error during AES decryption.
-    #[error("Decryption error.")]
-    BlockMode,
-}
-
-/// Dual to the `encrypt` method.
+/// Dual to the `encrypt` and `encrypt_with` methods.
 pub fn decrypt(pass: &Password, et: &EncryptedData) -> Result<Vec<u8>, DecryptionError> {
-    // Derive the key for AES.
-    // The key will be 256 bits, we are using sha256.
-    let mut key = [0u8; 32];
-    // generate the key and store it in the `key` array
-    pbkdf2::pbkdf2::<Hmac<sha2::Sha256>>(
-        pass.password.as_bytes(),
-        &et.metadata.salt,
-        et.metadata.iterations,
-        &mut key,
-    );
-    let cipher = Cipher::new_fix((&key).into(), (&et.metadata.initialization_vector).into());
-    cipher
-        .decrypt_vec(&et.cipher_text.ct)
-        .map_err(|_| DecryptionError::BlockMode)
+    let key = derive_key(pass, &et.metadata.key_derivation_method)
+        .ok_or(DecryptionError::KeyDerivation)?;
+    let ct = &et.cipher_text.ct;
+    match &et.metadata.encryption_method {
+        EncryptionMethod::Aes256 {
+            initialization_vector,
+        } => {
+            let cipher = AesCbc::new_fix((&*key).into(), initialization_vector.into());
+            cipher
+                .decrypt_vec(ct)
+                .map_err(|_| DecryptionError::BlockMode)
+        }
+        EncryptionMethod::Aes256Gcm { nonce } => {
+            let cipher = Aes256Gcm::new(GenericArray::from_slice(&key[..]));
+            cipher
+                .decrypt(GenericArray::from_slice(nonce), ct.as_slice())
+                .map_err(|_| DecryptionError::Authentication)
+        }
+        EncryptionMethod::ChaCha20Poly1305 { nonce } => {
+            let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(&key[..]));
+            cipher
+                .decrypt(GenericArray::from_slice(nonce), ct.as_slice())
+                .map_err(|_| DecryptionError::Authentication)
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    // Small parameters to keep the tests fast.
+    const TEST_ARGON2: KeyDerivationParameters = KeyDerivationParameters::Argon2id {
+        memory_cost: 64,
+        time_cost:   1,
+        parallelism: 1,
+    };
+
+    fn random_plaintext() -> Vec<u8> {
+        let rng = rand::thread_rng();
+        rng.sample_iter(rand::distributions::Uniform::new_inclusive(
+            u8::MIN,
+            u8::MAX,
+        ))
+        .take(1000)
+        .collect::<Vec<u8>>()
+    }
+
     #[test]
     fn encrypt_decrypt_success() {
         let pass = Password {
             password: "hello".into(),
         };
         let mut rng = rand::thread_rng();
-        let plaintext = rng
-            .sample_iter(rand::distributions::Uniform::new_inclusive(
-                u8::MIN,
-                u8::MAX,
-            ))
-            .take(1000)
-            .collect::<Vec<u8>>();
+        let plaintext = random_plaintext();
         let et = encrypt(&pass, &plaintext, &mut rng);
         let decrypted = decrypt(&pass, &et);
         assert_eq!(Ok(plaintext), decrypted, "Decryption failed.");
     }
+
+    #[test]
+    fn encrypt_with_all_methods() {
+        let pass = Password {
+            password: "hello".into(),
+        };
+        let mut rng = rand::thread_rng();
+        let plaintext = random_plaintext();
+        let kdfs = [
+            KeyDerivationParameters::Pbkdf2Sha256 { iterations: 1000 },
+            TEST_ARGON2,
+        ];
+        let ciphers = [
+            Cipher::Aes256Cbc,
+            Cipher::Aes256Gcm,
+            Cipher::ChaCha20Poly1305,
+        ];
+        for &kdf in kdfs.iter() {
+            for &cipher in ciphers.iter() {
+                let et = encrypt_with(&pass, &plaintext, cipher, kdf, &mut rng)
+                    .expect("Parameters are valid.");
+                // Check that the JSON format round trips.
+                let json = serde_json::to_string(&et).unwrap();
+                let parsed: EncryptedData = serde_json::from_str(&json).unwrap();
+                assert_eq!(parsed, et, "JSON serialization does not round trip.");
+                assert_eq!(Ok(plaintext.clone()), decrypt(&pass, &parsed));
+            }
+        }
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let pass = Password {
+            password: "hello".into(),
+        };
+        let wrong_pass = Password {
+            password: "hellO".into(),
+        };
+        let mut rng = rand::thread_rng();
+        let plaintext = random_plaintext();
+        for &cipher in [Cipher::Aes256Gcm, Cipher::ChaCha20Poly1305].iter() {
+            let mut et = encrypt_with(&pass, &plaintext, cipher, TEST_ARGON2, &mut rng)
+                .expect("Parameters are valid.");
+            assert_eq!(
+                decrypt(&wrong_pass, &et),
+                Err(DecryptionError::Authentication)
+            );
+            et.cipher_text.ct[17] ^= 1;
+            assert_eq!(decrypt(&pass, &et), Err(DecryptionError::Authentication));
+        }
+    }
+
+    #[test]
+    fn invalid_key_derivation_parameters() {
+        let pass = Password {
+            password: "hello".into(),
+        };
+        let kdf = KeyDerivationParameters::Argon2id {
+            memory_cost: 64,
+            time_cost:   0,
+            parallelism: 1,
+        };
+        assert_eq!(
+            encrypt_with(
+                &pass,
+                b"data",
+                Cipher::Aes256Gcm,
+                kdf,
+                &mut rand::thread_rng()
+            ),
+            Err(EncryptionError::KeyDerivation)
+        );
+    }
+
+    #[test]
+    // Data in the format exported by the wallet, with AES-256 in CBC mode and
+    // PBKDF2.
+    fn decrypt_legacy_export() {
+        let data = r#"{"metadata":{"encryptionMethod":"AES-256","keyDerivationMethod":"PBKDF2WithHmacSHA256","iterations":100000,"salt":"BwECAwQFBgcICQoLDA0ODw==","initializationVector":"KgECAwQFBgcICQoLDA0OYw=="},"cipherText":"iIbv4whUKREo96HVte3zP79jwydFF11Wo/uvvxc4hx0="}"#;
+        let et: EncryptedData = serde_json::from_str(data).expect("Legacy format parses.");
+        assert!(matches!(
+            et.metadata().encryption_method(),
+            EncryptionMethod::Aes256 { .. }
+        ));
+        let pass = Password {
+            password: "password".into(),
+        };
+        assert_eq!(
+            decrypt(&pass, &et),
+            Ok(b"Concordium wallet export".to_vec())
+        );
+    }
+
+    #[test]
+    // The key derived from the password must not outlive its use.
+    fn derived_key_cleared_on_drop() {
+        let pass = Password {
+            password: "hello".into(),
+        };
+        let methods = [
+            KeyDerivationMethod::Pbkdf2Sha256 {
+                iterations: 10,
+                salt:       vec![1; 16],
+            },
+            KeyDerivationMethod::Argon2id {
+                memory_cost: 64,
+                time_cost:   1,
+                parallelism: 1,
+                salt:        vec![1; 16],
+            },
+        ];
+        for method in methods.iter() {
+            let mut key = std::mem::ManuallyDrop::new(
+                derive_key(&pass, method).expect("Parameters are valid."),
+            );
+            assert_ne!(**key, [0u8; 32]);
+            // Run the destructor, but keep the memory so it can be inspected.
+            unsafe { std::ptr::drop_in_place(&mut *key) };
+            assert_eq!(**key, [0u8; 32], "Derived key was not cleared.");
+        }
+    }
 }
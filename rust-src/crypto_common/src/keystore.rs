@@ -0,0 +1,225 @@
+//! A container for encrypted keys. Besides the encrypted key it records an
+//! identifier, the type of the key, and when it was created, none of which
+//! change when the key is re-encrypted with a new password or new parameters.
+//! Files store a [Versioned] keystore, see [Keystore::versioned].
+use crate::{encryption::*, SerdeDeserialize, SerdeSerialize, Version, Versioned, VERSION_0};
+use rand::Rng;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// The version of the keystore format defined in this module.
+pub const KEYSTORE_VERSION: Version = VERSION_0;
+
+/// Length of the randomly generated key identifiers, in bytes.
+const KEY_ID_LENGTH: usize = 16;
+
+#[derive(Debug, Error)]
+pub enum KeystoreError {
+    #[error("Unsupported keystore version {0}.")]
+    UnsupportedVersion(Version),
+    #[error("{0}")]
+    Encryption(#[from] EncryptionError),
+    #[error("{0}")]
+    Decryption(#[from] DecryptionError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
+/// An encrypted key together with its metadata.
+pub struct Keystore {
+    #[serde(rename = "keyId")]
+    /// Random identifier of the key, in base16.
+    key_id:     String,
+    #[serde(rename = "keyType")]
+    /// What the key is, e.g., `"anonymity-revoker"` or `"baker"`. This is only
+    /// informative, and is not interpreted.
+    key_type:   String,
+    #[serde(rename = "createdAt")]
+    /// Time the key was first stored, in seconds since the Unix epoch.
+    created_at: u64,
+    #[serde(rename = "encryptedKey")]
+    encrypted:  EncryptedData,
+}
+
+impl Keystore {
+    /// Store the given key, encrypted with the default method of [encrypt].
+    pub fn new<A: AsRef<[u8]>, R: Rng>(
+        pass: &Password,
+        key_type: &str,
+        key: &A,
+        csprng: &mut R,
+    ) -> Self {
+        let encrypted = encrypt(pass, key, csprng);
+        Self::from_encrypted(key_type, encrypted, csprng)
+    }
+
+    /// Store the given key, encrypted with the given cipher and key derivation
+    /// parameters.
+    pub fn new_with<A: AsRef<[u8]>, R: Rng>(
+        pass: &Password,
+        key_type: &str,
+        key: &A,
+        cipher: Cipher,
+        key_derivation: KeyDerivationParameters,
+        csprng: &mut R,
+    ) -> Result<Self, KeystoreError> {
+        let encrypted = encrypt_with(pass, key, cipher, key_derivation, csprng)?;
+        Ok(Self::from_encrypted(key_type, encrypted, csprng))
+    }
+
+    /// Store already encrypted data, e.g., a file produced by [encrypt] before
+    /// keystores existed. The key is assigned a fresh identifier, and the
+    /// current time as creation time.
+    pub fn from_encrypted<R: Rng>(
+        key_type: &str,
+        encrypted: EncryptedData,
+        csprng: &mut R,
+    ) -> Self {
+        let key_id: [u8; KEY_ID_LENGTH] = csprng.gen();
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Keystore {
+            key_id: hex::encode(key_id),
+            key_type: key_type.into(),
+            created_at,
+            encrypted,
+        }
+    }
+
+    pub fn key_id(&self) -> &str { &self.key_id }
+
+    pub fn key_type(&self) -> &str { &self.key_type }
+
+    pub fn created_at(&self) -> u64 { self.created_at }
+
+    /// The methods and parameters the key is encrypted with.
+    pub fn metadata(&self) -> &EncryptionMetadata { self.encrypted.metadata() }
+
+    /// Decrypt the stored key. It is up to the caller to clear the returned
+    /// key when it is no longer needed.
+    pub fn decrypt(&self, pass: &Password) -> Result<Vec<u8>, KeystoreError> {
+        Ok(decrypt(pass, &self.encrypted)?)
+    }
+
+    /// Re-encrypt the key with a new password and the given parameters. The
+    /// identifier, type and creation time of the key are unchanged. The
+    /// decrypted key is never returned.
+    pub fn reencrypt<R: Rng>(
+        &self,
+        old_pass: &Password,
+        new_pass: &Password,
+        cipher: Cipher,
+        key_derivation: KeyDerivationParameters,
+        csprng: &mut R,
+    ) -> Result<Self, KeystoreError> {
+        let key = Zeroizing::new(self.decrypt(old_pass)?);
+        let encrypted = encrypt_with(new_pass, &*key, cipher, key_derivation, csprng)?;
+        Ok(Keystore {
+            encrypted,
+            ..self.clone()
+        })
+    }
+
+    /// The keystore with its version, as it is stored in files.
+    pub fn versioned(self) -> Versioned<Self> { Versioned::new(KEYSTORE_VERSION, self) }
+
+    /// Dual to [Keystore::versioned], failing if the version is not supported.
+    pub fn from_versioned(versioned: Versioned<Self>) -> Result<Self, KeystoreError> {
+        if versioned.version == KEYSTORE_VERSION {
+            Ok(versioned.value)
+        } else {
+            Err(KeystoreError::UnsupportedVersion(versioned.version))
+        }
+    }
+}
+
+/// The contents of a file with an encrypted key, either a [Versioned]
+/// keystore, or [EncryptedData] without metadata as it was written before
+/// keystores existed. Tools that read encrypted keys should accept both.
+#[derive(Debug, SerdeSerialize, SerdeDeserialize)]
+#[serde(untagged)]
+pub enum EncryptedKeyFile {
+    Keystore(Versioned<Keystore>),
+    Legacy(EncryptedData),
+}
+
+impl EncryptedKeyFile {
+    /// Decrypt the key, failing if the keystore version is not supported.
+    pub fn decrypt(&self, pass: &Password) -> Result<Vec<u8>, KeystoreError> {
+        match self {
+            EncryptedKeyFile::Keystore(versioned) => {
+                if versioned.version == KEYSTORE_VERSION {
+                    versioned.value.decrypt(pass)
+                } else {
+                    Err(KeystoreError::UnsupportedVersion(versioned.version))
+                }
+            }
+            EncryptedKeyFile::Legacy(encrypted) => Ok(decrypt(pass, encrypted)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keystore_reencrypt() {
+        let mut rng = rand::thread_rng();
+        let pass: Password = "hello".parse().unwrap();
+        let new_pass: Password = "goodbye".parse().unwrap();
+        let kdf = KeyDerivationParameters::Argon2id {
+            memory_cost: 64,
+            time_cost:   1,
+            parallelism: 1,
+        };
+        let key = b"secret key material".to_vec();
+        let ks = Keystore::new_with(&pass, "test", &key, Cipher::Aes256Gcm, kdf, &mut rng)
+            .expect("Parameters are valid.");
+
+        // The JSON format round trips.
+        let json = serde_json::to_string(&ks.clone().versioned()).unwrap();
+        let parsed: Versioned<Keystore> = serde_json::from_str(&json).unwrap();
+        let parsed = Keystore::from_versioned(parsed).unwrap();
+        assert_eq!(parsed, ks);
+        assert_eq!(parsed.decrypt(&pass).unwrap(), key);
+
+        let ks2 = ks
+            .reencrypt(&pass, &new_pass, Cipher::ChaCha20Poly1305, kdf, &mut rng)
+            .expect("Re-encryption succeeds.");
+        assert_eq!(ks2.key_id(), ks.key_id());
+        assert_eq!(ks2.created_at(), ks.created_at());
+        assert!(matches!(
+            ks2.metadata().encryption_method(),
+            EncryptionMethod::ChaCha20Poly1305 { .. }
+        ));
+        assert!(ks2.decrypt(&pass).is_err());
+        assert_eq!(ks2.decrypt(&new_pass).unwrap(), key);
+
+        // Re-encryption requires the old password.
+        assert!(ks
+            .reencrypt(&new_pass, &new_pass, Cipher::Aes256Gcm, kdf, &mut rng)
+            .is_err());
+
+        assert!(Keystore::from_versioned(Versioned::new(Version::from(1), ks)).is_err());
+    }
+
+    #[test]
+    fn encrypted_key_file_formats() {
+        let mut rng = rand::thread_rng();
+        let pass: Password = "hello".parse().unwrap();
+        let key = b"secret key material".to_vec();
+
+        let ks = Keystore::new(&pass, "test", &key, &mut rng);
+        let json = serde_json::to_string(&ks.versioned()).unwrap();
+        let parsed: EncryptedKeyFile = serde_json::from_str(&json).unwrap();
+        assert!(matches!(parsed, EncryptedKeyFile::Keystore(_)));
+        assert_eq!(parsed.decrypt(&pass).unwrap(), key);
+
+        let json = serde_json::to_string(&encrypt(&pass, &key, &mut rng)).unwrap();
+        let parsed: EncryptedKeyFile = serde_json::from_str(&json).unwrap();
+        assert!(matches!(parsed, EncryptedKeyFile::Legacy(_)));
+        assert_eq!(parsed.decrypt(&pass).unwrap(), key);
+    }
+}
@@ -41,5 +41,9 @@ pub use std::os::raw::c_char;
 /// formats used by Concordium.
 pub mod encryption;
 
+#[cfg(feature = "encryption")]
+/// Versioned container for encrypted keys.
+pub mod keystore;
+
 /// Reexport for ease of use.
 pub use crypto_common_derive as derive;
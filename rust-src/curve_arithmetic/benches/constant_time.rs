@@ -0,0 +1,133 @@
+//! A timing leakage test of [Curve::mul_by_secret_scalar] in the style of
+//! dudect, see <https://eprint.iacr.org/2016/1123.pdf>.
+//!
+//! The running time of the multiplication is measured for two classes of
+//! inputs, a fixed scalar and uniformly random scalars, in random order.
+//! Welch's t-test is then used to check whether the two distributions of
+//! running times differ. A t-statistic with absolute value above 4.5 means that
+//! the implementation leaks information about the scalar with high confidence.
+//! As a sanity check the same test is run for [Curve::mul_by_scalar], which
+//! should fail it for the groups of the `pairing` crate where it is
+//! variable-time.
+//!
+//! Timing measurements on shared machines are too noisy for this to be run in
+//! CI. Run it locally, on an otherwise idle machine, with
+//!
+//! ```text
+//! cargo bench --bench constant_time -- [number of measurements]
+//! ```
+use curve_arithmetic::{ristretto::RistrettoPoint, Curve};
+use ff::Field;
+use pairing::bls12_381::{G1, G2};
+use rand::*;
+use std::time::Instant;
+
+/// Threshold of the t-statistic above which the test fails, as in dudect.
+const T_THRESHOLD: f64 = 4.5;
+
+/// Default number of measurements per test if none is given on the command
+/// line.
+const DEFAULT_MEASUREMENTS: usize = 10_000;
+
+/// Running mean and variance, using Welford's method.
+#[derive(Default, Clone, Copy)]
+struct Stats {
+    n:    f64,
+    mean: f64,
+    m2:   f64,
+}
+
+impl Stats {
+    fn push(&mut self, x: f64) {
+        self.n += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.n;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn variance(&self) -> f64 { self.m2 / (self.n - 1.0) }
+}
+
+/// Welch's t-statistic of the two samples.
+fn t_statistic(a: &Stats, b: &Stats) -> f64 {
+    (a.mean - b.mean) / (a.variance() / a.n + b.variance() / b.n).sqrt()
+}
+
+/// Measure the running time of `f` on inputs of the two classes, and return
+/// the largest absolute value of the t-statistic. As in dudect, the test is
+/// also done on the measurements below several percentiles, since the large
+/// outliers caused by interrupts and the like can hide small differences.
+fn leakage<C: Curve, F: Fn(&C, &C::Scalar) -> C>(f: F, measurements: usize) -> f64 {
+    let mut rng = thread_rng();
+    let fixed = C::scalar_from_u64(1);
+    let inputs: Vec<(bool, C, C::Scalar)> = (0..measurements)
+        .map(|_| {
+            let class = rng.gen::<bool>();
+            let scalar = if class {
+                C::generate_scalar(&mut rng)
+            } else {
+                fixed
+            };
+            (class, C::generate(&mut rng), scalar)
+        })
+        .collect();
+    let times: Vec<(bool, f64)> = inputs
+        .iter()
+        .map(|(class, point, scalar)| {
+            let start = Instant::now();
+            let result = f(point, scalar);
+            let elapsed = start.elapsed().as_nanos() as f64;
+            // Make sure the computation is not optimized away.
+            assert!(!result.is_zero_point() || scalar.is_zero());
+            (*class, elapsed)
+        })
+        .collect();
+
+    let mut sorted: Vec<f64> = times.iter().map(|(_, t)| *t).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).expect("Times are not NaN."));
+    let cutoffs = [0.5, 0.75, 0.9, 0.99, 1.0].iter().map(|p| {
+        let i = ((sorted.len() as f64 * p) as usize).min(sorted.len() - 1);
+        sorted[i]
+    });
+    cutoffs
+        .map(|cutoff| {
+            let mut stats = [Stats::default(); 2];
+            for (class, t) in times.iter().filter(|(_, t)| *t <= cutoff) {
+                stats[*class as usize].push(*t);
+            }
+            t_statistic(&stats[0], &stats[1]).abs()
+        })
+        .fold(0.0, f64::max)
+}
+
+fn report<C: Curve>(name: &str, measurements: usize) {
+    let secret = leakage::<C, _>(|p, s| p.mul_by_secret_scalar(s), measurements);
+    let public = leakage::<C, _>(|p, s| p.mul_by_scalar(s), measurements);
+    let verdict = |t: f64| {
+        if t > T_THRESHOLD {
+            "leaks"
+        } else {
+            "no leakage detected"
+        }
+    };
+    println!(
+        "{}: mul_by_secret_scalar max |t| = {:.2} ({}), mul_by_scalar max |t| = {:.2} ({})",
+        name,
+        secret,
+        verdict(secret),
+        public,
+        verdict(public)
+    );
+}
+
+fn main() {
+    // Cargo passes `--bench` to the benchmark, so take the first argument that
+    // is a number.
+    let measurements = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_MEASUREMENTS);
+    report::<G1>("G1", measurements);
+    report::<G2>("G2", measurements);
+    report::<RistrettoPoint>("Ristretto255", measurements);
+}
@@ -40,5 +40,27 @@ pub fn bench_multiexp(c: &mut Criterion) {
     }
 }
 
-criterion_group!(multiexp_benchmarks, bench_multiexp);
+/// Compare the WNAF method with Pippenger's method for the input sizes where
+/// 'multiexp' switches between them.
+pub fn bench_pippenger(c: &mut Criterion) {
+    let mut csprng = thread_rng();
+    let max = 1 << 12;
+    let mut gs = Vec::with_capacity(max);
+    let mut es = Vec::with_capacity(max);
+    for _ in 0..max {
+        gs.push(G1::generate(&mut csprng));
+        es.push(G1::generate_scalar(&mut csprng));
+    }
+    for i in (7..=12).map(|x| 1 << x) {
+        let mut group = c.benchmark_group(format!("Large({})", i));
+        let gsc = &gs[..i];
+        let esc = &es[..i];
+        group.sample_size(10);
+        group.bench_function("multiexp(4)", |b| b.iter(|| multiexp_worker(gsc, esc, 4)));
+        group.bench_function("pippenger", |b| b.iter(|| pippenger(gsc, esc)));
+        group.finish();
+    }
+}
+
+criterion_group!(multiexp_benchmarks, bench_multiexp, bench_pippenger);
 criterion_main!(multiexp_benchmarks);
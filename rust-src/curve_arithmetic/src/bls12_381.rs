@@ -0,0 +1,170 @@
+//! The BLS12-381 types used by the rest of the library. By default these are
+//! the types of the `pairing` crate. With the `blstrs-backend` feature they are
+//! the instances of the [bls12_381_blstrs](crate::bls12_381_blstrs) module
+//! instead. Both backends have the same serialization, so the choice does not
+//! affect any data that is stored or exchanged.
+//!
+//! Crates should refer to these aliases, and not the types of a particular
+//! backend, so that they work with either of them.
+
+#[cfg(feature = "blstrs-backend")]
+pub use crate::bls12_381_blstrs::{Bls12, Fr, G1, G2};
+#[cfg(not(feature = "blstrs-backend"))]
+pub use pairing::bls12_381::{Bls12, Fr, G1, G2};
+
+#[cfg(test)]
+mod tests {
+    //! The tests check that the selected backend agrees with the `pairing`
+    //! crate on the serialization of equal values, and on the results of the
+    //! operations. Run them with and without the `blstrs-backend` feature.
+    use super::*;
+    use crate::{Curve, Pairing};
+    use crypto_common::*;
+    use ff::{Field, PrimeField};
+    use rand::*;
+    use std::io::Cursor;
+
+    type OldG1 = pairing::bls12_381::G1;
+    type OldG2 = pairing::bls12_381::G2;
+    type OldBls12 = pairing::bls12_381::Bls12;
+
+    /// Convert a value of the `pairing` crate to the corresponding value of the
+    /// selected backend via its serialization.
+    fn convert<A: Serial, B: Deserial>(a: &A) -> B {
+        B::deserial(&mut Cursor::new(to_bytes(a))).expect("Serializations should be compatible.")
+    }
+
+    fn from_hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_generator_serialization() {
+        assert_eq!(
+            to_bytes(&G1::one_point()),
+            from_hex(
+                "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"
+            )
+        );
+        assert_eq!(
+            to_bytes(&G2::one_point()),
+            from_hex(
+                "93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8"
+            )
+        );
+    }
+
+    #[test]
+    fn test_scalar_compatibility() {
+        let mut csprng = thread_rng();
+        for _ in 0..100 {
+            let a = OldG1::generate_scalar(&mut csprng);
+            let b = OldG1::generate_scalar(&mut csprng);
+            let a_new: Fr = convert(&a);
+            let b_new: Fr = convert(&b);
+            assert_eq!(
+                to_bytes(&a),
+                to_bytes(&a_new),
+                "Scalar serialization differs."
+            );
+            assert_eq!(a.into_repr(), a_new.into_repr(), "Representations differ.");
+
+            let mut ab = a;
+            ab.mul_assign(&b);
+            ab.sub_assign(&b);
+            let mut ab_new = a_new;
+            ab_new.mul_assign(&b_new);
+            ab_new.sub_assign(&b_new);
+            assert_eq!(to_bytes(&ab), to_bytes(&ab_new), "Arithmetic differs.");
+            assert_eq!(
+                to_bytes(&a.inverse()),
+                to_bytes(&a_new.inverse()),
+                "Inverses differ."
+            );
+        }
+        let bytes: [u8; 40] = csprng.gen();
+        assert_eq!(
+            to_bytes(&OldG1::scalar_from_bytes(&bytes)),
+            to_bytes(&G1::scalar_from_bytes(&bytes))
+        );
+        assert_eq!(
+            to_bytes(&OldG1::scalar_from_u64(u64::MAX)),
+            to_bytes(&G1::scalar_from_u64(u64::MAX))
+        );
+    }
+
+    macro_rules! macro_test_group_compatibility {
+        ($function_name:ident, $old:ty, $new:ty) => {
+            #[test]
+            fn $function_name() {
+                let mut csprng = thread_rng();
+                assert_eq!(
+                    to_bytes(&<$old>::zero_point()),
+                    to_bytes(&<$new>::zero_point())
+                );
+                assert_eq!(
+                    to_bytes(&<$old>::one_point()),
+                    to_bytes(&<$new>::one_point())
+                );
+                for _ in 0..20 {
+                    let s = <$old>::generate_scalar(&mut csprng);
+                    let p = <$old>::one_point().mul_by_scalar(&s);
+                    let q = <$old>::generate(&mut csprng);
+                    let p_new = <$new>::one_point().mul_by_scalar(&convert(&s));
+                    let q_new: $new = convert(&q);
+                    assert_eq!(
+                        to_bytes(&p),
+                        to_bytes(&p_new),
+                        "Scalar multiplication differs."
+                    );
+                    assert_eq!(
+                        to_bytes(&p.minus_point(&q)),
+                        to_bytes(&p_new.minus_point(&q_new)),
+                        "Group operation differs."
+                    );
+                    let unchecked =
+                        <$new>::bytes_to_curve_unchecked(&mut Cursor::new(to_bytes(&q)))
+                            .expect("Unchecked deserialization should succeed.");
+                    assert_eq!(unchecked, q_new);
+
+                    let msg: [u8; 32] = csprng.gen();
+                    assert_eq!(
+                        to_bytes(&<$old>::hash_to_group(&msg)),
+                        to_bytes(&<$new>::hash_to_group(&msg)),
+                        "Hashing to the group differs."
+                    );
+                }
+            }
+        };
+    }
+
+    macro_test_group_compatibility!(test_g1_compatibility, OldG1, G1);
+    macro_test_group_compatibility!(test_g2_compatibility, OldG2, G2);
+
+    #[test]
+    fn test_pairing_compatibility() {
+        let mut csprng = thread_rng();
+        for _ in 0..5 {
+            let p = OldG1::generate(&mut csprng);
+            let q = OldG2::generate(&mut csprng);
+            let paired = OldBls12::pair(&p, &q);
+            let paired_new = Bls12::pair(&convert(&p), &convert(&q));
+            assert_eq!(to_bytes(&paired), to_bytes(&paired_new), "Pairings differ.");
+
+            let r = OldG1::generate(&mut csprng);
+            assert_eq!(
+                OldBls12::check_pairing_eq(&p, &q, &r, &q),
+                Bls12::check_pairing_eq(&convert(&p), &convert(&q), &convert(&r), &convert(&q))
+            );
+            assert!(Bls12::check_pairing_eq(
+                &convert(&p),
+                &convert(&q),
+                &convert(&p),
+                &convert(&q)
+            ));
+        }
+    }
+}
@@ -0,0 +1,420 @@
+//! An implementation of the [Curve] and [Pairing] abstractions for BLS12-381
+//! backed by the [blstrs](https://github.com/filecoin-project/blstrs) library.
+//!
+//! This is an alternative to the instances for the types of the `pairing`
+//! crate. The `blstrs-backend` feature enables it and selects it in
+//! [bls12_381](crate::bls12_381). The binary serialization of all the types is
+//! the same as that of the corresponding types of the `pairing` crate (see
+//! `crypto_common::impls`), and [Curve::hash_to_group] uses the same hash to
+//! curve suites. Thus values produced with one backend can be used with the
+//! other. The tests of this are in [bls12_381](crate::bls12_381).
+//!
+//! The `ff` and `group` traits of `blstrs` are from newer, incompatible,
+//! versions of those crates than the ones the [Curve] trait is defined in
+//! terms of, so all the types here are thin wrappers.
+use crate::curve_arithmetic::*;
+use anyhow::bail;
+use blstrs::{Fp, Fp12, Fp2, Fp6, G2Prepared, Scalar};
+use byteorder::ReadBytesExt;
+use crypto_common::{Buffer, Deserial, ParseResult, Serial};
+use ff::{Field, PrimeField, PrimeFieldDecodingError};
+use ff_blstrs::Field as _;
+use group_blstrs::Group as _;
+use pairing::bls12_381::{Fq, FrRepr};
+use pairing_blstrs::{MillerLoopResult as _, MultiMillerLoop};
+use rand::*;
+use std::{convert::TryInto, fmt};
+
+const HASH_TO_GROUP_G1_DST: &[u8; 55] = b"CONCORDIUM-hashtoG1-with-BLS12381G1_XMD:SHA-256_SSWU_RO";
+const HASH_TO_GROUP_G2_DST: &[u8; 55] = b"CONCORDIUM-hashtoG2-with-BLS12381G2_XMD:SHA-256_SSWU_RO";
+
+/// The scalar field of BLS12-381.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fr(pub Scalar);
+
+/// Sample a scalar uniformly at random by rejection sampling.
+fn random_scalar<R: RngCore + ?Sized>(rng: &mut R) -> Scalar {
+    loop {
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes);
+        // The modulus is less than 2^255, so the top bit is never set.
+        bytes[31] &= 0x7f;
+        if let Some(s) = Option::from(Scalar::from_bytes_le(&bytes)) {
+            return s;
+        }
+    }
+}
+
+impl fmt::Display for Fr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Fr(0x")?;
+        for b in self.0.to_bytes_le().iter().rev() {
+            write!(f, "{:02x}", b)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Field for Fr {
+    fn random<R: RngCore + ?Sized>(rng: &mut R) -> Self { Fr(random_scalar(rng)) }
+
+    fn zero() -> Self { Fr(Scalar::ZERO) }
+
+    fn one() -> Self { Fr(Scalar::ONE) }
+
+    fn is_zero(&self) -> bool { bool::from(self.0.is_zero()) }
+
+    fn square(&mut self) { self.0 = self.0.square(); }
+
+    fn double(&mut self) { self.0 = self.0.double(); }
+
+    fn negate(&mut self) { self.0 = -self.0; }
+
+    fn add_assign(&mut self, other: &Self) { self.0 += other.0; }
+
+    fn sub_assign(&mut self, other: &Self) { self.0 -= other.0; }
+
+    fn mul_assign(&mut self, other: &Self) { self.0 *= other.0; }
+
+    fn inverse(&self) -> Option<Self> { Option::from(self.0.invert()).map(Fr) }
+
+    // The Frobenius map is the identity on a prime field.
+    fn frobenius_map(&mut self, _power: usize) {}
+}
+
+impl From<Fr> for FrRepr {
+    fn from(x: Fr) -> Self { x.into_repr() }
+}
+
+impl PrimeField for Fr {
+    // The representation is the same as for the scalars of the `pairing` crate,
+    // i.e., four little-endian 64-bit limbs.
+    type Repr = FrRepr;
+
+    const CAPACITY: u32 = <pairing::bls12_381::Fr as PrimeField>::CAPACITY;
+    const NUM_BITS: u32 = <pairing::bls12_381::Fr as PrimeField>::NUM_BITS;
+    const S: u32 = <pairing::bls12_381::Fr as PrimeField>::S;
+
+    fn from_repr(repr: Self::Repr) -> Result<Self, PrimeFieldDecodingError> {
+        let mut bytes = [0u8; 32];
+        for (chunk, limb) in bytes.chunks_mut(8).zip(repr.as_ref().iter()) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        match Option::from(Scalar::from_bytes_le(&bytes)) {
+            Some(s) => Ok(Fr(s)),
+            None => Err(PrimeFieldDecodingError::NotInField(format!("{}", repr))),
+        }
+    }
+
+    fn into_repr(&self) -> Self::Repr {
+        let bytes = self.0.to_bytes_le();
+        let mut repr = FrRepr::default();
+        for (limb, chunk) in repr.as_mut().iter_mut().zip(bytes.chunks(8)) {
+            *limb = u64::from_le_bytes(chunk.try_into().expect("Chunks have length 8."));
+        }
+        repr
+    }
+
+    fn char() -> Self::Repr { pairing::bls12_381::Fr::char() }
+
+    fn multiplicative_generator() -> Self {
+        Self::from_repr(pairing::bls12_381::Fr::multiplicative_generator().into_repr())
+            .expect("The generator is a field element.")
+    }
+
+    fn root_of_unity() -> Self {
+        Self::from_repr(pairing::bls12_381::Fr::root_of_unity().into_repr())
+            .expect("The root of unity is a field element.")
+    }
+}
+
+/// Serialized in big-endian, as the scalars of the `pairing` crate.
+impl Serial for Fr {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        let mut bytes = self.0.to_bytes_le();
+        bytes.reverse();
+        out.write_all(&bytes)
+            .expect("Writing to buffer should succeed.");
+    }
+}
+
+impl Deserial for Fr {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let mut bytes = [0u8; 32];
+        source.read_exact(&mut bytes)?;
+        bytes.reverse();
+        match Option::from(Scalar::from_bytes_le(&bytes)) {
+            Some(s) => Ok(Fr(s)),
+            None => bail!("Not a field element."),
+        }
+    }
+}
+
+/// Same as the helper for the `pairing` instance. Take the first 256 bits
+/// (little-endian), and unset the top two.
+fn scalar_from_bytes_helper<A: AsRef<[u8]>>(bytes: A) -> Fr {
+    let mut fr = [0u64; 4];
+    for (i, chunk) in bytes.as_ref().chunks(8).take(4).enumerate() {
+        let mut v = [0u8; 8];
+        v[..chunk.len()].copy_from_slice(&chunk);
+        fr[i] = u64::from_le_bytes(v);
+    }
+    fr[3] &= !(1u64 << 63 | 1u64 << 62);
+    Fr::from_repr(FrRepr(fr)).expect("The scalar with top two bits erased should be valid.")
+}
+
+macro_rules! blstrs_curve_instance {
+    ($name:ident, $projective:ty, $affine:ty, $length:expr, $dst:expr, $doc:expr) => {
+        #[doc = $doc]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub struct $name(pub $projective);
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}(0x", stringify!($name))?;
+                for b in self.compress().iter() {
+                    write!(f, "{:02x}", b)?;
+                }
+                write!(f, ")")
+            }
+        }
+
+        /// Serialized in the compressed format, as the points of the `pairing`
+        /// crate.
+        impl Serial for $name {
+            fn serial<B: Buffer>(&self, out: &mut B) {
+                out.write_all(&self.compress())
+                    .expect("Writing to buffer should succeed.");
+            }
+        }
+
+        impl Deserial for $name {
+            fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+                let mut bytes = [0u8; $length];
+                source.read_exact(&mut bytes)?;
+                Ok(Self::decompress(&bytes)?)
+            }
+        }
+
+        impl Curve for $name {
+            // The base field is not used in any computations, it is only here
+            // to satisfy the trait. The type of the `pairing` crate is used
+            // since it implements the required traits.
+            type Base = Fq;
+            type Compressed = [u8; $length];
+            type Scalar = Fr;
+
+            const GROUP_ELEMENT_LENGTH: usize = $length;
+            const SCALAR_LENGTH: usize = 32;
+
+            fn zero_point() -> Self { $name(<$projective>::identity()) }
+
+            fn one_point() -> Self { $name(<$projective>::generator()) }
+
+            fn is_zero_point(&self) -> bool { bool::from(self.0.is_identity()) }
+
+            fn inverse_point(&self) -> Self { $name(-self.0) }
+
+            fn double_point(&self) -> Self { $name(self.0.double()) }
+
+            fn plus_point(&self, other: &Self) -> Self { $name(self.0 + other.0) }
+
+            fn minus_point(&self, other: &Self) -> Self { $name(self.0 - other.0) }
+
+            fn mul_by_scalar(&self, scalar: &Self::Scalar) -> Self { $name(self.0 * scalar.0) }
+
+            // Scalar multiplication in blst is constant-time.
+            fn mul_by_secret_scalar(&self, scalar: &Self::Scalar) -> Self {
+                self.mul_by_scalar(scalar)
+            }
+
+            fn compress(&self) -> Self::Compressed { <$affine>::from(self.0).to_compressed() }
+
+            fn decompress(c: &Self::Compressed) -> Result<Self, CurveDecodingError> {
+                match Option::from(<$affine>::from_compressed(c)) {
+                    Some(p) => Ok($name(<$projective>::from(p))),
+                    None => Err(CurveDecodingError::NotOnCurve),
+                }
+            }
+
+            fn decompress_unchecked(c: &Self::Compressed) -> Result<Self, CurveDecodingError> {
+                match Option::from(<$affine>::from_compressed_unchecked(c)) {
+                    Some(p) => Ok($name(<$projective>::from(p))),
+                    None => Err(CurveDecodingError::NotOnCurve),
+                }
+            }
+
+            fn bytes_to_curve_unchecked<R: ReadBytesExt>(bytes: &mut R) -> anyhow::Result<Self> {
+                let mut c = [0u8; $length];
+                bytes.read_exact(&mut c)?;
+                Ok(Self::decompress_unchecked(&c)?)
+            }
+
+            fn generate<T: Rng>(csprng: &mut T) -> Self {
+                $name(<$projective>::generator() * random_scalar(csprng))
+            }
+
+            fn generate_scalar<T: Rng>(csprng: &mut T) -> Self::Scalar { Fr(random_scalar(csprng)) }
+
+            fn scalar_from_u64(n: u64) -> Self::Scalar { Fr(Scalar::from(n)) }
+
+            fn scalar_from_bytes<A: AsRef<[u8]>>(bytes: A) -> Self::Scalar {
+                scalar_from_bytes_helper(bytes)
+            }
+
+            fn hash_to_group(bytes: &[u8]) -> Self {
+                $name(<$projective>::hash_to_curve(bytes, $dst, &[]))
+            }
+        }
+    };
+}
+
+blstrs_curve_instance!(
+    G1,
+    blstrs::G1Projective,
+    blstrs::G1Affine,
+    48,
+    HASH_TO_GROUP_G1_DST,
+    "The first group of BLS12-381."
+);
+
+blstrs_curve_instance!(
+    G2,
+    blstrs::G2Projective,
+    blstrs::G2Affine,
+    96,
+    HASH_TO_GROUP_G2_DST,
+    "The second group of BLS12-381."
+);
+
+/// The target field of the BLS12-381 pairing, the degree 12 extension of the
+/// base field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fq12(pub Fp12);
+
+impl fmt::Display for Fq12 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "Fq12({:?})", self.0) }
+}
+
+/// Sample a base field element uniformly at random by rejection sampling.
+fn random_fp<R: RngCore + ?Sized>(rng: &mut R) -> Fp {
+    loop {
+        let mut bytes = [0u8; 48];
+        rng.fill_bytes(&mut bytes);
+        // The modulus is less than 2^381.
+        bytes[0] &= 0x1f;
+        if let Some(x) = Option::from(Fp::from_bytes_be(&bytes)) {
+            return x;
+        }
+    }
+}
+
+impl Field for Fq12 {
+    fn random<R: RngCore + ?Sized>(rng: &mut R) -> Self {
+        let mut fp2 = || Fp2::new(random_fp(rng), random_fp(rng));
+        let c0 = Fp6::new(fp2(), fp2(), fp2());
+        let c1 = Fp6::new(fp2(), fp2(), fp2());
+        Fq12(Fp12::new(c0, c1))
+    }
+
+    fn zero() -> Self { Fq12(Fp12::ZERO) }
+
+    fn one() -> Self { Fq12(Fp12::ONE) }
+
+    fn is_zero(&self) -> bool { bool::from(self.0.is_zero()) }
+
+    fn square(&mut self) { self.0 = self.0.square(); }
+
+    fn double(&mut self) { self.0 = self.0.double(); }
+
+    fn negate(&mut self) { self.0 = -self.0; }
+
+    fn add_assign(&mut self, other: &Self) { self.0 += other.0; }
+
+    fn sub_assign(&mut self, other: &Self) { self.0 -= other.0; }
+
+    fn mul_assign(&mut self, other: &Self) { self.0 *= other.0; }
+
+    fn inverse(&self) -> Option<Self> { Option::from(self.0.invert()).map(Fq12) }
+
+    fn frobenius_map(&mut self, power: usize) {
+        let p = Fq::char();
+        for _ in 0..power {
+            self.0 = self.0.pow_vartime(p.as_ref());
+        }
+    }
+}
+
+/// Write an element of the base field as the `pairing` crate does, i.e., as
+/// the six 64-bit limbs of its representation, least significant limb first,
+/// and each limb in big-endian.
+fn serial_fp<B: Buffer>(x: &Fp, out: &mut B) {
+    let bytes = x.to_bytes_be();
+    for limb in bytes.rchunks(8) {
+        out.write_all(limb)
+            .expect("Writing to buffer should succeed.");
+    }
+}
+
+/// The same as the serialization of `Fq12` of the `pairing` crate. Both
+/// libraries use the same tower of extensions.
+impl Serial for Fq12 {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        let c0_6 = self.0.c0();
+        let c1_6 = self.0.c1();
+        let coeffs = [
+            c1_6.c2(),
+            c1_6.c1(),
+            c1_6.c0(),
+            c0_6.c2(),
+            c0_6.c1(),
+            c0_6.c0(),
+        ];
+        for p in coeffs.iter() {
+            serial_fp(&p.c1(), out);
+            serial_fp(&p.c0(), out);
+        }
+    }
+}
+
+/// The BLS12-381 pairing.
+#[derive(Clone, Copy, Debug)]
+pub struct Bls12;
+
+impl Pairing for Bls12 {
+    type BaseField = Fq;
+    type G1 = G1;
+    type G1Prepared = blstrs::G1Affine;
+    type G2 = G2;
+    type G2Prepared = G2Prepared;
+    type ScalarField = Fr;
+    type TargetField = Fq12;
+
+    fn g1_prepare(g: &Self::G1) -> Self::G1Prepared { blstrs::G1Affine::from(g.0) }
+
+    fn g2_prepare(g: &Self::G2) -> Self::G2Prepared {
+        G2Prepared::from(blstrs::G2Affine::from(g.0))
+    }
+
+    /// `blstrs` does not expose the result of the Miller loop as a field
+    /// element, so this includes the final exponentiation, and
+    /// [Pairing::final_exponentiation] below is the identity. The result of
+    /// combining the two is the same as for the `pairing` instance.
+    fn miller_loop<'a, I>(i: I) -> Self::TargetField
+    where
+        I: IntoIterator<Item = &'a (&'a Self::G1Prepared, &'a Self::G2Prepared)>, {
+        let terms = i.into_iter().map(|&(p, q)| (p, q)).collect::<Vec<_>>();
+        let gt = blstrs::Bls12::multi_miller_loop(&terms).final_exponentiation();
+        Fq12(Fp12::from(gt))
+    }
+
+    fn final_exponentiation(x: &Self::TargetField) -> Option<Self::TargetField> {
+        if x.is_zero() {
+            None
+        } else {
+            Some(*x)
+        }
+    }
+
+    fn generate_scalar<T: Rng>(csprng: &mut T) -> Self::ScalarField { Fr(random_scalar(csprng)) }
+}
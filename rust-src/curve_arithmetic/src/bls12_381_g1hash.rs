@@ -1,7 +1,7 @@
 use ff::{Field, PrimeField};
 use group::{CurveProjective, EncodedPoint};
 use pairing::bls12_381::{Fq, FqRepr, G1Uncompressed, G1};
-use sha2::{Digest, Sha256};
+use sha2::Sha256;
 use std::{
     convert::TryInto,
     io::{Cursor, Write},
@@ -505,6 +505,24 @@ pub fn hash_to_curve(msg: &[u8], dst: &[u8]) -> G1 {
     r // This now guarantied to be in G1
 }
 
+/// Implements the nonuniform encoding `encode_to_curve` of
+/// https://www.rfc-editor.org/rfc/rfc9380.html#section-3
+/// It follows the steps
+///    1. u = hash_to_field(msg, 1)
+///    2. Q = map_to_curve(u[0])
+///    3. P = clear_cofactor(Q) = h_eff * Q
+///    4. return P
+pub fn encode_to_curve(msg: &[u8], dst: &[u8]) -> G1 {
+    let b = crate::hash_to_curve::expand_message_xmd::<Sha256>(msg, dst, 64);
+    let u = fq_from_bytes(
+        b[..32].try_into().expect("Slice has length 32."),
+        b[32..].try_into().expect("Slice has length 32."),
+    );
+    let mut p = map_to_curve(u); // This is on E, but not necessarily in G1
+    p.mul_assign(15132376222941642753); // Clearing cofactor with h_eff = 15132376222941642753
+    p
+}
+
 /// Implements https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#section-6.6.3
 /// It uses the function sswu_3mod4 to get xn, xd, y such that (xn/xd : y : 1)
 /// is a point on E' (written in Jacobian coordinates). Since inversions are
@@ -557,58 +575,13 @@ fn fq_from_bytes(left_bytes: &[u8; 32], right_bytes: &[u8; 32]) -> Fq {
 /// len_in_bytes is fixed to 128
 /// Domain separation string (dst) should be at most 255 bytes
 fn expand_message_xmd(msg: &[u8], dst: &[u8]) -> ([u8; 32], [u8; 32], [u8; 32], [u8; 32]) {
-    // DST_prime = DST || I2OSP(len(DST), 1)
-    let mut dst_prime = dst.to_vec();
-    dst_prime.push(dst.len().try_into().unwrap()); // panics if dst is more than 255 bytes
-                                                   // msg_prime = Z_pad || msg || l_i_b_str || I2OSP(0, 1) || DST_prime
-
-    // b_0 = H(msg_prime)
-    let mut h = Sha256::new();
-    // todo a possible optimization here would be to save the state of H(Z_pad)
-    h.update(vec![0; 64]); // z_pad = I2OSP(0, 64), 64 is the input block size of Sha265
-    h.update(msg);
-    h.update(vec![0, 128]); // l_i_b_str = I2OSP(128, 2)
-    h.update([0u8]);
-    h.update(&dst_prime);
-    let mut b_0: [u8; 32] = [0u8; 32];
-    b_0.copy_from_slice(h.finalize().as_slice());
-
-    // b_1 = H(b_0 || I2OSP(1, 1) || DST_prime)
-    let mut h = Sha256::new();
-    h.update(b_0);
-    h.update([1u8]);
-    h.update(&dst_prime);
-    let mut b_1: [u8; 32] = [0u8; 32];
-    b_1.copy_from_slice(h.finalize().as_slice());
-
-    // b_2 = H(strxor(b_0, b_1)  || I2OSP(2, 1) || DST_prime)
-    let mut h = Sha256::new();
-    let xor: Vec<u8> = b_0.iter().zip(b_1.iter()).map(|(x, y)| x ^ y).collect();
-    h.update(xor);
-    h.update([2u8]);
-    h.update(&dst_prime);
-    let mut b_2: [u8; 32] = [0u8; 32];
-    b_2.copy_from_slice(h.finalize().as_slice());
-
-    // b_3 = H(strxor(b_1, b_2)  || I2OSP(3, 1) || DST_prime)
-    let mut h = Sha256::new();
-    let xor: Vec<u8> = b_0.iter().zip(b_2.iter()).map(|(x, y)| x ^ y).collect();
-    h.update(xor);
-    h.update([3u8]);
-    h.update(&dst_prime);
-    let mut b_3: [u8; 32] = [0u8; 32];
-    b_3.copy_from_slice(h.finalize().as_slice());
-
-    // b_4 = H(strxor(b_2, b_3)  || I2OSP(4, 1) || DST_prime)
-    let mut h = Sha256::new();
-    let xor: Vec<u8> = b_0.iter().zip(b_3.iter()).map(|(x, y)| x ^ y).collect();
-    h.update(xor);
-    h.update([4u8]);
-    h.update(dst_prime);
-    let mut b_4: [u8; 32] = [0u8; 32];
-    b_4.copy_from_slice(h.finalize().as_slice());
-
-    (b_1, b_2, b_3, b_4)
+    let b = crate::hash_to_curve::expand_message_xmd::<Sha256>(msg, dst, 128);
+    let chunk = |i: usize| -> [u8; 32] {
+        b[32 * i..32 * (i + 1)]
+            .try_into()
+            .expect("Chunks have length 32.")
+    };
+    (chunk(0), chunk(1), chunk(2), chunk(3))
 }
 
 // Returns a point on E1 with coordinates x,y,z.
@@ -1,7 +1,7 @@
 use ff::{Field, PrimeField, SqrtField};
 use group::{CurveProjective, EncodedPoint};
 use pairing::bls12_381::{Fq, Fq2, FqRepr, G2Uncompressed, G2};
-use sha2::{Digest, Sha256};
+use sha2::Sha256;
 use std::{
     convert::TryInto,
     io::{Cursor, Write},
@@ -28,6 +28,26 @@ pub fn hash_to_curve_g2(msg: &[u8], dst: &[u8]) -> G2 {
     clear_cofactor_g2(r) // This now guaranteed to be in G2
 }
 
+/// Implements the nonuniform encoding `encode_to_curve` of
+/// https://www.rfc-editor.org/rfc/rfc9380.html#section-3
+/// It follows the steps
+///    1. u = hash_to_field(msg, 1)
+///    2. Q = map_to_curve(u[0])
+///    3. P = clear_cofactor(Q) = h_eff * Q
+///    4. return P
+pub fn encode_to_curve_g2(msg: &[u8], dst: &[u8]) -> G2 {
+    let b = crate::hash_to_curve::expand_message_xmd::<Sha256>(msg, dst, 128);
+    let b: Vec<&[u8; 32]> = b
+        .chunks(32)
+        .map(|chunk| chunk.try_into().expect("Chunks have length 32."))
+        .collect();
+    let u = Fq2 {
+        c0: fq_from_bytes(b[0], b[1]),
+        c1: fq_from_bytes(b[2], b[3]),
+    };
+    clear_cofactor_g2(map_to_curve_g2(u)) // This is now guaranteed to be in G2
+}
+
 /// This is an inefficient method for clearing the cofactor.
 /// Corresponds to multiplying by h_eff in https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#section-8.8.2
 /// A much faster equivalent implementation is available in https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#appendix-G.4
@@ -175,45 +195,11 @@ fn sgn0(x: Fq2) -> u64 {
 /// len_in_bytes is fixed to 256
 /// Domain separation string (dst) should be at most 255 bytes
 fn expand_message_xmd(msg: &[u8], dst: &[u8]) -> [[u8; 32]; 8] {
-    // DST_prime = DST || I2OSP(len(DST), 1)
-    let mut dst_prime = dst.to_vec();
-    dst_prime.push(dst.len().try_into().unwrap()); // panics if dst is more than 255 bytes
-
-    // b_0 = H(msg_prime), msg_prime = Z_pad || msg || l_i_b_str || I2OSP(0, 1) ||
-    // DST_prime
-    let mut h = Sha256::new();
-    h.update(vec![0; 64]); // z_pad = I2OSP(0, 64), 64 is the input block size of Sha265
-    h.update(msg);
-    h.update(vec![1, 0]); // l_i_b_str = I2OSP(256, 2)
-    h.update([0u8]);
-    h.update(&dst_prime);
-    let mut b_0: [u8; 32] = [0u8; 32];
-    b_0.copy_from_slice(h.finalize().as_slice());
-
-    // b_1 = H(b_0 || I2OSP(1, 1) || DST_prime)
-    let mut h = Sha256::new();
-    h.update(b_0);
-    h.update([1u8]);
-    h.update(&dst_prime);
-
-    let mut b = [[0u8; 32]; 8]; // b[i] corresponds to b_i+1 in specification.
-    b[0].copy_from_slice(h.finalize().as_slice());
-
-    // compute remaining uniform bytes
-    for i in 1u8..8 {
-        // b_i = H(strxor(b_0, b_i-1)  || I2OSP(i, 1) || DST_prime)
-        let mut h = Sha256::new();
-        let xor: Vec<u8> = b_0
-            .iter()
-            .zip(b[i as usize - 1].iter())
-            .map(|(x, y)| x ^ y)
-            .collect();
-        h.update(xor);
-        h.update([i + 1]); // offset as standard drops b_0 and returns index b_1-b_8
-        h.update(&dst_prime);
-        b[i as usize].copy_from_slice(h.finalize().as_slice());
+    let uniform_bytes = crate::hash_to_curve::expand_message_xmd::<Sha256>(msg, dst, 256);
+    let mut b = [[0u8; 32]; 8];
+    for (b_i, chunk) in b.iter_mut().zip(uniform_bytes.chunks(32)) {
+        b_i.copy_from_slice(chunk);
     }
-
     b
 }
 
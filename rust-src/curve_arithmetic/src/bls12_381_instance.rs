@@ -2,7 +2,7 @@
 
 use crate::{bls12_381_g1hash::*, bls12_381_g2hash::*, curve_arithmetic::*};
 use byteorder::ReadBytesExt;
-use ff::{Field, PrimeField};
+use ff::{Field, PrimeField, PrimeFieldRepr};
 use group::{CurveAffine, CurveProjective, EncodedPoint};
 use pairing::{
     bls12_381::{
@@ -12,6 +12,7 @@ use pairing::{
     Engine, PairingCurveAffine,
 };
 use rand::*;
+use subtle::{Choice, ConditionallySelectable};
 
 const HASH_TO_GROUP_G1_DST: &[u8; 55] = b"CONCORDIUM-hashtoG1-with-BLS12381G1_XMD:SHA-256_SSWU_RO";
 const HASH_TO_GROUP_G2_DST: &[u8; 55] = b"CONCORDIUM-hashtoG2-with-BLS12381G2_XMD:SHA-256_SSWU_RO";
@@ -31,6 +32,80 @@ fn scalar_from_bytes_helper<A: AsRef<[u8]>>(bytes: A) -> Fr {
     Fr::from_repr(FrRepr(fr)).expect("The scalar with top two bits erased should be valid.")
 }
 
+/// Types that consist of exactly `LIMBS` `u64` values, without padding or any
+/// other data, so that they can be swapped limb by limb.
+///
+/// # Safety
+/// Implementations must guarantee the above layout.
+unsafe trait Limbs: Copy {
+    const LIMBS: usize;
+}
+
+// The projective points of the `pairing` crate consist of three coordinates,
+// each of which is one (G1) or two (G2) field elements of six `u64` limbs. The
+// types are not `repr(C)`, so these check at compile time that there is
+// nothing else in them, and that they are aligned as `u64`.
+const _: [(); std::mem::size_of::<G1>()] = [(); 18 * 8];
+const _: [(); std::mem::align_of::<G1>()] = [(); 8];
+const _: [(); std::mem::size_of::<G2>()] = [(); 36 * 8];
+const _: [(); std::mem::align_of::<G2>()] = [(); 8];
+
+unsafe impl Limbs for G1 {
+    const LIMBS: usize = 18;
+}
+
+unsafe impl Limbs for G2 {
+    const LIMBS: usize = 36;
+}
+
+/// Swap `a` and `b` if `choice` is 1, and leave them unchanged if it is 0,
+/// without branching on `choice`.
+fn conditional_swap<T: Limbs>(a: &mut T, b: &mut T, choice: Choice) {
+    // This is safe since `T` consists of exactly `T::LIMBS` aligned `u64`s.
+    let a = unsafe { std::slice::from_raw_parts_mut(a as *mut T as *mut u64, T::LIMBS) };
+    let b = unsafe { std::slice::from_raw_parts_mut(b as *mut T as *mut u64, T::LIMBS) };
+    for (x, y) in a.iter_mut().zip(b.iter_mut()) {
+        u64::conditional_swap(x, y, choice);
+    }
+}
+
+// Helper function for both G1 and G2 instances. Scalar multiplication with the
+// Montgomery ladder, which performs one addition and one doubling per bit of
+// the scalar regardless of its value. To make the number of bits independent
+// of the scalar `s`, the ladder is run on whichever of `s + r` and `s + 2r`
+// has exactly 256 bits, where `r` is the group order. Since `2^254 < r < 2^255`
+// one of them always does.
+//
+// The intermediate points are never zero unless `s` is, except with
+// negligible probability, so the exceptional cases of the addition formulas
+// are not reached. This is only best-effort: the field arithmetic of the
+// `pairing` crate is not constant-time, so the running time can still depend
+// on the scalar. The `blstrs` backend should be used if that is required.
+fn mul_by_secret_scalar_helper<G: CurveProjective<Scalar = Fr> + Limbs>(p: &G, scalar: &Fr) -> G {
+    let modulus = Fr::char();
+    let mut k = scalar.into_repr();
+    k.add_nocarry(&modulus);
+    let mut k2 = k;
+    k2.add_nocarry(&modulus);
+    // All ones if the top bit of s + r is not set, and 0 otherwise.
+    let mask = (k.0[3] >> 63).wrapping_sub(1);
+    for (x, y) in k.0.iter_mut().zip(k2.0.iter()) {
+        *x ^= mask & (*x ^ *y);
+    }
+    // The invariant is that r1 - r0 = p.
+    let mut r0 = *p;
+    let mut r1 = *p;
+    r1.double();
+    for i in (0..255).rev() {
+        let bit = Choice::from(((k.0[i / 64] >> (i % 64)) & 1) as u8);
+        conditional_swap(&mut r0, &mut r1, bit);
+        r1.add_assign(&r0);
+        r0.double();
+        conditional_swap(&mut r0, &mut r1, bit);
+    }
+    r0
+}
+
 impl Curve for G2 {
     type Base = Fq;
     type Compressed = G2Compressed;
@@ -76,6 +151,10 @@ impl Curve for G2 {
         p
     }
 
+    fn mul_by_secret_scalar(&self, scalar: &Self::Scalar) -> Self {
+        mul_by_secret_scalar_helper(self, scalar)
+    }
+
     fn compress(&self) -> Self::Compressed { self.into_affine().into_compressed() }
 
     fn decompress(c: &Self::Compressed) -> Result<G2, CurveDecodingError> {
@@ -160,6 +239,10 @@ impl Curve for G1 {
         p
     }
 
+    fn mul_by_secret_scalar(&self, scalar: &Self::Scalar) -> Self {
+        mul_by_secret_scalar_helper(self, scalar)
+    }
+
     fn compress(&self) -> Self::Compressed { self.into_affine().into_compressed() }
 
     fn decompress(c: &Self::Compressed) -> Result<G1, CurveDecodingError> {
@@ -242,6 +325,10 @@ impl Curve for G1Affine {
         self.mul(s).into_affine()
     }
 
+    fn mul_by_secret_scalar(&self, scalar: &Self::Scalar) -> Self {
+        mul_by_secret_scalar_helper(&self.into_projective(), scalar).into_affine()
+    }
+
     fn compress(&self) -> Self::Compressed { self.into_compressed() }
 
     fn decompress(c: &Self::Compressed) -> Result<G1Affine, CurveDecodingError> {
@@ -323,6 +410,10 @@ impl Curve for G2Affine {
         self.mul(s).into_affine()
     }
 
+    fn mul_by_secret_scalar(&self, scalar: &Self::Scalar) -> Self {
+        mul_by_secret_scalar_helper(&self.into_projective(), scalar).into_affine()
+    }
+
     fn compress(&self) -> Self::Compressed { self.into_compressed() }
 
     fn decompress(c: &Self::Compressed) -> Result<G2Affine, CurveDecodingError> {
@@ -398,6 +489,34 @@ mod tests {
     use crypto_common::*;
     use std::io::Cursor;
 
+    macro_rules! macro_test_mul_by_secret_scalar {
+        ($function_name:ident, $curve_type:path) => {
+            #[test]
+            fn $function_name() {
+                let mut rng = rand::thread_rng();
+                let mut minus_one = Fr::one();
+                minus_one.negate();
+                let mut scalars = vec![Fr::zero(), Fr::one(), minus_one];
+                for _ in 0..100 {
+                    scalars.push(Fr::random(&mut rng));
+                }
+                for s in scalars.iter() {
+                    let p = <$curve_type>::generate(&mut rng);
+                    assert_eq!(
+                        p.mul_by_secret_scalar(s),
+                        p.mul_by_scalar(s),
+                        "Scalar multiplications differ."
+                    );
+                }
+            }
+        };
+    }
+
+    macro_test_mul_by_secret_scalar!(mul_by_secret_scalar_g1, G1);
+    macro_test_mul_by_secret_scalar!(mul_by_secret_scalar_g2, G2);
+    macro_test_mul_by_secret_scalar!(mul_by_secret_scalar_g1_affine, G1Affine);
+    macro_test_mul_by_secret_scalar!(mul_by_secret_scalar_g2_affine, G2Affine);
+
     // Check that scalar_from_bytes_helper works on small values.
     #[test]
     fn scalar_from_bytes_small() {
@@ -54,6 +54,19 @@ pub trait Curve:
     /// and integer n.
     fn mul_by_scalar(&self, scalar: &Self::Scalar) -> Self;
     #[must_use]
+    /// Exponentiation by a secret scalar. Unlike [Curve::mul_by_scalar] the
+    /// sequence of group operations and memory accesses does not depend on the
+    /// scalar, at the cost of being slower. This should be used whenever the
+    /// scalar is secret, e.g., a secret key or the randomness of a commitment
+    /// or an encryption.
+    ///
+    /// Whether this is constant-time depends on the field arithmetic of the
+    /// instance. It is for the `blstrs` backend of BLS12-381 and for
+    /// Ristretto255. For the default BLS12-381 instances, based on the
+    /// `pairing` crate, it is only best-effort, since that field arithmetic is
+    /// not constant-time.
+    fn mul_by_secret_scalar(&self, scalar: &Self::Scalar) -> Self;
+    #[must_use]
     fn compress(&self) -> Self::Compressed;
     fn decompress(c: &Self::Compressed) -> Result<Self, CurveDecodingError>;
     fn decompress_unchecked(c: &Self::Compressed) -> Result<Self, CurveDecodingError>;
@@ -174,12 +187,134 @@ pub trait Pairing: Sized + 'static + Clone {
     }
 }
 
-/// Like 'multiexp_worker', but computes a reasonable window size automatically.
+/// Compute the multiexponentiation $\prod_i g_i^{e_i}$ (in additive notation
+/// $\sum_i e_i g_i$). For small inputs this uses 'multiexp_worker' with a
+/// fixed window size, and for large inputs it uses 'pippenger'.
+///
+/// Assumes the lengths of inputs are the same.
 #[inline(always)]
 pub fn multiexp<C: Curve, X: Borrow<C>>(gs: &[X], exps: &[C::Scalar]) -> C {
-    // This number is based on the benchmark in benches/multiexp_bench.rs
-    let window_size = 4;
-    multiexp_worker(gs, exps, window_size)
+    if exps.len() >= PIPPENGER_THRESHOLD {
+        pippenger(gs, exps)
+    } else {
+        // This number is based on the benchmark in benches/multiexp_bench.rs
+        let window_size = 4;
+        multiexp_worker(gs, exps, window_size)
+    }
+}
+
+/// Compute the multiexponentiation $\sum_i e_i g_i$ when the exponents are
+/// secret. Each term is computed with [Curve::mul_by_secret_scalar], so this is
+/// considerably slower than 'multiexp' and should only be used for a few terms.
+///
+/// Assumes the lengths of inputs are the same.
+pub fn multiexp_secret<C: Curve, X: Borrow<C>>(gs: &[X], exps: &[C::Scalar]) -> C {
+    gs.iter()
+        .zip(exps.iter())
+        .fold(C::zero_point(), |acc, (g, e)| {
+            acc.plus_point(&g.borrow().mul_by_secret_scalar(e))
+        })
+}
+
+/// The number of terms from which 'multiexp' uses Pippenger's method instead
+/// of the WNAF method. With 255-bit scalars the WNAF method with window size 4
+/// costs about 59 additions per term, whereas Pippenger's method costs about
+/// 54 per term at 512 terms, and fewer for larger inputs. The benchmark in
+/// benches/multiexp_bench.rs compares the two.
+pub const PIPPENGER_THRESHOLD: usize = 512;
+
+/// Compute the multiexponentiation using Pippenger's bucket method, see
+/// e.g. Section 4 of <https://eprint.iacr.org/2012/549.pdf>.
+///
+/// The scalars are split into windows of `c` bits. For each window the points
+/// are sorted into $2^c - 1$ buckets according to the value of the scalar in
+/// that window, and the buckets are summed with the running sum trick. The
+/// window size is chosen based on the number of terms.
+///
+/// Assumes the lengths of inputs are the same.
+/// With the `parallel` feature the windows are processed in parallel.
+pub fn pippenger<C: Curve, X: Borrow<C>>(gs: &[X], exps: &[C::Scalar]) -> C {
+    let k = exps.len();
+    assert_eq!(gs.len(), k);
+    let num_bits = C::Scalar::NUM_BITS as usize;
+    let window_size = pippenger_window_size(k, num_bits);
+    let num_windows = (num_bits + window_size - 1) / window_size;
+    let reprs = exps.iter().map(|e| e.into_repr()).collect::<Vec<_>>();
+
+    #[cfg(feature = "parallel")]
+    let window_sums = {
+        use rayon::prelude::*;
+        let gs = gs.iter().map(|g| *g.borrow()).collect::<Vec<C>>();
+        (0..num_windows)
+            .into_par_iter()
+            .map(|w| pippenger_window(&gs, &reprs, w * window_size, window_size))
+            .collect::<Vec<C>>()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let window_sums = (0..num_windows)
+        .map(|w| pippenger_window(gs, &reprs, w * window_size, window_size))
+        .collect::<Vec<C>>();
+
+    // Combine the windows, starting from the most significant one.
+    let mut result = C::zero_point();
+    for window_sum in window_sums.iter().rev() {
+        for _ in 0..window_size {
+            result = result.double_point();
+        }
+        result = result.plus_point(window_sum);
+    }
+    result
+}
+
+/// Choose the window size for 'pippenger' that minimizes the estimated number
+/// of group additions. Each of the `ceil(num_bits / c)` windows costs one
+/// addition per term, and about $2^{c+1}$ additions to sum the buckets.
+fn pippenger_window_size(k: usize, num_bits: usize) -> usize {
+    let cost = |c: usize| ((num_bits + c - 1) / c) * (k + (2 << c));
+    // Window sizes above 16 are never optimal for the input sizes we use, and
+    // would require a lot of memory for the buckets.
+    (1..=16).min_by_key(|&c| cost(c)).unwrap_or(1)
+}
+
+/// Compute $\sum_i d_i g_i$ where $d_i$ are the bits `start..start + c` of
+/// the i-th scalar.
+fn pippenger_window<C: Curve, X: Borrow<C>, R: AsRef<[u64]>>(
+    gs: &[X],
+    reprs: &[R],
+    start: usize,
+    c: usize,
+) -> C {
+    let mut buckets = vec![C::zero_point(); (1 << c) - 1];
+    for (g, repr) in gs.iter().zip(reprs.iter()) {
+        let digit = scalar_window(repr.as_ref(), start, c);
+        if digit != 0 {
+            buckets[digit - 1] = buckets[digit - 1].plus_point(g.borrow());
+        }
+    }
+    // The bucket at index i should be counted i+1 times. Summing the running
+    // sums from the top does exactly that.
+    let mut running_sum = C::zero_point();
+    let mut sum = C::zero_point();
+    for bucket in buckets.iter().rev() {
+        running_sum = running_sum.plus_point(bucket);
+        sum = sum.plus_point(&running_sum);
+    }
+    sum
+}
+
+/// Extract the bits `start..start + c` of the little-endian limbs, with bits
+/// beyond the end treated as 0. Assumes 0 < c < 64.
+fn scalar_window(limbs: &[u64], start: usize, c: usize) -> usize {
+    let limb = start / 64;
+    let offset = start % 64;
+    if limb >= limbs.len() {
+        return 0;
+    }
+    let mut bits = limbs[limb] >> offset;
+    if offset + c > 64 && limb + 1 < limbs.len() {
+        bits |= limbs[limb + 1] << (64 - offset);
+    }
+    (bits & ((1 << c) - 1)) as usize
 }
 
 /// This implements the WNAF method from
@@ -212,10 +347,54 @@ pub fn multiexp_worker<C: Curve, X: Borrow<C>>(
 ///
 /// See <https://link.springer.com/content/pdf/10.1007%2F3-540-45537-X_13.pdf> for what it means
 /// for the table to be computed correctly.
+///
+/// With the `parallel` feature large inputs are split between threads.
 pub fn multiexp_worker_given_table<C: Curve>(
     exps: &[C::Scalar],
     table: &[Vec<C>],
     window_size: usize,
+) -> C {
+    #[cfg(feature = "parallel")]
+    {
+        if exps.len() >= 2 * MIN_PARALLEL_MULTIEXP_CHUNK {
+            return multiexp_given_table_parallel(exps, table, window_size);
+        }
+    }
+    multiexp_given_table_single(exps, table, window_size)
+}
+
+/// The minimal number of terms each thread handles when a multiexponentiation
+/// is split between threads. Below this the overhead of an additional thread,
+/// in particular the extra doublings, outweighs the gain.
+#[cfg(feature = "parallel")]
+const MIN_PARALLEL_MULTIEXP_CHUNK: usize = 64;
+
+/// Split the multiexponentiation into chunks which are computed in parallel,
+/// and add the results. The result is the same as that of
+/// `multiexp_given_table_single`, only the order of group operations differs.
+#[cfg(feature = "parallel")]
+fn multiexp_given_table_parallel<C: Curve>(
+    exps: &[C::Scalar],
+    table: &[Vec<C>],
+    window_size: usize,
+) -> C {
+    use rayon::prelude::*;
+    let num_threads = rayon::current_num_threads();
+    let chunk_size = std::cmp::max(
+        MIN_PARALLEL_MULTIEXP_CHUNK,
+        (exps.len() + num_threads - 1) / num_threads,
+    );
+    exps.par_chunks(chunk_size)
+        .zip(table.par_chunks(chunk_size))
+        .map(|(exps, table)| multiexp_given_table_single(exps, table, window_size))
+        .reduce(C::zero_point, |a, b| a.plus_point(&b))
+}
+
+/// The single-threaded worker for `multiexp_worker_given_table`.
+fn multiexp_given_table_single<C: Curve>(
+    exps: &[C::Scalar],
+    table: &[Vec<C>],
+    window_size: usize,
 ) -> C {
     // Compute the wnaf
 
@@ -325,4 +504,87 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    pub fn test_multiscalar_large() {
+        // Large enough that the computation is split between threads when the
+        // `parallel` feature is enabled.
+        let mut csprng = thread_rng();
+        for &l in [128, 129, 1000].iter() {
+            let mut gs = Vec::with_capacity(l);
+            let mut es = Vec::with_capacity(l);
+            for _ in 0..l {
+                gs.push(G1::generate(&mut csprng));
+                es.push(G1::generate_scalar(&mut csprng));
+            }
+            let mut goal = G1::zero_point();
+            for (g, e) in gs.iter().zip(es.iter()) {
+                goal = goal.plus_point(&g.mul_by_scalar(e))
+            }
+            assert_eq!(
+                multiexp(&gs, &es),
+                goal,
+                "Multiexponentiation of {} terms differs from the naive method.",
+                l
+            );
+        }
+    }
+
+    /// Naive multiply + add method.
+    fn naive_multiexp(gs: &[G1], es: &[<G1 as Curve>::Scalar]) -> G1 {
+        let mut goal = G1::zero_point();
+        for (g, e) in gs.iter().zip(es.iter()) {
+            goal = goal.plus_point(&g.mul_by_scalar(e))
+        }
+        goal
+    }
+
+    #[test]
+    pub fn test_pippenger() {
+        let mut csprng = thread_rng();
+        let mut minus_one = <G1 as Curve>::Scalar::one();
+        minus_one.negate();
+        for _ in 0..20 {
+            let l = csprng.gen_range(0, 700);
+            let mut gs = Vec::with_capacity(l);
+            let mut es = Vec::with_capacity(l);
+            for _ in 0..l {
+                gs.push(G1::generate(&mut csprng));
+                // Include the extreme scalars, which have all windows equal to
+                // 0, or almost all bits set.
+                es.push(match csprng.gen_range(0, 4) {
+                    0 => <G1 as Curve>::Scalar::zero(),
+                    1 => minus_one,
+                    _ => G1::generate_scalar(&mut csprng),
+                });
+            }
+            assert_eq!(
+                pippenger(&gs, &es),
+                naive_multiexp(&gs, &es),
+                "Pippenger's method produces a different answer than the naive method for {} \
+                 terms.",
+                l
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_scalar_window() {
+        let mut csprng = thread_rng();
+        for _ in 0..100 {
+            let limbs: [u64; 4] = csprng.gen();
+            let c = csprng.gen_range(1, 17);
+            let start = csprng.gen_range(0, 300);
+            let mut expected = 0;
+            for i in (start..start + c).rev() {
+                let bit = if i < 256 {
+                    (limbs[i / 64] >> (i % 64)) & 1
+                } else {
+                    0
+                };
+                expected = (expected << 1) | bit as usize;
+            }
+            assert_eq!(scalar_window(&limbs, start, c), expected);
+        }
+    }
 }
@@ -0,0 +1,277 @@
+//! Hashing to elliptic curves as specified in [RFC 9380](https://www.rfc-editor.org/rfc/rfc9380.html).
+//!
+//! Each supported suite is a type implementing [HashToCurveSuite], which
+//! carries the identifier of the suite as defined in the RFC. The suites
+//! ending in `_RO_` are the random oracle encodings (`hash_to_curve`), and the
+//! ones ending in `_NU_` are the nonuniform encodings (`encode_to_curve`).
+//! Nonuniform encodings are cheaper to compute, but their output is not
+//! uniformly distributed, so they must only be used where that is acceptable.
+//!
+//! The domain separation tag (DST) is chosen by the caller. The RFC recommends
+//! that it contains the suite identifier, see [HashToCurveSuite::SUITE_ID].
+use crate::{
+    bls12_381_g1hash::{encode_to_curve, hash_to_curve},
+    bls12_381_g2hash::{encode_to_curve_g2, hash_to_curve_g2},
+    ristretto::RistrettoPoint,
+};
+use pairing::bls12_381::{G1, G2};
+use sha2::{
+    digest::{generic_array::typenum::Unsigned, BlockInput},
+    Digest, Sha256, Sha512,
+};
+use std::convert::TryInto;
+
+/// A hash to curve suite of RFC 9380.
+pub trait HashToCurveSuite {
+    /// The group the suite hashes to.
+    type Output;
+    /// The identifier of the suite, as specified in the RFC.
+    const SUITE_ID: &'static str;
+    /// Hash the message to the group using the given domain separation tag.
+    /// Tags longer than 255 bytes are first hashed as specified in section
+    /// 5.3.3 of the RFC.
+    fn hash(msg: &[u8], dst: &[u8]) -> Self::Output;
+}
+
+/// The random oracle suite for the group G1 of BLS12-381.
+pub struct Bls12381G1XmdSha256SswuRo;
+
+/// The nonuniform suite for the group G1 of BLS12-381.
+pub struct Bls12381G1XmdSha256SswuNu;
+
+/// The random oracle suite for the group G2 of BLS12-381.
+pub struct Bls12381G2XmdSha256SswuRo;
+
+/// The nonuniform suite for the group G2 of BLS12-381.
+pub struct Bls12381G2XmdSha256SswuNu;
+
+/// The random oracle suite for Ristretto255. The RFC does not define a
+/// nonuniform variant.
+pub struct Ristretto255XmdSha512R255MapRo;
+
+impl HashToCurveSuite for Bls12381G1XmdSha256SswuRo {
+    type Output = G1;
+
+    const SUITE_ID: &'static str = "BLS12381G1_XMD:SHA-256_SSWU_RO_";
+
+    fn hash(msg: &[u8], dst: &[u8]) -> G1 { hash_to_curve(msg, &oversize_dst::<Sha256>(dst)) }
+}
+
+impl HashToCurveSuite for Bls12381G1XmdSha256SswuNu {
+    type Output = G1;
+
+    const SUITE_ID: &'static str = "BLS12381G1_XMD:SHA-256_SSWU_NU_";
+
+    fn hash(msg: &[u8], dst: &[u8]) -> G1 { encode_to_curve(msg, &oversize_dst::<Sha256>(dst)) }
+}
+
+impl HashToCurveSuite for Bls12381G2XmdSha256SswuRo {
+    type Output = G2;
+
+    const SUITE_ID: &'static str = "BLS12381G2_XMD:SHA-256_SSWU_RO_";
+
+    fn hash(msg: &[u8], dst: &[u8]) -> G2 { hash_to_curve_g2(msg, &oversize_dst::<Sha256>(dst)) }
+}
+
+impl HashToCurveSuite for Bls12381G2XmdSha256SswuNu {
+    type Output = G2;
+
+    const SUITE_ID: &'static str = "BLS12381G2_XMD:SHA-256_SSWU_NU_";
+
+    fn hash(msg: &[u8], dst: &[u8]) -> G2 { encode_to_curve_g2(msg, &oversize_dst::<Sha256>(dst)) }
+}
+
+impl HashToCurveSuite for Ristretto255XmdSha512R255MapRo {
+    type Output = RistrettoPoint;
+
+    const SUITE_ID: &'static str = "ristretto255_XMD:SHA-512_R255MAP_RO_";
+
+    fn hash(msg: &[u8], dst: &[u8]) -> RistrettoPoint {
+        RistrettoPoint::hash_with_dst(msg, &oversize_dst::<Sha512>(dst))
+    }
+}
+
+/// Replace a domain separation tag longer than 255 bytes by
+/// `H("H2C-OVERSIZE-DST-" || DST)`, see section 5.3.3 of RFC 9380.
+fn oversize_dst<H: Digest>(dst: &[u8]) -> Vec<u8> {
+    if dst.len() > 255 {
+        let mut h = H::new();
+        h.update(b"H2C-OVERSIZE-DST-");
+        h.update(dst);
+        h.finalize().to_vec()
+    } else {
+        dst.to_vec()
+    }
+}
+
+/// Implements `expand_message_xmd` of section 5.3.1 of RFC 9380 for the hash
+/// function `H`. The domain separation tag must be at most 255 bytes, and
+/// `len_in_bytes` at most 255 times the output size of `H`.
+pub(crate) fn expand_message_xmd<H: Digest + BlockInput>(
+    msg: &[u8],
+    dst: &[u8],
+    len_in_bytes: usize,
+) -> Vec<u8> {
+    let b_in_bytes = H::OutputSize::to_usize();
+    let ell = (len_in_bytes + b_in_bytes - 1) / b_in_bytes;
+    assert!(ell <= 255, "Requested output is too long.");
+    let len_in_bytes_u16: u16 = len_in_bytes
+        .try_into()
+        .expect("Requested output is too long.");
+    // DST_prime = DST || I2OSP(len(DST), 1)
+    let dst_len: u8 = dst.len().try_into().expect("DST is at most 255 bytes.");
+
+    // b_0 = H(Z_pad || msg || I2OSP(len_in_bytes, 2) || I2OSP(0, 1) || DST_prime)
+    let mut h = H::new();
+    h.update(vec![0u8; H::BlockSize::to_usize()]);
+    h.update(msg);
+    h.update(len_in_bytes_u16.to_be_bytes());
+    h.update([0u8]);
+    h.update(dst);
+    h.update([dst_len]);
+    let b_0 = h.finalize();
+
+    // b_1 = H(b_0 || I2OSP(1, 1) || DST_prime)
+    let mut h = H::new();
+    h.update(&b_0);
+    h.update([1u8]);
+    h.update(dst);
+    h.update([dst_len]);
+    let mut b_i = h.finalize();
+
+    let mut uniform_bytes = Vec::with_capacity(ell * b_in_bytes);
+    uniform_bytes.extend_from_slice(&b_i);
+    for i in 2..=ell {
+        // b_i = H(strxor(b_0, b_(i - 1)) || I2OSP(i, 1) || DST_prime)
+        let xor: Vec<u8> = b_0.iter().zip(b_i.iter()).map(|(x, y)| x ^ y).collect();
+        let mut h = H::new();
+        h.update(xor);
+        h.update([i as u8]);
+        h.update(dst);
+        h.update([dst_len]);
+        b_i = h.finalize();
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len_in_bytes);
+    uniform_bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_common::*;
+
+    fn from_hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// The messages of the test vectors in Appendix J of RFC 9380.
+    fn messages() -> [Vec<u8>; 5] {
+        [
+            b"".to_vec(),
+            b"abc".to_vec(),
+            b"abcdef0123456789".to_vec(),
+            [&b"q128_"[..], &[b'q'; 128][..]].concat(),
+            [&b"a512_"[..], &[b'a'; 512][..]].concat(),
+        ]
+    }
+
+    /// Check the suite against the points of the test vectors, given in the
+    /// compressed serialization.
+    fn check_suite<S: HashToCurveSuite>(expected: [&str; 5])
+    where
+        S::Output: Serial, {
+        let dst = format!("QUUX-V01-CS02-with-{}", S::SUITE_ID);
+        for (msg, expected) in messages().iter().zip(expected.iter()) {
+            let p = S::hash(msg, dst.as_bytes());
+            assert_eq!(
+                to_bytes(&p),
+                from_hex(expected),
+                "Suite {}, message {:?}.",
+                S::SUITE_ID,
+                String::from_utf8_lossy(&msg[..msg.len().min(16)])
+            );
+        }
+    }
+
+    // Appendix J.9.1.
+    #[test]
+    fn test_bls12381g1_ro() {
+        check_suite::<Bls12381G1XmdSha256SswuRo>([
+            "852926add2207b76ca4fa57a8734416c8dc95e24501772c814278700eed6d1e4e8cf62d9c09db0fac349612b759e79a1",
+            "83567bc5ef9c690c2ab2ecdf6a96ef1c139cc0b2f284dca0a9a7943388a49a3aee664ba5379a7655d3c68900be2f6903",
+            "91e0b079dea29a68f0383ee94fed1b940995272407e3bb916bbf268c263ddd57a6a27200a784cbc248e84f357ce82d98",
+            "b5f68eaa693b95ccb85215dc65fa81038d69629f70aeee0d0f677cf22285e7bf58d7cb86eefe8f2e9bc3f8cb84fac488",
+            "882aabae8b7dedb0e78aeb619ad3bfd9277a2f77ba7fad20ef6aabdc6c31d19ba5a6d12283553294c1825c4b3ca2dcfe",
+        ])
+    }
+
+    // Appendix J.9.2.
+    #[test]
+    fn test_bls12381g1_nu() {
+        check_suite::<Bls12381G1XmdSha256SswuNu>([
+            "984bb665c37ff561a89ec2122dd343f20e0f4cbcaec84e3c3052ea81d1834e192c426074b02ed3dca4e7676ce4ce48ba",
+            "a09769f3ab59bfd551d53a5f846b9984c59b97d6842b20a2c565baa167945e3d026a3755b6345df8ec7e6acb6868ae6d",
+            "b974dbb8e6b5d20b84df7e625e2fbfecb2cdb5f77d5eae5fb2955e5ce7313cae8364bc2fff520a6c25619739c6bdcb6a",
+            "aa7a047c4a8397b3446450642c2ac64d7239b61872c9ae7a59707a8f4f950f101e766afe58223b3bff3a19a7f754027c",
+            "8e7a16a975904f131682edbb03d9560d3e48214c9986bd50417a77108d13dc957500edf96462a3d01e62dc6cd468ef11",
+        ])
+    }
+
+    // Appendix J.10.1.
+    #[test]
+    fn test_bls12381g2_ro() {
+        check_suite::<Bls12381G2XmdSha256SswuRo>([
+            "a5cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a",
+            "939cddbccdc5e91b9623efd38c49f81a6f83f175e80b06fc374de9eb4b41dfe4ca3a230ed250fbe3a2acf73a41177fd802c2d18e033b960562aae3cab37a27ce00d80ccd5ba4b7fe0e7a210245129dbec7780ccc7954725f4168aff2787776e6",
+            "990d119345b94fbd15497bcba94ecf7db2cbfd1e1fe7da034d26cbba169fb3968288b3fafb265f9ebd380512a71c3f2c121982811d2491fde9ba7ed31ef9ca474f0e1501297f68c298e9f4c0028add35aea8bb83d53c08cfc007c1e005723cd0",
+            "8934aba516a52d8ae479939a91998299c76d39cc0c035cd18813bec433f587e2d7a4fef038260eef0cef4d02aae3eb9119a84dd7248a1066f737cc34502ee5555bd3c19f2ecdb3c7d9e24dc65d4e25e50d83f0f77105e955d78f4762d33c17da",
+            "91fca2ff525572795a801eed17eb12785887c7b63fb77a42be46ce4a34131d71f7a73e95fee3f812aea3de78b4d0156901a6ba2f9a11fa5598b2d8ace0fbe0a0eacb65deceb476fbbcb64fd24557c2f4b18ecfc5663e54ae16a84f5ab7f62534",
+        ])
+    }
+
+    // Appendix J.10.2.
+    #[test]
+    fn test_bls12381g2_nu() {
+        check_suite::<Bls12381G2XmdSha256SswuNu>([
+            "b26b855e9e69b1f691f816e48ac6977664d24d99f8724868a184186469ddfd4617367e94527d4b74fc86413483afb35b00e7f4568a82b4b7dc1f14c6aaa055edf51502319c723c4dc2688c7fe5944c213f510328082396515734b6612c4e7bb7",
+            "a296238ea82c6d4adb3c838ee3cb2346049c90b96d602d7bb1b469b905c9228be25c627bffee872def773d5b2a2eb57d108ed59fd9fae381abfd1d6bce2fd2fa220990f0f837fa30e0f27914ed6e1454db0d1ee957b219f61da6ff8be0d6441f",
+            "8da75be60fb6aa0e9e3143e40c42796edf15685cafe0279afd2a67c3dff1c82341f17effd402e4f1af240ea90f4b659b038af300ef34c7759a6caaa4e69363cafeed218a1f207e93b2c70d91a1263d375d6730bd6b6509dcac3ba5b567e85bf3",
+            "b2c8c05c1d5fc7bfa847f4d7d81e294e66b9a78bc9953990c358945e1f042eedafce608b67fdd3ab0cb2e6e263b9b1ad0c5ae723be00e6c3f0efe184fdc0702b64588fe77dda152ab13099a3bacd3876767fa7bbad6d6fd90b3642e902b208f9",
+            "b565c2f625032d232f13121d3cfb476f45275c303a037faa255f9da62000c2c864ea881e2bcddd111edc4a3c0da3e88d0ea4e7c33d43e17cc516a72f76437c4bf81d8f4eac69ac355d3bf9b71b8138d55dc10fd458be115afa798b55dac34be1",
+        ])
+    }
+
+    // Appendices K.1 and K.3.
+    #[test]
+    fn test_expand_message_xmd() {
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        assert_eq!(
+            expand_message_xmd::<Sha256>(b"", dst, 0x20),
+            from_hex("68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235")
+        );
+        assert_eq!(
+            expand_message_xmd::<Sha256>(b"abc", dst, 0x80),
+            from_hex("abba86a6129e366fc877aab32fc4ffc70120d8996c88aee2fe4b32d6c7b6437a647e6c3163d40b76a73cf6a5674ef1d890f95b664ee0afa5359a5c4e07985635bbecbac65d747d3d2da7ec2b8221b17b0ca9dc8a1ac1c07ea6a1e60583e2cb00058e77b7b72a298425cd1b941ad4ec65e8afc50303a22c0f99b0509b4c895f40")
+        );
+        let dst = b"QUUX-V01-CS02-with-expander-SHA512-256";
+        assert_eq!(
+            expand_message_xmd::<Sha512>(b"", dst, 0x20),
+            from_hex("6b9a7312411d92f921c6f68ca0b6380730a1a4d982c507211a90964c394179ba")
+        );
+    }
+
+    #[test]
+    fn test_oversize_dst() {
+        let dst = [b'1'; 256];
+        let hashed = Sha256::digest(&[&b"H2C-OVERSIZE-DST-"[..], &dst[..]].concat());
+        assert_eq!(
+            Bls12381G1XmdSha256SswuRo::hash(b"abc", &dst),
+            Bls12381G1XmdSha256SswuRo::hash(b"abc", &hashed)
+        );
+        assert_eq!(oversize_dst::<Sha256>(&dst[..255]), dst[..255].to_vec());
+    }
+}
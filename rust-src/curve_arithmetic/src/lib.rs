@@ -1,13 +1,18 @@
 //! Basic definitions of the curve and pairing abstractions, and implementations
 //! of these abstractions for the curves used on Concordium.
+pub mod bls12_381;
+#[cfg(feature = "blstrs-backend")]
+pub mod bls12_381_blstrs;
 mod bls12_381_g1hash;
 mod bls12_381_g2hash;
 mod bls12_381_instance;
 mod curve_arithmetic;
 pub use crate::curve_arithmetic::*;
 
+pub mod hash_to_curve;
+pub mod ristretto;
 pub mod secret_value;
-pub use secret_value::{Secret, Value};
+pub use secret_value::{clear_field_element, Secret, Value};
 
 #[macro_use]
 extern crate crypto_common_derive;
@@ -0,0 +1,559 @@
+//! An implementation of the [Curve] abstraction for the Ristretto255 group,
+//! backed by the [curve25519-dalek](https://github.com/dalek-cryptography/curve25519-dalek) library.
+//!
+//! Ristretto255 is a prime order group built on top of Curve25519. It has no
+//! pairing, but group operations are considerably cheaper than in the groups
+//! of BLS12-381, so it is a good choice for constructions that are generic
+//! over [Curve], such as ElGamal encryption, Pedersen commitments and
+//! bulletproofs.
+//!
+//! Group elements are serialized in the standard 32 byte encoding. Scalars are
+//! serialized in 32 bytes big-endian, as the scalars of the other instances in
+//! this library.
+use crate::{curve_arithmetic::*, hash_to_curve::expand_message_xmd};
+use anyhow::bail;
+use byteorder::ReadBytesExt;
+use crypto_common::{Buffer, Deserial, ParseResult, Serial};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::CompressedRistretto, scalar::Scalar,
+    traits::Identity,
+};
+use ff::{Field, PrimeField, PrimeFieldDecodingError, PrimeFieldRepr};
+use rand::*;
+use sha2::Sha512;
+use std::{convert::TryInto, fmt};
+
+/// Domain separation string for [Curve::hash_to_group]. The suite is
+/// `ristretto255_XMD:SHA-512_R255MAP_RO_` of RFC 9380.
+const HASH_TO_GROUP_DST: &[u8; 70] =
+    b"CONCORDIUM-hashtoRistretto255-with-ristretto255_XMD:SHA-512_R255MAP_RO";
+
+/// The order of the group, 2^252 + 27742317777372353535851937790883648493, in
+/// little-endian limbs.
+const MODULUS: [u64; 4] = [
+    0x5812631a5cf5d3ed,
+    0x14def9dea2f79cd6,
+    0x0000000000000000,
+    0x1000000000000000,
+];
+
+/// A primitive 4th root of unity, `2^((l - 1)/4)`, in little-endian limbs.
+const ROOT_OF_UNITY: [u64; 4] = [
+    0xbe8775dfebbe07d4,
+    0x0ef0565342ce83fe,
+    0x7d3d6d60abc1c27a,
+    0x094a7310e07981e7,
+];
+
+/// An element of the scalar field of Ristretto255.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RistrettoScalar(pub Scalar);
+
+/// The representation of [RistrettoScalar] as a 256-bit integer, in four
+/// little-endian 64-bit limbs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RistrettoScalarRepr(pub [u64; 4]);
+
+impl AsRef<[u64]> for RistrettoScalarRepr {
+    fn as_ref(&self) -> &[u64] { &self.0 }
+}
+
+impl AsMut<[u64]> for RistrettoScalarRepr {
+    fn as_mut(&mut self) -> &mut [u64] { &mut self.0 }
+}
+
+impl From<u64> for RistrettoScalarRepr {
+    fn from(n: u64) -> Self { RistrettoScalarRepr([n, 0, 0, 0]) }
+}
+
+impl From<RistrettoScalar> for RistrettoScalarRepr {
+    fn from(s: RistrettoScalar) -> Self { s.into_repr() }
+}
+
+impl fmt::Display for RistrettoScalarRepr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x")?;
+        for limb in self.0.iter().rev() {
+            write!(f, "{:016x}", limb)?;
+        }
+        Ok(())
+    }
+}
+
+impl PrimeFieldRepr for RistrettoScalarRepr {
+    fn sub_noborrow(&mut self, other: &Self) {
+        let mut borrow = false;
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            let (d, b1) = a.overflowing_sub(*b);
+            let (d, b2) = d.overflowing_sub(u64::from(borrow));
+            *a = d;
+            borrow = b1 || b2;
+        }
+    }
+
+    fn add_nocarry(&mut self, other: &Self) {
+        let mut carry = false;
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            let (s, c1) = a.overflowing_add(*b);
+            let (s, c2) = s.overflowing_add(u64::from(carry));
+            *a = s;
+            carry = c1 || c2;
+        }
+    }
+
+    fn num_bits(&self) -> u32 {
+        let mut bits = 256;
+        for limb in self.0.iter().rev() {
+            if *limb == 0 {
+                bits -= 64;
+            } else {
+                return bits - limb.leading_zeros();
+            }
+        }
+        bits
+    }
+
+    fn is_zero(&self) -> bool { self.0.iter().all(|limb| *limb == 0) }
+
+    fn is_odd(&self) -> bool { self.0[0] & 1 == 1 }
+
+    fn is_even(&self) -> bool { !self.is_odd() }
+
+    fn div2(&mut self) { self.shr(1) }
+
+    fn shr(&mut self, amt: u32) {
+        if amt >= 256 {
+            *self = Self::default();
+            return;
+        }
+        let limbs = (amt / 64) as usize;
+        let bits = amt % 64;
+        for i in 0..4 {
+            let lo = self.0.get(i + limbs).copied().unwrap_or(0);
+            let hi = self.0.get(i + limbs + 1).copied().unwrap_or(0);
+            self.0[i] = if bits == 0 {
+                lo
+            } else {
+                (lo >> bits) | (hi << (64 - bits))
+            };
+        }
+    }
+
+    fn mul2(&mut self) { self.shl(1) }
+
+    fn shl(&mut self, amt: u32) {
+        if amt >= 256 {
+            *self = Self::default();
+            return;
+        }
+        let limbs = (amt / 64) as usize;
+        let bits = amt % 64;
+        for i in (0..4).rev() {
+            let hi = if i >= limbs { self.0[i - limbs] } else { 0 };
+            let lo = if i > limbs { self.0[i - limbs - 1] } else { 0 };
+            self.0[i] = if bits == 0 {
+                hi
+            } else {
+                (hi << bits) | (lo >> (64 - bits))
+            };
+        }
+    }
+}
+
+/// Sample a scalar uniformly at random. Reducing 512 bits modulo the group
+/// order has negligible bias.
+fn random_scalar<R: RngCore + ?Sized>(rng: &mut R) -> Scalar {
+    let mut bytes = [0u8; 64];
+    rng.fill_bytes(&mut bytes);
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+impl fmt::Display for RistrettoScalar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RistrettoScalar({})", self.into_repr())
+    }
+}
+
+impl Field for RistrettoScalar {
+    fn random<R: RngCore + ?Sized>(rng: &mut R) -> Self { RistrettoScalar(random_scalar(rng)) }
+
+    fn zero() -> Self { RistrettoScalar(Scalar::zero()) }
+
+    fn one() -> Self { RistrettoScalar(Scalar::one()) }
+
+    fn is_zero(&self) -> bool { self.0 == Scalar::zero() }
+
+    fn square(&mut self) { self.0 *= self.0; }
+
+    fn double(&mut self) { self.0 += self.0; }
+
+    fn negate(&mut self) { self.0 = -self.0; }
+
+    fn add_assign(&mut self, other: &Self) { self.0 += other.0; }
+
+    fn sub_assign(&mut self, other: &Self) { self.0 -= other.0; }
+
+    fn mul_assign(&mut self, other: &Self) { self.0 *= other.0; }
+
+    fn inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            None
+        } else {
+            Some(RistrettoScalar(self.0.invert()))
+        }
+    }
+
+    // The Frobenius map is the identity on a prime field.
+    fn frobenius_map(&mut self, _power: usize) {}
+}
+
+impl PrimeField for RistrettoScalar {
+    type Repr = RistrettoScalarRepr;
+
+    const CAPACITY: u32 = 252;
+    const NUM_BITS: u32 = 253;
+    const S: u32 = 2;
+
+    fn from_repr(repr: Self::Repr) -> Result<Self, PrimeFieldDecodingError> {
+        let mut bytes = [0u8; 32];
+        for (chunk, limb) in bytes.chunks_mut(8).zip(repr.0.iter()) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        match Scalar::from_canonical_bytes(bytes) {
+            Some(s) => Ok(RistrettoScalar(s)),
+            None => Err(PrimeFieldDecodingError::NotInField(format!("{}", repr))),
+        }
+    }
+
+    fn into_repr(&self) -> Self::Repr {
+        let mut repr = RistrettoScalarRepr::default();
+        for (limb, chunk) in repr.0.iter_mut().zip(self.0.as_bytes().chunks(8)) {
+            *limb = u64::from_le_bytes(chunk.try_into().expect("Chunks have length 8."));
+        }
+        repr
+    }
+
+    fn char() -> Self::Repr { RistrettoScalarRepr(MODULUS) }
+
+    fn multiplicative_generator() -> Self { RistrettoScalar(Scalar::from(2u64)) }
+
+    fn root_of_unity() -> Self {
+        Self::from_repr(RistrettoScalarRepr(ROOT_OF_UNITY))
+            .expect("The root of unity is a field element.")
+    }
+}
+
+impl Serial for RistrettoScalar {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        let mut bytes = self.0.to_bytes();
+        bytes.reverse();
+        out.write_all(&bytes)
+            .expect("Writing to buffer should succeed.");
+    }
+}
+
+impl Deserial for RistrettoScalar {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let mut bytes = [0u8; 32];
+        source.read_exact(&mut bytes)?;
+        bytes.reverse();
+        match Scalar::from_canonical_bytes(bytes) {
+            Some(s) => Ok(RistrettoScalar(s)),
+            None => bail!("Not a canonical scalar."),
+        }
+    }
+}
+
+/// An element of the Ristretto255 group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RistrettoPoint(pub curve25519_dalek::ristretto::RistrettoPoint);
+
+impl fmt::Display for RistrettoPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RistrettoPoint(0x")?;
+        for b in self.0.compress().as_bytes().iter() {
+            write!(f, "{:02x}", b)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Serial for RistrettoPoint {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        out.write_all(self.0.compress().as_bytes())
+            .expect("Writing to buffer should succeed.");
+    }
+}
+
+impl Deserial for RistrettoPoint {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let mut bytes = [0u8; 32];
+        source.read_exact(&mut bytes)?;
+        Ok(Self::decompress(&CompressedRistretto(bytes))?)
+    }
+}
+
+impl RistrettoPoint {
+    /// Hash to the group with the `ristretto255_XMD:SHA-512_R255MAP_RO_` suite
+    /// of RFC 9380 and the given domain separation tag, which must be at most
+    /// 255 bytes.
+    pub(crate) fn hash_with_dst(msg: &[u8], dst: &[u8]) -> Self {
+        let mut uniform_bytes = [0u8; 64];
+        uniform_bytes.copy_from_slice(&expand_message_xmd::<Sha512>(msg, dst, 64));
+        RistrettoPoint(
+            curve25519_dalek::ristretto::RistrettoPoint::from_uniform_bytes(&uniform_bytes),
+        )
+    }
+}
+
+impl Curve for RistrettoPoint {
+    // curve25519-dalek does not expose the base field of the curve. It is not
+    // used by any of the constructions, so the scalar field is used to satisfy
+    // the trait.
+    type Base = RistrettoScalar;
+    type Compressed = CompressedRistretto;
+    type Scalar = RistrettoScalar;
+
+    const GROUP_ELEMENT_LENGTH: usize = 32;
+    const SCALAR_LENGTH: usize = 32;
+
+    fn zero_point() -> Self {
+        RistrettoPoint(curve25519_dalek::ristretto::RistrettoPoint::identity())
+    }
+
+    fn one_point() -> Self { RistrettoPoint(RISTRETTO_BASEPOINT_POINT) }
+
+    fn is_zero_point(&self) -> bool { *self == Self::zero_point() }
+
+    fn inverse_point(&self) -> Self { RistrettoPoint(-self.0) }
+
+    fn double_point(&self) -> Self { RistrettoPoint(self.0 + self.0) }
+
+    fn plus_point(&self, other: &Self) -> Self { RistrettoPoint(self.0 + other.0) }
+
+    fn minus_point(&self, other: &Self) -> Self { RistrettoPoint(self.0 - other.0) }
+
+    fn mul_by_scalar(&self, scalar: &Self::Scalar) -> Self { RistrettoPoint(self.0 * scalar.0) }
+
+    // Scalar multiplication in curve25519-dalek is constant-time.
+    fn mul_by_secret_scalar(&self, scalar: &Self::Scalar) -> Self { self.mul_by_scalar(scalar) }
+
+    fn compress(&self) -> Self::Compressed { self.0.compress() }
+
+    fn decompress(c: &Self::Compressed) -> Result<Self, CurveDecodingError> {
+        match c.decompress() {
+            Some(p) => Ok(RistrettoPoint(p)),
+            None => Err(CurveDecodingError::NotOnCurve),
+        }
+    }
+
+    // Decoding always validates the encoding, there is no cheaper unchecked
+    // variant.
+    fn decompress_unchecked(c: &Self::Compressed) -> Result<Self, CurveDecodingError> {
+        Self::decompress(c)
+    }
+
+    fn bytes_to_curve_unchecked<R: ReadBytesExt>(bytes: &mut R) -> anyhow::Result<Self> {
+        let mut c = [0u8; 32];
+        bytes.read_exact(&mut c)?;
+        Ok(Self::decompress_unchecked(&CompressedRistretto(c))?)
+    }
+
+    fn generate<T: Rng>(csprng: &mut T) -> Self {
+        let mut bytes = [0u8; 64];
+        csprng.fill_bytes(&mut bytes);
+        RistrettoPoint(curve25519_dalek::ristretto::RistrettoPoint::from_uniform_bytes(&bytes))
+    }
+
+    fn generate_scalar<T: Rng>(csprng: &mut T) -> Self::Scalar {
+        RistrettoScalar(random_scalar(csprng))
+    }
+
+    fn scalar_from_u64(n: u64) -> Self::Scalar { RistrettoScalar(Scalar::from(n)) }
+
+    /// Take the first 32 bytes (little-endian), and unset the top four bits,
+    /// so that the result is less than the group order.
+    fn scalar_from_bytes<A: AsRef<[u8]>>(bytes: A) -> Self::Scalar {
+        let mut s = [0u8; 32];
+        let bytes = bytes.as_ref();
+        let len = std::cmp::min(bytes.len(), 32);
+        s[..len].copy_from_slice(&bytes[..len]);
+        s[31] &= 0x0f;
+        RistrettoScalar(
+            Scalar::from_canonical_bytes(s)
+                .expect("The scalar with top four bits erased is valid."),
+        )
+    }
+
+    /// Hash to the group as specified by the
+    /// `ristretto255_XMD:SHA-512_R255MAP_RO_` suite of RFC 9380.
+    fn hash_to_group(bytes: &[u8]) -> Self { Self::hash_with_dst(bytes, HASH_TO_GROUP_DST) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_common::*;
+    use std::io::Cursor;
+
+    fn from_hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_scalar_byte_conversion() {
+        let mut csprng = thread_rng();
+        for _ in 0..1000 {
+            let scalar = RistrettoPoint::generate_scalar(&mut csprng);
+            let scalar_res = serialize_deserialize(&scalar);
+            assert!(scalar_res.is_ok());
+            assert_eq!(scalar, scalar_res.unwrap());
+            assert_eq!(
+                RistrettoScalar::from_repr(scalar.into_repr()).unwrap(),
+                scalar,
+                "Repr roundtrip."
+            );
+        }
+        // The modulus is not a valid scalar.
+        assert!(RistrettoScalar::from_repr(RistrettoScalar::char()).is_err());
+    }
+
+    #[test]
+    fn test_group_byte_conversion() {
+        let mut csprng = thread_rng();
+        for _ in 0..1000 {
+            let curve = RistrettoPoint::generate(&mut csprng);
+            let curve_res = serialize_deserialize(&curve);
+            assert!(curve_res.is_ok());
+            assert_eq!(curve, curve_res.unwrap());
+            let bytes = to_bytes(&curve);
+            let curve_res = RistrettoPoint::bytes_to_curve_unchecked(&mut Cursor::new(&bytes));
+            assert_eq!(curve, curve_res.unwrap());
+        }
+        // The encoding of the base point, from RFC 9496.
+        assert_eq!(
+            to_bytes(&RistrettoPoint::one_point()),
+            from_hex("e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76")
+        );
+        // A non-canonical encoding of the identity, from RFC 9496.
+        let bad = from_hex("00ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+        assert!(RistrettoPoint::deserial(&mut Cursor::new(&bad)).is_err());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let mut csprng = thread_rng();
+        let g = RistrettoPoint::one_point();
+        // Small multiples of the generator.
+        let mut acc = RistrettoPoint::zero_point();
+        for i in 0..16u64 {
+            assert_eq!(g.mul_by_scalar(&RistrettoPoint::scalar_from_u64(i)), acc);
+            acc = acc.plus_point(&g);
+        }
+        for _ in 0..100 {
+            let a = RistrettoPoint::generate_scalar(&mut csprng);
+            let b = RistrettoPoint::generate_non_zero_scalar(&mut csprng);
+            let mut ab = a;
+            ab.mul_assign(&b);
+            let p = g.mul_by_scalar(&a).mul_by_scalar(&b);
+            assert_eq!(p, g.mul_by_scalar(&ab));
+            let mut b_inv = b.inverse().unwrap();
+            b_inv.mul_assign(&b);
+            assert_eq!(b_inv, RistrettoScalar::one());
+            assert!(p.minus_point(&p).is_zero_point());
+            assert_eq!(p.double_point(), p.plus_point(&p));
+            assert_eq!(
+                p.plus_point(&p.inverse_point()),
+                RistrettoPoint::zero_point()
+            );
+        }
+        // The root of unity has order 4.
+        let mut w = RistrettoScalar::root_of_unity();
+        w.square();
+        assert_ne!(w, RistrettoScalar::one());
+        w.square();
+        assert_eq!(w, RistrettoScalar::one());
+    }
+
+    #[test]
+    fn test_repr_shifts() {
+        let mut csprng = thread_rng();
+        for _ in 0..100 {
+            let orig = RistrettoPoint::generate_scalar(&mut csprng).into_repr();
+            assert!(orig.num_bits() <= RistrettoScalar::NUM_BITS);
+            // Shifting left and back clears the top `amt` bits.
+            let amt = csprng.gen_range(0, 256);
+            let mut r = orig;
+            r.shl(amt);
+            r.shr(amt);
+            let mut expected = orig;
+            for (i, limb) in expected.0.iter_mut().enumerate() {
+                let keep = (256 - amt).saturating_sub(64 * i as u32);
+                if keep < 64 {
+                    *limb &= (1u64 << keep) - 1;
+                }
+            }
+            assert_eq!(r, expected, "Shift by {}.", amt);
+            let mut doubled = orig;
+            doubled.mul2();
+            doubled.div2();
+            assert_eq!(doubled, orig);
+            let mut sum = orig;
+            sum.add_nocarry(&orig);
+            sum.sub_noborrow(&orig);
+            assert_eq!(sum, orig);
+        }
+    }
+
+    #[test]
+    fn test_scalar_from_bytes() {
+        let mut csprng = thread_rng();
+        for _ in 0..1000 {
+            let n = RistrettoPoint::generate_scalar(&mut csprng);
+            let mut bytes = to_bytes(&n);
+            bytes.reverse();
+            let m = RistrettoPoint::scalar_from_bytes(&bytes);
+            // n and m only differ in the top four bits.
+            let n = n.into_repr().0;
+            let m = m.into_repr().0;
+            assert_eq!(n[..3], m[..3]);
+            assert_eq!(n[3] & 0x0fff_ffff_ffff_ffff, m[3]);
+        }
+    }
+
+    #[test]
+    fn test_hash_to_group() {
+        let vectors = [
+            (
+                "",
+                "bad11ae71b85b1b0cae8214c354210d1693752c3f8fc7d159d11993cf0c8cf77",
+            ),
+            (
+                "abc",
+                "06fc578bced24325278e259118318f5be0eb02b9b0064796da24791ee70d3460",
+            ),
+            (
+                "abcdef0123456789",
+                "5e47d675fbdfafe95c20c57df5d60ddc699f3e30c935940a8bf18ad29210bc71",
+            ),
+        ];
+        for (msg, expected) in vectors.iter() {
+            let p = RistrettoPoint::hash_to_group(msg.as_bytes());
+            assert_eq!(to_bytes(&p), from_hex(expected), "Hash of {:?}.", msg);
+        }
+        // The underlying map, from the test vectors of RFC 9496.
+        let uniform = from_hex(
+            "5d1be09e3d0c82fc538112490e35701979d99e06ca3e2b5b54bffe8b4dc772c14d98b696a1bbfb5ca32c436cc61c16563790306c79eaca7705668b47dffe5bb6",
+        );
+        let p = curve25519_dalek::ristretto::RistrettoPoint::from_uniform_bytes(
+            &uniform[..].try_into().unwrap(),
+        );
+        assert_eq!(
+            p.compress().as_bytes()[..],
+            from_hex("3066f82a1a747d45120d1740f14358531a8f04bbffe6a819f86dfe50f44a0a46")[..]
+        );
+    }
+}
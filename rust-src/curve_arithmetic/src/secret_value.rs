@@ -7,6 +7,7 @@ use crypto_common::*;
 use ff::Field;
 use rand::*;
 use std::{
+    fmt,
     ops::{Deref, Drop},
     ptr,
     rc::Rc,
@@ -19,7 +20,7 @@ use std::{
 /// it, so we cannot use it at the moment. Hence the temporary hack of 'F:
 /// Field'.
 #[repr(transparent)]
-#[derive(Debug, PartialEq, Eq, Serialize)]
+#[derive(PartialEq, Eq, Serialize)]
 pub struct Secret<T: Field + Serialize> {
     secret: T,
 }
@@ -38,16 +39,27 @@ impl<F: Field + Serialize> Deref for Secret<F> {
     fn deref(&self) -> &Self::Target { &self.secret }
 }
 
+/// The secret is not printed, so that it does not end up in logs.
+impl<F: Field + Serialize> fmt::Debug for Secret<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { f.write_str("Secret(<redacted>)") }
+}
+
+/// Overwrite a field element with zero in a way that the compiler will not
+/// optimize away. This is what [Secret] does when dropped, and it is meant for
+/// the `Drop` implementations of secret keys that store field elements
+/// directly.
 // This works for our current fields since they are arrays
 // But in the future we need to revisit, especially if our
 // upstream dependencies decide to implement drop themselves.
+pub fn clear_field_element<F: Field>(x: &mut F) {
+    // This implementation is what the Zeroize trait implementations do.
+    // It protects against most reorderings by the compiler.
+    unsafe { ptr::write_volatile(x, F::zero()) }
+    atomic::compiler_fence(atomic::Ordering::SeqCst);
+}
+
 impl<F: Field + Serialize> Drop for Secret<F> {
-    fn drop(&mut self) {
-        // This implementation is what the Zeroize trait implementations do.
-        // It protects against most reorderings by the compiler.
-        unsafe { ptr::write_volatile(&mut self.secret, F::zero()) }
-        atomic::compiler_fence(atomic::Ordering::SeqCst);
-    }
+    fn drop(&mut self) { clear_field_element(&mut self.secret) }
 }
 
 /// A secret value. The idea of this datatype is to mark
@@ -123,4 +135,23 @@ mod tests {
     macro_test_value_to_byte_conversion!(value_to_byte_conversion_bls12_381_g1_affine, G1Affine);
 
     macro_test_value_to_byte_conversion!(value_to_byte_conversion_bls12_381_g2_affine, G2Affine);
+
+    #[test]
+    fn secret_is_cleared_on_drop() {
+        let mut csprng = thread_rng();
+        let scalar = G1Affine::generate_non_zero_scalar(&mut csprng);
+        let mut secret = std::mem::ManuallyDrop::new(Secret::new(scalar));
+        // Run the destructor, but keep the memory so it can be inspected.
+        unsafe { std::ptr::drop_in_place(&mut *secret) };
+        assert!(secret.secret.is_zero(), "Secret was not cleared.");
+    }
+
+    #[test]
+    fn secret_debug_is_redacted() {
+        let scalar = <G1Affine as Curve>::scalar_from_u64(1234567);
+        let value = Value::<G1Affine>::new(scalar);
+        let printed = format!("{:?}", value);
+        assert!(!printed.contains(&format!("{:?}", scalar)));
+        assert!(printed.contains("<redacted>"));
+    }
 }
@@ -3,7 +3,7 @@
 use crate::errors::{InternalError::DivisionByZero, *};
 use crypto_common::*;
 use curve_arithmetic::{Curve, Secret, Value};
-use ff::Field;
+use ff::{Field, PrimeField, PrimeFieldRepr};
 use rand::*;
 use std::rc::Rc;
 
@@ -52,16 +52,19 @@ impl<C: Curve> SecretKey<C> {
     pub fn prf_exponent(&self, n: u8) -> Result<C::Scalar, PrfError> {
         let mut x = C::scalar_from_u64(u64::from(n));
         x.add_assign(self);
-        match x.inverse() {
-            None => Err(PrfError(DivisionByZero)),
-            Some(y) => Ok(y),
+        if x.is_zero() {
+            return Err(PrfError(DivisionByZero));
         }
+        // Invert as x^(p-2) since the `inverse` method is not constant-time.
+        let mut exponent = C::Scalar::char();
+        exponent.sub_noborrow(&2.into());
+        Ok(x.pow(exponent))
     }
 
     /// Compute the PRF function given the base `g` and the counter.
     pub fn prf(&self, g: &C, n: u8) -> Result<C, PrfError> {
         let y = self.prf_exponent(n)?;
-        Ok(g.mul_by_scalar(&y))
+        Ok(g.mul_by_secret_scalar(&y))
     }
 
     /// Generate a `SecretKey` from a `csprng`.
@@ -87,4 +90,25 @@ mod tests {
             assert_eq!(sk2, sk);
         }
     }
+
+    #[test]
+    pub fn prf_exponent_is_inverse() {
+        let mut csprng = thread_rng();
+        for n in 0..=20 {
+            let sk = SecretKey::<G1>::generate(&mut csprng);
+            let mut x = G1::scalar_from_u64(u64::from(n));
+            x.add_assign(&sk);
+            let mut y = sk.prf_exponent(n).expect("The key is not -n.");
+            y.mul_assign(&x);
+            assert_eq!(y, <G1 as Curve>::Scalar::one());
+        }
+    }
+
+    #[test]
+    pub fn key_debug_is_redacted() {
+        let mut csprng = thread_rng();
+        let sk = SecretKey::<G1>::generate(&mut csprng);
+        let printed = format!("{:?}", sk);
+        assert!(!printed.contains(&format!("{:?}", *sk)));
+    }
 }
@@ -24,9 +24,63 @@ const EXPANDED_SECRET_KEY_NONCE_LENGTH: usize = 32;
 pub const EXPANDED_SECRET_KEY_LENGTH: usize =
     EXPANDED_SECRET_KEY_KEY_LENGTH + EXPANDED_SECRET_KEY_NONCE_LENGTH;
 
+/// The prefix of the serialization of `PublicKey`s that are not in the default
+/// suite. This is the compressed encoding of the identity point.
+pub const SUITE_TAG: [u8; PUBLIC_KEY_LENGTH] = [
+    1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+/// The length of a `BatchableProof`, in bytes.
+pub const BATCHABLE_PROOF_LENGTH: usize = 128;
+
 /// Suite string as defined by <https://tools.ietf.org/id/draft-irtf-cfrg-vrf-07.html#suites>
 pub const SUITE_STRING: [u8; 1] = [3u8];
 
+/// Suite string of ECVRF-EDWARDS25519-SHA512-ELL2 as defined by <https://www.rfc-editor.org/rfc/rfc9381.html#section-5.5>
+pub const ELL2_SUITE_STRING: [u8; 1] = [4u8];
+
+/// Domain separation tag for `encode_to_curve` in the ELL2 suite. This is
+/// "ECVRF_" || h2c_suite_ID_string || suite_string, see
+/// <https://www.rfc-editor.org/rfc/rfc9381.html#section-5.4.1.2>
+pub(crate) const ELL2_ENCODE_TO_CURVE_DST: &[u8] = b"ECVRF_edwards25519_XMD:SHA-512_ELL2_NU_\x04";
+
+/// The ciphersuites supported by this crate. A `PublicKey` carries the suite
+/// it is used with, which determines how inputs are hashed to the curve and
+/// how challenges and VRF outputs are computed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Suite {
+    /// ECVRF-EDWARDS25519-SHA512-TAI as specified in <https://tools.ietf.org/id/draft-irtf-cfrg-vrf-07.html>.
+    /// This is the suite used by the chain, and the default.
+    EdwardsSha512Tai,
+    /// ECVRF-EDWARDS25519-SHA512-ELL2 as specified in <https://www.rfc-editor.org/rfc/rfc9381.html>.
+    EdwardsSha512Ell2,
+}
+
+impl Default for Suite {
+    fn default() -> Self { Suite::EdwardsSha512Tai }
+}
+
+impl Suite {
+    /// The suite string that is prepended to all hash inputs.
+    pub fn suite_string(self) -> [u8; 1] {
+        match self {
+            Suite::EdwardsSha512Tai => SUITE_STRING,
+            Suite::EdwardsSha512Ell2 => ELL2_SUITE_STRING,
+        }
+    }
+
+    /// The suite with the given suite string, if it is supported.
+    pub fn from_suite_string(suite_string: [u8; 1]) -> Option<Suite> {
+        if suite_string == SUITE_STRING {
+            Some(Suite::EdwardsSha512Tai)
+        } else if suite_string == ELL2_SUITE_STRING {
+            Some(Suite::EdwardsSha512Ell2)
+        } else {
+            None
+        }
+    }
+}
+
 /// Strings combined with inputs to the hash function for domain separation, as
 /// discussed in <https://tools.ietf.org/id/draft-irtf-cfrg-vrf-07.html#rfc.section.7.7>
 pub const ZERO_STRING: [u8; 1] = [0u8];
@@ -34,6 +34,14 @@ impl Keypair {
 
         expanded.prove(&self.public, &message)
     }
+
+    /// Construct a VRF proof with this keypair's secret key, in the format
+    /// accepted by `verify_batch_batchable`.
+    pub fn prove_batchable(&self, message: &[u8]) -> BatchableProof {
+        let expanded: ExpandedSecretKey = (&self.secret).into();
+
+        expanded.prove_batchable(&self.public, message)
+    }
 }
 
 // foreign interface
@@ -206,6 +214,7 @@ extern "C" fn ecvrf_public_key_cmp(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constants::*;
 
     use curve25519_dalek::scalar::Scalar;
 
@@ -436,4 +445,354 @@ mod tests {
             )
         }
     }
+
+    /// Test against the ECVRF-EDWARDS25519-SHA512-ELL2 test vectors of
+    /// https://www.rfc-editor.org/rfc/rfc9381.html#appendix-B.4
+    #[test]
+    fn test_vrf_ell2_proof_and_hash() {
+        /// As in 'test_vrf_proof_and_hash', except that the secret scalar is
+        /// not checked since it is computed as in the TAI suite, and the
+        /// point 'H' that 'alpha' hashes to is checked since it is computed
+        /// differently.
+        fn test_example(
+            sk_bytes: [u8; 32],   // Secret key - 32 random bytes
+            alpha_bytes: Vec<u8>, // The input to the VRF
+            pk_bytes: [u8; 32],   // Public key - derived from the secret key
+            h_bytes: [u8; 32],    // The input hashed to the curve
+            pi_bytes: [u8; 80],   // Result of 'ECVRF_prove(SK, alpha)', encoded as bytestring
+            beta_bytes: [u8; 64], // Hash of pi
+        ) {
+            let sk = SecretKey(sk_bytes);
+            let pk = PublicKey::from(&sk).with_suite(Suite::EdwardsSha512Ell2);
+            assert_eq!(pk.as_bytes(), &pk_bytes);
+
+            let h = pk
+                .hash_to_curve(&alpha_bytes)
+                .expect("Hashing in the ELL2 suite cannot fail.");
+            assert_eq!(h.compress().to_bytes(), h_bytes);
+
+            let proof = sk.prove(&pk, &alpha_bytes);
+            let mut proof_bytes: Vec<u8> = Vec::new();
+            proof.serial(&mut proof_bytes);
+            assert!(proof_bytes.iter().eq(pi_bytes.iter()));
+            assert!(pk.verify(&proof, &alpha_bytes));
+
+            let p2h = pk.proof_to_hash(&proof);
+            assert!(p2h.iter().eq(beta_bytes.iter()));
+
+            // The suites are separated, so the proof is not valid for the
+            // same key in the TAI suite.
+            let tai_pk = pk.with_suite(Suite::EdwardsSha512Tai);
+            assert!(!tai_pk.verify(&proof, &alpha_bytes));
+        }
+
+        {
+            // First example from https://www.rfc-editor.org/rfc/rfc9381.html#appendix-B.4:
+            // SK = 9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60
+            // PK = d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a
+            // alpha = (the empty string)
+            // H = b8066ebbb706c72b64390324e4a3276f129569eab100c26b9f05011200c1bad9
+            // pi = 7d9c633ffeee27349264cf5c667579fc583b4bda63ab71d001f89c10003ab46f14adf9a3cd8b8412d9038531e865c341cafa73589b023d14311c331a9ad15ff2fb37831e00f0acaa6d73bc9997b06501
+            // beta = 9d574bf9b8302ec0fc1e21c3ec5368269527b87b462ce36dab2d14ccf80c53cccf6758f058c5b1c856b116388152bbe509ee3b9ecfe63d93c3b4346c1fbc6c54
+
+            let sk_bytes: [u8; 32] = [
+                0x9d, 0x61, 0xb1, 0x9d, 0xef, 0xfd, 0x5a, 0x60, 0xba, 0x84, 0x4a, 0xf4, 0x92, 0xec,
+                0x2c, 0xc4, 0x44, 0x49, 0xc5, 0x69, 0x7b, 0x32, 0x69, 0x19, 0x70, 0x3b, 0xac, 0x03,
+                0x1c, 0xae, 0x7f, 0x60,
+            ];
+
+            let pk_bytes: [u8; 32] = [
+                0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64,
+                0x07, 0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68,
+                0xf7, 0x07, 0x51, 0x1a,
+            ];
+
+            let alpha_bytes: Vec<u8> = Vec::new();
+
+            let h_bytes: [u8; 32] = [
+                0xb8, 0x06, 0x6e, 0xbb, 0xb7, 0x06, 0xc7, 0x2b, 0x64, 0x39, 0x03, 0x24, 0xe4, 0xa3,
+                0x27, 0x6f, 0x12, 0x95, 0x69, 0xea, 0xb1, 0x00, 0xc2, 0x6b, 0x9f, 0x05, 0x01, 0x12,
+                0x00, 0xc1, 0xba, 0xd9,
+            ];
+
+            // pi
+            let pi_bytes: [u8; 80] = [
+                0x7d, 0x9c, 0x63, 0x3f, 0xfe, 0xee, 0x27, 0x34, 0x92, 0x64, 0xcf, 0x5c, 0x66, 0x75,
+                0x79, 0xfc, 0x58, 0x3b, 0x4b, 0xda, 0x63, 0xab, 0x71, 0xd0, 0x01, 0xf8, 0x9c, 0x10,
+                0x00, 0x3a, 0xb4, 0x6f, 0x14, 0xad, 0xf9, 0xa3, 0xcd, 0x8b, 0x84, 0x12, 0xd9, 0x03,
+                0x85, 0x31, 0xe8, 0x65, 0xc3, 0x41, 0xca, 0xfa, 0x73, 0x58, 0x9b, 0x02, 0x3d, 0x14,
+                0x31, 0x1c, 0x33, 0x1a, 0x9a, 0xd1, 0x5f, 0xf2, 0xfb, 0x37, 0x83, 0x1e, 0x00, 0xf0,
+                0xac, 0xaa, 0x6d, 0x73, 0xbc, 0x99, 0x97, 0xb0, 0x65, 0x01,
+            ];
+
+            // beta
+            let beta_bytes: [u8; 64] = [
+                0x9d, 0x57, 0x4b, 0xf9, 0xb8, 0x30, 0x2e, 0xc0, 0xfc, 0x1e, 0x21, 0xc3, 0xec, 0x53,
+                0x68, 0x26, 0x95, 0x27, 0xb8, 0x7b, 0x46, 0x2c, 0xe3, 0x6d, 0xab, 0x2d, 0x14, 0xcc,
+                0xf8, 0x0c, 0x53, 0xcc, 0xcf, 0x67, 0x58, 0xf0, 0x58, 0xc5, 0xb1, 0xc8, 0x56, 0xb1,
+                0x16, 0x38, 0x81, 0x52, 0xbb, 0xe5, 0x09, 0xee, 0x3b, 0x9e, 0xcf, 0xe6, 0x3d, 0x93,
+                0xc3, 0xb4, 0x34, 0x6c, 0x1f, 0xbc, 0x6c, 0x54,
+            ];
+
+            test_example(
+                sk_bytes,
+                alpha_bytes,
+                pk_bytes,
+                h_bytes,
+                pi_bytes,
+                beta_bytes,
+            )
+        }
+
+        {
+            // Second example from https://www.rfc-editor.org/rfc/rfc9381.html#appendix-B.4:
+            // SK = 4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb
+            // PK = 3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c
+            // alpha = 72
+            // H = 76ac3ccb86158a9104dff819b1ca293426d305fd76b39b13c9356d9b58c08e57
+            // pi = 47b327393ff2dd81336f8a2ef10339112401253b3c714eeda879f12c509072ef055b48372bb82efbdce8e10c8cb9a2f9d60e93908f93df1623ad78a86a028d6bc064dbfc75a6a57379ef855dc6733801
+            // beta = 38561d6b77b71d30eb97a062168ae12b667ce5c28caccdf76bc88e093e4635987cd96814ce55b4689b3dd2947f80e59aac7b7675f8083865b46c89b2ce9cc735
+
+            let sk_bytes: [u8; 32] = [
+                0x4c, 0xcd, 0x08, 0x9b, 0x28, 0xff, 0x96, 0xda, 0x9d, 0xb6, 0xc3, 0x46, 0xec, 0x11,
+                0x4e, 0x0f, 0x5b, 0x8a, 0x31, 0x9f, 0x35, 0xab, 0xa6, 0x24, 0xda, 0x8c, 0xf6, 0xed,
+                0x4f, 0xb8, 0xa6, 0xfb,
+            ];
+
+            let pk_bytes: [u8; 32] = [
+                0x3d, 0x40, 0x17, 0xc3, 0xe8, 0x43, 0x89, 0x5a, 0x92, 0xb7, 0x0a, 0xa7, 0x4d, 0x1b,
+                0x7e, 0xbc, 0x9c, 0x98, 0x2c, 0xcf, 0x2e, 0xc4, 0x96, 0x8c, 0xc0, 0xcd, 0x55, 0xf1,
+                0x2a, 0xf4, 0x66, 0x0c,
+            ];
+
+            let alpha_bytes: Vec<u8> = vec![0x72];
+
+            let h_bytes: [u8; 32] = [
+                0x76, 0xac, 0x3c, 0xcb, 0x86, 0x15, 0x8a, 0x91, 0x04, 0xdf, 0xf8, 0x19, 0xb1, 0xca,
+                0x29, 0x34, 0x26, 0xd3, 0x05, 0xfd, 0x76, 0xb3, 0x9b, 0x13, 0xc9, 0x35, 0x6d, 0x9b,
+                0x58, 0xc0, 0x8e, 0x57,
+            ];
+
+            // pi
+            let pi_bytes: [u8; 80] = [
+                0x47, 0xb3, 0x27, 0x39, 0x3f, 0xf2, 0xdd, 0x81, 0x33, 0x6f, 0x8a, 0x2e, 0xf1, 0x03,
+                0x39, 0x11, 0x24, 0x01, 0x25, 0x3b, 0x3c, 0x71, 0x4e, 0xed, 0xa8, 0x79, 0xf1, 0x2c,
+                0x50, 0x90, 0x72, 0xef, 0x05, 0x5b, 0x48, 0x37, 0x2b, 0xb8, 0x2e, 0xfb, 0xdc, 0xe8,
+                0xe1, 0x0c, 0x8c, 0xb9, 0xa2, 0xf9, 0xd6, 0x0e, 0x93, 0x90, 0x8f, 0x93, 0xdf, 0x16,
+                0x23, 0xad, 0x78, 0xa8, 0x6a, 0x02, 0x8d, 0x6b, 0xc0, 0x64, 0xdb, 0xfc, 0x75, 0xa6,
+                0xa5, 0x73, 0x79, 0xef, 0x85, 0x5d, 0xc6, 0x73, 0x38, 0x01,
+            ];
+
+            // beta
+            let beta_bytes: [u8; 64] = [
+                0x38, 0x56, 0x1d, 0x6b, 0x77, 0xb7, 0x1d, 0x30, 0xeb, 0x97, 0xa0, 0x62, 0x16, 0x8a,
+                0xe1, 0x2b, 0x66, 0x7c, 0xe5, 0xc2, 0x8c, 0xac, 0xcd, 0xf7, 0x6b, 0xc8, 0x8e, 0x09,
+                0x3e, 0x46, 0x35, 0x98, 0x7c, 0xd9, 0x68, 0x14, 0xce, 0x55, 0xb4, 0x68, 0x9b, 0x3d,
+                0xd2, 0x94, 0x7f, 0x80, 0xe5, 0x9a, 0xac, 0x7b, 0x76, 0x75, 0xf8, 0x08, 0x38, 0x65,
+                0xb4, 0x6c, 0x89, 0xb2, 0xce, 0x9c, 0xc7, 0x35,
+            ];
+
+            test_example(
+                sk_bytes,
+                alpha_bytes,
+                pk_bytes,
+                h_bytes,
+                pi_bytes,
+                beta_bytes,
+            )
+        }
+
+        {
+            // Third example from https://www.rfc-editor.org/rfc/rfc9381.html#appendix-B.4:
+            // SK = c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7
+            // PK = fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025
+            // alpha = af82
+            // H = 13d2a8b5ca32db7e98094a61f656a08c6c964344e058879a386a947a4e189ed1
+            // pi = 926e895d308f5e328e7aa159c06eddbe56d06846abf5d98c2512235eaa57fdce35b46edfc655bc828d44ad09d1150f31374e7ef73027e14760d42e77341fe05467bb286cc2c9d7fde29120a0b2320d04
+            // beta = 121b7f9b9aaaa29099fc04a94ba52784d44eac976dd1a3cca458733be5cd090a7b5fbd148444f17f8daf1fb55cb04b1ae85a626e30a54b4b0f8abf4a43314a58
+
+            let sk_bytes: [u8; 32] = [
+                0xc5, 0xaa, 0x8d, 0xf4, 0x3f, 0x9f, 0x83, 0x7b, 0xed, 0xb7, 0x44, 0x2f, 0x31, 0xdc,
+                0xb7, 0xb1, 0x66, 0xd3, 0x85, 0x35, 0x07, 0x6f, 0x09, 0x4b, 0x85, 0xce, 0x3a, 0x2e,
+                0x0b, 0x44, 0x58, 0xf7,
+            ];
+
+            let pk_bytes: [u8; 32] = [
+                0xfc, 0x51, 0xcd, 0x8e, 0x62, 0x18, 0xa1, 0xa3, 0x8d, 0xa4, 0x7e, 0xd0, 0x02, 0x30,
+                0xf0, 0x58, 0x08, 0x16, 0xed, 0x13, 0xba, 0x33, 0x03, 0xac, 0x5d, 0xeb, 0x91, 0x15,
+                0x48, 0x90, 0x80, 0x25,
+            ];
+
+            let alpha_bytes: Vec<u8> = vec![0xaf, 0x82];
+
+            let h_bytes: [u8; 32] = [
+                0x13, 0xd2, 0xa8, 0xb5, 0xca, 0x32, 0xdb, 0x7e, 0x98, 0x09, 0x4a, 0x61, 0xf6, 0x56,
+                0xa0, 0x8c, 0x6c, 0x96, 0x43, 0x44, 0xe0, 0x58, 0x87, 0x9a, 0x38, 0x6a, 0x94, 0x7a,
+                0x4e, 0x18, 0x9e, 0xd1,
+            ];
+
+            // pi
+            let pi_bytes: [u8; 80] = [
+                0x92, 0x6e, 0x89, 0x5d, 0x30, 0x8f, 0x5e, 0x32, 0x8e, 0x7a, 0xa1, 0x59, 0xc0, 0x6e,
+                0xdd, 0xbe, 0x56, 0xd0, 0x68, 0x46, 0xab, 0xf5, 0xd9, 0x8c, 0x25, 0x12, 0x23, 0x5e,
+                0xaa, 0x57, 0xfd, 0xce, 0x35, 0xb4, 0x6e, 0xdf, 0xc6, 0x55, 0xbc, 0x82, 0x8d, 0x44,
+                0xad, 0x09, 0xd1, 0x15, 0x0f, 0x31, 0x37, 0x4e, 0x7e, 0xf7, 0x30, 0x27, 0xe1, 0x47,
+                0x60, 0xd4, 0x2e, 0x77, 0x34, 0x1f, 0xe0, 0x54, 0x67, 0xbb, 0x28, 0x6c, 0xc2, 0xc9,
+                0xd7, 0xfd, 0xe2, 0x91, 0x20, 0xa0, 0xb2, 0x32, 0x0d, 0x04,
+            ];
+
+            // beta
+            let beta_bytes: [u8; 64] = [
+                0x12, 0x1b, 0x7f, 0x9b, 0x9a, 0xaa, 0xa2, 0x90, 0x99, 0xfc, 0x04, 0xa9, 0x4b, 0xa5,
+                0x27, 0x84, 0xd4, 0x4e, 0xac, 0x97, 0x6d, 0xd1, 0xa3, 0xcc, 0xa4, 0x58, 0x73, 0x3b,
+                0xe5, 0xcd, 0x09, 0x0a, 0x7b, 0x5f, 0xbd, 0x14, 0x84, 0x44, 0xf1, 0x7f, 0x8d, 0xaf,
+                0x1f, 0xb5, 0x5c, 0xb0, 0x4b, 0x1a, 0xe8, 0x5a, 0x62, 0x6e, 0x30, 0xa5, 0x4b, 0x4b,
+                0x0f, 0x8a, 0xbf, 0x4a, 0x43, 0x31, 0x4a, 0x58,
+            ];
+
+            test_example(
+                sk_bytes,
+                alpha_bytes,
+                pk_bytes,
+                h_bytes,
+                pi_bytes,
+                beta_bytes,
+            )
+        }
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut csprng = thread_rng();
+        let keypairs = (0..10)
+            .map(|i| {
+                let mut keypair = Keypair::generate(&mut csprng);
+                if i % 2 == 1 {
+                    keypair.public = keypair.public.with_suite(Suite::EdwardsSha512Ell2);
+                }
+                keypair
+            })
+            .collect::<Vec<_>>();
+        let messages = (0..10u8).map(|i| vec![i; i as usize]).collect::<Vec<_>>();
+        let proofs = keypairs
+            .iter()
+            .zip(messages.iter())
+            .map(|(keypair, message)| keypair.prove(message))
+            .collect::<Vec<_>>();
+
+        let mut batch = keypairs
+            .iter()
+            .zip(proofs.iter())
+            .zip(messages.iter())
+            .map(|((keypair, proof), message)| (&keypair.public, proof, &message[..]))
+            .collect::<Vec<_>>();
+        assert!(verify_batch(&mut csprng, &batch));
+        assert!(verify_batch(&mut csprng, &[]));
+
+        // A single proof for the wrong message invalidates the batch.
+        batch[3].2 = &messages[4][..];
+        assert!(!verify_batch(&mut csprng, &batch));
+        batch[3].2 = &messages[3][..];
+
+        // As does a proof checked in the wrong suite.
+        let wrong_suite = keypairs[3].public.with_suite(Suite::EdwardsSha512Tai);
+        batch[3].0 = &wrong_suite;
+        assert!(!verify_batch(&mut csprng, &batch));
+        batch[3].0 = &keypairs[3].public;
+
+        // And a proof with a modified response.
+        let Proof(gamma, c, s) = proofs[5].clone();
+        let wrong_response = Proof(gamma, c, s + Scalar::one());
+        batch[5].1 = &wrong_response;
+        assert!(!verify_batch(&mut csprng, &batch));
+    }
+
+    #[test]
+    fn test_verify_batch_batchable() {
+        let mut csprng = thread_rng();
+        let keypairs = (0..10)
+            .map(|i| {
+                let mut keypair = Keypair::generate(&mut csprng);
+                if i % 2 == 1 {
+                    keypair.public = keypair.public.with_suite(Suite::EdwardsSha512Ell2);
+                }
+                keypair
+            })
+            .collect::<Vec<_>>();
+        let messages = (0..10u8).map(|i| vec![i; i as usize]).collect::<Vec<_>>();
+        let proofs = keypairs
+            .iter()
+            .zip(messages.iter())
+            .map(|(keypair, message)| keypair.prove_batchable(message))
+            .collect::<Vec<_>>();
+
+        let mut batch = keypairs
+            .iter()
+            .zip(proofs.iter())
+            .zip(messages.iter())
+            .map(|((keypair, proof), message)| (&keypair.public, proof, &message[..]))
+            .collect::<Vec<_>>();
+        assert!(verify_batch_batchable(&mut csprng, &batch));
+        assert!(verify_batch_batchable(&mut csprng, &[]));
+
+        // Batchable proofs carry the same VRF output as standard proofs.
+        for (pk, proof, message) in batch.iter() {
+            let standard = proof
+                .to_proof(pk, message)
+                .expect("Hashing should succeed.");
+            assert!(pk.verify(&standard, message));
+            let keypair = keypairs.iter().find(|kp| &&kp.public == pk).unwrap();
+            assert_eq!(standard, keypair.prove(message));
+        }
+
+        // Serialization round trip.
+        let mut proof_bytes: Vec<u8> = Vec::new();
+        proofs[0].serial(&mut proof_bytes);
+        assert_eq!(proof_bytes.len(), BATCHABLE_PROOF_LENGTH);
+        let deserialized = BatchableProof::deserial(&mut std::io::Cursor::new(&proof_bytes))
+            .expect("Deserialization should succeed.");
+        assert_eq!(deserialized, proofs[0]);
+
+        // A single proof for the wrong message invalidates the batch.
+        batch[3].2 = &messages[4][..];
+        assert!(!verify_batch_batchable(&mut csprng, &batch));
+        batch[3].2 = &messages[3][..];
+
+        // As does a proof checked in the wrong suite.
+        let wrong_suite = keypairs[3].public.with_suite(Suite::EdwardsSha512Tai);
+        batch[3].0 = &wrong_suite;
+        assert!(!verify_batch_batchable(&mut csprng, &batch));
+    }
+
+    #[test]
+    fn test_public_key_suite_serialization() {
+        let mut csprng = thread_rng();
+        let tai = Keypair::generate(&mut csprng).public;
+
+        // Keys in the default suite keep the 32 byte format used by the chain.
+        let bytes = to_bytes(&tai);
+        assert_eq!(bytes, tai.as_bytes().to_vec());
+        let deserialized: PublicKey =
+            from_bytes(&mut std::io::Cursor::new(&bytes)).expect("Deserialization should succeed.");
+        assert_eq!(deserialized.suite(), Suite::EdwardsSha512Tai);
+        assert_eq!(deserialized, tai);
+
+        // Other suites are tagged.
+        let ell2 = tai.with_suite(Suite::EdwardsSha512Ell2);
+        let bytes = to_bytes(&ell2);
+        assert_eq!(bytes.len(), 2 * PUBLIC_KEY_LENGTH + 1);
+        assert_eq!(&bytes[..PUBLIC_KEY_LENGTH], &SUITE_TAG);
+        let deserialized: PublicKey =
+            from_bytes(&mut std::io::Cursor::new(&bytes)).expect("Deserialization should succeed.");
+        assert_eq!(deserialized.suite(), Suite::EdwardsSha512Ell2);
+        assert_eq!(deserialized, ell2);
+
+        // Unknown suites, and the default suite in the tagged format, are
+        // rejected.
+        let mut wrong_suite = bytes.clone();
+        wrong_suite[PUBLIC_KEY_LENGTH] = 17;
+        assert!(from_bytes::<PublicKey, _>(&mut std::io::Cursor::new(&wrong_suite)).is_err());
+        wrong_suite[PUBLIC_KEY_LENGTH] = SUITE_STRING[0];
+        assert!(from_bytes::<PublicKey, _>(&mut std::io::Cursor::new(&wrong_suite)).is_err());
+    }
 }
@@ -0,0 +1,356 @@
+//! The `edwards25519_XMD:SHA-512_ELL2_NU_` encoding of
+//! <https://www.rfc-editor.org/rfc/rfc9380.html#section-6.8.2>, which is the
+//! `encode_to_curve` function of the ECVRF-EDWARDS25519-SHA512-ELL2 suite.
+//!
+//! curve25519-dalek does not expose its field arithmetic, so this module
+//! contains a minimal implementation of arithmetic modulo 2^255 - 19. None of
+//! it is constant time. That is fine since it is only ever applied to public
+//! inputs, the public key and the VRF input.
+
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use sha2::{Digest, Sha512};
+
+/// An element of GF(2^255 - 19), stored as four little-endian 64-bit limbs and
+/// always kept fully reduced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FieldElement([u64; 4]);
+
+/// The prime 2^255 - 19.
+const MODULUS: [u64; 4] = [
+    0xffff_ffff_ffff_ffed,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+    0x7fff_ffff_ffff_ffff,
+];
+
+/// p - 2, the exponent used for inversion.
+const INVERT_EXPONENT: [u64; 4] = [
+    0xffff_ffff_ffff_ffeb,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+    0x7fff_ffff_ffff_ffff,
+];
+
+/// (p - 1) / 2, the exponent of the Legendre symbol.
+const LEGENDRE_EXPONENT: [u64; 4] = [
+    0xffff_ffff_ffff_fff6,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+    0x3fff_ffff_ffff_ffff,
+];
+
+/// (p + 3) / 8, the exponent giving a candidate square root since p = 5 mod 8.
+const SQRT_EXPONENT: [u64; 4] = [
+    0xffff_ffff_ffff_fffe,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+    0x0fff_ffff_ffff_ffff,
+];
+
+/// A square root of -1, namely 2^((p - 1) / 4).
+const SQRT_M1: FieldElement = FieldElement([
+    0xc4ee_1b27_4a0e_a0b0,
+    0x2f43_1806_ad2f_e478,
+    0x2b4d_0099_3dfb_d7a7,
+    0x2b83_2480_4fc1_df0b,
+]);
+
+/// The square root of -486664 with `sgn0` equal to 0, used by the rational map
+/// from curve25519 to edwards25519, see
+/// <https://www.rfc-editor.org/rfc/rfc9380.html#appendix-D.1>
+const SQRT_M486664: FieldElement = FieldElement([
+    0xcc6e_04aa_ff45_7e06,
+    0xc5a1_d3d1_4b7d_1a82,
+    0xd27b_08dc_03fc_4f7e,
+    0x0f26_edf4_60a0_06bb,
+]);
+
+/// The Montgomery `A` coefficient of curve25519.
+const J: FieldElement = FieldElement([486_662, 0, 0, 0]);
+
+/// The non-square `Z` used by the Elligator 2 map for curve25519.
+const Z: FieldElement = FieldElement([2, 0, 0, 0]);
+
+impl FieldElement {
+    const ONE: FieldElement = FieldElement([1, 0, 0, 0]);
+    const ZERO: FieldElement = FieldElement([0, 0, 0, 0]);
+
+    /// Reduce any 256-bit integer modulo p.
+    fn reduce(mut limbs: [u64; 4]) -> Self {
+        // 2^255 = 19 mod p, so fold the top bit into the bottom limb.
+        let top = limbs[3] >> 63;
+        limbs[3] &= 0x7fff_ffff_ffff_ffff;
+        add_small(&mut limbs, 19 * top);
+        // Now limbs < 2^255 + 19, and limbs >= p iff limbs + 19 >= 2^255.
+        let mut plus_19 = limbs;
+        add_small(&mut plus_19, 19);
+        if plus_19[3] >> 63 == 1 {
+            plus_19[3] &= 0x7fff_ffff_ffff_ffff;
+            FieldElement(plus_19)
+        } else {
+            FieldElement(limbs)
+        }
+    }
+
+    /// Interpret a big-endian byte string of at most 64 bytes as an integer
+    /// and reduce it modulo p. This is `OS2IP(bytes) mod p`.
+    fn from_bytes_be(bytes: &[u8]) -> Self {
+        assert!(bytes.len() <= 64);
+        let mut wide = [0u8; 64];
+        wide[64 - bytes.len()..].copy_from_slice(bytes);
+        let mut hi = [0u64; 4];
+        let mut lo = [0u64; 4];
+        for i in 0..4 {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(&wide[56 - 8 * i..64 - 8 * i]);
+            lo[i] = u64::from_be_bytes(buf);
+            buf.copy_from_slice(&wide[24 - 8 * i..32 - 8 * i]);
+            hi[i] = u64::from_be_bytes(buf);
+        }
+        // 2^256 = 38 mod p
+        FieldElement::reduce(lo).add(&FieldElement::reduce(hi).mul(&FieldElement([38, 0, 0, 0])))
+    }
+
+    /// The little-endian encoding of the canonical representative.
+    fn to_bytes(self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (chunk, limb) in out.chunks_mut(8).zip(self.0.iter()) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        out
+    }
+
+    /// The `sgn0` function of RFC 9380, i.e., the parity of the canonical
+    /// representative.
+    fn sgn0(&self) -> bool { self.0[0] & 1 == 1 }
+
+    fn is_zero(&self) -> bool { *self == FieldElement::ZERO }
+
+    fn add(&self, other: &Self) -> Self {
+        // Both summands are below 2^255, so the sum fits in 256 bits.
+        let mut out = [0u64; 4];
+        let mut carry = 0u128;
+        for ((o, a), b) in out.iter_mut().zip(self.0.iter()).zip(other.0.iter()) {
+            let v = u128::from(*a) + u128::from(*b) + carry;
+            *o = v as u64;
+            carry = v >> 64;
+        }
+        FieldElement::reduce(out)
+    }
+
+    fn neg(&self) -> Self {
+        // p - self does not underflow since self <= p - 1.
+        let mut out = [0u64; 4];
+        let mut borrow = 0u64;
+        for ((o, m), a) in out.iter_mut().zip(MODULUS.iter()).zip(self.0.iter()) {
+            let (v, b1) = m.overflowing_sub(*a);
+            let (v, b2) = v.overflowing_sub(borrow);
+            *o = v;
+            borrow = u64::from(b1 || b2);
+        }
+        FieldElement::reduce(out)
+    }
+
+    fn sub(&self, other: &Self) -> Self { self.add(&other.neg()) }
+
+    fn mul(&self, other: &Self) -> Self {
+        let mut wide = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let v = u128::from(wide[i + j])
+                    + u128::from(self.0[i]) * u128::from(other.0[j])
+                    + carry;
+                wide[i + j] = v as u64;
+                carry = v >> 64;
+            }
+            wide[i + 4] = carry as u64;
+        }
+        // Fold the upper half using 2^256 = 38 mod p.
+        let mut out = [0u64; 4];
+        let mut carry = 0u128;
+        for i in 0..4 {
+            let v = u128::from(wide[i]) + 38 * u128::from(wide[i + 4]) + carry;
+            out[i] = v as u64;
+            carry = v >> 64;
+        }
+        while carry != 0 {
+            let mut c = 38 * carry;
+            for limb in out.iter_mut() {
+                let v = u128::from(*limb) + c;
+                *limb = v as u64;
+                c = v >> 64;
+            }
+            carry = c;
+        }
+        FieldElement::reduce(out)
+    }
+
+    fn square(&self) -> Self { self.mul(self) }
+
+    /// Exponentiation by a 256-bit little-endian exponent.
+    fn pow(&self, exponent: &[u64; 4]) -> Self {
+        let mut acc = FieldElement::ONE;
+        for limb in exponent.iter().rev() {
+            for bit in (0..64).rev() {
+                acc = acc.square();
+                if (limb >> bit) & 1 == 1 {
+                    acc = acc.mul(self);
+                }
+            }
+        }
+        acc
+    }
+
+    /// The `inv0` function of RFC 9380, mapping 0 to 0.
+    fn invert(&self) -> Self { self.pow(&INVERT_EXPONENT) }
+
+    fn is_square(&self) -> bool {
+        let legendre = self.pow(&LEGENDRE_EXPONENT);
+        legendre == FieldElement::ONE || legendre.is_zero()
+    }
+
+    /// A square root of `self`, if one exists.
+    fn sqrt(&self) -> Option<Self> {
+        let candidate = self.pow(&SQRT_EXPONENT);
+        if candidate.square() == *self {
+            return Some(candidate);
+        }
+        let candidate = candidate.mul(&SQRT_M1);
+        if candidate.square() == *self {
+            Some(candidate)
+        } else {
+            None
+        }
+    }
+}
+
+/// Add a small value to a 256-bit integer, ignoring overflow beyond 256 bits.
+/// This is only used where the result is known to fit.
+fn add_small(limbs: &mut [u64; 4], value: u64) {
+    let mut carry = value;
+    for limb in limbs.iter_mut() {
+        let (v, overflow) = limb.overflowing_add(carry);
+        *limb = v;
+        carry = u64::from(overflow);
+    }
+}
+
+/// `expand_message_xmd` from <https://www.rfc-editor.org/rfc/rfc9380.html#section-5.3.1>
+/// instantiated with SHA-512, specialized to outputs of at most 64 bytes.
+/// The message is given in parts which are concatenated.
+fn expand_message_xmd(msg: &[&[u8]], dst: &[u8], len_in_bytes: usize) -> Vec<u8> {
+    assert!(len_in_bytes <= 64 && dst.len() <= 255);
+    let mut h = Sha512::new();
+    h.update(&[0u8; 128][..]); // Z_pad
+    for part in msg {
+        h.update(part);
+    }
+    h.update((len_in_bytes as u16).to_be_bytes());
+    h.update([0u8]);
+    h.update(dst);
+    h.update([dst.len() as u8]);
+    let b_0 = h.finalize();
+    let b_1 = Sha512::new()
+        .chain(b_0)
+        .chain([1u8])
+        .chain(dst)
+        .chain([dst.len() as u8])
+        .finalize();
+    b_1[..len_in_bytes].to_vec()
+}
+
+/// Elligator 2 for curve25519 as in <https://www.rfc-editor.org/rfc/rfc9380.html#section-6.7.1>,
+/// followed by the rational map to edwards25519. Returns the affine
+/// coordinates of a point on edwards25519.
+fn map_to_curve(u: &FieldElement) -> (FieldElement, FieldElement) {
+    let mut tv1 = Z.mul(&u.square());
+    if tv1 == FieldElement::ONE.neg() {
+        tv1 = FieldElement::ZERO;
+    }
+    let x1 = J.neg().mul(&FieldElement::ONE.add(&tv1).invert());
+    let gx1 = x1.mul(&x1.mul(&x1.add(&J)).add(&FieldElement::ONE));
+    let (s, t) = if gx1.is_square() {
+        let y = gx1.sqrt().expect("gx1 is a square.");
+        (x1, if y.sgn0() { y } else { y.neg() })
+    } else {
+        let x2 = x1.neg().sub(&J);
+        let gx2 = tv1.mul(&gx1);
+        let y = gx2.sqrt().expect("gx2 is a square when gx1 is not.");
+        (x2, if y.sgn0() { y.neg() } else { y })
+    };
+    // Rational map from curve25519 to edwards25519.
+    let s_plus_one = s.add(&FieldElement::ONE);
+    if t.is_zero() || s_plus_one.is_zero() {
+        return (FieldElement::ZERO, FieldElement::ONE);
+    }
+    let v = SQRT_M486664.mul(&s).mul(&t.invert());
+    let w = s.sub(&FieldElement::ONE).mul(&s_plus_one.invert());
+    (v, w)
+}
+
+/// The `encode_to_curve` function of the `edwards25519_XMD:SHA-512_ELL2_NU_`
+/// suite, applied to the concatenation of the message parts.
+pub(crate) fn encode_to_curve(msg: &[&[u8]], dst: &[u8]) -> EdwardsPoint {
+    let u = FieldElement::from_bytes_be(&expand_message_xmd(msg, dst, 48));
+    let (x, y) = map_to_curve(&u);
+    let mut bytes = y.to_bytes();
+    bytes[31] |= (x.sgn0() as u8) << 7;
+    CompressedEdwardsY(bytes)
+        .decompress()
+        .expect("The map always produces a point on the curve.")
+        .mul_by_cofactor()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(SQRT_M1.square(), FieldElement::ONE.neg());
+        assert_eq!(
+            SQRT_M486664.square(),
+            FieldElement([486_664, 0, 0, 0]).neg()
+        );
+        assert!(!SQRT_M486664.sgn0());
+        assert!(!Z.is_square());
+    }
+
+    #[test]
+    fn test_field_arithmetic() {
+        let minus_one = FieldElement::ONE.neg();
+        assert_eq!(minus_one.0, [
+            MODULUS[0] - 1,
+            MODULUS[1],
+            MODULUS[2],
+            MODULUS[3]
+        ]);
+        assert_eq!(minus_one.square(), FieldElement::ONE);
+        assert_eq!(minus_one.add(&FieldElement::ONE), FieldElement::ZERO);
+        let x = FieldElement::from_bytes_be(&[0xab; 48]);
+        assert_eq!(x.mul(&x.invert()), FieldElement::ONE);
+        assert_eq!(
+            x.square().sqrt().map(|r| r == x || r == x.neg()),
+            Some(true)
+        );
+        // The modulus itself reduces to zero.
+        assert!(FieldElement::reduce(MODULUS).is_zero());
+    }
+
+    /// The first vector of <https://www.rfc-editor.org/rfc/rfc9380.html#appendix-J.5.2>
+    #[test]
+    fn test_encode_to_curve_vector() {
+        let dst = b"QUUX-V01-CS02-with-edwards25519_XMD:SHA-512_ELL2_NU_";
+        let p = encode_to_curve(&[b""], dst);
+        // P.y = 222e314d04a4d5725e9f2aff9fb2a6b69ef375a1214eb19021ceab2d687f0f9b
+        // and P.x = 1ff2b70ecf862799e11b7ae744e3489aa058ce805dd323a936375a84695e76da
+        // is even, so the compressed point is P.y in little-endian.
+        let expected: [u8; 32] = [
+            0x9b, 0x0f, 0x7f, 0x68, 0x2d, 0xab, 0xce, 0x21, 0x90, 0xb1, 0x4e, 0x21, 0xa1, 0x75,
+            0xf3, 0x9e, 0xb6, 0xa6, 0xb2, 0x9f, 0xff, 0x2a, 0x9f, 0x5e, 0x72, 0xd5, 0xa4, 0x04,
+            0x4d, 0x31, 0x2e, 0x22,
+        ];
+        assert_eq!(p.compress().to_bytes(), expected);
+    }
+}
@@ -1,7 +1,10 @@
 //! Implementation of the verifiable random function as specified in <https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-vrf-09>.
+//! The ECVRF-EDWARDS25519-SHA512-ELL2 suite of the final version, <https://www.rfc-editor.org/rfc/rfc9381.html>,
+//! is supported as well, see `Suite`.
 
 mod constants;
 mod ecvrf;
+mod elligator;
 mod errors;
 mod proof;
 mod public;
@@ -1,6 +1,6 @@
 //! An VRF Proof.
 
-use crate::{constants::*, errors::*};
+use crate::{constants::*, errors::*, public::PublicKey};
 use core::fmt::Debug;
 use crypto_common::*;
 use curve25519_dalek::{
@@ -11,8 +11,15 @@ use sha2::*;
 
 /// Implements <https://tools.ietf.org/id/draft-irtf-cfrg-vrf-07.html#rfc.section.5.4.3>
 pub fn hash_points(pts: &[CompressedEdwardsY]) -> Scalar {
+    hash_points_with_suite(Suite::EdwardsSha512Tai, pts)
+}
+
+/// Challenge generation for the given suite. This is the same function in
+/// draft-07 and RFC 9381, the difference between the two being only in which
+/// points are hashed, see <https://www.rfc-editor.org/rfc/rfc9381.html#section-5.4.3>
+pub fn hash_points_with_suite(suite: Suite, pts: &[CompressedEdwardsY]) -> Scalar {
     let mut hash: Sha512 = Sha512::new();
-    hash.update(SUITE_STRING);
+    hash.update(suite.suite_string());
     hash.update(TWO_STRING);
     for p in pts {
         hash.update(p.to_bytes());
@@ -80,10 +87,15 @@ impl Debug for Proof {
 
 /// Implements <https://tools.ietf.org/id/draft-irtf-cfrg-vrf-07.html#rfc.section.5.2>
 impl Proof {
-    pub fn to_hash(&self) -> [u8; 64] {
+    /// The VRF output of a proof in the TAI suite.
+    pub fn to_hash(&self) -> [u8; 64] { self.to_hash_with_suite(Suite::EdwardsSha512Tai) }
+
+    /// The VRF output of a proof in the given suite, see
+    /// <https://www.rfc-editor.org/rfc/rfc9381.html#section-5.2>
+    pub fn to_hash_with_suite(&self, suite: Suite) -> [u8; 64] {
         let p = self.0.mul_by_cofactor();
         let hash: Sha512 = Sha512::new()
-            .chain(SUITE_STRING)
+            .chain(suite.suite_string())
             .chain(THREE_STRING)
             .chain(p.compress().to_bytes())
             .chain(ZERO_STRING);
@@ -92,3 +104,73 @@ impl Proof {
         c_bytes
     }
 }
+
+/// A proof that carries the commitments `U` and `V` instead of the challenge
+/// `c`. The challenge can be recomputed from the commitments, whereas a
+/// `Proof` only allows recomputing the commitments from the challenge one
+/// proof at a time. This is what makes these proofs suitable for
+/// `verify_batch_batchable`. The components are `Gamma`, `U`, `V` and `s`.
+///
+/// A `BatchableProof` determines the same VRF output as the corresponding
+/// `Proof`, and can be converted to one with `to_proof`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BatchableProof(
+    pub EdwardsPoint,
+    pub EdwardsPoint,
+    pub EdwardsPoint,
+    pub Scalar,
+);
+
+impl Serial for BatchableProof {
+    #[inline]
+    fn serial<B: Buffer>(&self, x: &mut B) {
+        x.write_all(&self.0.compress().to_bytes()[..])
+            .expect("Writing to buffer should succeed.");
+        x.write_all(&self.1.compress().to_bytes()[..])
+            .expect("Writing to buffer should succeed.");
+        x.write_all(&self.2.compress().to_bytes()[..])
+            .expect("Writing to buffer should succeed.");
+        x.write_all(&self.3.reduce().to_bytes()[..])
+            .expect("Writing to buffer should succeed.");
+    }
+}
+
+impl Deserial for BatchableProof {
+    #[inline]
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let mut points = [EdwardsPoint::default(); 3];
+        for point in points.iter_mut() {
+            let mut point_bytes: [u8; 32] = [0u8; 32];
+            source.read_exact(&mut point_bytes)?;
+            *point = CompressedEdwardsY(point_bytes)
+                .decompress()
+                .ok_or(ProofError(InternalError::PointDecompression))?;
+        }
+        let mut scalar_bytes: [u8; 32] = [0u8; 32];
+        source.read_exact(&mut scalar_bytes)?;
+        let s = Scalar::from_canonical_bytes(scalar_bytes)
+            .ok_or(ProofError(InternalError::ScalarFormat))?;
+        Ok(BatchableProof(points[0], points[1], points[2], s))
+    }
+}
+
+impl BatchableProof {
+    /// Convert to the standard proof format by recomputing the challenge. This
+    /// returns `None` if the message cannot be hashed to the curve, in which
+    /// case the proof is invalid anyway.
+    pub fn to_proof(&self, public_key: &PublicKey, message: &[u8]) -> Option<Proof> {
+        let h = public_key.hash_to_curve(message)?;
+        let c = public_key.challenge(&h, &self.0, &self.1, &self.2);
+        Some(Proof(self.0, c, self.3))
+    }
+}
+
+impl Debug for BatchableProof {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        write!(
+            f,
+            "BatchableProof( {:?}, {:?}, {:?}, {:?} )",
+            &self.0, &self.1, &self.2, &self.3
+        )
+    }
+}
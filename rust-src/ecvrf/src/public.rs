@@ -1,5 +1,6 @@
 //! ed25519 public keys.
 
+use anyhow::bail;
 use core::fmt::Debug;
 use crypto_common::*;
 use curve25519_dalek::{
@@ -7,25 +8,45 @@ use curve25519_dalek::{
     digest::Digest,
     edwards::{CompressedEdwardsY, EdwardsPoint},
     scalar::Scalar,
+    traits::{IsIdentity, VartimeMultiscalarMul},
 };
+use rand::{CryptoRng, Rng};
 use sha2::Sha512;
 
-use crate::{constants::*, errors::*, proof::*, secret::*};
+use crate::{constants::*, elligator, errors::*, proof::*, secret::*};
 /// An ed25519-like public key. This has a bit stricter requirements than the
 /// signature scheme public keys, in particular points of small order are not
 /// allowed, and this is checked during serialization.
+///
+/// The key also records the ciphersuite it is used with. Keys in the default
+/// TAI suite are serialized as the 32 bytes of the compressed point, which is
+/// the format used by the chain. Keys in any other suite are serialized as
+/// `SUITE_TAG`, followed by the suite string and the compressed point. The tag
+/// is the encoding of the identity, which is never a valid key, so the two
+/// formats cannot be confused, and readers that only support the untagged
+/// format reject tagged keys instead of silently using the wrong suite.
 #[derive(Copy, Clone, Default, Eq, PartialEq)]
-pub struct PublicKey(pub(crate) CompressedEdwardsY, pub(crate) EdwardsPoint);
+pub struct PublicKey(
+    pub(crate) CompressedEdwardsY,
+    pub(crate) EdwardsPoint,
+    pub(crate) Suite,
+);
 
 impl Debug for PublicKey {
     fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-        write!(f, "PublicKey({:?}), {:?})", self.0, self.1)
+        write!(f, "PublicKey({:?}), {:?}, {:?})", self.0, self.1, self.2)
     }
 }
 
 impl Serial for PublicKey {
     #[inline]
     fn serial<B: Buffer>(&self, x: &mut B) {
+        if self.2 != Suite::default() {
+            x.write_all(&SUITE_TAG)
+                .expect("Writing to buffer should succeed.");
+            x.write_all(&self.2.suite_string())
+                .expect("Writing to buffer should succeed.");
+        }
         x.write_all(&self.0.to_bytes())
             .expect("Writing to buffer should succeed.")
     }
@@ -39,6 +60,18 @@ impl Deserial for PublicKey {
     fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
         let mut buf = [0u8; PUBLIC_KEY_LENGTH];
         source.read_exact(&mut buf)?;
+        let suite = if buf == SUITE_TAG {
+            let mut suite_string = [0u8; 1];
+            source.read_exact(&mut suite_string)?;
+            let suite = match Suite::from_suite_string(suite_string) {
+                Some(suite) if suite != Suite::default() => suite,
+                _ => bail!("Unsupported VRF suite {}.", suite_string[0]),
+            };
+            source.read_exact(&mut buf)?;
+            suite
+        } else {
+            Suite::default()
+        };
 
         let compressed = CompressedEdwardsY(buf);
         let point = compressed
@@ -47,7 +80,7 @@ impl Deserial for PublicKey {
         // Verify the public key is valid, c.f. verify_key below.
         // In particular check that the point is not the point at infinity.
         if !point.is_small_order() {
-            Ok(PublicKey(compressed, point))
+            Ok(PublicKey(compressed, point, suite))
         } else {
             Err(ProofError(InternalError::Verify).into())
         }
@@ -89,6 +122,14 @@ impl PublicKey {
     #[inline]
     pub fn as_bytes(&self) -> &'_ [u8; PUBLIC_KEY_LENGTH] { &(self.0).0 }
 
+    /// The ciphersuite this key is used with.
+    #[inline]
+    pub fn suite(&self) -> Suite { self.2 }
+
+    /// The same key, but used with the given ciphersuite.
+    #[inline]
+    pub fn with_suite(self, suite: Suite) -> PublicKey { PublicKey(self.0, self.1, suite) }
+
     /// Internal utility function for mangling the bits of a (formerly
     /// mathematically well-defined) "scalar" and multiplying it to produce a
     /// public key.
@@ -102,13 +143,25 @@ impl PublicKey {
         let point = &Scalar::from_bits(*bits) * &constants::ED25519_BASEPOINT_TABLE;
         let compressed = point.compress();
 
-        PublicKey(compressed, point)
+        PublicKey(compressed, point, Suite::default())
     }
 
-    /// Implements <https://tools.ietf.org/id/draft-irtf-cfrg-vrf-07.html#rfc.section.5.4.1.1>
+    /// Hash the message to the curve as specified by the key's suite. For the
+    /// TAI suite this implements <https://tools.ietf.org/id/draft-irtf-cfrg-vrf-07.html#rfc.section.5.4.1.1>
     /// The failure should not happen in practice, expected number of iterations
-    /// is 2.
+    /// is 2. For the ELL2 suite this implements <https://www.rfc-editor.org/rfc/rfc9381.html#section-5.4.1.2>
+    /// and never fails.
     pub fn hash_to_curve(&self, message: &[u8]) -> Option<EdwardsPoint> {
+        match self.2 {
+            Suite::EdwardsSha512Tai => self.hash_to_curve_tai(message),
+            Suite::EdwardsSha512Ell2 => Some(elligator::encode_to_curve(
+                &[self.as_bytes(), message],
+                ELL2_ENCODE_TO_CURVE_DST,
+            )),
+        }
+    }
+
+    fn hash_to_curve_tai(&self, message: &[u8]) -> Option<EdwardsPoint> {
         let mut p_candidate_bytes = [0u8; 32];
         let mut h: Sha512 = Sha512::new();
         h.update(SUITE_STRING);
@@ -154,11 +207,145 @@ impl PublicKey {
             let gamma_to_c = c * gamma; // should equal h^cx
             let v = h_to_s - gamma_to_c; // should equal h^k
 
-            let derivable_c =
-                hash_points(&[h.compress(), gamma.compress(), u.compress(), v.compress()]);
+            let derivable_c = self.challenge(&h, gamma, &u, &v);
             *c == derivable_c
         } else {
             false
         }
     }
+
+    /// The challenge for the given points. In the ELL2 suite the public key is
+    /// included in the hash, as in <https://www.rfc-editor.org/rfc/rfc9381.html#section-5.1>,
+    /// whereas draft-07 only hashes the four points.
+    pub(crate) fn challenge(
+        &self,
+        h: &EdwardsPoint,
+        gamma: &EdwardsPoint,
+        u: &EdwardsPoint,
+        v: &EdwardsPoint,
+    ) -> Scalar {
+        let points = [h.compress(), gamma.compress(), u.compress(), v.compress()];
+        match self.2 {
+            Suite::EdwardsSha512Tai => hash_points(&points),
+            Suite::EdwardsSha512Ell2 => hash_points_with_suite(self.2, &[
+                self.0, points[0], points[1], points[2], points[3],
+            ]),
+        }
+    }
+
+    /// The VRF output of a proof, computed in this key's suite.
+    pub fn proof_to_hash(&self, pi: &Proof) -> [u8; 64] { pi.to_hash_with_suite(self.2) }
+}
+
+/// Verify a batch of proofs, each with respect to its own public key and
+/// message, using a single multiscalar multiplication. Returns `true` if all
+/// of the proofs are valid and `false` otherwise, without indicating which
+/// proof failed. Keys in the batch may use different suites.
+///
+/// For each proof the commitments `U = s*B - c*Y` and `V = s*H - c*Gamma` are
+/// recomputed in variable time and the challenge is checked against them, as
+/// in `verify`. The two verification equations of all proofs are then combined
+/// with random 128-bit weights drawn from `csprng` and checked with a single
+/// multiscalar multiplication.
+#[allow(clippy::many_single_char_names)]
+pub fn verify_batch<R: Rng + CryptoRng>(
+    csprng: &mut R,
+    batch: &[(&PublicKey, &Proof, &[u8])],
+) -> bool {
+    let mut combination = BatchCombination::new(batch.len());
+    for (pk, pi, message) in batch {
+        let h = match pk.hash_to_curve(message) {
+            Some(h) => h,
+            None => return false,
+        };
+        let Proof(gamma, c, s) = pi;
+        let u = EdwardsPoint::vartime_double_scalar_mul_basepoint(&-c, &pk.1, s);
+        let v = EdwardsPoint::vartime_multiscalar_mul(&[*s, -c], &[h, *gamma]);
+        if pk.challenge(&h, gamma, &u, &v) != *c {
+            return false;
+        }
+        combination.add(csprng, pk, &h, gamma, &u, &v, c, s);
+    }
+    combination.check()
+}
+
+/// Verify a batch of proofs in the `BatchableProof` format, which carry the
+/// commitments, so only the challenges need to be recomputed. Otherwise this
+/// behaves as `verify_batch`.
+///
+/// The combined equation is checked after multiplying by the cofactor, so
+/// unlike `verify` the check does not detect proofs that differ from a valid
+/// one only by components of small order. Such differences do not affect the
+/// VRF output.
+#[allow(clippy::many_single_char_names)]
+pub fn verify_batch_batchable<R: Rng + CryptoRng>(
+    csprng: &mut R,
+    batch: &[(&PublicKey, &BatchableProof, &[u8])],
+) -> bool {
+    let mut combination = BatchCombination::new(batch.len());
+    for (pk, pi, message) in batch {
+        let h = match pk.hash_to_curve(message) {
+            Some(h) => h,
+            None => return false,
+        };
+        let BatchableProof(gamma, u, v, s) = pi;
+        let c = pk.challenge(&h, gamma, u, v);
+        combination.add(csprng, pk, &h, gamma, u, v, &c, s);
+    }
+    combination.check()
+}
+
+/// A random linear combination of the verification equations of a batch of
+/// proofs, accumulated for a single multiscalar multiplication.
+struct BatchCombination {
+    b_coefficient: Scalar,
+    scalars:       Vec<Scalar>,
+    points:        Vec<EdwardsPoint>,
+}
+
+impl BatchCombination {
+    fn new(len: usize) -> Self {
+        BatchCombination {
+            b_coefficient: Scalar::zero(),
+            scalars:       Vec::with_capacity(5 * len + 1),
+            points:        Vec::with_capacity(5 * len + 1),
+        }
+    }
+
+    /// Add `r * (s*B - c*Y - U) + l * (s*H - c*Gamma - V)` for random weights
+    /// `r` and `l`.
+    #[allow(clippy::too_many_arguments, clippy::many_single_char_names)]
+    fn add<R: Rng + CryptoRng>(
+        &mut self,
+        csprng: &mut R,
+        pk: &PublicKey,
+        h: &EdwardsPoint,
+        gamma: &EdwardsPoint,
+        u: &EdwardsPoint,
+        v: &EdwardsPoint,
+        c: &Scalar,
+        s: &Scalar,
+    ) {
+        let r = random_weight(csprng);
+        let l = random_weight(csprng);
+        self.b_coefficient += r * s;
+        self.scalars
+            .extend_from_slice(&[-(r * c), -r, l * s, -(l * c), -l]);
+        self.points.extend_from_slice(&[pk.1, *u, *h, *gamma, *v]);
+    }
+
+    fn check(mut self) -> bool {
+        self.scalars.push(self.b_coefficient);
+        self.points.push(constants::ED25519_BASEPOINT_POINT);
+        EdwardsPoint::vartime_multiscalar_mul(self.scalars, self.points)
+            .mul_by_cofactor()
+            .is_identity()
+    }
+}
+
+/// A uniformly random scalar below 2^128.
+fn random_weight<R: Rng + CryptoRng>(csprng: &mut R) -> Scalar {
+    let mut bytes = [0u8; 32];
+    csprng.fill_bytes(&mut bytes[..16]);
+    Scalar::from_bits(bytes)
 }
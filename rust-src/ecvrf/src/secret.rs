@@ -3,7 +3,7 @@
 use crate::{constants::*, errors::*, public::*};
 use core::fmt::Debug;
 use crypto_common::*;
-use curve25519_dalek::{constants, digest::Digest, scalar::Scalar};
+use curve25519_dalek::{constants, digest::Digest, edwards::EdwardsPoint, scalar::Scalar};
 use rand::{CryptoRng, Rng};
 use sha2::Sha512;
 use subtle::{Choice, ConstantTimeEq};
@@ -36,7 +36,7 @@ impl Deserial for SecretKey {
 
 impl Debug for SecretKey {
     fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-        write!(f, "SecretKey: {:?}", &self.0[..])
+        write!(f, "SecretKey: <redacted>")
     }
 }
 
@@ -81,6 +81,11 @@ impl SecretKey {
         ExpandedSecretKey::from(self).prove(&public_key, &message)
     }
 
+    /// Construct a VRF proof in the format accepted by `verify_batch_batchable`.
+    pub fn prove_batchable(&self, public_key: &PublicKey, message: &[u8]) -> BatchableProof {
+        ExpandedSecretKey::from(self).prove_batchable(public_key, message)
+    }
+
     /// Generate a `SecretKey` from a `csprng`.
     pub fn generate<T>(csprng: &mut T) -> SecretKey
     where
@@ -136,9 +141,28 @@ impl<'a> From<&'a SecretKey> for ExpandedSecretKey {
 use crate::proof::*;
 
 impl ExpandedSecretKey {
-    /// VRF proof with expanded secret key
+    /// VRF proof with expanded secret key, in the suite of the public key.
     /// Implements <https://tools.ietf.org/id/draft-irtf-cfrg-vrf-07.html#rfc.section.5.1>
+    /// and <https://www.rfc-editor.org/rfc/rfc9381.html#section-5.1>
     pub fn prove(&self, public_key: &PublicKey, alpha: &[u8]) -> Proof {
+        let (_, proof) = self.prove_with_commitments(public_key, alpha);
+        proof
+    }
+
+    /// Like `prove`, but produce a proof that can be verified with
+    /// `verify_batch_batchable`.
+    pub fn prove_batchable(&self, public_key: &PublicKey, alpha: &[u8]) -> BatchableProof {
+        let ((u, v), Proof(gamma, _, s)) = self.prove_with_commitments(public_key, alpha);
+        BatchableProof(gamma, u, v, s)
+    }
+
+    /// Produce the proof together with the commitments `U = k*B` and `V = k*H`
+    /// that the challenge is computed from.
+    fn prove_with_commitments(
+        &self,
+        public_key: &PublicKey,
+        alpha: &[u8],
+    ) -> ((EdwardsPoint, EdwardsPoint), Proof) {
         let x = self.key;
         let h = public_key
             .hash_to_curve(alpha)
@@ -148,16 +172,13 @@ impl ExpandedSecretKey {
 
         let gamma = x * h;
 
-        let c = hash_points(&[
-            h.compress(),
-            gamma.compress(),
-            (k * constants::ED25519_BASEPOINT_POINT).compress(), // b^k
-            (k * h).compress(),                                  // h^k
-        ]);
+        let u = k * constants::ED25519_BASEPOINT_POINT; // b^k
+        let v = k * h; // h^k
+        let c = public_key.challenge(&h, &gamma, &u, &v);
 
         let k_plus_cx = k + c * x;
 
-        Proof(gamma, c, k_plus_cx)
+        ((u, v), Proof(gamma, c, k_plus_cx))
     }
 
     /// Implements <https://tools.ietf.org/id/draft-irtf-cfrg-vrf-07.html#rfc.section.5.4.2.2>
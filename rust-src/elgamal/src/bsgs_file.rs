@@ -0,0 +1,249 @@
+//! A persistent, file-backed table for the baby step giant step algorithm.
+//!
+//! Building a [BabyStepGiantStep](crate::BabyStepGiantStep) table of size `m`
+//! takes `m` group additions and `m` point serializations, which dominates the
+//! running time of tools that only need to decrypt a few amounts. The format
+//! defined here can be written once, and then used directly from memory, e.g.,
+//! a memory mapped file, without any parsing of the table itself.
+//!
+//! The format is, with all integers big-endian,
+//!
+//! - the magic bytes `BSGS`
+//! - a 4 byte format version, currently 0
+//! - the table size `m` as 8 bytes
+//! - the base of the logarithm, and the point `base^{-m}`, both serialized as
+//!   group elements
+//! - `m` entries of 16 bytes each, consisting of an 8 byte fingerprint of
+//!   `base^j` followed by `j` as 8 bytes, for `j` in `0..m`. The entries are
+//!   sorted by fingerprint.
+//!
+//! The fingerprint of a point is the last 8 bytes of its serialization. Since
+//! fingerprints can collide, a candidate `j` found in the table is only
+//! accepted after checking that `base^j` is the point that was looked up.
+use crate::secret::DiscreteLog;
+use anyhow::{bail, ensure};
+use crypto_common::*;
+use curve_arithmetic::Curve;
+use std::io::{Read, Write};
+
+/// Magic bytes at the start of the table file.
+const MAGIC: [u8; 4] = *b"BSGS";
+
+/// The current (and only) version of the table format.
+const FORMAT_VERSION: u32 = 0;
+
+/// Size of a single table entry, the fingerprint and the index.
+const ENTRY_SIZE: usize = 16;
+
+/// Fingerprint of a point, used as the lookup key in the table.
+fn fingerprint<C: Curve>(point: &C) -> u64 {
+    let bytes = to_bytes(point);
+    let mut fp = [0u8; 8];
+    fp.copy_from_slice(&bytes[bytes.len() - 8..]);
+    u64::from_be_bytes(fp)
+}
+
+/// Compute the table for the given base and write it to `out` in the format
+/// described in the module documentation.
+///
+/// This needs to hold all the `m` entries in memory while sorting them, that
+/// is, `16 * m` bytes.
+pub fn write_bsgs_table<C: Curve, W: Write>(base: &C, m: u64, out: &mut W) -> std::io::Result<()> {
+    let mut entries = Vec::with_capacity(m as usize);
+    let mut base_j = C::zero_point();
+    for j in 0..m {
+        entries.push((fingerprint(&base_j), j));
+        base_j = base_j.plus_point(base);
+    }
+    entries.sort_unstable();
+
+    let mut header = Vec::new();
+    header.write_all(&MAGIC)?;
+    header.put(&FORMAT_VERSION);
+    header.put(&m);
+    header.put(base);
+    header.put(&base_j.inverse_point());
+    out.write_all(&header)?;
+    for (fp, j) in entries {
+        out.write_all(&fp.to_be_bytes())?;
+        out.write_all(&j.to_be_bytes())?;
+    }
+    Ok(())
+}
+
+/// A baby step giant step table in the format described in the module
+/// documentation. The data is anything that can be viewed as bytes, typically
+/// a `Vec<u8>` read from a file, or a memory mapped file. Lookups work directly
+/// on the data, so a memory mapped table only has the pages that are used
+/// loaded into memory.
+pub struct BabyStepGiantStepFile<C: Curve, D: AsRef<[u8]> = Vec<u8>> {
+    /// The table data, including the header.
+    data:          D,
+    /// Offset of the first entry in `data`.
+    entries_start: usize,
+    /// The base of the logarithm.
+    base:          C,
+    /// Point base^{-m}
+    inverse_point: C,
+    /// Size of the table.
+    m:             u64,
+}
+
+impl<C: Curve, D: AsRef<[u8]>> BabyStepGiantStepFile<C, D> {
+    /// Check the header and the entries of the table, and construct the
+    /// instance. This is linear in the size of the table, but does no group
+    /// operations apart from checking the header.
+    pub fn from_bytes(data: D) -> anyhow::Result<Self> {
+        let (entries_start, base, inverse_point, m) = {
+            let bytes = data.as_ref();
+            let mut source = bytes;
+            let mut magic = [0u8; 4];
+            source.read_exact(&mut magic)?;
+            ensure!(magic == MAGIC, "Not a baby step giant step table.");
+            let version: u32 = source.get()?;
+            ensure!(
+                version == FORMAT_VERSION,
+                "Unsupported table format version {}.",
+                version
+            );
+            let m: u64 = source.get()?;
+            ensure!(m > 0, "The table must not be empty.");
+            let base: C = source.get()?;
+            let inverse_point: C = source.get()?;
+            if base.mul_by_scalar(&C::scalar_from_u64(m)).inverse_point() != inverse_point {
+                bail!("The inverse point does not match the base and the table size.")
+            }
+            let entries = source;
+            match (m as usize).checked_mul(ENTRY_SIZE) {
+                Some(len) if len == entries.len() => {}
+                _ => bail!("The table has an incorrect length."),
+            }
+            // The entries are compared as bytes, which agrees with comparison of
+            // big-endian integers.
+            let sorted = entries
+                .chunks_exact(ENTRY_SIZE)
+                .zip(entries.chunks_exact(ENTRY_SIZE).skip(1))
+                .all(|(a, b)| a[..8] <= b[..8]);
+            ensure!(sorted, "The table entries are not sorted.");
+            (bytes.len() - entries.len(), base, inverse_point, m)
+        };
+        Ok(Self {
+            data,
+            entries_start,
+            base,
+            inverse_point,
+            m,
+        })
+    }
+
+    /// Size of the table.
+    pub fn table_size(&self) -> u64 { self.m }
+
+    /// The base with respect to which logarithms are computed.
+    pub fn base(&self) -> &C { &self.base }
+
+    /// Get the underlying data back.
+    pub fn into_inner(self) -> D { self.data }
+
+    /// Get the entry at the given position as a pair of fingerprint and index.
+    fn entry(&self, i: usize) -> (u64, u64) {
+        let start = self.entries_start + i * ENTRY_SIZE;
+        let bytes = &self.data.as_ref()[start..start + ENTRY_SIZE];
+        let mut fp = [0u8; 8];
+        let mut j = [0u8; 8];
+        fp.copy_from_slice(&bytes[..8]);
+        j.copy_from_slice(&bytes[8..]);
+        (u64::from_be_bytes(fp), u64::from_be_bytes(j))
+    }
+
+    /// Find `j < m` such that `base^j = y`, if it exists.
+    fn lookup(&self, y: &C) -> Option<u64> {
+        let fp = fingerprint(y);
+        // Binary search for the first entry with fingerprint not less than `fp`.
+        let (mut lo, mut hi) = (0, self.m as usize);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.entry(mid).0 < fp {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        (lo..self.m as usize)
+            .map(|i| self.entry(i))
+            .take_while(|(entry_fp, _)| *entry_fp == fp)
+            .map(|(_, j)| j)
+            .find(|j| self.base.mul_by_scalar(&C::scalar_from_u64(*j)) == *y)
+    }
+
+    /// Compute the discrete log using the table. Like
+    /// [BabyStepGiantStep::discrete_log](crate::BabyStepGiantStep::discrete_log)
+    /// this is linear in `l / m` where `l` is the value stored in the exponent
+    /// of `v`.
+    pub fn discrete_log(&self, v: &C) -> u64 {
+        let mut y = *v;
+        for i in 0..=u64::MAX {
+            if let Some(j) = self.lookup(&y) {
+                return i * self.m + j;
+            }
+            y = y.plus_point(&self.inverse_point);
+        }
+        unreachable!("It should not be feasible to do 2^64 group additions.")
+    }
+}
+
+impl<C: Curve, D: AsRef<[u8]>> DiscreteLog<C> for BabyStepGiantStepFile<C, D> {
+    fn discrete_log(&self, v: &C) -> u64 { BabyStepGiantStepFile::discrete_log(self, v) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BabyStepGiantStep;
+    use curve_arithmetic::ristretto::RistrettoPoint;
+    use pairing::bls12_381::G1;
+    use rand::*;
+
+    fn test_bsgs_file_generic<C: Curve>() {
+        let mut csprng = thread_rng();
+        let base = C::generate(&mut csprng);
+        let m = 1 << 10;
+        let mut data = Vec::new();
+        write_bsgs_table(&base, m, &mut data).expect("Writing to a vector succeeds.");
+        let table = BabyStepGiantStepFile::<C>::from_bytes(data).expect("Table is well-formed.");
+        let in_memory = BabyStepGiantStep::new(&base, m);
+        for _ in 0..100 {
+            let l = u64::from(csprng.gen::<u16>()) * 7;
+            let v = base.mul_by_scalar(&C::scalar_from_u64(l));
+            assert_eq!(table.discrete_log(&v), l, "Incorrect discrete logarithm.");
+            assert_eq!(
+                table.discrete_log(&v),
+                in_memory.discrete_log(&v),
+                "The file table disagrees with the in-memory table."
+            );
+        }
+    }
+
+    #[test]
+    fn test_bsgs_file_g1() { test_bsgs_file_generic::<G1>() }
+
+    #[test]
+    fn test_bsgs_file_ristretto() { test_bsgs_file_generic::<RistrettoPoint>() }
+
+    #[test]
+    fn test_bsgs_file_malformed() {
+        let mut csprng = thread_rng();
+        let base = G1::generate(&mut csprng);
+        let mut data = Vec::new();
+        write_bsgs_table(&base, 16, &mut data).expect("Writing to a vector succeeds.");
+        assert!(BabyStepGiantStepFile::<G1, _>::from_bytes(&data[..data.len() - 1]).is_err());
+        let mut wrong_magic = data.clone();
+        wrong_magic[0] = b'X';
+        assert!(BabyStepGiantStepFile::<G1>::from_bytes(wrong_magic).is_err());
+        let mut unsorted = data.clone();
+        let len = unsorted.len();
+        unsorted[len - ENTRY_SIZE..len - ENTRY_SIZE + 8].copy_from_slice(&[0; 8]);
+        assert!(BabyStepGiantStepFile::<G1>::from_bytes(unsorted).is_err());
+        assert!(BabyStepGiantStepFile::<G1>::from_bytes(data).is_ok());
+    }
+}
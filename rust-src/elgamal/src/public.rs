@@ -7,7 +7,7 @@ use crate::{cipher::*, message::*, secret::*};
 
 use crypto_common::*;
 use crypto_common_derive::*;
-use curve_arithmetic::{multiexp, Curve, Value};
+use curve_arithmetic::{multiexp_secret, Curve, Value};
 
 /// Elgamal public key .
 #[derive(Copy, Clone, Eq, PartialEq, Serialize, SerdeBase16Serialize)]
@@ -26,7 +26,7 @@ impl<'a, C: Curve> From<&'a SecretKey<C>> for PublicKey<C> {
     /// Derive this public key from its corresponding `SecretKey`.
     fn from(secret_key: &SecretKey<C>) -> PublicKey<C> {
         let generator: C = secret_key.generator;
-        let key = generator.mul_by_scalar(&secret_key.scalar);
+        let key = generator.mul_by_secret_scalar(&secret_key.scalar);
         PublicKey { generator, key }
     }
 }
@@ -38,10 +38,13 @@ impl<C: Curve> PublicKey<C> {
     where
         T: Rng, {
         let k = Randomness::generate(csprng);
-        let g = self.generator.mul_by_scalar(&k.randomness);
+        let g = self.generator.mul_by_secret_scalar(&k.randomness);
         // FIXME: Could use multiexponentiation when we are calling from
         // encrypt_exponent_rand.
-        let s = self.key.mul_by_scalar(&k.randomness).plus_point(&m.value);
+        let s = self
+            .key
+            .mul_by_secret_scalar(&k.randomness)
+            .plus_point(&m.value);
         (Cipher(g, s), k)
     }
 
@@ -54,8 +57,8 @@ impl<C: Curve> PublicKey<C> {
     }
 
     pub fn hide(&self, k: &C::Scalar, message: &Message<C>) -> Cipher<C> {
-        let t = self.generator.mul_by_scalar(k);
-        let s = self.key.mul_by_scalar(&k).plus_point(&message.value);
+        let t = self.generator.mul_by_secret_scalar(k);
+        let s = self.key.mul_by_secret_scalar(k).plus_point(&message.value);
         Cipher(t, s)
     }
 
@@ -85,8 +88,8 @@ impl<C: Curve> PublicKey<C> {
     where
         T: Rng, {
         let randomness = C::generate_scalar(csprng);
-        let g = self.generator.mul_by_scalar(&randomness);
-        let s = multiexp(&[self.key, *h], &[randomness, *e.as_ref()]);
+        let g = self.generator.mul_by_secret_scalar(&randomness);
+        let s = multiexp_secret(&[self.key, *h], &[randomness, *e.as_ref()]);
         let randomness = Randomness::new(randomness);
         (Cipher(g, s), randomness)
     }
@@ -4,13 +4,13 @@
 use crate::{cipher::*, message::*};
 use anyhow::{bail, Result};
 use crypto_common::*;
-use curve_arithmetic::{Curve, Value};
+use curve_arithmetic::{clear_field_element, Curve, Value};
 use ff::Field;
 use rand::*;
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
 /// Elgamal secret key packed together with a chosen generator.
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, SerdeBase16Serialize)]
+#[derive(PartialEq, Eq, Clone, Serialize, SerdeBase16Serialize)]
 pub struct SecretKey<C: Curve> {
     /// Generator of the group, not secret but convenient to have here.
     pub generator: C,
@@ -18,18 +18,32 @@ pub struct SecretKey<C: Curve> {
     pub scalar:    C::Scalar,
 }
 
-// THIS IS COMMENTED FOR NOW FOR COMPATIBILITY WITH BLS CURVE IMPLEMENTATION
-// ONCE WE HAVE TAKEN OVER THE SOURCE OF THE CURVE THIS SHOULD BE IMPLEMENTED
-// Overwrite secret key material with null bytes when it goes out of scope.
-//
-// impl Drop for SecretKey {
-// fn drop(&mut self) {
-// (self.0).into_repr().0.clear();
-// }
-// }
+/// Overwrite secret key material with zeros when it goes out of scope.
+impl<C: Curve> Drop for SecretKey<C> {
+    fn drop(&mut self) { clear_field_element(&mut self.scalar) }
+}
+
+/// Only the generator is shown.
+impl<C: Curve> fmt::Debug for SecretKey<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SecretKey")
+            .field("generator", &self.generator)
+            .field("scalar", &"<redacted>")
+            .finish()
+    }
+}
 
 pub type BabyStepGiantStepTable = HashMap<Vec<u8>, u64>;
 
+/// A precomputed structure that can be used to compute small discrete
+/// logarithms with respect to a fixed base, such as [BabyStepGiantStep], or
+/// the file-backed [BabyStepGiantStepFile].
+pub trait DiscreteLog<C: Curve> {
+    /// Compute `l` such that `v = base^l`. The base is fixed by the instance.
+    /// The running time is linear in `l`, so `l` must be small.
+    fn discrete_log(&self, v: &C) -> u64;
+}
+
 #[derive(Eq, PartialEq, Debug)]
 /// The table for the baby step giant step algorithm, with some auxiliary data.
 pub struct BabyStepGiantStep<C: Curve> {
@@ -114,10 +128,14 @@ impl<C: Curve> BabyStepGiantStep<C> {
     }
 }
 
+impl<C: Curve> DiscreteLog<C> for BabyStepGiantStep<C> {
+    fn discrete_log(&self, v: &C) -> u64 { BabyStepGiantStep::discrete_log(self, v) }
+}
+
 impl<C: Curve> SecretKey<C> {
     pub fn decrypt(&self, c: &Cipher<C>) -> Message<C> {
         let x = c.0; // k * g
-        let kag = x.mul_by_scalar(&self.scalar); // k * a * g
+        let kag = x.mul_by_secret_scalar(&self.scalar); // k * a * g
         let y = c.1; // m + k * a * g
         let value = y.minus_point(&kag); // m
         Message { value }
@@ -139,9 +157,9 @@ impl<C: Curve> SecretKey<C> {
     /// be represented in 64 bits, and are small enough. Otherwise this function
     /// will appear to not terminate.
     ///
-    /// This function takes an auxiliary instance of BabyStepGiantStep to speed
-    /// up decryption.
-    pub fn decrypt_exponent(&self, c: &Cipher<C>, bsgs: &BabyStepGiantStep<C>) -> u64 {
+    /// This function takes an auxiliary instance of BabyStepGiantStep, or
+    /// another precomputed [DiscreteLog] structure, to speed up decryption.
+    pub fn decrypt_exponent<D: DiscreteLog<C> + ?Sized>(&self, c: &Cipher<C>, bsgs: &D) -> u64 {
         let dec = self.decrypt(c).value;
         bsgs.discrete_log(&dec)
     }
@@ -188,6 +206,17 @@ mod tests {
     macro_test_secret_key_to_byte_conversion!(secret_key_to_byte_conversion_g1, G1);
     macro_test_secret_key_to_byte_conversion!(secret_key_to_byte_conversion_g2, G2);
 
+    #[test]
+    fn test_secret_key_cleared_on_drop() {
+        let mut csprng = thread_rng();
+        let mut sk = std::mem::ManuallyDrop::new(SecretKey::<G1>::generate_all(&mut csprng));
+        let scalar = sk.scalar;
+        assert!(!format!("{:?}", *sk).contains(&format!("{:?}", scalar)));
+        // Run the destructor, but keep the memory so it can be inspected.
+        unsafe { std::ptr::drop_in_place(&mut *sk) };
+        assert!(sk.scalar.is_zero(), "Secret key was not cleared.");
+    }
+
     // Test serialiation of baby-step-giant-step since it is implemented manually.
     #[test]
     fn test_bsgs_serialize() {
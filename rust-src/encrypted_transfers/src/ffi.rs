@@ -7,7 +7,7 @@ use ffi_helpers::*;
 use prelude::StdRng;
 use std::io::Cursor;
 
-type Group = pairing::bls12_381::G1;
+type Group = id::constants::ArCurve;
 
 /// # Safety
 /// This function is safe if the pointers are all non-null, and produced
@@ -142,13 +142,39 @@ unsafe extern "C" fn make_encrypted_transfer_data(
     ptr
 }
 
+/// The code returned through FFI for the outcome of verifying transfer data.
+/// Success is 1, and failures are negative, identifying the part of the
+/// transfer data that was rejected.
+/// - -1: the proof could not be parsed
+/// - -2: the accounting proof is invalid
+/// - -3: the range proof of the transfer amount is invalid
+/// - -4: the range proof of the remaining amount is invalid
+/// - -5: the chunks of the proof are malformed
+/// - -6: the aggregated range proof is invalid
+/// - -7: the encryption of the transfer amount for the auditor is invalid
+/// - -8: the version of the transfer data is not supported
+/// - -9: the transfer amounts do not match the receivers
+fn verification_result_code(result: Result<(), VerificationError>) -> i32 {
+    match result {
+        Ok(()) => 1,
+        Err(VerificationError::AccountingProof) => -2,
+        Err(VerificationError::TransferAmountRangeProof(_)) => -3,
+        Err(VerificationError::RemainingAmountRangeProof(_)) => -4,
+        Err(VerificationError::MalformedChunks { .. }) => -5,
+        Err(VerificationError::AggregatedRangeProof(_)) => -6,
+        Err(VerificationError::AuditorEncryption) => -7,
+        Err(VerificationError::UnsupportedVersion(_)) => -8,
+        Err(VerificationError::MalformedReceivers { .. }) => -9,
+    }
+}
+
 /// # Safety
 /// This function is safe if the pointers to structures are all non-null, and
 /// produced by `Box::into_raw`. The `transfer_proof_ptr` can be null in case
 /// the length is 0, but otherwise it must be non-null and dereferenceable.
 ///
-/// Return 0 in case verification was unsuccesful, and a non-zero value
-/// otherwise.
+/// Return 1 in case verification was successful, and otherwise a negative
+/// value identifying the reason for failure, see [verification_result_code].
 #[no_mangle]
 unsafe extern "C" fn verify_encrypted_transfer(
     ctx_ptr: *const GlobalContext<Group>,
@@ -163,7 +189,7 @@ unsafe extern "C" fn verify_encrypted_transfer(
     encrypted_agg_index: u64,
     transfer_proof_ptr: *const u8,
     transfer_proof_len: size_t,
-) -> u8 {
+) -> i32 {
     let ctx = from_ptr!(ctx_ptr);
 
     let receiver_pk = from_ptr!(receiver_pk_ptr);
@@ -192,7 +218,7 @@ unsafe extern "C" fn verify_encrypted_transfer(
     let proof = if let Ok(td) = (&mut Cursor::new(transfer_proof)).get() {
         td
     } else {
-        return 0;
+        return -1;
     };
 
     let transfer_data = EncryptedAmountTransferData {
@@ -202,11 +228,13 @@ unsafe extern "C" fn verify_encrypted_transfer(
         proof,
     };
 
-    if verify_transfer_data(ctx, &receiver_pk, &sender_pk, &initial, &transfer_data) {
-        1
-    } else {
-        0
-    }
+    verification_result_code(verify_transfer_data(
+        ctx,
+        &receiver_pk,
+        &sender_pk,
+        &initial,
+        &transfer_data,
+    ))
 }
 
 /// # Safety
@@ -258,8 +286,8 @@ unsafe extern "C" fn make_sec_to_pub_data(
 /// produced by `Box::into_raw`. The `transfer_proof_ptr` can be null in case
 /// the length is 0, but otherwise it must be non-null and dereferenceable.
 ///
-/// Return 0 in case verification was unsuccesful, and a non-zero value
-/// otherwise.
+/// Return 1 in case verification was successful, and otherwise a negative
+/// value identifying the reason for failure, see [verification_result_code].
 #[no_mangle]
 unsafe extern "C" fn verify_sec_to_pub_transfer(
     ctx_ptr: *const GlobalContext<Group>,
@@ -272,7 +300,7 @@ unsafe extern "C" fn verify_sec_to_pub_transfer(
     encrypted_agg_index: u64,
     transfer_proof_ptr: *const u8,
     transfer_proof_len: size_t,
-) -> u8 {
+) -> i32 {
     let ctx = from_ptr!(ctx_ptr);
 
     let sender_pk = from_ptr!(sender_pk_ptr);
@@ -293,7 +321,7 @@ unsafe extern "C" fn verify_sec_to_pub_transfer(
     let proof = if let Ok(td) = (&mut Cursor::new(transfer_proof)).get() {
         td
     } else {
-        return 0;
+        return -1;
     };
 
     let transfer_amount = Amount { microgtu };
@@ -305,11 +333,12 @@ unsafe extern "C" fn verify_sec_to_pub_transfer(
         proof,
     };
 
-    if verify_sec_to_pub_transfer_data(ctx, &sender_pk, &initial, &transfer_data) {
-        1
-    } else {
-        0
-    }
+    verification_result_code(verify_sec_to_pub_transfer_data(
+        ctx,
+        &sender_pk,
+        &initial,
+        &transfer_data,
+    ))
 }
 
 /// # Safety
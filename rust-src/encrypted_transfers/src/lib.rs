@@ -10,10 +10,11 @@ pub mod proofs;
 pub mod types;
 
 use crate::types::{CHUNK_SIZE as CHUNK_SIZE_ENC_TRANS, *};
-use crypto_common::types::Amount;
+use crypto_common::{types::Amount, Versioned};
 use curve_arithmetic::*;
 use elgamal::*;
 use id::types::*;
+pub use proofs::VerificationError;
 use rand::*;
 use random_oracle::*;
 
@@ -106,8 +107,11 @@ pub fn aggregate<C: Curve>(
 /// one that is used to contruct the table.
 ///
 /// If not, this function will (almost certainly) appear not to terminate.
-pub fn decrypt_amount<C: Curve>(
-    table: &BabyStepGiantStep<C>,
+///
+/// The table is typically a [BabyStepGiantStep], or a
+/// [BabyStepGiantStepFile] loaded from a precomputed file.
+pub fn decrypt_amount<C: Curve, D: DiscreteLog<C> + ?Sized>(
+    table: &D,
     sk: &SecretKey<C>,
     amount: &EncryptedAmount<C>,
 ) -> Amount {
@@ -116,6 +120,34 @@ pub fn decrypt_amount<C: Curve>(
     Amount::from(CHUNK_SIZE_ENC_TRANS.chunks_to_u64([low_chunk, hi_chunk].iter().copied()))
 }
 
+/// Decrypt all the given incoming amounts of an account, sharing the same
+/// table. The result is in the same order as the input, with each amount
+/// paired with its index.
+///
+/// With the `parallel` feature the amounts are decrypted on multiple threads.
+/// The same assumptions as for [decrypt_amount] apply to each amount.
+pub fn decrypt_amounts<C: Curve, D: DiscreteLog<C> + Sync + ?Sized>(
+    table: &D,
+    sk: &SecretKey<C>,
+    amounts: &[IndexedEncryptedAmount<C>],
+) -> Vec<(EncryptedAmountIndex, Amount)> {
+    let decrypt = |amount: &IndexedEncryptedAmount<C>| {
+        (
+            amount.index,
+            decrypt_amount(table, sk, &amount.encrypted_chunks),
+        )
+    };
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        amounts.par_iter().map(decrypt).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        amounts.iter().map(decrypt).collect()
+    }
+}
+
 impl<C: Curve> EncryptedAmount<C> {
     /// Join chunks of an encrypted amount into a single ciphertext.
     /// The resulting ciphertext will in general not be easily decryptable.
@@ -183,16 +215,15 @@ pub fn make_transfer_data<C: Curve, R: Rng>(
 /// - encryption of amount on sender account before transfer
 /// - encrypted amount transaction,
 ///
-/// The return value is going to be `true` if verification succeeds and `false`
-/// if not.
+/// If verification fails the error identifies the part of the transfer data
+/// that was rejected.
 pub fn verify_transfer_data<C: Curve>(
     ctx: &GlobalContext<C>,
     receiver_pk: &PublicKey<C>,
     sender_pk: &PublicKey<C>,
     before_amount: &EncryptedAmount<C>,
     transfer_data: &EncryptedAmountTransferData<C>,
-) -> bool {
-    // Fixme: Put context into the random oracle.
+) -> Result<(), VerificationError> {
     let mut ro = RandomOracle::domain("EncryptedTransfer");
     ro.append_message(b"ctx", &ctx);
     ro.append_message(b"receiver_pk", &receiver_pk);
@@ -208,7 +239,253 @@ pub fn verify_transfer_data<C: Curve>(
         receiver_pk,
         &before_amount.join(),
     )
-    .is_ok()
+}
+
+/// Produce the payload of an encrypted amount transaction with a single
+/// aggregated range proof for the chunks of both the transfer and the remaining
+/// amount. The resulting proof is smaller than the one produced by
+/// [make_transfer_data], but it is only accepted by
+/// [verify_versioned_transfer_data].
+///
+/// The arguments are the same as for [make_transfer_data]. The global context
+/// must contain at least 128 bulletproof generators.
+pub fn make_transfer_data_aggregated<C: Curve, R: Rng>(
+    ctx: &GlobalContext<C>,
+    receiver_pk: &PublicKey<C>,
+    sender_sk: &SecretKey<C>,
+    input_amount: &AggregatedDecryptedAmount<C>,
+    to_transfer: Amount,
+    csprng: &mut R,
+) -> Option<VersionedEncryptedAmountTransferData<C>> {
+    let sender_pk = &PublicKey::from(sender_sk);
+    let mut ro = RandomOracle::domain("EncryptedTransfer");
+    ro.append_message(b"ctx", &ctx);
+    ro.append_message(b"receiver_pk", &receiver_pk);
+    ro.append_message(b"sender_pk", &sender_pk);
+
+    proofs::gen_enc_trans_aggregated(
+        ctx,
+        &mut ro,
+        sender_pk,
+        sender_sk,
+        receiver_pk,
+        input_amount.agg_index,
+        &input_amount.agg_encrypted_amount.join(),
+        input_amount.agg_amount,
+        to_transfer,
+        csprng,
+    )
+}
+
+/// Verify an encrypted amount transaction whose proof is in any of the
+/// supported versions. The arguments and the return value are as for
+/// [verify_transfer_data].
+pub fn verify_versioned_transfer_data<C: Curve>(
+    ctx: &GlobalContext<C>,
+    receiver_pk: &PublicKey<C>,
+    sender_pk: &PublicKey<C>,
+    before_amount: &EncryptedAmount<C>,
+    transfer_data: &VersionedEncryptedAmountTransferData<C>,
+) -> Result<(), VerificationError> {
+    let mut ro = RandomOracle::domain("EncryptedTransfer");
+    ro.append_message(b"ctx", &ctx);
+    ro.append_message(b"receiver_pk", &receiver_pk);
+    ro.append_message(b"sender_pk", &sender_pk);
+
+    proofs::verify_enc_trans_versioned(
+        ctx,
+        &mut ro,
+        transfer_data,
+        sender_pk,
+        receiver_pk,
+        &before_amount.join(),
+    )
+}
+
+/// Produce the payload of an encrypted amount transaction that can optionally
+/// be audited. If `auditor_pk` is given, the transfer amount is additionally
+/// encrypted under it, with a proof that it is the same amount as the one
+/// encrypted for the receiver. The auditor can then decrypt the amount with
+/// [decrypt_auditor_amount].
+///
+/// The remaining arguments are the same as for [make_transfer_data], and the
+/// transfer itself is proved as in [make_transfer_data_aggregated]. The auditor
+/// public key must use the same generator as the receiver's. The global context
+/// must contain at least 128 bulletproof generators.
+///
+/// The return value is going to be `None` if a transfer could not be produced.
+pub fn make_auditable_transfer_data<C: Curve, R: Rng>(
+    ctx: &GlobalContext<C>,
+    receiver_pk: &PublicKey<C>,
+    sender_sk: &SecretKey<C>,
+    auditor_pk: Option<&PublicKey<C>>,
+    input_amount: &AggregatedDecryptedAmount<C>,
+    to_transfer: Amount,
+    csprng: &mut R,
+) -> Option<Versioned<AuditableEncryptedAmountTransferData<C>>> {
+    let sender_pk = &PublicKey::from(sender_sk);
+    let mut ro = RandomOracle::domain("AuditableEncryptedTransfer");
+    ro.append_message(b"ctx", &ctx);
+    ro.append_message(b"receiver_pk", &receiver_pk);
+    ro.append_message(b"sender_pk", &sender_pk);
+    ro.append_message(b"auditor_pk", &auditor_pk);
+
+    let data = proofs::gen_enc_trans_auditable(
+        ctx,
+        &mut ro,
+        sender_pk,
+        sender_sk,
+        receiver_pk,
+        auditor_pk,
+        input_amount.agg_index,
+        &input_amount.agg_encrypted_amount.join(),
+        input_amount.agg_amount,
+        to_transfer,
+        csprng,
+    )?;
+    Some(Versioned::new(AUDITABLE_TRANSFER_DATA_VERSION, data))
+}
+
+/// Verify an encrypted amount transaction produced by
+/// [make_auditable_transfer_data]. If `auditor_pk` is given the transaction
+/// must contain an encryption of the transfer amount under it, and otherwise
+/// it must contain none. The remaining arguments and the return value are as
+/// for [verify_transfer_data].
+pub fn verify_auditable_transfer_data<C: Curve>(
+    ctx: &GlobalContext<C>,
+    receiver_pk: &PublicKey<C>,
+    sender_pk: &PublicKey<C>,
+    auditor_pk: Option<&PublicKey<C>>,
+    before_amount: &EncryptedAmount<C>,
+    transfer_data: &Versioned<AuditableEncryptedAmountTransferData<C>>,
+) -> Result<(), VerificationError> {
+    if transfer_data.version != AUDITABLE_TRANSFER_DATA_VERSION {
+        return Err(VerificationError::UnsupportedVersion(transfer_data.version));
+    }
+    let mut ro = RandomOracle::domain("AuditableEncryptedTransfer");
+    ro.append_message(b"ctx", &ctx);
+    ro.append_message(b"receiver_pk", &receiver_pk);
+    ro.append_message(b"sender_pk", &sender_pk);
+    ro.append_message(b"auditor_pk", &auditor_pk);
+
+    proofs::verify_enc_trans_auditable(
+        ctx,
+        &mut ro,
+        &transfer_data.value,
+        sender_pk,
+        receiver_pk,
+        auditor_pk,
+        &before_amount.join(),
+    )
+}
+
+/// Decrypt the transfer amount of an auditable encrypted amount transaction
+/// with the secret key of the auditor. Returns `None` if the transaction has no
+/// encryption for the auditor.
+///
+/// The transaction must have been verified with
+/// [verify_auditable_transfer_data] for the auditor's public key, otherwise
+/// this function might not terminate, see [decrypt_amount]. The table must be
+/// constructed for the generator `ctx.encryption_in_exponent_generator()`.
+pub fn decrypt_auditor_amount<C: Curve, D: DiscreteLog<C> + ?Sized>(
+    table: &D,
+    auditor_sk: &SecretKey<C>,
+    transfer_data: &AuditableEncryptedAmountTransferData<C>,
+) -> Option<Amount> {
+    let auditor_amount = transfer_data.auditor_amount.as_ref()?;
+    Some(decrypt_amount(
+        table,
+        auditor_sk,
+        &auditor_amount.encrypted_amount,
+    ))
+}
+
+/// Produce the payload of an encrypted amount transaction to several receivers
+/// at once. This is cheaper than a separate transaction for each receiver,
+/// since there is only one encryption of the remaining amount, and one proof.
+///
+/// The arguments are
+///
+/// - global context with parameters for generating proofs, and generators for
+///   encrypting amounts.
+/// - secret key of the sender of the transfer
+/// - input amount from which to send
+/// - the public key of each receiver, together with the amount to send to them
+///
+/// The global context must contain `32 * m` bulletproof generators, where `m`
+/// is `2 * (transfers.len() + 1)` rounded up to a power of two. The number of
+/// receivers this allows is given by [max_multi_transfer_receivers].
+///
+/// The return value is going to be `None` if a transfer could not be produced.
+/// This could be because the amounts to send add up to more than the input
+/// amount, because there are no receivers or more than
+/// [max_multi_transfer_receivers], or because of some other data inconsistency
+/// that means a proof could not be produced.
+pub fn make_multi_transfer_data<C: Curve, R: Rng>(
+    ctx: &GlobalContext<C>,
+    sender_sk: &SecretKey<C>,
+    input_amount: &AggregatedDecryptedAmount<C>,
+    transfers: &[(PublicKey<C>, Amount)],
+    csprng: &mut R,
+) -> Option<MultiEncryptedAmountTransferData<C>> {
+    let sender_pk = &PublicKey::from(sender_sk);
+    let mut ro = RandomOracle::domain("MultiEncryptedTransfer");
+    ro.append_message(b"ctx", &ctx);
+    ro.extend_from(b"receiver_pks", transfers.iter().map(|(pk, _)| pk));
+    ro.append_message(b"sender_pk", &sender_pk);
+
+    proofs::gen_multi_enc_trans(
+        ctx,
+        &mut ro,
+        sender_pk,
+        sender_sk,
+        transfers,
+        input_amount.agg_index,
+        &input_amount.agg_encrypted_amount.join(),
+        input_amount.agg_amount,
+        csprng,
+    )
+}
+
+/// The maximum number of receivers of a [MultiEncryptedAmountTransferData]
+/// with the given global context. The chunks of all the transfer amounts and
+/// the remaining amount are proved to be small by a single range proof, whose
+/// number of values is a power of two, at most 128, and limited by the number
+/// of bulletproof generators in the context. With the 256 generators of the
+/// standard context this is 3 receivers.
+pub fn max_multi_transfer_receivers<C: Curve>(context: &GlobalContext<C>) -> usize {
+    let chunk_size = usize::from(u8::from(CHUNK_SIZE_ENC_TRANS));
+    let chunks_per_amount = 64 / chunk_size;
+    let max_values = std::cmp::min(context.bulletproof_generators().G_H.len() / chunk_size, 128);
+    // The largest power of two that is at most max_values, or 0.
+    let num_values = (max_values + 1).next_power_of_two() / 2;
+    (num_values / chunks_per_amount).saturating_sub(1)
+}
+
+/// Verify an encrypted amount transaction to several receivers, produced by
+/// [make_multi_transfer_data]. The public keys of the receivers must be in the
+/// same order as the transfer amounts in the transaction. The remaining
+/// arguments and the return value are as for [verify_transfer_data].
+pub fn verify_multi_transfer_data<C: Curve>(
+    ctx: &GlobalContext<C>,
+    receiver_pks: &[PublicKey<C>],
+    sender_pk: &PublicKey<C>,
+    before_amount: &EncryptedAmount<C>,
+    transfer_data: &MultiEncryptedAmountTransferData<C>,
+) -> Result<(), VerificationError> {
+    let mut ro = RandomOracle::domain("MultiEncryptedTransfer");
+    ro.append_message(b"ctx", &ctx);
+    ro.extend_from(b"receiver_pks", receiver_pks.iter());
+    ro.append_message(b"sender_pk", &sender_pk);
+
+    proofs::verify_multi_enc_trans(
+        ctx,
+        &mut ro,
+        transfer_data,
+        sender_pk,
+        receiver_pks,
+        &before_amount.join(),
+    )
 }
 
 /// Produce the payload of an secret to public amount transaction.
@@ -263,28 +540,27 @@ pub fn make_sec_to_pub_transfer_data<C: Curve, R: Rng>(
 /// - encryption of amount on sender account before transfer
 /// - secret to public amount transaction
 ///
-/// The return value is going to be `true` if verification succeeds and `false`
-/// if not.
-
+/// If verification fails the error identifies the part of the transfer data
+/// that was rejected.
 pub fn verify_sec_to_pub_transfer_data<C: Curve>(
     ctx: &GlobalContext<C>,
     pk: &PublicKey<C>,
     before_amount: &EncryptedAmount<C>,
     transfer_data: &SecToPubAmountTransferData<C>,
-) -> bool {
-    // Fixme: Put context into the random oracle.
+) -> Result<(), VerificationError> {
     let mut ro = RandomOracle::domain("SecToPubTransfer");
     ro.append_message(b"ctx", &ctx);
     ro.append_message(b"pk", &pk);
 
     // FIXME: Revise order of arguments in verify_sec_to_pub_trans to be more
     // consistent with the rest.
-    proofs::verify_sec_to_pub_trans(ctx, &mut ro, transfer_data, pk, &before_amount.join()).is_ok()
+    proofs::verify_sec_to_pub_trans(ctx, &mut ro, transfer_data, pk, &before_amount.join())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crypto_common::{serialize_deserialize, to_bytes, Version};
     use pairing::bls12_381::G1;
 
     // Test that decryption is the inverse to encryption.
@@ -310,6 +586,48 @@ mod tests {
         );
     }
 
+    // Test that decrypting many amounts with a table loaded from its file format
+    // gives back the original amounts, with their indices.
+    #[test]
+    fn test_decrypt_amounts() {
+        let mut csprng = thread_rng();
+        let context = GlobalContext::<G1>::generate(String::from("genesis_string"));
+
+        let sk = SecretKey::generate(context.elgamal_generator(), &mut csprng);
+        let pk = PublicKey::from(&sk);
+
+        let amounts = (0..5)
+            .map(|_| Amount::from(u64::from(csprng.gen::<u32>())))
+            .collect::<Vec<_>>();
+        let encrypted = amounts
+            .iter()
+            .enumerate()
+            .map(|(i, amount)| IndexedEncryptedAmount {
+                encrypted_chunks: encrypt_amount(&context, &pk, *amount, &mut csprng).0,
+                index:            EncryptedAmountIndex::from(i as u64 + 10),
+            })
+            .collect::<Vec<_>>();
+
+        let mut data = Vec::new();
+        write_bsgs_table(
+            context.encryption_in_exponent_generator(),
+            1 << 16,
+            &mut data,
+        )
+        .expect("Writing to a vector succeeds.");
+        let table = BabyStepGiantStepFile::<G1>::from_bytes(data).expect("Table is well-formed.");
+
+        let decrypted = decrypt_amounts(&table, &sk, &encrypted);
+        assert_eq!(decrypted.len(), amounts.len());
+        for (i, ((index, decrypted), amount)) in decrypted.iter().zip(amounts.iter()).enumerate() {
+            assert_eq!(index.index, i as u64 + 10, "Index of the amount changed.");
+            assert_eq!(
+                amount, decrypted,
+                "Decrypted amount differs from the original."
+            );
+        }
+    }
+
     #[test]
     fn test_scale() {
         let mut csprng = thread_rng();
@@ -396,8 +714,412 @@ mod tests {
                 &S_in_chunks.0,
                 &transfer_data
             ),
-            true
+            Ok(())
         );
+
+        // Replacing the remaining amount must be detected by the accounting proof.
+        let mut tampered = transfer_data;
+        tampered.remaining_amount =
+            encrypt_amount(&context, &pk_sender, Amount::from(s - a), &mut csprng).0;
+        assert_eq!(
+            verify_transfer_data(
+                &context,
+                &pk_receiver,
+                &pk_sender,
+                &S_in_chunks.0,
+                &tampered
+            ),
+            Err(VerificationError::AccountingProof)
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    fn test_make_and_verify_versioned_transfer_data() {
+        let mut csprng = thread_rng();
+        let sk_sender: SecretKey<G1> = SecretKey::generate_all(&mut csprng);
+        let pk_sender = PublicKey::from(&sk_sender);
+        let sk_receiver: SecretKey<G1> = SecretKey::generate(&pk_sender.generator, &mut csprng);
+        let pk_receiver = PublicKey::from(&sk_receiver);
+        let s: u64 = csprng.gen(); // amount on account.
+
+        let a = csprng.gen_range(0, s); // amount to send
+
+        let m = 4; // 2 chunks for each of the two amounts
+        let n = 32;
+        let nm = n * m;
+
+        let context = GlobalContext::<G1>::generate_size(String::from("genesis_string"), nm);
+        let S_in_chunks = encrypt_amount(&context, &pk_sender, Amount::from(s), &mut csprng);
+
+        let index = csprng.gen::<u64>().into(); // index is only important for on-chain stuff, not for proofs.
+        let input_amount = AggregatedDecryptedAmount {
+            agg_amount:           Amount::from(s),
+            agg_encrypted_amount: S_in_chunks.0.clone(),
+            agg_index:            index,
+        };
+        let aggregated = make_transfer_data_aggregated(
+            &context,
+            &pk_receiver,
+            &sk_sender,
+            &input_amount,
+            Amount::from(a),
+            &mut csprng,
+        )
+        .unwrap();
+        let aggregated = serialize_deserialize(&aggregated).expect("Serialization roundtrip.");
+        assert_eq!(
+            verify_versioned_transfer_data(
+                &context,
+                &pk_receiver,
+                &pk_sender,
+                &S_in_chunks.0,
+                &aggregated
+            ),
+            Ok(()),
+            "Aggregated transfer data should verify."
+        );
+
+        let separate = make_transfer_data(
+            &context,
+            &pk_receiver,
+            &sk_sender,
+            &input_amount,
+            Amount::from(a),
+            &mut csprng,
+        )
+        .unwrap();
+        assert!(
+            to_bytes(&aggregated.proof).len() < to_bytes(&separate.proof).len(),
+            "The aggregated proof should be smaller."
+        );
+        let separate = VersionedEncryptedAmountTransferData::from(separate);
+        assert_eq!(
+            verify_versioned_transfer_data(
+                &context,
+                &pk_receiver,
+                &pk_sender,
+                &S_in_chunks.0,
+                &separate
+            ),
+            Ok(()),
+            "Version 0 transfer data should verify."
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    fn test_make_and_verify_auditable_transfer_data() {
+        let mut csprng = thread_rng();
+        let sk_sender: SecretKey<G1> = SecretKey::generate_all(&mut csprng);
+        let pk_sender = PublicKey::from(&sk_sender);
+        let sk_receiver: SecretKey<G1> = SecretKey::generate(&pk_sender.generator, &mut csprng);
+        let pk_receiver = PublicKey::from(&sk_receiver);
+        let sk_auditor: SecretKey<G1> = SecretKey::generate(&pk_sender.generator, &mut csprng);
+        let pk_auditor = PublicKey::from(&sk_auditor);
+        let s: u64 = csprng.gen(); // amount on account.
+
+        let a = csprng.gen_range(0, s); // amount to send
+
+        let m = 4; // 2 chunks for each of the two amounts
+        let n = 32;
+        let nm = n * m;
+
+        let context = GlobalContext::<G1>::generate_size(String::from("genesis_string"), nm);
+        let S_in_chunks = encrypt_amount(&context, &pk_sender, Amount::from(s), &mut csprng);
+
+        let index = csprng.gen::<u64>().into(); // index is only important for on-chain stuff, not for proofs.
+        let input_amount = AggregatedDecryptedAmount {
+            agg_amount:           Amount::from(s),
+            agg_encrypted_amount: S_in_chunks.0.clone(),
+            agg_index:            index,
+        };
+        let audited = make_auditable_transfer_data(
+            &context,
+            &pk_receiver,
+            &sk_sender,
+            Some(&pk_auditor),
+            &input_amount,
+            Amount::from(a),
+            &mut csprng,
+        )
+        .unwrap();
+        let audited = serialize_deserialize(&audited).expect("Serialization roundtrip.");
+        assert_eq!(
+            verify_auditable_transfer_data(
+                &context,
+                &pk_receiver,
+                &pk_sender,
+                Some(&pk_auditor),
+                &S_in_chunks.0,
+                &audited
+            ),
+            Ok(()),
+            "Audited transfer data should verify."
+        );
+        assert_eq!(
+            verify_auditable_transfer_data(
+                &context,
+                &pk_receiver,
+                &pk_sender,
+                None,
+                &S_in_chunks.0,
+                &audited
+            ),
+            Err(VerificationError::AuditorEncryption),
+            "Audited transfer data should not verify without the auditor."
+        );
+        let pk_other = PublicKey::from(&SecretKey::generate(&pk_sender.generator, &mut csprng));
+        assert_eq!(
+            verify_auditable_transfer_data(
+                &context,
+                &pk_receiver,
+                &pk_sender,
+                Some(&pk_other),
+                &S_in_chunks.0,
+                &audited
+            ),
+            Err(VerificationError::AuditorEncryption),
+            "Audited transfer data should not verify for a different auditor."
+        );
+
+        let m = 1 << 16;
+        let table = BabyStepGiantStep::new(context.encryption_in_exponent_generator(), m);
+        assert_eq!(
+            decrypt_auditor_amount(&table, &sk_auditor, &audited.value),
+            Some(Amount::from(a)),
+            "The auditor should decrypt the transferred amount."
+        );
+        assert_eq!(
+            decrypt_amount(
+                &table,
+                &sk_receiver,
+                &audited.value.transfer.transfer_amount
+            ),
+            Amount::from(a),
+            "The receiver should decrypt the transferred amount."
+        );
+
+        // An encryption for the auditor of a different amount must be rejected.
+        let mut tampered = serialize_deserialize(&audited).expect("Serialization roundtrip.");
+        if let Some(auditor_amount) = tampered.value.auditor_amount.as_mut() {
+            auditor_amount.encrypted_amount =
+                encrypt_amount(&context, &pk_auditor, Amount::from(a + 1), &mut csprng).0;
+        }
+        assert_eq!(
+            verify_auditable_transfer_data(
+                &context,
+                &pk_receiver,
+                &pk_sender,
+                Some(&pk_auditor),
+                &S_in_chunks.0,
+                &tampered
+            ),
+            Err(VerificationError::AuditorEncryption),
+            "A wrong encryption for the auditor should not verify."
+        );
+
+        let unaudited = make_auditable_transfer_data(
+            &context,
+            &pk_receiver,
+            &sk_sender,
+            None,
+            &input_amount,
+            Amount::from(a),
+            &mut csprng,
+        )
+        .unwrap();
+        assert_eq!(
+            verify_auditable_transfer_data(
+                &context,
+                &pk_receiver,
+                &pk_sender,
+                None,
+                &S_in_chunks.0,
+                &unaudited
+            ),
+            Ok(()),
+            "Transfer data without an auditor should verify."
+        );
+        let mut unsupported = unaudited;
+        unsupported.version = Version { value: 1 };
+        assert_eq!(
+            verify_auditable_transfer_data(
+                &context,
+                &pk_receiver,
+                &pk_sender,
+                None,
+                &S_in_chunks.0,
+                &unsupported
+            ),
+            Err(VerificationError::UnsupportedVersion(Version { value: 1 }))
+        );
+        assert_eq!(
+            decrypt_auditor_amount(&table, &sk_auditor, &unsupported.value),
+            None
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_make_and_verify_multi_transfer_data() {
+        let mut csprng = thread_rng();
+        let context = GlobalContext::<G1>::generate(String::from("genesis_string"));
+        let sk_sender: SecretKey<G1> = SecretKey::generate_all(&mut csprng);
+        let pk_sender = PublicKey::from(&sk_sender);
+        let table = BabyStepGiantStep::new(context.encryption_in_exponent_generator(), 1 << 16);
+        // The standard context has generators for a range proof of 8 chunks,
+        // so 3 receivers. With 2 receivers the 6 chunks are padded to 8.
+        let max_receivers = max_multi_transfer_receivers(&context);
+        assert_eq!(max_receivers, 3);
+        for num_receivers in 1..=max_receivers as u64 {
+            let s: u64 = csprng.gen(); // amount on account.
+            let S_in_chunks = encrypt_amount(&context, &pk_sender, Amount::from(s), &mut csprng);
+            let input_amount = AggregatedDecryptedAmount {
+                agg_amount:           Amount::from(s),
+                agg_encrypted_amount: S_in_chunks.0.clone(),
+                agg_index:            csprng.gen::<u64>().into(),
+            };
+            let receivers: Vec<SecretKey<G1>> = (0..num_receivers)
+                .map(|_| SecretKey::generate(&pk_sender.generator, &mut csprng))
+                .collect();
+            let transfers: Vec<(PublicKey<G1>, Amount)> = receivers
+                .iter()
+                .map(|sk| {
+                    let a = csprng.gen_range(0, s / num_receivers);
+                    (PublicKey::from(sk), Amount::from(a))
+                })
+                .collect();
+            let pk_receivers: Vec<PublicKey<G1>> = transfers.iter().map(|(pk, _)| *pk).collect();
+
+            let data = make_multi_transfer_data(
+                &context,
+                &sk_sender,
+                &input_amount,
+                &transfers,
+                &mut csprng,
+            )
+            .expect("Producing the transfer should succeed.");
+            let data = serialize_deserialize(&data).expect("Serialization roundtrip.");
+            assert_eq!(
+                verify_multi_transfer_data(
+                    &context,
+                    &pk_receivers,
+                    &pk_sender,
+                    &S_in_chunks.0,
+                    &data
+                ),
+                Ok(()),
+                "Transfer data to {} receivers should verify.",
+                num_receivers
+            );
+
+            for (sk, (_, a), transfer_amount) in izip!(
+                receivers.iter(),
+                transfers.iter(),
+                data.transfer_amounts.iter()
+            ) {
+                assert_eq!(
+                    decrypt_amount(&table, sk, transfer_amount),
+                    *a,
+                    "The receiver should decrypt the amount sent to them."
+                );
+            }
+            let total: u64 = transfers.iter().map(|(_, a)| u64::from(*a)).sum();
+            assert_eq!(
+                decrypt_amount(&table, &sk_sender, &data.remaining_amount),
+                Amount::from(s - total),
+                "The sender should decrypt the remaining amount."
+            );
+
+            assert_eq!(
+                verify_multi_transfer_data(
+                    &context,
+                    &pk_receivers[1..],
+                    &pk_sender,
+                    &S_in_chunks.0,
+                    &data
+                ),
+                Err(VerificationError::MalformedReceivers {
+                    receivers:        num_receivers as usize - 1,
+                    transfer_amounts: num_receivers as usize,
+                }),
+                "Transfer data should not verify with a missing receiver."
+            );
+            let mut reversed = pk_receivers.clone();
+            reversed.reverse();
+            if num_receivers > 1 {
+                assert_eq!(
+                    verify_multi_transfer_data(
+                        &context,
+                        &reversed,
+                        &pk_sender,
+                        &S_in_chunks.0,
+                        &data
+                    ),
+                    Err(VerificationError::AccountingProof),
+                    "Transfer data should not verify with the receivers in a different order."
+                );
+            }
+        }
+
+        // Sending more than the input amount in total is not possible.
+        let input_amount = AggregatedDecryptedAmount {
+            agg_amount:           Amount::from(10),
+            agg_encrypted_amount: encrypt_amount_with_fixed_randomness(&context, Amount::from(10)),
+            agg_index:            EncryptedAmountAggIndex::from(0),
+        };
+        let pk_receiver = PublicKey::from(&SecretKey::generate(&pk_sender.generator, &mut csprng));
+        let transfers = [
+            (pk_receiver, Amount::from(6)),
+            (pk_receiver, Amount::from(5)),
+        ];
+        assert!(make_multi_transfer_data(
+            &context,
+            &sk_sender,
+            &input_amount,
+            &transfers,
+            &mut csprng
+        )
+        .is_none());
+
+        // Neither is sending to more receivers than the context supports.
+        let transfers = vec![(pk_receiver, Amount::from(1)); max_receivers + 1];
+        assert!(make_multi_transfer_data(
+            &context,
+            &sk_sender,
+            &input_amount,
+            &transfers,
+            &mut csprng
+        )
+        .is_none());
+        let transfers = vec![(pk_receiver, Amount::from(1)); max_receivers];
+        let data =
+            make_multi_transfer_data(&context, &sk_sender, &input_amount, &transfers, &mut csprng)
+                .expect("Producing the transfer should succeed.");
+        let mut too_many = data.clone();
+        too_many
+            .transfer_amounts
+            .push(data.transfer_amounts[0].clone());
+        assert_eq!(
+            verify_multi_transfer_data(
+                &context,
+                &vec![pk_receiver; max_receivers + 1],
+                &pk_sender,
+                &input_amount.agg_encrypted_amount,
+                &too_many
+            ),
+            Err(VerificationError::MalformedReceivers {
+                receivers:        max_receivers + 1,
+                transfer_amounts: max_receivers + 1,
+            }),
+            "Transfer data to too many receivers should not verify."
+        );
+
+        // A context with more generators supports more receivers.
+        let large_context =
+            GlobalContext::<G1>::generate_size(String::from("genesis_string"), 32 * 16);
+        assert_eq!(max_multi_transfer_receivers(&large_context), 7);
     }
 
     #[test]
@@ -435,7 +1157,7 @@ mod tests {
 
         assert_eq!(
             verify_sec_to_pub_transfer_data(&context, &pk_sender, &S_in_chunks.0, &transfer_data),
-            true
+            Ok(())
         );
     }
 }
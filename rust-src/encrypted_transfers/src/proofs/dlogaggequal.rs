@@ -48,7 +48,7 @@ impl<C: Curve> SigmaProtocol for DlogAndAggregateDlogsEqual<C> {
         csprng: &mut R,
     ) -> Option<(Self::CommitMessage, Self::ProverState)> {
         let rand_scalar_common = C::generate_non_zero_scalar(csprng);
-        let commit_dlog = self.dlog.coeff.mul_by_scalar(&rand_scalar_common);
+        let commit_dlog = self.dlog.coeff.mul_by_secret_scalar(&rand_scalar_common);
         let mut rands_vec = Vec::with_capacity(self.aggregate_dlogs.len());
         let mut point_vec = Vec::with_capacity(self.aggregate_dlogs.len());
         for aggregate_dlog in &self.aggregate_dlogs {
@@ -65,7 +65,7 @@ impl<C: Curve> SigmaProtocol for DlogAndAggregateDlogsEqual<C> {
                     rand = C::generate_non_zero_scalar(csprng);
                 }
                 // FIXME: Multiexponentiation would be useful in this case.
-                point = point.plus_point(&g.mul_by_scalar(&rand));
+                point = point.plus_point(&g.mul_by_secret_scalar(&rand));
 
                 if !first {
                     rands.push(rand); // Maybe not do this one for the first
@@ -37,8 +37,8 @@ impl<C: Curve> SigmaProtocol for DlogEqual<C> {
         csprng: &mut R,
     ) -> Option<(Self::CommitMessage, Self::ProverState)> {
         let rand_scalar = C::generate_non_zero_scalar(csprng);
-        let randomized_point_1 = self.dlog1.coeff.mul_by_scalar(&rand_scalar);
-        let randomized_point_2 = self.dlog2.coeff.mul_by_scalar(&rand_scalar);
+        let randomized_point_1 = self.dlog1.coeff.mul_by_secret_scalar(&rand_scalar);
+        let randomized_point_2 = self.dlog2.coeff.mul_by_secret_scalar(&rand_scalar);
         let commit = (randomized_point_1, randomized_point_2);
         Some((commit, rand_scalar))
     }
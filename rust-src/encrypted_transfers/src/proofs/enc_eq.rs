@@ -0,0 +1,282 @@
+//! This module provides the implementation of the `enc_eq` sigma protocol. It
+//! enables one to prove that two ElGamal encryptions in the exponent, under
+//! different public keys, are of the same value. More precisely, it proves
+//! knowledge of x, r_1 and r_2 such that
+//!
+//! c_{1,1} = g^{r_1}, c_{1,2} = h^x pk_1^{r_1}, and
+//! c_{2,1} = g^{r_2}, c_{2,2} = h^x pk_2^{r_2},
+//!
+//! where g is the common generator of the public keys pk_1 and pk_2, and h is
+//! the base used for encryption in the exponent.
+//!
+//! It is used to prove that the encryption of a transferred amount for an
+//! auditor is of the same amount as the encryption for the receiver, one chunk
+//! at a time.
+use crypto_common::*;
+use crypto_common_derive::*;
+use curve_arithmetic::{multiexp, multiexp_secret, Curve, Value};
+use elgamal::{Cipher, PublicKey, Randomness};
+use ff::Field;
+use id::sigma_protocols::common::*;
+use random_oracle::{Challenge, RandomOracle};
+
+/// The [EncEq] sigma protocol for a single pair of ciphers.
+pub struct EncEq<C: Curve> {
+    /// The first public key. The generator must be the same for both keys.
+    pub pk_1:     PublicKey<C>,
+    /// The second public key.
+    pub pk_2:     PublicKey<C>,
+    /// The base h used for encryption in the exponent.
+    pub h:        C,
+    /// Encryption of the value under `pk_1`.
+    pub cipher_1: Cipher<C>,
+    /// Encryption of the value under `pk_2`.
+    pub cipher_2: Cipher<C>,
+}
+
+/// Secret values which the [EncEq] proof talks about.
+pub struct EncEqSecret<C: Curve> {
+    /// The encrypted value x.
+    pub value:        Value<C>,
+    /// Randomness r_1 of the encryption under `pk_1`.
+    pub randomness_1: Randomness<C>,
+    /// Randomness r_2 of the encryption under `pk_2`.
+    pub randomness_2: Randomness<C>,
+}
+
+/// Witness for the [EncEq] protocol.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct EncEqWitness<C: Curve> {
+    /// The triple (s, t_1, t_2) where
+    /// * s = alpha - c x
+    /// * t_1 = R_1 - c r_1
+    /// * t_2 = R_2 - c r_2
+    /// where c is the challenge and alpha, R_1 and R_2 are prover chosen
+    /// random scalars.
+    pub witness: (C::Scalar, C::Scalar, C::Scalar),
+}
+
+/// The commit message of the [EncEq] protocol, one cipher for each public key,
+/// encrypting the same random value.
+#[derive(Debug, Serialize)]
+pub struct EncEqCommit<C: Curve> {
+    pub cipher_1: Cipher<C>,
+    pub cipher_2: Cipher<C>,
+}
+
+impl<C: Curve> EncEq<C> {
+    /// Encrypt `value` in the exponent under `pk` with the given randomness.
+    /// Both are secret, so the constant-time scalar multiplication is used.
+    fn encrypt_secret(
+        &self,
+        pk: &PublicKey<C>,
+        value: &C::Scalar,
+        randomness: &C::Scalar,
+    ) -> Cipher<C> {
+        Cipher(
+            pk.generator.mul_by_secret_scalar(randomness),
+            multiexp_secret(&[self.h, pk.key], &[*value, *randomness]),
+        )
+    }
+
+    /// Compute cipher^c * Enc_pk(value; randomness) for the extraction of the
+    /// commit message.
+    fn extract_cipher(
+        &self,
+        pk: &PublicKey<C>,
+        cipher: &Cipher<C>,
+        challenge: &C::Scalar,
+        value: &C::Scalar,
+        randomness: &C::Scalar,
+    ) -> Cipher<C> {
+        Cipher(
+            multiexp(&[cipher.0, pk.generator], &[*challenge, *randomness]),
+            multiexp(&[cipher.1, self.h, pk.key], &[
+                *challenge,
+                *value,
+                *randomness,
+            ]),
+        )
+    }
+}
+
+/// Compute alpha - c * x.
+fn response<F: Field>(alpha: &F, challenge: &F, secret: &F) -> F {
+    let mut s = *challenge;
+    s.mul_assign(secret);
+    s.negate();
+    s.add_assign(alpha);
+    s
+}
+
+impl<C: Curve> SigmaProtocol for EncEq<C> {
+    type CommitMessage = EncEqCommit<C>;
+    type ProtocolChallenge = C::Scalar;
+    // The triple (alpha, R_1, R_2).
+    type ProverState = (C::Scalar, C::Scalar, C::Scalar);
+    type ProverWitness = EncEqWitness<C>;
+    type SecretData = EncEqSecret<C>;
+
+    fn public(&self, ro: &mut RandomOracle) {
+        ro.append_message(b"pk_1", &self.pk_1);
+        ro.append_message(b"pk_2", &self.pk_2);
+        ro.append_message(b"h", &self.h);
+        ro.append_message(b"cipher_1", &self.cipher_1);
+        ro.append_message(b"cipher_2", &self.cipher_2)
+    }
+
+    fn get_challenge(&self, challenge: &Challenge) -> Self::ProtocolChallenge {
+        C::scalar_from_bytes(challenge)
+    }
+
+    fn commit_point<R: rand::Rng>(
+        &self,
+        csprng: &mut R,
+    ) -> Option<(Self::CommitMessage, Self::ProverState)> {
+        let alpha = C::generate_non_zero_scalar(csprng);
+        let rand_1 = C::generate_non_zero_scalar(csprng);
+        let rand_2 = C::generate_non_zero_scalar(csprng);
+        let commit = EncEqCommit {
+            cipher_1: self.encrypt_secret(&self.pk_1, &alpha, &rand_1),
+            cipher_2: self.encrypt_secret(&self.pk_2, &alpha, &rand_2),
+        };
+        Some((commit, (alpha, rand_1, rand_2)))
+    }
+
+    fn generate_witness(
+        &self,
+        secret: Self::SecretData,
+        state: Self::ProverState,
+        challenge: &Self::ProtocolChallenge,
+    ) -> Option<Self::ProverWitness> {
+        let (alpha, rand_1, rand_2) = state;
+        Some(EncEqWitness {
+            witness: (
+                response(&alpha, challenge, &*secret.value),
+                response(&rand_1, challenge, &*secret.randomness_1),
+                response(&rand_2, challenge, &*secret.randomness_2),
+            ),
+        })
+    }
+
+    fn extract_point(
+        &self,
+        challenge: &Self::ProtocolChallenge,
+        witness: &Self::ProverWitness,
+    ) -> Option<Self::CommitMessage> {
+        let (s, t_1, t_2) = &witness.witness;
+        Some(EncEqCommit {
+            cipher_1: self.extract_cipher(&self.pk_1, &self.cipher_1, challenge, s, t_1),
+            cipher_2: self.extract_cipher(&self.pk_2, &self.cipher_2, challenge, s, t_2),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use elgamal::SecretKey;
+    use pairing::bls12_381::G1;
+    use rand::Rng;
+
+    impl<C: Curve> EncEq<C> {
+        fn with_valid_data<R: Rng>(
+            rng: &mut R,
+            f: impl FnOnce(Self, <Self as SigmaProtocol>::SecretData, &mut R) -> (),
+        ) {
+            let sk_1 = SecretKey::generate_all(rng);
+            let pk_1 = PublicKey::from(&sk_1);
+            let sk_2 = SecretKey::generate(&pk_1.generator, rng);
+            let pk_2 = PublicKey::from(&sk_2);
+            let h = C::generate(rng);
+            let value = Value::<C>::from(u64::from(rng.gen::<u32>()));
+            let (cipher_1, randomness_1) =
+                pk_1.encrypt_exponent_rand_given_generator(&value, &h, rng);
+            let (cipher_2, randomness_2) =
+                pk_2.encrypt_exponent_rand_given_generator(&value, &h, rng);
+            let enc_eq = EncEq {
+                pk_1,
+                pk_2,
+                h,
+                cipher_1,
+                cipher_2,
+            };
+            let secret = EncEqSecret {
+                value,
+                randomness_1,
+                randomness_2,
+            };
+            f(enc_eq, secret, rng)
+        }
+    }
+
+    fn generate_challenge_prefix<R: rand::Rng>(csprng: &mut R) -> Vec<u8> {
+        // length of the challenge
+        let l = csprng.gen_range(0, 1000);
+        let mut challenge_prefix = vec![0; l];
+        for v in challenge_prefix.iter_mut() {
+            *v = csprng.gen();
+        }
+        challenge_prefix
+    }
+
+    #[test]
+    fn enc_eq_correctness() {
+        let mut rng = rand::thread_rng();
+        for _i in 1..20 {
+            EncEq::<G1>::with_valid_data(&mut rng, |enc_eq, secret, rng| {
+                let challenge_prefix = generate_challenge_prefix(rng);
+                let mut ro = RandomOracle::domain(&challenge_prefix);
+                let proof =
+                    prove(&mut ro.split(), &enc_eq, secret, rng).expect("Proving should succeed.");
+                assert!(
+                    verify(&mut ro, &enc_eq, &proof),
+                    "Verification of produced proof."
+                );
+            })
+        }
+    }
+
+    #[test]
+    fn enc_eq_soundness() {
+        let mut rng = rand::thread_rng();
+        for _i in 1..20 {
+            EncEq::<G1>::with_valid_data(&mut rng, |enc_eq, secret, rng| {
+                let challenge_prefix = generate_challenge_prefix(rng);
+                let ro = RandomOracle::domain(&challenge_prefix);
+                let proof =
+                    prove(&mut ro.split(), &enc_eq, secret, rng).expect("Proving should succeed.");
+
+                let mut wrong_ro = RandomOracle::domain(generate_challenge_prefix(rng));
+                assert!(!verify(&mut wrong_ro, &enc_eq, &proof));
+
+                let mut wrong_enc_eq = enc_eq;
+                {
+                    // An encryption of a different value under the second key.
+                    let tmp = wrong_enc_eq.cipher_2;
+                    let v = Value::<G1>::from(u64::from(rng.gen::<u32>()));
+                    wrong_enc_eq.cipher_2 = wrong_enc_eq.pk_2.encrypt_exponent_given_generator(
+                        &v,
+                        &wrong_enc_eq.h,
+                        rng,
+                    );
+                    assert!(!verify(&mut ro.split(), &wrong_enc_eq, &proof));
+                    wrong_enc_eq.cipher_2 = tmp;
+                }
+                {
+                    let tmp = wrong_enc_eq.pk_2;
+                    let sk = SecretKey::generate(&tmp.generator, rng);
+                    wrong_enc_eq.pk_2 = PublicKey::from(&sk);
+                    assert!(!verify(&mut ro.split(), &wrong_enc_eq, &proof));
+                    wrong_enc_eq.pk_2 = tmp;
+                }
+                {
+                    let tmp = wrong_enc_eq.h;
+                    wrong_enc_eq.h = G1::generate(rng);
+                    assert!(!verify(&mut ro.split(), &wrong_enc_eq, &proof));
+                    wrong_enc_eq.h = tmp;
+                }
+            })
+        }
+    }
+}
@@ -49,7 +49,7 @@
 use crate::types::CHUNK_SIZE;
 use crypto_common::*;
 use crypto_common_derive::*;
-use curve_arithmetic::{multiexp, Curve};
+use curve_arithmetic::{multiexp, multiexp_secret, Curve};
 use elgamal::ChunkSize;
 use ff::Field;
 use id::sigma_protocols::{
@@ -73,7 +73,7 @@ pub struct ElgDec<C: Curve> {
 }
 
 impl<C: Curve> ElgDec<C> {
-    fn public(&self, ro: &mut RandomOracle) {
+    pub(crate) fn public(&self, ro: &mut RandomOracle) {
         ro.append_message(b"public", &self.public);
         ro.extend_from(b"coeff", &self.coeff)
     }
@@ -154,7 +154,7 @@ pub struct EncTransState<C: Curve> {
 
 /// This function takes scalars x_1, ..., x_n and returns
 /// \sum_{i=1}^n 2^{(chunk_size)*(i-1)} (x_i)
-fn linear_combination_with_powers_of_two<C: Curve>(
+pub(crate) fn linear_combination_with_powers_of_two<C: Curve>(
     scalars: &[C::Scalar],
     chunk_size: ChunkSize,
 ) -> C::Scalar {
@@ -222,13 +222,13 @@ impl<C: Curve> SigmaProtocol for EncTrans<C> {
         }
         // For dlog and elcdec:
         let rand_scalar_common = C::generate_non_zero_scalar(csprng);
-        let commit_dlog = self.dlog.coeff.mul_by_scalar(&rand_scalar_common);
+        let commit_dlog = self.dlog.coeff.mul_by_secret_scalar(&rand_scalar_common);
         let rand_lin_a = linear_combination_with_powers_of_two::<C>(&Rs_a, CHUNK_SIZE);
         let rand_lin_s_prime = linear_combination_with_powers_of_two::<C>(&Rs_s_prime, CHUNK_SIZE);
         let mut rand_lin = rand_lin_a;
         rand_lin.add_assign(&rand_lin_s_prime);
         let rands = [rand_scalar_common, rand_lin];
-        let point = multiexp(&self.elg_dec.coeff, &rands);
+        let point = multiexp_secret(&self.elg_dec.coeff, &rands);
 
         let commit = EncTransCommit {
             dlog:    commit_dlog,
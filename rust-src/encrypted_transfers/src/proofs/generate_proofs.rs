@@ -1,14 +1,19 @@
 //! Implementation of high-level protocols for encrypted transfers, combining
 //! all the building parts into a single wrapper per operation.
 #![allow(non_snake_case)]
-use crate::{proofs::enc_trans::*, types::*};
+use crate::{
+    max_multi_transfer_receivers,
+    proofs::{enc_eq::*, enc_trans::*, multi_enc_trans::*},
+    types::*,
+};
 use bulletproofs::range_proof::{
     prove_given_scalars as bulletprove, verify_efficient,
     VerificationError as BulletproofVerificationError,
 };
-use crypto_common::types::Amount;
+use crypto_common::{types::Amount, Version};
 use curve_arithmetic::{Curve, Value};
 use elgamal::{Cipher, PublicKey, Randomness, SecretKey};
+use ff::Field;
 use id::{
     sigma_protocols::{com_eq::*, common::*, dlog::*},
     types::GlobalContext,
@@ -17,6 +22,7 @@ use pedersen_scheme::{Commitment, CommitmentKey, Randomness as PedersenRandomnes
 use rand::*;
 use random_oracle::*;
 use std::rc::Rc;
+use thiserror::Error;
 
 /// This function is an implementation of the genEncExpInfo documented in the
 /// bluepaper without bulletproof part.
@@ -141,6 +147,92 @@ pub fn gen_enc_trans_proof_info<C: Curve>(
     }
 }
 
+/// The encryptions of the chunks of the transfer amount and the remaining
+/// amount, together with the sigma protocol for proving that accounting is done
+/// correctly, and its secret. This is the part of the proof of an encrypted
+/// transfer that does not depend on how the chunks are shown to be small.
+struct EncTransInfo<C: Curve> {
+    protocol:       EncTrans<C>,
+    secret:         EncTransSecret<C>,
+    a_chunks:       Vec<u64>,
+    A:              Vec<Cipher<C>>,
+    A_rand:         Vec<Randomness<C>>,
+    s_prime_chunks: Vec<u64>,
+    S_prime:        Vec<Cipher<C>>,
+    S_prime_rand:   Vec<Randomness<C>>,
+}
+
+/// Encrypt the chunks of the transfer amount `a` and the remaining amount
+/// `s - a`, and construct the EncTrans protocol for them. See [gen_enc_trans]
+/// for the meaning of the arguments. Returns None if s < a.
+#[allow(clippy::too_many_arguments)]
+fn gen_enc_trans_info<C: Curve, R: Rng>(
+    context: &GlobalContext<C>,
+    pk_sender: &PublicKey<C>,
+    sk_sender: &SecretKey<C>,
+    pk_receiver: &PublicKey<C>,
+    S: &Cipher<C>,
+    s: Amount,
+    a: Amount,
+    csprng: &mut R,
+) -> Option<EncTransInfo<C>> {
+    if s < a {
+        return None;
+    }
+    let generator = context.encryption_in_exponent_generator();
+
+    let s_prime = u64::from(s) - u64::from(a);
+    let s_prime_chunks = CHUNK_SIZE.u64_to_chunks(s_prime);
+    let a_chunks = CHUNK_SIZE.u64_to_chunks(u64::from(a));
+    let A_enc_randomness = a_chunks
+        .iter()
+        .map(|&x| {
+            pk_receiver.encrypt_exponent_rand_given_generator(
+                &Value::<C>::from(x),
+                generator,
+                csprng,
+            )
+        })
+        .collect::<Vec<_>>();
+    let (A, A_rand): (Vec<_>, Vec<_>) = A_enc_randomness.iter().cloned().unzip();
+    let S_prime_enc_randomness = s_prime_chunks
+        .iter()
+        .map(|&x| {
+            pk_sender.encrypt_exponent_rand_given_generator(&Value::<C>::from(x), generator, csprng)
+        })
+        .collect::<Vec<_>>();
+    let (S_prime, S_prime_rand): (Vec<_>, Vec<_>) = S_prime_enc_randomness.iter().cloned().unzip();
+
+    let a_secrets = izip!(a_chunks.iter(), A_rand.iter())
+        .map(|(a_i, r_i)| ComEqSecret::<C> {
+            r: PedersenRandomness::from_u64(*a_i),
+            a: Randomness::to_value(r_i),
+        })
+        .collect();
+    let s_prime_secrets = izip!(s_prime_chunks.iter(), S_prime_rand.iter())
+        .map(|(a_i, r_i)| ComEqSecret::<C> {
+            r: PedersenRandomness::from_u64(*a_i),
+            a: Randomness::to_value(r_i),
+        })
+        .collect();
+    let protocol = gen_enc_trans_proof_info(&pk_sender, &pk_receiver, &S, &A, &S_prime, &generator);
+    let secret = EncTransSecret {
+        dlog_secret:     Rc::new(sk_sender.scalar),
+        encexp1_secrets: a_secrets,
+        encexp2_secrets: s_prime_secrets,
+    };
+    Some(EncTransInfo {
+        protocol,
+        secret,
+        a_chunks,
+        A,
+        A_rand,
+        s_prime_chunks,
+        S_prime,
+        S_prime_rand,
+    })
+}
+
 /// Implementation of genEncTrans in the bluepaper
 ///
 /// This function produces transfer data containing
@@ -190,56 +282,22 @@ pub fn gen_enc_trans<C: Curve, R: Rng>(
     a: Amount,
     csprng: &mut R,
 ) -> Option<EncryptedAmountTransferData<C>> {
-    if s < a {
-        return None;
-    }
-
     // For Bulletproofs
     // The number of generators is the total number of bits used to encode the
     // value, i.e., 64 since amounts are 64-bit values.
     let gens = context.bulletproof_generators().take(64);
     let generator = context.encryption_in_exponent_generator();
 
-    let s_prime = u64::from(s) - u64::from(a);
-    let s_prime_chunks = CHUNK_SIZE.u64_to_chunks(s_prime);
-    let a_chunks = CHUNK_SIZE.u64_to_chunks(u64::from(a));
-    let A_enc_randomness = a_chunks
-        .iter()
-        .map(|&x| {
-            pk_receiver.encrypt_exponent_rand_given_generator(
-                &Value::<C>::from(x),
-                generator,
-                csprng,
-            )
-        })
-        .collect::<Vec<_>>();
-    let (A, A_rand): (Vec<_>, Vec<_>) = A_enc_randomness.iter().cloned().unzip();
-    let S_prime_enc_randomness = s_prime_chunks
-        .iter()
-        .map(|&x| {
-            pk_sender.encrypt_exponent_rand_given_generator(&Value::<C>::from(x), generator, csprng)
-        })
-        .collect::<Vec<_>>();
-    let (S_prime, S_prime_rand): (Vec<_>, Vec<_>) = S_prime_enc_randomness.iter().cloned().unzip();
-
-    let a_secrets = izip!(a_chunks.iter(), A_rand.iter())
-        .map(|(a_i, r_i)| ComEqSecret::<C> {
-            r: PedersenRandomness::from_u64(*a_i),
-            a: Randomness::to_value(r_i),
-        })
-        .collect();
-    let s_prime_secrets = izip!(s_prime_chunks.iter(), S_prime_rand.iter())
-        .map(|(a_i, r_i)| ComEqSecret::<C> {
-            r: PedersenRandomness::from_u64(*a_i),
-            a: Randomness::to_value(r_i),
-        })
-        .collect();
-    let protocol = gen_enc_trans_proof_info(&pk_sender, &pk_receiver, &S, &A, &S_prime, &generator);
-    let secret = EncTransSecret {
-        dlog_secret:     Rc::new(sk_sender.scalar),
-        encexp1_secrets: a_secrets,
-        encexp2_secrets: s_prime_secrets,
-    };
+    let EncTransInfo {
+        protocol,
+        secret,
+        a_chunks,
+        A,
+        A_rand,
+        s_prime_chunks,
+        S_prime,
+        S_prime_rand,
+    } = gen_enc_trans_info(context, pk_sender, sk_sender, pk_receiver, S, s, a, csprng)?;
     let sigma_proof = prove(ro, &protocol, secret, csprng)?;
     let cmm_key_bulletproof_a = CommitmentKey {
         g: *generator,
@@ -306,6 +364,514 @@ pub fn gen_enc_trans<C: Curve, R: Rng>(
     })
 }
 
+/// The commitment key used for the chunk commitments in
+/// [AggregatedEncryptedAmountTransferProof]. The chunks of both the transfer
+/// and the remaining amount are committed to with the same key, which is what
+/// allows a single range proof for all of them.
+fn aggregated_chunk_commitment_key<C: Curve>(context: &GlobalContext<C>) -> CommitmentKey<C> {
+    CommitmentKey {
+        g: *context.encryption_in_exponent_generator(),
+        h: *context.elgamal_generator(),
+    }
+}
+
+/// The ComEq protocol linking the commitment to a chunk with the encryption of
+/// the chunk under the public key `pk`. If the cipher is (g^r, h^x pk^r) and
+/// the commitment is h^x k^rho, where k is the second component of the
+/// commitment key (see [aggregated_chunk_commitment_key]), then the protocol
+/// proves knowledge of -r and rho such that
+/// - g^{-r} = (g^r)^{-1}, and
+/// - pk^{-r} k^rho = (h^x k^rho) / (h^x pk^r).
+/// Combined with the proof of knowledge of the encrypted chunk from EncTrans
+/// this shows that the commitment and the cipher are for the same chunk.
+fn chunk_link_protocol<C: Curve>(
+    context: &GlobalContext<C>,
+    pk: &PublicKey<C>,
+    cipher: &Cipher<C>,
+    commitment: &Commitment<C>,
+) -> ComEq<C, C> {
+    ComEq {
+        commitment: Commitment(commitment.0.minus_point(&cipher.1)),
+        y:          cipher.0.inverse_point(),
+        cmm_key:    CommitmentKey {
+            g: pk.key,
+            h: *context.elgamal_generator(),
+        },
+        g:          pk.generator,
+    }
+}
+
+/// The number of chunks in the aggregated proof: the chunks of the transfer
+/// amount followed by the chunks of the remaining amount.
+fn num_aggregated_chunks() -> usize { 2 * (64 / usize::from(u8::from(CHUNK_SIZE))) }
+
+/// A variant of [gen_enc_trans] that produces the transfer data with an
+/// [AggregatedEncryptedAmountTransferProof], i.e., with a single range proof
+/// for the chunks of both the transfer and the remaining amount. The arguments
+/// are the same as for [gen_enc_trans], and the random oracle is expected to be
+/// in the same state.
+///
+/// Since the chunks are encrypted under different public keys they cannot be
+/// used directly as commitments in a single range proof. Instead the chunks are
+/// committed to with a common commitment key, and the sigma proof additionally
+/// proves that each commitment is to the same value as the corresponding
+/// encrypted chunk (see [chunk_link_protocol]).
+///
+/// The global context must contain at least 128 bulletproof generators.
+/// Returns None if s < a or if producing one of the proofs fails.
+#[allow(clippy::too_many_arguments)]
+pub fn gen_enc_trans_aggregated<C: Curve, R: Rng>(
+    context: &GlobalContext<C>,
+    ro: &mut RandomOracle,
+    pk_sender: &PublicKey<C>,
+    sk_sender: &SecretKey<C>,
+    pk_receiver: &PublicKey<C>,
+    index: EncryptedAmountAggIndex,
+    S: &Cipher<C>,
+    s: Amount,
+    a: Amount,
+    csprng: &mut R,
+) -> Option<VersionedEncryptedAmountTransferData<C>> {
+    let (data, _, _) = gen_enc_trans_aggregated_worker(
+        context,
+        ro,
+        pk_sender,
+        sk_sender,
+        pk_receiver,
+        index,
+        S,
+        s,
+        a,
+        csprng,
+    )?;
+    Some(data)
+}
+
+/// The worker for [gen_enc_trans_aggregated]. In addition to the transfer data
+/// it returns the chunks of the transfer amount and the randomness used to
+/// encrypt them, which are needed to prove further statements about the
+/// encrypted transfer amount.
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+fn gen_enc_trans_aggregated_worker<C: Curve, R: Rng>(
+    context: &GlobalContext<C>,
+    ro: &mut RandomOracle,
+    pk_sender: &PublicKey<C>,
+    sk_sender: &SecretKey<C>,
+    pk_receiver: &PublicKey<C>,
+    index: EncryptedAmountAggIndex,
+    S: &Cipher<C>,
+    s: Amount,
+    a: Amount,
+    csprng: &mut R,
+) -> Option<(
+    VersionedEncryptedAmountTransferData<C>,
+    Vec<u64>,
+    Vec<Randomness<C>>,
+)> {
+    let num_chunks = num_aggregated_chunks();
+    let gens = context
+        .bulletproof_generators()
+        .take(num_chunks * usize::from(u8::from(CHUNK_SIZE)));
+
+    let EncTransInfo {
+        protocol,
+        secret,
+        a_chunks,
+        A,
+        A_rand,
+        s_prime_chunks,
+        S_prime,
+        S_prime_rand,
+    } = gen_enc_trans_info(context, pk_sender, sk_sender, pk_receiver, S, s, a, csprng)?;
+
+    let cmm_key = aggregated_chunk_commitment_key(context);
+    let chunks: Vec<C::Scalar> = a_chunks
+        .iter()
+        .chain(s_prime_chunks.iter())
+        .copied()
+        .map(C::scalar_from_u64)
+        .collect();
+    let (chunk_commitments, chunk_randomness): (Vec<_>, Vec<_>) = chunks
+        .iter()
+        .map(|x| cmm_key.commit(&Value::<C>::new(*x), csprng))
+        .unzip();
+
+    let encrypted_chunks = izip!(A.iter(), A_rand.iter())
+        .map(|(cipher, r)| (pk_receiver, cipher, r))
+        .chain(
+            izip!(S_prime.iter(), S_prime_rand.iter()).map(|(cipher, r)| (pk_sender, cipher, r)),
+        );
+    let mut links = Vec::with_capacity(num_chunks);
+    let mut link_secrets = Vec::with_capacity(num_chunks);
+    for ((pk, cipher, r), cmm, rho) in izip!(
+        encrypted_chunks,
+        chunk_commitments.iter(),
+        chunk_randomness.iter()
+    ) {
+        links.push(chunk_link_protocol(context, pk, cipher, cmm));
+        let mut minus_r = *r.as_ref();
+        minus_r.negate();
+        link_secrets.push(ComEqSecret::<C> {
+            r: rho.clone(),
+            a: Value::new(minus_r),
+        });
+    }
+    let protocol = AndAdapter {
+        first:  protocol,
+        second: ReplicateAdapter { protocols: links },
+    };
+    let sigma_proof = prove(ro, &protocol, (secret, link_secrets), csprng)?;
+
+    let chunks_correct_encryption = bulletprove(
+        ro,
+        csprng,
+        u8::from(CHUNK_SIZE),
+        num_chunks as u8,
+        &chunks,
+        &gens,
+        &cmm_key,
+        &chunk_randomness,
+    )?;
+    let proof = AggregatedEncryptedAmountTransferProof {
+        accounting: sigma_proof,
+        chunk_commitments,
+        chunks_correct_encryption,
+    };
+
+    let data = VersionedEncryptedAmountTransferData {
+        remaining_amount: EncryptedAmount {
+            encryptions: [S_prime[0], S_prime[1]],
+        },
+        transfer_amount: EncryptedAmount {
+            encryptions: [A[0], A[1]],
+        },
+        index,
+        proof: VersionedEncryptedAmountTransferProof::V1(proof),
+    };
+    Some((data, a_chunks, A_rand))
+}
+
+/// The protocol proving that the chunks of `auditor_amount`, encrypted under
+/// `pk_auditor`, are of the same values as the chunks of `transfer_amount`,
+/// encrypted under `pk_receiver`.
+fn auditor_encryption_protocol<C: Curve>(
+    context: &GlobalContext<C>,
+    pk_receiver: &PublicKey<C>,
+    pk_auditor: &PublicKey<C>,
+    transfer_amount: &EncryptedAmount<C>,
+    auditor_amount: &EncryptedAmount<C>,
+) -> ReplicateAdapter<EncEq<C>> {
+    let protocols = izip!(
+        transfer_amount.encryptions.iter(),
+        auditor_amount.encryptions.iter()
+    )
+    .map(|(cipher_1, cipher_2)| EncEq {
+        pk_1:     *pk_receiver,
+        pk_2:     *pk_auditor,
+        h:        *context.encryption_in_exponent_generator(),
+        cipher_1: *cipher_1,
+        cipher_2: *cipher_2,
+    })
+    .collect();
+    ReplicateAdapter { protocols }
+}
+
+/// A variant of [gen_enc_trans_aggregated] that additionally encrypts the
+/// transfer amount under the public key of an auditor, if one is given. The
+/// encryption is accompanied by a proof that it is of the same amount as the
+/// encryption for the receiver, which is produced with the random oracle in the
+/// state after the proofs of the transfer. The remaining arguments are the same
+/// as for [gen_enc_trans_aggregated].
+///
+/// The auditor's public key must use the same generator as the receiver's.
+/// Returns None if s < a or if producing one of the proofs fails.
+#[allow(clippy::too_many_arguments)]
+pub fn gen_enc_trans_auditable<C: Curve, R: Rng>(
+    context: &GlobalContext<C>,
+    ro: &mut RandomOracle,
+    pk_sender: &PublicKey<C>,
+    sk_sender: &SecretKey<C>,
+    pk_receiver: &PublicKey<C>,
+    pk_auditor: Option<&PublicKey<C>>,
+    index: EncryptedAmountAggIndex,
+    S: &Cipher<C>,
+    s: Amount,
+    a: Amount,
+    csprng: &mut R,
+) -> Option<AuditableEncryptedAmountTransferData<C>> {
+    let (transfer, a_chunks, A_rand) = gen_enc_trans_aggregated_worker(
+        context,
+        ro,
+        pk_sender,
+        sk_sender,
+        pk_receiver,
+        index,
+        S,
+        s,
+        a,
+        csprng,
+    )?;
+    let pk_auditor = match pk_auditor {
+        Some(pk_auditor) => pk_auditor,
+        None => {
+            return Some(AuditableEncryptedAmountTransferData {
+                transfer,
+                auditor_amount: None,
+            })
+        }
+    };
+    if pk_auditor.generator != pk_receiver.generator {
+        return None;
+    }
+    let generator = context.encryption_in_exponent_generator();
+    let a_values: Vec<Value<C>> = a_chunks.iter().copied().map(Value::from).collect();
+    let (B, B_rand): (Vec<_>, Vec<_>) = pk_auditor
+        .encrypt_exponent_vec_given_generator(a_values.iter(), generator, csprng)
+        .into_iter()
+        .unzip();
+    let encrypted_amount = EncryptedAmount {
+        encryptions: [B[0], B[1]],
+    };
+    let protocol = auditor_encryption_protocol(
+        context,
+        pk_receiver,
+        pk_auditor,
+        &transfer.transfer_amount,
+        &encrypted_amount,
+    );
+    let secrets = izip!(a_values, A_rand, B_rand)
+        .map(|(value, randomness_1, randomness_2)| EncEqSecret {
+            value,
+            randomness_1,
+            randomness_2,
+        })
+        .collect();
+    let equal_chunks = prove(ro, &protocol, secrets, csprng)?;
+    Some(AuditableEncryptedAmountTransferData {
+        transfer,
+        auditor_amount: Some(AuditorEncryptedAmount {
+            encrypted_amount,
+            proof: AuditorEncryptionProof { equal_chunks },
+        }),
+    })
+}
+
+/// Encrypt the chunks of `x` under `pk`, in the exponent of the generator of
+/// the global context. Returns the chunks, their encryptions, and the
+/// randomness used for them.
+#[allow(clippy::type_complexity)]
+fn encrypt_in_chunks<C: Curve, R: Rng>(
+    context: &GlobalContext<C>,
+    pk: &PublicKey<C>,
+    x: u64,
+    csprng: &mut R,
+) -> (Vec<u64>, Vec<Cipher<C>>, Vec<Randomness<C>>) {
+    let chunks = CHUNK_SIZE.u64_to_chunks(x);
+    let values: Vec<Value<C>> = chunks.iter().copied().map(Value::from).collect();
+    let (ciphers, randomness) = pk
+        .encrypt_exponent_vec_given_generator(
+            values.iter(),
+            context.encryption_in_exponent_generator(),
+            csprng,
+        )
+        .into_iter()
+        .unzip();
+    (chunks, ciphers, randomness)
+}
+
+/// The secrets of the ComEq protocols produced by [gen_enc_exp_info] for the
+/// encryptions of the given chunks with the given randomness.
+fn enc_exp_secrets<C: Curve>(chunks: &[u64], randomness: &[Randomness<C>]) -> Vec<ComEqSecret<C>> {
+    izip!(chunks.iter(), randomness.iter())
+        .map(|(a_i, r_i)| ComEqSecret::<C> {
+            r: PedersenRandomness::from_u64(*a_i),
+            a: Randomness::to_value(r_i),
+        })
+        .collect()
+}
+
+/// The sigma protocol of a transfer to several receivers. It consists of the
+/// [MultiEncTrans] protocol for the accounting, and a [chunk_link_protocol] for
+/// each chunk commitment, linking it to the corresponding chunk of the transfer
+/// amounts (in the order of the receivers) and then the remaining amount.
+///
+/// The caller must ensure that there is a transfer amount for each receiver,
+/// and a commitment for each chunk.
+#[allow(clippy::too_many_arguments)]
+fn multi_enc_trans_protocol<C: Curve>(
+    context: &GlobalContext<C>,
+    pk_sender: &PublicKey<C>,
+    pk_receivers: &[PublicKey<C>],
+    S: &Cipher<C>,
+    transfer_amounts: &[EncryptedAmount<C>],
+    remaining_amount: &EncryptedAmount<C>,
+    chunk_commitments: &[Commitment<C>],
+) -> AndAdapter<MultiEncTrans<C>, ReplicateAdapter<ComEq<C, C>>> {
+    let h = context.encryption_in_exponent_generator();
+    let cmm_key = CommitmentKey {
+        g: pk_sender.generator,
+        h: *h,
+    };
+    let multi_enc_trans = MultiEncTrans {
+        dlog:    Dlog {
+            public: pk_sender.key,
+            coeff:  pk_sender.generator,
+        },
+        elg_dec: ElgDec {
+            public: S.1,
+            coeff:  [S.0, *h],
+        },
+        encexp1: izip!(pk_receivers.iter(), transfer_amounts.iter())
+            .map(|(pk, amount)| gen_enc_exp_info(&cmm_key, pk, &amount.encryptions))
+            .collect(),
+        encexp2: gen_enc_exp_info(&cmm_key, pk_sender, &remaining_amount.encryptions),
+    };
+    let encrypted_chunks = izip!(pk_receivers.iter(), transfer_amounts.iter())
+        .flat_map(|(pk, amount)| amount.encryptions.iter().map(move |cipher| (pk, cipher)))
+        .chain(
+            remaining_amount
+                .encryptions
+                .iter()
+                .map(|cipher| (pk_sender, cipher)),
+        );
+    let links = izip!(encrypted_chunks, chunk_commitments.iter())
+        .map(|((pk, cipher), cmm)| chunk_link_protocol(context, pk, cipher, cmm))
+        .collect();
+    AndAdapter {
+        first:  multi_enc_trans,
+        second: ReplicateAdapter { protocols: links },
+    }
+}
+
+/// Produce the data of an encrypted transfer to several receivers, with a
+/// single encryption of the remaining amount, and a single proof for all of
+/// them. `transfers` lists the public key of each receiver together with the
+/// amount to send to them. The remaining arguments are as for [gen_enc_trans],
+/// and the random oracle is expected to be in the state set up by
+/// `make_multi_transfer_data` in lib.rs.
+///
+/// The proof is constructed as in [gen_enc_trans_aggregated], with one range
+/// proof for the chunks of all the amounts. Since the range proof needs a
+/// power of two number of values, it is padded with commitments to 0 with
+/// randomness 0, which are not part of the transfer data. The global context
+/// must contain `32 * m` bulletproof generators, where `m` is the number of
+/// chunks, `2 * (transfers.len() + 1)`, rounded up to a power of two.
+///
+/// Returns None if there are no receivers or more than
+/// [max_multi_transfer_receivers], if the sum of the amounts to send exceeds
+/// s, or if producing one of the proofs fails.
+#[allow(clippy::too_many_arguments)]
+pub fn gen_multi_enc_trans<C: Curve, R: Rng>(
+    context: &GlobalContext<C>,
+    ro: &mut RandomOracle,
+    pk_sender: &PublicKey<C>,
+    sk_sender: &SecretKey<C>,
+    transfers: &[(PublicKey<C>, Amount)],
+    index: EncryptedAmountAggIndex,
+    S: &Cipher<C>,
+    s: Amount,
+    csprng: &mut R,
+) -> Option<MultiEncryptedAmountTransferData<C>> {
+    if transfers.is_empty() || transfers.len() > max_multi_transfer_receivers(context) {
+        return None;
+    }
+    let total = transfers
+        .iter()
+        .try_fold(0u64, |sum, (_, a)| sum.checked_add(u64::from(*a)))?;
+    let s_prime = u64::from(s).checked_sub(total)?;
+
+    let outputs: Vec<_> = transfers
+        .iter()
+        .map(|(pk, a)| encrypt_in_chunks(context, pk, u64::from(*a), csprng))
+        .collect();
+    let (s_prime_chunks, S_prime, S_prime_rand) =
+        encrypt_in_chunks(context, pk_sender, s_prime, csprng);
+
+    let cmm_key = aggregated_chunk_commitment_key(context);
+    let chunks: Vec<C::Scalar> = outputs
+        .iter()
+        .flat_map(|(a_chunks, _, _)| a_chunks.iter())
+        .chain(s_prime_chunks.iter())
+        .copied()
+        .map(C::scalar_from_u64)
+        .collect();
+    let (chunk_commitments, chunk_randomness): (Vec<_>, Vec<_>) = chunks
+        .iter()
+        .map(|x| cmm_key.commit(&Value::<C>::new(*x), csprng))
+        .unzip();
+
+    let pk_receivers: Vec<PublicKey<C>> = transfers.iter().map(|(pk, _)| *pk).collect();
+    let transfer_amounts: Vec<EncryptedAmount<C>> = outputs
+        .iter()
+        .map(|(_, A, _)| EncryptedAmount {
+            encryptions: [A[0], A[1]],
+        })
+        .collect();
+    let remaining_amount = EncryptedAmount {
+        encryptions: [S_prime[0], S_prime[1]],
+    };
+    let protocol = multi_enc_trans_protocol(
+        context,
+        pk_sender,
+        &pk_receivers,
+        S,
+        &transfer_amounts,
+        &remaining_amount,
+        &chunk_commitments,
+    );
+    let secret = MultiEncTransSecret {
+        dlog_secret:     Rc::new(sk_sender.scalar),
+        encexp1_secrets: outputs
+            .iter()
+            .map(|(a_chunks, _, A_rand)| enc_exp_secrets(a_chunks, A_rand))
+            .collect(),
+        encexp2_secrets: enc_exp_secrets(&s_prime_chunks, &S_prime_rand),
+    };
+    let encryption_randomness = outputs
+        .iter()
+        .flat_map(|(_, _, A_rand)| A_rand.iter())
+        .chain(S_prime_rand.iter());
+    let link_secrets = izip!(encryption_randomness, chunk_randomness.iter())
+        .map(|(r, rho)| {
+            let mut minus_r = *r.as_ref();
+            minus_r.negate();
+            ComEqSecret::<C> {
+                r: rho.clone(),
+                a: Value::new(minus_r),
+            }
+        })
+        .collect();
+    let accounting = prove(ro, &protocol, (secret, link_secrets), csprng)?;
+
+    let num_values = chunks.len().next_power_of_two();
+    let mut padded_chunks = chunks;
+    padded_chunks.resize(num_values, C::Scalar::zero());
+    let mut padded_randomness = chunk_randomness;
+    padded_randomness.resize(num_values, PedersenRandomness::new(C::Scalar::zero()));
+    let chunks_correct_encryption = bulletprove(
+        ro,
+        csprng,
+        u8::from(CHUNK_SIZE),
+        num_values as u8,
+        &padded_chunks,
+        context.bulletproof_generators(),
+        &cmm_key,
+        &padded_randomness,
+    )?;
+
+    Some(MultiEncryptedAmountTransferData {
+        remaining_amount,
+        transfer_amounts,
+        index,
+        proof: MultiEncryptedAmountTransferProof {
+            accounting,
+            chunk_commitments,
+            chunks_correct_encryption,
+        },
+    })
+}
+
 /// Implementation of genSecToPubTrans in the bluepaper
 ///
 /// For sending secret balance to public balance.
@@ -451,15 +1017,47 @@ pub fn gen_sec_to_pub_trans<C: Curve, R: Rng>(
     })
 }
 
-/// The verifier does three checks. In case verification fails, it can be useful
-/// to know which of the checks led to failure.
-#[derive(Debug, PartialEq)]
+/// The reason why verification of an encrypted transfer, or a secret to public
+/// transfer, failed. Each variant identifies the part of the transfer data that
+/// was rejected.
+#[derive(Debug, PartialEq, Error)]
 pub enum VerificationError {
-    SigmaProofError,
-    /// The first check failed (see function below for what this means)
-    FirstBulletproofError(BulletproofVerificationError),
-    /// The second check failed.
-    SecondBulletproofError(BulletproofVerificationError),
+    /// The accounting proof failed. This is the sigma proof that the sender
+    /// knows the secret key and the chunks of the encrypted amounts, and that
+    /// the transfer amount and the remaining amount add up to the input
+    /// amount.
+    #[error("The accounting proof is invalid.")]
+    AccountingProof,
+    /// The range proof showing that the chunks of the transfer amount are small
+    /// enough failed.
+    #[error("The range proof of the transfer amount is invalid: {0:?}.")]
+    TransferAmountRangeProof(BulletproofVerificationError),
+    /// The range proof showing that the chunks of the remaining amount are
+    /// small enough failed.
+    #[error("The range proof of the remaining amount is invalid: {0:?}.")]
+    RemainingAmountRangeProof(BulletproofVerificationError),
+    /// The range proof of an aggregated transfer proof, covering the chunks of
+    /// both the transfer and the remaining amount, failed.
+    #[error("The aggregated range proof of the transfer and remaining amount is invalid: {0:?}.")]
+    AggregatedRangeProof(BulletproofVerificationError),
+    /// The proof does not have the expected number of chunk commitments.
+    #[error("Expected commitments to {expected} chunks, but got {actual}.")]
+    MalformedChunks { expected: usize, actual: usize },
+    /// The encryption of the transfer amount for the auditor is missing when
+    /// an auditor is designated, present when none is, or its proof failed.
+    #[error("The encryption of the transfer amount for the auditor is invalid.")]
+    AuditorEncryption,
+    /// The version of the transfer data is not supported.
+    #[error("Unsupported version {0} of the transfer data.")]
+    UnsupportedVersion(Version),
+    /// The number of transfer amounts of a transfer to several receivers does
+    /// not match the number of receivers, or the number of receivers is not
+    /// between 1 and [max_multi_transfer_receivers] for the global context.
+    #[error("Transfer with {transfer_amounts} transfer amounts to {receivers} receivers.")]
+    MalformedReceivers {
+        receivers:        usize,
+        transfer_amounts: usize,
+    },
 }
 
 /// This function is for verifying that an encrypted transfer
@@ -493,6 +1091,30 @@ pub fn verify_enc_trans<C: Curve>(
     pk_sender: &PublicKey<C>,
     pk_receiver: &PublicKey<C>,
     S: &Cipher<C>,
+) -> Result<(), VerificationError> {
+    verify_enc_trans_v0(
+        context,
+        ro,
+        &transaction.transfer_amount,
+        &transaction.remaining_amount,
+        &transaction.proof,
+        pk_sender,
+        pk_receiver,
+        S,
+    )
+}
+
+/// The worker for [verify_enc_trans], checking the given amounts and proof.
+#[allow(clippy::too_many_arguments)]
+fn verify_enc_trans_v0<C: Curve>(
+    context: &GlobalContext<C>,
+    ro: &mut RandomOracle,
+    transfer_amount: &EncryptedAmount<C>,
+    remaining_amount: &EncryptedAmount<C>,
+    proof: &EncryptedAmountTransferProof<C>,
+    pk_sender: &PublicKey<C>,
+    pk_receiver: &PublicKey<C>,
+    S: &Cipher<C>,
 ) -> Result<(), VerificationError> {
     let generator = context.encryption_in_exponent_generator();
     // For Bulletproofs
@@ -502,17 +1124,17 @@ pub fn verify_enc_trans<C: Curve>(
         &pk_sender,
         &pk_receiver,
         &S,
-        &transaction.transfer_amount.as_ref(),
-        &transaction.remaining_amount.as_ref(),
+        &transfer_amount.as_ref(),
+        &remaining_amount.as_ref(),
         &generator,
     );
-    if !verify(ro, &protocol, &transaction.proof.accounting) {
-        return Err(VerificationError::SigmaProofError);
+    if !verify(ro, &protocol, &proof.accounting) {
+        return Err(VerificationError::AccountingProof);
     }
     let num_chunks = 64 / usize::from(u8::from(CHUNK_SIZE));
     let commitments_a = {
         let mut commitments_a = Vec::with_capacity(num_chunks);
-        let ta: &[Cipher<C>; 2] = transaction.transfer_amount.as_ref();
+        let ta: &[Cipher<C>; 2] = transfer_amount.as_ref();
         for cipher in ta {
             commitments_a.push(Commitment(cipher.1));
         }
@@ -521,7 +1143,7 @@ pub fn verify_enc_trans<C: Curve>(
 
     let commitments_s_prime = {
         let mut commitments_s_prime = Vec::with_capacity(num_chunks);
-        let ts_prime: &[Cipher<C>; 2] = transaction.remaining_amount.as_ref();
+        let ts_prime: &[Cipher<C>; 2] = remaining_amount.as_ref();
         for cipher in ts_prime {
             commitments_s_prime.push(Commitment(cipher.1));
         }
@@ -541,27 +1163,198 @@ pub fn verify_enc_trans<C: Curve>(
         ro,
         u8::from(CHUNK_SIZE),
         &commitments_a,
-        &transaction.proof.transfer_amount_correct_encryption,
+        &proof.transfer_amount_correct_encryption,
         &gens,
         &cmm_key_bulletproof_a,
     );
     if let Err(err) = first_bulletproof {
-        return Err(VerificationError::FirstBulletproofError(err));
+        return Err(VerificationError::TransferAmountRangeProof(err));
     }
     let second_bulletproof = verify_efficient(
         ro,
         u8::from(CHUNK_SIZE),
         &commitments_s_prime,
-        &transaction.proof.remaining_amount_correct_encryption,
+        &proof.remaining_amount_correct_encryption,
         &gens,
         &cmm_key_bulletproof_s_prime,
     );
     if let Err(err) = second_bulletproof {
-        return Err(VerificationError::SecondBulletproofError(err));
+        return Err(VerificationError::RemainingAmountRangeProof(err));
     }
     Ok(())
 }
 
+/// Verify transfer data with a proof in any of the supported versions. For
+/// [VersionedEncryptedAmountTransferProof::V0] this is the same as
+/// [verify_enc_trans]. For [VersionedEncryptedAmountTransferProof::V1] it
+/// checks the proof produced by [gen_enc_trans_aggregated]. The arguments are
+/// the same as for [verify_enc_trans], and the random oracle is expected to be
+/// in the same state.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_enc_trans_versioned<C: Curve>(
+    context: &GlobalContext<C>,
+    ro: &mut RandomOracle,
+    transaction: &VersionedEncryptedAmountTransferData<C>,
+    pk_sender: &PublicKey<C>,
+    pk_receiver: &PublicKey<C>,
+    S: &Cipher<C>,
+) -> Result<(), VerificationError> {
+    let proof = match &transaction.proof {
+        VersionedEncryptedAmountTransferProof::V0(proof) => {
+            return verify_enc_trans_v0(
+                context,
+                ro,
+                &transaction.transfer_amount,
+                &transaction.remaining_amount,
+                proof,
+                pk_sender,
+                pk_receiver,
+                S,
+            )
+        }
+        VersionedEncryptedAmountTransferProof::V1(proof) => proof,
+    };
+    let num_chunks = num_aggregated_chunks();
+    if proof.chunk_commitments.len() != num_chunks {
+        return Err(VerificationError::MalformedChunks {
+            expected: num_chunks,
+            actual:   proof.chunk_commitments.len(),
+        });
+    }
+    let generator = context.encryption_in_exponent_generator();
+    let A: &[Cipher<C>; 2] = transaction.transfer_amount.as_ref();
+    let S_prime: &[Cipher<C>; 2] = transaction.remaining_amount.as_ref();
+    let encrypted_chunks = A
+        .iter()
+        .map(|cipher| (pk_receiver, cipher))
+        .chain(S_prime.iter().map(|cipher| (pk_sender, cipher)));
+    let links = izip!(encrypted_chunks, proof.chunk_commitments.iter())
+        .map(|((pk, cipher), cmm)| chunk_link_protocol(context, pk, cipher, cmm))
+        .collect();
+    let protocol = AndAdapter {
+        first:  gen_enc_trans_proof_info(&pk_sender, &pk_receiver, &S, A, S_prime, &generator),
+        second: ReplicateAdapter { protocols: links },
+    };
+    if !verify(ro, &protocol, &proof.accounting) {
+        return Err(VerificationError::AccountingProof);
+    }
+
+    let gens = context.bulletproof_generators();
+    verify_efficient(
+        ro,
+        u8::from(CHUNK_SIZE),
+        &proof.chunk_commitments,
+        &proof.chunks_correct_encryption,
+        &gens,
+        &aggregated_chunk_commitment_key(context),
+    )
+    .map_err(VerificationError::AggregatedRangeProof)
+}
+
+/// Verify transfer data produced by [gen_enc_trans_auditable]. This checks the
+/// transfer as [verify_enc_trans_versioned] does, and then the encryption of
+/// the transfer amount for the auditor. The data must contain an encryption for
+/// the auditor if and only if `pk_auditor` is given. The remaining arguments
+/// are the same as for [verify_enc_trans_versioned].
+#[allow(clippy::too_many_arguments)]
+pub fn verify_enc_trans_auditable<C: Curve>(
+    context: &GlobalContext<C>,
+    ro: &mut RandomOracle,
+    transaction: &AuditableEncryptedAmountTransferData<C>,
+    pk_sender: &PublicKey<C>,
+    pk_receiver: &PublicKey<C>,
+    pk_auditor: Option<&PublicKey<C>>,
+    S: &Cipher<C>,
+) -> Result<(), VerificationError> {
+    verify_enc_trans_versioned(
+        context,
+        ro,
+        &transaction.transfer,
+        pk_sender,
+        pk_receiver,
+        S,
+    )?;
+    match (pk_auditor, &transaction.auditor_amount) {
+        (None, None) => Ok(()),
+        (Some(pk_auditor), Some(auditor_amount)) => {
+            if pk_auditor.generator != pk_receiver.generator {
+                return Err(VerificationError::AuditorEncryption);
+            }
+            let protocol = auditor_encryption_protocol(
+                context,
+                pk_receiver,
+                pk_auditor,
+                &transaction.transfer.transfer_amount,
+                &auditor_amount.encrypted_amount,
+            );
+            if verify(ro, &protocol, &auditor_amount.proof.equal_chunks) {
+                Ok(())
+            } else {
+                Err(VerificationError::AuditorEncryption)
+            }
+        }
+        _ => Err(VerificationError::AuditorEncryption),
+    }
+}
+
+/// Verify the data of a transfer to several receivers produced by
+/// [gen_multi_enc_trans]. The receivers' public keys must be in the same order
+/// as the transfer amounts. The remaining arguments are as for
+/// [verify_enc_trans], and the random oracle is expected to be in the same
+/// state as for [gen_multi_enc_trans].
+pub fn verify_multi_enc_trans<C: Curve>(
+    context: &GlobalContext<C>,
+    ro: &mut RandomOracle,
+    transaction: &MultiEncryptedAmountTransferData<C>,
+    pk_sender: &PublicKey<C>,
+    pk_receivers: &[PublicKey<C>],
+    S: &Cipher<C>,
+) -> Result<(), VerificationError> {
+    let num_receivers = pk_receivers.len();
+    if num_receivers == 0
+        || num_receivers > max_multi_transfer_receivers(context)
+        || transaction.transfer_amounts.len() != num_receivers
+    {
+        return Err(VerificationError::MalformedReceivers {
+            receivers:        num_receivers,
+            transfer_amounts: transaction.transfer_amounts.len(),
+        });
+    }
+    let proof = &transaction.proof;
+    let num_chunks = (num_receivers + 1) * transaction.remaining_amount.encryptions.len();
+    if proof.chunk_commitments.len() != num_chunks {
+        return Err(VerificationError::MalformedChunks {
+            expected: num_chunks,
+            actual:   proof.chunk_commitments.len(),
+        });
+    }
+    let protocol = multi_enc_trans_protocol(
+        context,
+        pk_sender,
+        pk_receivers,
+        S,
+        &transaction.transfer_amounts,
+        &transaction.remaining_amount,
+        &proof.chunk_commitments,
+    );
+    if !verify(ro, &protocol, &proof.accounting) {
+        return Err(VerificationError::AccountingProof);
+    }
+
+    // The padding added by the prover, commitments to 0 with randomness 0.
+    let mut commitments = proof.chunk_commitments.clone();
+    commitments.resize(num_chunks.next_power_of_two(), Commitment(C::zero_point()));
+    verify_efficient(
+        ro,
+        u8::from(CHUNK_SIZE),
+        &commitments,
+        &proof.chunks_correct_encryption,
+        context.bulletproof_generators(),
+        &aggregated_chunk_commitment_key(context),
+    )
+    .map_err(VerificationError::AggregatedRangeProof)
+}
+
 /// This function is for verifying that an encrypted transfer
 /// has been done corretly.
 /// The arguments are
@@ -609,7 +1402,7 @@ pub fn verify_sec_to_pub_trans<C: Curve>(
         &generator,
     );
     if !verify(ro, &protocol, &transaction.proof.accounting) {
-        return Err(VerificationError::SigmaProofError);
+        return Err(VerificationError::AccountingProof);
     }
 
     let num_chunks = 64 / usize::from(u8::from(CHUNK_SIZE));
@@ -640,8 +1433,7 @@ pub fn verify_sec_to_pub_trans<C: Curve>(
         &cmm_key_bulletproof_s_prime,
     );
     if let Err(err) = bulletproof {
-        // Maybe introduce yet another error type for this type of transaction
-        return Err(VerificationError::SecondBulletproofError(err));
+        return Err(VerificationError::RemainingAmountRangeProof(err));
     }
     Ok(())
 }
@@ -716,6 +1508,76 @@ mod test {
         )
     }
 
+    #[allow(non_snake_case)]
+    #[test]
+    fn test_enc_trans_aggregated() {
+        let mut csprng = thread_rng();
+        let sk_sender: SecretKey<G1> = SecretKey::generate_all(&mut csprng);
+        let pk_sender = PublicKey::from(&sk_sender);
+        let sk_receiver: SecretKey<G1> = SecretKey::generate(&pk_sender.generator, &mut csprng);
+        let pk_receiver = PublicKey::from(&sk_receiver);
+        let s = csprng.gen::<u64>(); // amount on account.
+
+        let a = csprng.gen_range(0, s); // amount to send
+
+        let m = 4; // 2 chunks for each of the two amounts
+        let n = 32;
+        let nm = n * m;
+
+        let context = GlobalContext::<SomeCurve>::generate_size(String::from("genesis_string"), nm);
+        let generator = context.encryption_in_exponent_generator(); // h
+        let s_value = Value::from(s);
+        let S = pk_sender.encrypt_exponent_given_generator(&s_value, generator, &mut csprng);
+
+        let challenge_prefix = generate_challenge_prefix(&mut csprng);
+        let ro = RandomOracle::domain(&challenge_prefix);
+
+        let index = csprng.gen::<u64>().into(); // index is only important for on-chain stuff, not for proofs.
+        let transaction = gen_enc_trans_aggregated(
+            &context,
+            &mut ro.split(),
+            &pk_sender,
+            &sk_sender,
+            &pk_receiver,
+            index,
+            &S,
+            Amount::from(s),
+            Amount::from(a),
+            &mut csprng,
+        )
+        .expect("Could not produce proof.");
+
+        assert_eq!(
+            verify_enc_trans_versioned(
+                &context,
+                &mut ro.split(),
+                &transaction,
+                &pk_sender,
+                &pk_receiver,
+                &S,
+            ),
+            Ok(())
+        );
+
+        // Swapping the commitments to the two chunks of the transfer amount
+        // must break the link between commitments and encryptions.
+        let mut swapped = transaction;
+        if let VersionedEncryptedAmountTransferProof::V1(proof) = &mut swapped.proof {
+            proof.chunk_commitments.swap(0, 1);
+        }
+        assert_eq!(
+            verify_enc_trans_versioned(
+                &context,
+                &mut ro.split(),
+                &swapped,
+                &pk_sender,
+                &pk_receiver,
+                &S,
+            ),
+            Err(VerificationError::AccountingProof)
+        );
+    }
+
     #[allow(non_snake_case)]
     #[test]
     fn test_sec_to_pub() {
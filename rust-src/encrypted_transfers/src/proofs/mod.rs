@@ -4,8 +4,12 @@
 // they are not used.
 mod dlogaggequal;
 mod dlogeq;
+mod enc_eq;
 mod enc_trans;
 mod generate_proofs;
+mod multi_enc_trans;
 
+pub use enc_eq::*;
 pub use enc_trans::*;
 pub use generate_proofs::*;
+pub use multi_enc_trans::*;
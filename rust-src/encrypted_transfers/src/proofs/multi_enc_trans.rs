@@ -0,0 +1,428 @@
+//! This module provides the sigma protocol used for encrypted transfers to
+//! several receivers at once. It is the [EncTrans] protocol generalized to a
+//! list of transfer amounts, each encrypted under the public key of its own
+//! receiver.
+//!
+//! It enables one to prove knowledge of a secret key `sk`, exponent `s`, the
+//! chunks a_{k,1}, ..., a_{k,t} of the amount sent to the k'th receiver for
+//! k in {1, .., n}, the chunks s_1', ..., s_(t')' of the remaining amount, and
+//! the randomness of all their encryptions, such that pk_sender = g^sk,
+//! S_2 = S_1^sk h^s, the chunks of the k'th amount are encrypted under
+//! pk_receiver_k, the chunks of the remaining amount are encrypted under
+//! pk_sender, and
+//!
+//! s = \sum_{k=1}^n \sum_{j=1}^t 2^{(chunk_size)*(j-1)} a_{k,j}
+//!     +\sum_{j=1}^(t') 2^{(chunk_size)*(j-1)} s_j'.
+//!
+//! The knowledge of the encrypted chunks is proved with ComEq protocols as in
+//! [EncTrans], and the linear relation is checked in the same way, except that
+//! the powers of two start again for each receiver.
+//!
+//! [EncTrans]: super::EncTrans
+
+#![allow(non_snake_case)]
+use crate::{proofs::enc_trans::*, types::CHUNK_SIZE};
+use crypto_common::*;
+use crypto_common_derive::*;
+use curve_arithmetic::{multiexp, multiexp_secret, Curve};
+use ff::Field;
+use id::sigma_protocols::{
+    com_eq::{ComEq, ComEqSecret, CommittedPoints, Witness as ComEqWitness},
+    common::*,
+    dlog::*,
+};
+use pedersen_scheme::{Randomness as PedersenRandomness, Value};
+use random_oracle::{Challenge, RandomOracle};
+use std::rc::Rc;
+
+/// The [MultiEncTrans] sigma protocol.
+pub struct MultiEncTrans<C: Curve> {
+    pub dlog:    Dlog<C>,
+    /// elg_dec contains the publicly known values S_1, S_2 and h
+    pub elg_dec: ElgDec<C>,
+    /// For each receiver, the ComEq's for the chunks of the amount sent to
+    /// them, i.e., the a_{k,j}'s, g, h, and pk_receiver_k.
+    pub encexp1: Vec<Vec<ComEq<C, C>>>,
+    /// encexp2 contains the publicly known values s'_{i,j}'s, g, h, pk_sender
+    pub encexp2: Vec<ComEq<C, C>>,
+}
+
+/// Witness for the [MultiEncTrans] protocol. As for
+/// [EncTransWitness](super::EncTransWitness) the witnesses of elg_dec are
+/// determined by the ones below.
+#[derive(Debug, Serialize, Clone)]
+pub struct MultiEncTransWitness<C: Curve> {
+    /// The common witness for both dlog and elc-dec
+    witness_common:  C::Scalar,
+    /// For EncExp/ComEq's involving a_{k,j}, for all the receivers in order.
+    #[size_length = 4]
+    witness_encexp1: Vec<ComEqWitness<C>>,
+    /// For EncExp/ComEq's involving s_i'
+    #[size_length = 4]
+    witness_encexp2: Vec<ComEqWitness<C>>,
+}
+
+/// Secret values which the [MultiEncTrans] proof talks about.
+pub struct MultiEncTransSecret<C: Curve> {
+    /// dlog_secret contains the secret key `sk`
+    pub dlog_secret:     Rc<C::Scalar>,
+    /// ComEq secrets for encexp1, for each receiver.
+    pub encexp1_secrets: Vec<Vec<ComEqSecret<C>>>,
+    /// ComeEq secrets for encexp2
+    pub encexp2_secrets: Vec<ComEqSecret<C>>,
+}
+
+/// The commit message of the [MultiEncTrans] protocol.
+#[derive(Debug, Serialize)]
+pub struct MultiEncTransCommit<C: Curve> {
+    /// Commitmessage for dlog
+    dlog:    C,
+    /// Commitmessage for elg_dec
+    elg_dec: C,
+    /// Commitmessages for EncExp/ComEq's involving a_{k,j}
+    #[size_length = 4]
+    encexp1: Vec<CommittedPoints<C, C>>,
+    /// Commitmessages for EncExp/ComEq's involving s_i'
+    #[size_length = 4]
+    encexp2: Vec<CommittedPoints<C, C>>,
+}
+
+/// The state of the prover after committing.
+#[derive(Debug, Serialize)]
+pub struct MultiEncTransState<C: Curve> {
+    /// Randomness used for dlog
+    dlog:    C::Scalar,
+    /// Randomness used for EncExp/ComEq's involving a_{k,j}
+    #[size_length = 4]
+    encexp1: Vec<(Value<C>, PedersenRandomness<C>)>,
+    /// Randomness used for EncExp/ComEq's involving s_i'
+    #[size_length = 4]
+    encexp2: Vec<(Value<C>, PedersenRandomness<C>)>,
+}
+
+impl<C: Curve> MultiEncTrans<C> {
+    /// Total number of chunks of the transfer amounts.
+    fn num_encexp1(&self) -> usize { self.encexp1.iter().map(Vec::len).sum() }
+
+    /// Given one scalar for each ComEq in encexp1 and encexp2 (in order),
+    /// compute the linear combination that the secret s of elg_dec is of the
+    /// chunks. The caller ensures that the lengths match.
+    fn linear_combination(&self, encexp1: &[C::Scalar], encexp2: &[C::Scalar]) -> C::Scalar {
+        let mut sum = linear_combination_with_powers_of_two::<C>(encexp2, CHUNK_SIZE);
+        let mut start = 0;
+        for receiver in self.encexp1.iter() {
+            let end = start + receiver.len();
+            sum.add_assign(&linear_combination_with_powers_of_two::<C>(
+                &encexp1[start..end],
+                CHUNK_SIZE,
+            ));
+            start = end;
+        }
+        sum
+    }
+}
+
+impl<C: Curve> SigmaProtocol for MultiEncTrans<C> {
+    type CommitMessage = MultiEncTransCommit<C>;
+    type ProtocolChallenge = C::Scalar;
+    type ProverState = MultiEncTransState<C>;
+    type ProverWitness = MultiEncTransWitness<C>;
+    type SecretData = MultiEncTransSecret<C>;
+
+    fn public(&self, ro: &mut RandomOracle) {
+        self.elg_dec.public(ro);
+        // The number of chunks of each transfer amount is part of the statement.
+        for receiver in self.encexp1.iter() {
+            ro.append_message(b"num_chunks", &(receiver.len() as u64));
+            receiver.iter().for_each(|p| p.public(ro));
+        }
+        self.encexp2.iter().for_each(|p| p.public(ro));
+        self.dlog.public(ro)
+    }
+
+    fn get_challenge(&self, challenge: &Challenge) -> Self::ProtocolChallenge {
+        C::scalar_from_bytes(challenge)
+    }
+
+    fn commit_point<R: rand::Rng>(
+        &self,
+        csprng: &mut R,
+    ) -> Option<(Self::CommitMessage, Self::ProverState)> {
+        let mut commit_encexp_1 = Vec::with_capacity(self.num_encexp1());
+        let mut rands_encexp_1 = Vec::with_capacity(self.num_encexp1());
+        let mut commit_encexp_2 = Vec::with_capacity(self.encexp2.len());
+        let mut rands_encexp_2 = Vec::with_capacity(self.encexp2.len());
+        let mut Rs_a = Vec::with_capacity(self.num_encexp1());
+        let mut Rs_s_prime = Vec::with_capacity(self.encexp2.len());
+        for comeq in self.encexp1.iter().flatten() {
+            let (comm_point, (alpha, R_i)) = comeq.commit_point(csprng)?;
+            Rs_a.push(*R_i);
+            rands_encexp_1.push((alpha, R_i));
+            commit_encexp_1.push(comm_point);
+        }
+        for comeq in self.encexp2.iter() {
+            let (comm_point, (alpha, R_s)) = comeq.commit_point(csprng)?;
+            Rs_s_prime.push(*R_s);
+            rands_encexp_2.push((alpha, R_s));
+            commit_encexp_2.push(comm_point);
+        }
+        // For dlog and elg-dec:
+        let rand_scalar_common = C::generate_non_zero_scalar(csprng);
+        let commit_dlog = self.dlog.coeff.mul_by_secret_scalar(&rand_scalar_common);
+        let rand_lin = self.linear_combination(&Rs_a, &Rs_s_prime);
+        let point = multiexp_secret(&self.elg_dec.coeff, &[rand_scalar_common, rand_lin]);
+
+        let commit = MultiEncTransCommit {
+            dlog:    commit_dlog,
+            elg_dec: point,
+            encexp1: commit_encexp_1,
+            encexp2: commit_encexp_2,
+        };
+        let rand = MultiEncTransState {
+            dlog:    rand_scalar_common,
+            encexp1: rands_encexp_1,
+            encexp2: rands_encexp_2,
+        };
+        Some((commit, rand))
+    }
+
+    fn generate_witness(
+        &self,
+        secret: Self::SecretData,
+        state: Self::ProverState,
+        challenge: &Self::ProtocolChallenge,
+    ) -> Option<Self::ProverWitness> {
+        let mut witness_common = *challenge;
+        witness_common.mul_assign(&secret.dlog_secret);
+        witness_common.negate();
+        witness_common.add_assign(&state.dlog);
+        if secret.encexp1_secrets.len() != self.encexp1.len()
+            || izip!(secret.encexp1_secrets.iter(), self.encexp1.iter())
+                .any(|(secrets, receiver)| secrets.len() != receiver.len())
+            || secret.encexp2_secrets.len() != self.encexp2.len()
+        {
+            return None;
+        }
+        let mut witness_encexp1 = Vec::with_capacity(state.encexp1.len());
+        for (sec, encexp1, comeq1) in izip!(
+            secret.encexp1_secrets.into_iter().flatten(),
+            state.encexp1.into_iter(),
+            self.encexp1.iter().flatten()
+        ) {
+            witness_encexp1.push(comeq1.generate_witness(sec, encexp1, challenge)?);
+        }
+        let mut witness_encexp2 = Vec::with_capacity(state.encexp2.len());
+        for (sec, encexp2, comeq2) in izip!(
+            secret.encexp2_secrets.into_iter(),
+            state.encexp2.into_iter(),
+            self.encexp2.iter()
+        ) {
+            witness_encexp2.push(comeq2.generate_witness(sec, encexp2, challenge)?);
+        }
+
+        Some(MultiEncTransWitness {
+            witness_common,
+            witness_encexp1,
+            witness_encexp2,
+        })
+    }
+
+    fn extract_point(
+        &self,
+        challenge: &Self::ProtocolChallenge,
+        witness: &Self::ProverWitness,
+    ) -> Option<Self::CommitMessage> {
+        if self.num_encexp1() != witness.witness_encexp1.len() {
+            return None;
+        }
+        if self.encexp2.len() != witness.witness_encexp2.len() {
+            return None;
+        }
+        let mut commit_encexp1 = Vec::with_capacity(witness.witness_encexp1.len());
+        let mut commit_encexp2 = Vec::with_capacity(witness.witness_encexp2.len());
+        let mut w_a_vec = Vec::with_capacity(witness.witness_encexp1.len());
+        let mut w_s_prime_vec = Vec::with_capacity(witness.witness_encexp2.len());
+        for (comeq, witness) in izip!(self.encexp1.iter().flatten(), &witness.witness_encexp1) {
+            commit_encexp1.push(comeq.extract_point(challenge, witness)?);
+            w_a_vec.push(witness.witness.1);
+        }
+        for (comeq, witness) in izip!(&self.encexp2, &witness.witness_encexp2) {
+            commit_encexp2.push(comeq.extract_point(challenge, witness)?);
+            w_s_prime_vec.push(witness.witness.1);
+        }
+
+        // For dlog and elg-dec:
+        let w_lin = self.linear_combination(&w_a_vec, &w_s_prime_vec);
+        let dlog_point = multiexp(&[self.dlog.coeff, self.dlog.public], &[
+            witness.witness_common,
+            *challenge,
+        ]);
+        let elg_dec_point = multiexp(
+            &[
+                self.elg_dec.public,
+                self.elg_dec.coeff[0],
+                self.elg_dec.coeff[1],
+            ],
+            &[*challenge, witness.witness_common, w_lin],
+        );
+        Some(MultiEncTransCommit {
+            dlog:    dlog_point,
+            elg_dec: elg_dec_point,
+            encexp1: commit_encexp1,
+            encexp2: commit_encexp2,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use elgamal::{PublicKey, Randomness, SecretKey};
+    use pairing::bls12_381::G1;
+    use pedersen_scheme::{Commitment, CommitmentKey};
+    use rand::Rng;
+
+    /// Encrypt the chunks of `x` under `pk`, and construct the ComEq's and
+    /// their secrets for them.
+    fn encrypt_chunks<C: Curve, R: Rng>(
+        pk: &PublicKey<C>,
+        h: &C,
+        x: u64,
+        rng: &mut R,
+    ) -> (Vec<ComEq<C, C>>, Vec<ComEqSecret<C>>) {
+        CHUNK_SIZE
+            .u64_to_chunks(x)
+            .into_iter()
+            .map(|chunk| {
+                let (cipher, r): (_, Randomness<C>) =
+                    pk.encrypt_exponent_rand_given_generator(&Value::from(chunk), h, rng);
+                let com_eq = ComEq {
+                    commitment: Commitment(cipher.1),
+                    y:          cipher.0,
+                    cmm_key:    CommitmentKey { g: pk.key, h: *h },
+                    g:          pk.generator,
+                };
+                let secret = ComEqSecret {
+                    r: PedersenRandomness::from_u64(chunk),
+                    a: Randomness::to_value(&r),
+                };
+                (com_eq, secret)
+            })
+            .unzip()
+    }
+
+    impl<C: Curve> MultiEncTrans<C> {
+        fn with_valid_data<R: Rng>(
+            rng: &mut R,
+            f: impl FnOnce(Self, <Self as SigmaProtocol>::SecretData, &mut R) -> (),
+        ) {
+            let sk = SecretKey::generate_all(rng);
+            let pk = PublicKey::from(&sk);
+            let h = C::generate(rng);
+            let s = u64::from(rng.gen::<u32>()) << 8;
+            let S = pk.encrypt_exponent_given_generator(&Value::from(s), &h, rng);
+
+            let num_receivers = rng.gen_range(1, 5);
+            let mut remaining = s;
+            let mut encexp1 = Vec::with_capacity(num_receivers);
+            let mut encexp1_secrets = Vec::with_capacity(num_receivers);
+            for _ in 0..num_receivers {
+                let a = rng.gen_range(0, remaining / 2 + 1);
+                remaining -= a;
+                let sk_receiver = SecretKey::generate(&pk.generator, rng);
+                let pk_receiver = PublicKey::from(&sk_receiver);
+                let (com_eqs, secrets) = encrypt_chunks(&pk_receiver, &h, a, rng);
+                encexp1.push(com_eqs);
+                encexp1_secrets.push(secrets);
+            }
+            let (encexp2, encexp2_secrets) = encrypt_chunks(&pk, &h, remaining, rng);
+
+            let multi_enc_trans = MultiEncTrans {
+                dlog: Dlog {
+                    public: pk.key,
+                    coeff:  sk.generator,
+                },
+                elg_dec: ElgDec {
+                    public: S.1,
+                    coeff:  [S.0, h],
+                },
+                encexp1,
+                encexp2,
+            };
+            let secret = MultiEncTransSecret {
+                dlog_secret: Rc::new(sk.scalar),
+                encexp1_secrets,
+                encexp2_secrets,
+            };
+            f(multi_enc_trans, secret, rng)
+        }
+    }
+
+    fn generate_challenge_prefix<R: rand::Rng>(csprng: &mut R) -> Vec<u8> {
+        // length of the challenge
+        let l = csprng.gen_range(0, 1000);
+        let mut challenge_prefix = vec![0; l];
+        for v in challenge_prefix.iter_mut() {
+            *v = csprng.gen();
+        }
+        challenge_prefix
+    }
+
+    #[test]
+    fn multi_enc_trans_correctness() {
+        let mut rng = rand::thread_rng();
+        for _i in 1..20 {
+            MultiEncTrans::<G1>::with_valid_data(&mut rng, |protocol, secret, rng| {
+                let challenge_prefix = generate_challenge_prefix(rng);
+                let mut ro = RandomOracle::domain(&challenge_prefix);
+                let proof = prove(&mut ro.split(), &protocol, secret, rng)
+                    .expect("Proving should succeed.");
+                assert!(
+                    verify(&mut ro, &protocol, &proof),
+                    "Verification of produced proof."
+                );
+            })
+        }
+    }
+
+    #[test]
+    fn multi_enc_trans_soundness() {
+        let mut rng = rand::thread_rng();
+        for _i in 1..20 {
+            MultiEncTrans::<G1>::with_valid_data(&mut rng, |protocol, secret, rng| {
+                let challenge_prefix = generate_challenge_prefix(rng);
+                let ro = RandomOracle::domain(&challenge_prefix);
+                let proof = prove(&mut ro.split(), &protocol, secret, rng)
+                    .expect("Proving should succeed.");
+
+                let mut wrong_ro = RandomOracle::domain(generate_challenge_prefix(rng));
+                assert!(!verify(&mut wrong_ro, &protocol, &proof));
+
+                let mut wrong_protocol = protocol;
+                {
+                    // An input amount that does not match the outputs.
+                    let tmp = wrong_protocol.elg_dec.public;
+                    wrong_protocol.elg_dec.public = G1::generate(rng);
+                    assert!(!verify(&mut ro.split(), &wrong_protocol, &proof));
+                    wrong_protocol.elg_dec.public = tmp;
+                }
+                if wrong_protocol.encexp1.len() > 1 {
+                    // Moving a chunk to a different receiver changes its weight.
+                    let last = wrong_protocol.encexp1.len() - 1;
+                    let chunk = wrong_protocol.encexp1[last].pop().unwrap();
+                    wrong_protocol.encexp1[0].push(chunk);
+                    assert!(!verify(&mut ro.split(), &wrong_protocol, &proof));
+                    let chunk = wrong_protocol.encexp1[0].pop().unwrap();
+                    wrong_protocol.encexp1[last].push(chunk);
+                }
+                for receiver in 0..wrong_protocol.encexp1.len() {
+                    let tmp = wrong_protocol.encexp1[receiver][0].cmm_key;
+                    wrong_protocol.encexp1[receiver][0].cmm_key = CommitmentKey::generate(rng);
+                    assert!(!verify(&mut ro.split(), &wrong_protocol, &proof));
+                    wrong_protocol.encexp1[receiver][0].cmm_key = tmp;
+                }
+                assert!(verify(&mut ro.split(), &wrong_protocol, &proof));
+            })
+        }
+    }
+}
@@ -4,7 +4,8 @@ use bulletproofs::range_proof::*;
 use crypto_common::{types::Amount, *};
 use curve_arithmetic::*;
 use elgamal::*;
-use id::sigma_protocols::common::*;
+use id::sigma_protocols::{com_eq, common::*};
+use pedersen_scheme::Commitment;
 
 #[derive(Clone, Copy, Serialize, SerdeSerialize, SerdeDeserialize, Debug, Default)]
 #[serde(transparent)]
@@ -87,6 +88,90 @@ pub struct EncryptedAmountTransferData<C: Curve> {
     pub proof:            EncryptedAmountTransferProof<C>,
 }
 
+/// Data that will go onto an encrypted amount transfer, with the proof in one
+/// of the supported versions. See [VersionedEncryptedAmountTransferProof].
+#[derive(Serialize, SerdeSerialize, SerdeDeserialize, Clone, Debug)]
+#[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
+#[serde(rename_all = "camelCase")]
+pub struct VersionedEncryptedAmountTransferData<C: Curve> {
+    /// Encryption of the remaining amount.
+    pub remaining_amount: EncryptedAmount<C>,
+    /// Amount that will be sent.
+    pub transfer_amount:  EncryptedAmount<C>,
+    /// The index such that the encrypted amount used in the transfer represents
+    /// the aggregate of all encrypted amounts with indices < `index` existing
+    /// on the account at the time. New encrypted amounts can only add new
+    /// indices.
+    pub index:            EncryptedAmountAggIndex,
+    /// A collection of all the proofs.
+    pub proof:            VersionedEncryptedAmountTransferProof<C>,
+}
+
+impl<C: Curve> From<EncryptedAmountTransferData<C>> for VersionedEncryptedAmountTransferData<C> {
+    fn from(data: EncryptedAmountTransferData<C>) -> Self {
+        VersionedEncryptedAmountTransferData {
+            remaining_amount: data.remaining_amount,
+            transfer_amount:  data.transfer_amount,
+            index:            data.index,
+            proof:            VersionedEncryptedAmountTransferProof::V0(data.proof),
+        }
+    }
+}
+
+/// Encryption of the transfer amount under the public key of an auditor,
+/// together with a proof that it is the same amount that is sent to the
+/// receiver.
+#[derive(Serialize, SerdeSerialize, SerdeDeserialize, Clone, Debug)]
+#[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
+#[serde(rename_all = "camelCase")]
+pub struct AuditorEncryptedAmount<C: Curve> {
+    /// The transfer amount encrypted under the auditor's public key, in the
+    /// same chunks as the encryption for the receiver.
+    pub encrypted_amount: EncryptedAmount<C>,
+    /// Proof that the chunks encrypt the same values as the chunks of the
+    /// transfer amount.
+    pub proof:            AuditorEncryptionProof<C>,
+}
+
+/// Data that will go onto an encrypted amount transfer that can optionally be
+/// audited. If an auditor is designated, the transfer amount is additionally
+/// encrypted under the auditor's public key, so that the auditor can see the
+/// amount without knowing the secret key of the receiver.
+#[derive(Serialize, SerdeSerialize, SerdeDeserialize, Clone, Debug)]
+#[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
+#[serde(rename_all = "camelCase")]
+pub struct AuditableEncryptedAmountTransferData<C: Curve> {
+    /// The transfer data, as for a transfer without an auditor.
+    pub transfer:       VersionedEncryptedAmountTransferData<C>,
+    /// The encryption of the transfer amount for the auditor, if there is one.
+    pub auditor_amount: Option<AuditorEncryptedAmount<C>>,
+}
+
+/// The version of [AuditableEncryptedAmountTransferData].
+pub const AUDITABLE_TRANSFER_DATA_VERSION: Version = VERSION_0;
+
+/// Data that will go onto an encrypted amount transfer to several receivers at
+/// once. There is a single encryption of the remaining amount, and a single
+/// proof for all the transfer amounts.
+#[derive(Serialize, SerdeSerialize, SerdeDeserialize, Clone, Debug)]
+#[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
+#[serde(rename_all = "camelCase")]
+pub struct MultiEncryptedAmountTransferData<C: Curve> {
+    /// Encryption of the remaining amount.
+    pub remaining_amount: EncryptedAmount<C>,
+    /// Amounts that will be sent, one for each receiver, in the order of the
+    /// receivers.
+    #[size_length = 1]
+    pub transfer_amounts: Vec<EncryptedAmount<C>>,
+    /// The index such that the encrypted amount used in the transfer represents
+    /// the aggregate of all encrypted amounts with indices < `index` existing
+    /// on the account at the time. New encrypted amounts can only add new
+    /// indices.
+    pub index:            EncryptedAmountAggIndex,
+    /// A collection of all the proofs.
+    pub proof:            MultiEncryptedAmountTransferProof<C>,
+}
+
 /// Data that will go onto a secret to public amount transfer.
 #[derive(Serialize, SerdeSerialize, SerdeDeserialize, Debug, Clone)]
 #[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
@@ -148,3 +233,105 @@ pub struct SecToPubAmountTransferProof<C: Curve> {
     /// small enough.
     pub remaining_amount_correct_encryption: RangeProof<C>,
 }
+
+/// Proof that an encrypted transfer data is well-formed, using a single range
+/// proof for all the chunks of both the transfer and the remaining amount.
+///
+/// The chunks of the two amounts are encrypted under different public keys, so
+/// they cannot be used directly as commitments in the same range proof.
+/// Instead the proof contains Pedersen commitments to all the chunks under a
+/// common key, together with a proof that each commitment is to the same value
+/// as the corresponding encrypted chunk. This proof is part of the same sigma
+/// proof as the accounting.
+///
+/// For chunks of 32 bits this is around 380 bytes smaller than
+/// [EncryptedAmountTransferProof].
+#[derive(Serialize, SerdeBase16Serialize, Clone, Debug)]
+pub struct AggregatedEncryptedAmountTransferProof<C: Curve> {
+    /// Proof that accounting is done correctly, i.e., remaining + transfer is
+    /// the original amount, and that the chunk commitments are to the
+    /// encrypted chunks.
+    pub accounting:
+        SigmaProof<AndWitness<proofs::EncTransWitness<C>, ReplicateWitness<com_eq::Witness<C>>>>,
+    /// Commitments to the chunks of the transfer amount, followed by the chunks
+    /// of the remaining amount.
+    #[size_length = 1]
+    pub chunk_commitments:         Vec<Commitment<C>>,
+    /// Proof that all the chunks are small enough.
+    pub chunks_correct_encryption: RangeProof<C>,
+}
+
+/// Proof that a [MultiEncryptedAmountTransferData] is well-formed. As in
+/// [AggregatedEncryptedAmountTransferProof] the chunks are committed to under a
+/// common key, so that a single range proof covers the chunks of all the
+/// amounts.
+#[derive(Serialize, SerdeBase16Serialize, Clone, Debug)]
+pub struct MultiEncryptedAmountTransferProof<C: Curve> {
+    /// Proof that accounting is done correctly, i.e., the sum of the remaining
+    /// amount and all the transfer amounts is the original amount, and that
+    /// the chunk commitments are to the encrypted chunks.
+    pub accounting: SigmaProof<
+        AndWitness<proofs::MultiEncTransWitness<C>, ReplicateWitness<com_eq::Witness<C>>>,
+    >,
+    /// Commitments to the chunks of each of the transfer amounts in order,
+    /// followed by the chunks of the remaining amount.
+    #[size_length = 1]
+    pub chunk_commitments:         Vec<Commitment<C>>,
+    /// Proof that all the chunks are small enough. If the number of chunks is
+    /// not a power of two the range proof also includes commitments to 0 with
+    /// randomness 0, up to the next power of two.
+    pub chunks_correct_encryption: RangeProof<C>,
+}
+
+/// Proof that an encryption of the transfer amount for an auditor is of the
+/// same amount as the encryption for the receiver.
+#[derive(Serialize, SerdeBase16Serialize, Clone, Debug)]
+pub struct AuditorEncryptionProof<C: Curve> {
+    /// Proof that each chunk of the auditor's encryption is of the same value
+    /// as the corresponding chunk of the transfer amount.
+    pub equal_chunks: SigmaProof<ReplicateWitness<proofs::EncEqWitness<C>>>,
+}
+
+/// The version of [EncryptedAmountTransferProof].
+pub const ENCRYPTED_AMOUNT_TRANSFER_PROOF_VERSION_0: Version = VERSION_0;
+/// The version of [AggregatedEncryptedAmountTransferProof].
+pub const ENCRYPTED_AMOUNT_TRANSFER_PROOF_VERSION_1: Version = Version { value: 1 };
+
+/// Proof that an encrypted transfer data is well-formed, in one of the
+/// supported formats. The binary serialization is the version, followed by the
+/// proof.
+#[derive(SerdeBase16Serialize, Clone, Debug)]
+pub enum VersionedEncryptedAmountTransferProof<C: Curve> {
+    /// Separate range proofs for the transfer and the remaining amount.
+    V0(EncryptedAmountTransferProof<C>),
+    /// A single range proof for the chunks of both amounts.
+    V1(AggregatedEncryptedAmountTransferProof<C>),
+}
+
+impl<C: Curve> Serial for VersionedEncryptedAmountTransferProof<C> {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        match self {
+            VersionedEncryptedAmountTransferProof::V0(proof) => {
+                out.put(&ENCRYPTED_AMOUNT_TRANSFER_PROOF_VERSION_0);
+                out.put(proof);
+            }
+            VersionedEncryptedAmountTransferProof::V1(proof) => {
+                out.put(&ENCRYPTED_AMOUNT_TRANSFER_PROOF_VERSION_1);
+                out.put(proof);
+            }
+        }
+    }
+}
+
+impl<C: Curve> Deserial for VersionedEncryptedAmountTransferProof<C> {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let version: Version = source.get()?;
+        if version == ENCRYPTED_AMOUNT_TRANSFER_PROOF_VERSION_0 {
+            Ok(VersionedEncryptedAmountTransferProof::V0(source.get()?))
+        } else if version == ENCRYPTED_AMOUNT_TRANSFER_PROOF_VERSION_1 {
+            Ok(VersionedEncryptedAmountTransferProof::V1(source.get()?))
+        } else {
+            anyhow::bail!("Unsupported encrypted transfer proof version {}.", version)
+        }
+    }
+}
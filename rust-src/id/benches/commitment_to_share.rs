@@ -1,7 +1,6 @@
 use criterion::*;
-use curve_arithmetic::*;
+use curve_arithmetic::{bls12_381::G1, *};
 use id::utils::commitment_to_share;
-use pairing::bls12_381::G1;
 use pedersen_scheme::Commitment;
 use rand::*;
 
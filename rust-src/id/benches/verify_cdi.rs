@@ -3,6 +3,7 @@ use crypto_common::{
     types::{KeyIndex, KeyPair, TransactionTime},
     *,
 };
+use curve_arithmetic::bls12_381::{Bls12, G1};
 use dodis_yampolskiy_prf as prf;
 use ed25519_dalek as ed25519;
 use either::Either::Left;
@@ -16,7 +17,6 @@ use id::{
     secret_sharing::Threshold,
     types::*,
 };
-use pairing::bls12_381::{Bls12, G1};
 use rand::*;
 use std::{collections::BTreeMap, convert::TryFrom, io::Cursor};
 
@@ -156,6 +156,7 @@ fn bench_parts(c: &mut Criterion) {
             tree.insert(AttributeTag::from(8u8), AttributeKind::from(31));
             tree
         },
+        statements: Vec::new(),
         _phantom: Default::default(),
     };
 
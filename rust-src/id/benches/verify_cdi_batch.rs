@@ -0,0 +1,163 @@
+use criterion::*;
+use crypto_common::types::{KeyIndex, KeyPair, TransactionTime};
+use curve_arithmetic::bls12_381::{Bls12, G1};
+use dodis_yampolskiy_prf as prf;
+use ed25519_dalek as ed25519;
+use either::Either::Left;
+use elgamal::{PublicKey, SecretKey};
+use id::{
+    account_holder::*,
+    chain::*,
+    constants::{ArCurve, BaseField, *},
+    identity_provider::*,
+    secret_sharing::Threshold,
+    types::*,
+};
+use rand::*;
+use std::{collections::BTreeMap, convert::TryFrom};
+
+type ExampleAttributeList = AttributeList<BaseField, AttributeKind>;
+
+const EXPIRY: TransactionTime = TransactionTime {
+    seconds: 111111111111111111,
+};
+
+/// Compare verifying credentials one at a time with verifying them as a
+/// batch, for different numbers of credentials.
+fn bench_verify_cdi_batch(c: &mut Criterion) {
+    let mut csprng = thread_rng();
+
+    let ip_secret_key = ps_sig::SecretKey::<Bls12>::generate(20, &mut csprng);
+    let ip_public_key = ps_sig::PublicKey::from(&ip_secret_key);
+    let keypair = ed25519::Keypair::generate(&mut csprng);
+    let ip_info = IpInfo {
+        ip_identity:       IpIdentity(88),
+        ip_description:    mk_dummy_description("IP88".to_string()),
+        ip_verify_key:     ip_public_key,
+        ip_cdi_verify_key: keypair.public,
+    };
+
+    let global_context = GlobalContext::generate(String::from("genesis_string"));
+    let ar_base = global_context.elgamal_generator();
+    let ars_infos = (1..=3u32)
+        .map(|i| {
+            let ar_identity = ArIdentity::try_from(i).unwrap();
+            let ar_secret_key = SecretKey::generate(ar_base, &mut csprng);
+            (ar_identity, ArInfo::<G1> {
+                ar_identity,
+                ar_description: mk_dummy_description(format!("AR{}", i)),
+                ar_public_key: PublicKey::from(&ar_secret_key),
+            })
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let valid_to = YearMonth::new(2021, 1).unwrap();
+    let created_at = YearMonth::new(2021, 1).unwrap();
+    let alist = {
+        let mut alist = BTreeMap::new();
+        alist.insert(AttributeTag::from(0u8), AttributeKind::from(55));
+        alist.insert(AttributeTag::from(8u8), AttributeKind::from(31));
+        alist
+    };
+    let alist = ExampleAttributeList {
+        valid_to,
+        created_at,
+        max_accounts: 255,
+        alist,
+        _phantom: Default::default(),
+    };
+    let aci = AccCredentialInfo {
+        cred_holder_info: CredentialHolderInfo::<ArCurve> {
+            id_cred: IdCredentials::generate(&mut csprng),
+        },
+        prf_key:          prf::SecretKey::generate(&mut csprng),
+    };
+    let initial_acc_data = InitialAccountData {
+        keys:      {
+            let mut keys = BTreeMap::new();
+            keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
+            keys
+        },
+        threshold: SignatureThreshold(1),
+    };
+
+    let context = IpContext::new(&ip_info, &ars_infos, &global_context);
+    let (pio, randomness) = generate_pio(&context, Threshold(2), &aci, &initial_acc_data)
+        .expect("Generating the pre-identity object succeed.");
+    let (ip_sig, _) = verify_credentials::<_, _, ArCurve>(
+        &pio,
+        context,
+        &alist,
+        EXPIRY,
+        &ip_secret_key,
+        &keypair.secret,
+    )
+    .unwrap();
+    let id_object = IdentityObject {
+        pre_identity_object: pio,
+        alist,
+        signature: ip_sig,
+    };
+    let id_use_data = IdObjectUseData { aci, randomness };
+
+    let policy = Policy {
+        valid_to,
+        created_at,
+        policy_vec: {
+            let mut tree = BTreeMap::new();
+            tree.insert(AttributeTag::from(8u8), AttributeKind::from(31));
+            tree
+        },
+        statements: Vec::new(),
+        _phantom: Default::default(),
+    };
+    let acc_data = CredentialData {
+        keys:      {
+            let mut keys = BTreeMap::new();
+            keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
+            keys
+        },
+        threshold: SignatureThreshold(1),
+    };
+
+    let max_batch_size = 64;
+    let cdis = (0..max_batch_size)
+        .map(|cred_counter| {
+            create_credential(
+                context,
+                &id_object,
+                &id_use_data,
+                cred_counter,
+                policy.clone(),
+                &acc_data,
+                &Left(EXPIRY),
+            )
+            .expect("Should generate the credential successfully.")
+            .0
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("Verify CDI batch");
+    for &n in [1, 4, 16, 64].iter() {
+        let batch = cdis
+            .iter()
+            .take(n)
+            .map(|cdi| (&ip_info, cdi, Left(EXPIRY)))
+            .collect::<Vec<_>>();
+        group.bench_with_input(BenchmarkId::new("Individually", n), &batch, |b, batch| {
+            b.iter(|| {
+                for (ip_info, cdi, new_or_existing) in batch.iter() {
+                    verify_cdi(&global_context, ip_info, &ars_infos, cdi, new_or_existing).unwrap()
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("Batched", n), &batch, |b, batch| {
+            let mut csprng = thread_rng();
+            b.iter(|| verify_cdi_batch(&global_context, &ars_infos, batch, &mut csprng).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(verify_cdi_batch_benches, bench_verify_cdi_batch);
+criterion_main!(verify_cdi_batch_benches);
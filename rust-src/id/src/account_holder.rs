@@ -3,7 +3,7 @@
 use crate::{
     secret_sharing::*,
     sigma_protocols::{
-        com_enc_eq, com_eq, com_eq_different_groups, com_eq_sig, com_mult, common::*, dlog,
+        com_enc_eq, com_eq, com_eq_different_groups, com_eq_sig, com_mult, com_set, common::*, dlog,
     },
     types::*,
     utils,
@@ -11,13 +11,15 @@ use crate::{
 use anyhow::{bail, ensure};
 use bulletproofs::{
     inner_product_proof::inner_product,
-    range_proof::{prove_given_scalars as bulletprove, prove_less_than_or_equal},
+    range_proof::{
+        prove_given_scalars as bulletprove, prove_less_than_or_equal, Generators, RangeProof,
+    },
 };
-use crypto_common::types::TransactionTime;
+use crypto_common::{types::TransactionTime, Version};
 use curve_arithmetic::{Curve, Pairing};
 use dodis_yampolskiy_prf as prf;
 use elgamal::{multicombine, Cipher};
-use ff::Field;
+use ff::{Field, PrimeField};
 use pedersen_scheme::{
     Commitment, CommitmentKey as PedersenKey, Randomness as PedersenRandomness, Value,
 };
@@ -36,7 +38,10 @@ pub fn build_pub_info_for_ip<C: Curve>(
     prf_key: &prf::SecretKey<C>,
     initial_account: &impl PublicInitialAccountData,
 ) -> Option<PublicInformationForIp<C>> {
-    let id_cred_pub = gc.on_chain_commitment_key.g.mul_by_scalar(id_cred_sec);
+    let id_cred_pub = gc
+        .on_chain_commitment_key
+        .g
+        .mul_by_secret_scalar(id_cred_sec);
 
     // From create_credential:
     // let id_cred_sec = &aci.cred_holder_info.id_cred.id_cred_sec;
@@ -528,7 +533,77 @@ pub fn create_credential<
 )>
 where
     AttributeType: Clone, {
-    let (unsigned_credential_info, commitments_randomness) = create_unsigned_credential(
+    create_credential_worker(
+        context,
+        id_object,
+        id_object_use_data,
+        cred_counter,
+        policy,
+        cred_data,
+        new_or_existing,
+        ID_RANGE_PROOFS_VERSION_0,
+    )
+}
+
+/// As [create_credential], but the credential counter and the in-range policy
+/// statements are proved by a single aggregated range proof, see
+/// [ID_RANGE_PROOFS_VERSION_1]. This makes credentials with in-range
+/// statements smaller. The global context must contain `64 * m` bulletproof
+/// generators, where `m` is `2 * (1 + s)` rounded up to a power of two, and `s`
+/// is the number of in-range statements.
+pub fn create_credential_aggregated<
+    'a,
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+>(
+    context: IpContext<'a, P, C>,
+    id_object: &IdentityObject<P, C, AttributeType>,
+    id_object_use_data: &IdObjectUseData<P, C>,
+    cred_counter: u8,
+    policy: Policy<C, AttributeType>,
+    cred_data: &impl CredentialDataWithSigning,
+    new_or_existing: &either::Either<TransactionTime, AccountAddress>,
+) -> anyhow::Result<(
+    CredentialDeploymentInfo<P, C, AttributeType>,
+    CommitmentsRandomness<C>,
+)>
+where
+    AttributeType: Clone, {
+    create_credential_worker(
+        context,
+        id_object,
+        id_object_use_data,
+        cred_counter,
+        policy,
+        cred_data,
+        new_or_existing,
+        ID_RANGE_PROOFS_VERSION_1,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_credential_worker<
+    'a,
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+>(
+    context: IpContext<'a, P, C>,
+    id_object: &IdentityObject<P, C, AttributeType>,
+    id_object_use_data: &IdObjectUseData<P, C>,
+    cred_counter: u8,
+    policy: Policy<C, AttributeType>,
+    cred_data: &impl CredentialDataWithSigning,
+    new_or_existing: &either::Either<TransactionTime, AccountAddress>,
+    range_proofs_version: Version,
+) -> anyhow::Result<(
+    CredentialDeploymentInfo<P, C, AttributeType>,
+    CommitmentsRandomness<C>,
+)>
+where
+    AttributeType: Clone, {
+    let (unsigned_credential_info, commitments_randomness) = create_unsigned_credential_worker(
         context,
         id_object,
         id_object_use_data,
@@ -536,6 +611,7 @@ where
         policy,
         cred_data.get_cred_key_info(),
         new_or_existing.as_ref().right(),
+        range_proofs_version,
     )?;
 
     let proof_acc_sk = AccountOwnershipProof {
@@ -578,6 +654,72 @@ pub fn create_unsigned_credential<
     UnsignedCredentialDeploymentInfo<P, C, AttributeType>,
     CommitmentsRandomness<C>,
 )>
+where
+    AttributeType: Clone, {
+    create_unsigned_credential_worker(
+        context,
+        id_object,
+        id_object_use_data,
+        cred_counter,
+        policy,
+        cred_key_info,
+        addr,
+        ID_RANGE_PROOFS_VERSION_0,
+    )
+}
+
+/// As [create_unsigned_credential], but with the range proofs aggregated as in
+/// [create_credential_aggregated].
+pub fn create_unsigned_credential_aggregated<
+    'a,
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+>(
+    context: IpContext<'a, P, C>,
+    id_object: &IdentityObject<P, C, AttributeType>,
+    id_object_use_data: &IdObjectUseData<P, C>,
+    cred_counter: u8,
+    policy: Policy<C, AttributeType>,
+    cred_key_info: CredentialPublicKeys,
+    addr: Option<&AccountAddress>,
+) -> anyhow::Result<(
+    UnsignedCredentialDeploymentInfo<P, C, AttributeType>,
+    CommitmentsRandomness<C>,
+)>
+where
+    AttributeType: Clone, {
+    create_unsigned_credential_worker(
+        context,
+        id_object,
+        id_object_use_data,
+        cred_counter,
+        policy,
+        cred_key_info,
+        addr,
+        ID_RANGE_PROOFS_VERSION_1,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_unsigned_credential_worker<
+    'a,
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+>(
+    context: IpContext<'a, P, C>,
+    id_object: &IdentityObject<P, C, AttributeType>,
+    id_object_use_data: &IdObjectUseData<P, C>,
+    cred_counter: u8,
+    policy: Policy<C, AttributeType>,
+    cred_key_info: CredentialPublicKeys,
+    addr: Option<&AccountAddress>,
+    range_proofs_version: Version,
+) -> anyhow::Result<(
+    UnsignedCredentialDeploymentInfo<P, C, AttributeType>,
+    CommitmentsRandomness<C>,
+)>
 where
     AttributeType: Clone, {
     let mut csprng = thread_rng();
@@ -767,20 +909,34 @@ where
         None => bail!("Cannot produce zero knowledge proof."),
     };
 
-    let cred_counter_less_than_max_accounts = match prove_less_than_or_equal(
-        &mut ro,
-        &mut csprng,
-        8,
-        u64::from(cred_counter),
-        u64::from(alist.max_accounts),
-        &context.global_context.bulletproof_generators(),
-        &context.global_context.on_chain_commitment_key,
-        &commitment_rands.cred_counter_rand,
-        &commitment_rands.max_accounts_rand,
-    ) {
-        Some(x) => x,
-        None => bail!("Cannot produce proof that cred_counter <= max_accounts."),
-    };
+    let gens = context.global_context.bulletproof_generators();
+    let cmm_key = &context.global_context.on_chain_commitment_key;
+    let (cred_counter_less_than_max_accounts, policy_proofs) =
+        if range_proofs_version == ID_RANGE_PROOFS_VERSION_0 {
+            prove_ranges_separately(
+                &mut ro,
+                &mut csprng,
+                gens,
+                cmm_key,
+                cred_counter,
+                alist,
+                &commitment_rands,
+                &cred_values.policy.statements,
+            )?
+        } else if range_proofs_version == ID_RANGE_PROOFS_VERSION_1 {
+            prove_ranges_aggregated(
+                &mut ro,
+                &mut csprng,
+                gens,
+                cmm_key,
+                cred_counter,
+                alist,
+                &commitment_rands,
+                &cred_values.policy.statements,
+            )?
+        } else {
+            bail!("Unsupported range proofs version {}.", range_proofs_version)
+        };
 
     // A list of signatures on the challenge used by the other proofs using the
     // credential keys.
@@ -802,6 +958,8 @@ where
         proof_reg_id: proof.witness.w1.w1,
         proof_ip_sig: proof.witness.w1.w2,
         cred_counter_less_than_max_accounts,
+        policy_proofs,
+        range_proofs_version,
     };
 
     let info = UnsignedCredentialDeploymentInfo {
@@ -951,6 +1109,237 @@ fn compute_pok_sig<
     Ok((prover, secret))
 }
 
+/// Prove that the credential counter is at most the maximum number of
+/// accounts, followed by the proofs of the policy statements, each with their
+/// own range proof. This is [ID_RANGE_PROOFS_VERSION_0]. The proofs continue
+/// the transcript of the credential, so they are bound to the credential
+/// values as well.
+#[allow(clippy::too_many_arguments)]
+fn prove_ranges_separately<C: Curve, AttributeType: Attribute<C::Scalar>, R: Rng>(
+    ro: &mut RandomOracle,
+    csprng: &mut R,
+    gens: &Generators<C>,
+    cmm_key: &PedersenKey<C>,
+    cred_counter: u8,
+    alist: &AttributeList<C::Scalar, AttributeType>,
+    commitment_rands: &CommitmentsRandomness<C>,
+    statements: &[AttributeStatement<C, AttributeType>],
+) -> anyhow::Result<(RangeProof<C>, Vec<AttributeProof<C>>)> {
+    let cred_counter_less_than_max_accounts = match prove_less_than_or_equal(
+        ro,
+        csprng,
+        8,
+        u64::from(cred_counter),
+        u64::from(alist.max_accounts),
+        gens,
+        cmm_key,
+        &commitment_rands.cred_counter_rand,
+        &commitment_rands.max_accounts_rand,
+    ) {
+        Some(x) => x,
+        None => bail!("Cannot produce proof that cred_counter <= max_accounts."),
+    };
+
+    let mut policy_proofs = Vec::with_capacity(statements.len());
+    for statement in statements.iter() {
+        let (attribute, randomness) = statement_attribute(alist, commitment_rands, statement)?;
+        match prove_attribute_statement(ro, csprng, gens, cmm_key, statement, attribute, randomness)
+        {
+            Some(proof) => policy_proofs.push(proof),
+            None => bail!(
+                "Cannot prove the policy statement about attribute {}.",
+                statement.attribute_tag()
+            ),
+        }
+    }
+    Ok((cred_counter_less_than_max_accounts, policy_proofs))
+}
+
+/// Prove the policy statements that are not in-range statements, followed by
+/// a single range proof for the credential counter and all the in-range
+/// statements. This is [ID_RANGE_PROOFS_VERSION_1].
+#[allow(clippy::too_many_arguments)]
+fn prove_ranges_aggregated<C: Curve, AttributeType: Attribute<C::Scalar>, R: Rng>(
+    ro: &mut RandomOracle,
+    csprng: &mut R,
+    gens: &Generators<C>,
+    cmm_key: &PedersenKey<C>,
+    cred_counter: u8,
+    alist: &AttributeList<C::Scalar, AttributeType>,
+    commitment_rands: &CommitmentsRandomness<C>,
+    statements: &[AttributeStatement<C, AttributeType>],
+) -> anyhow::Result<(RangeProof<C>, Vec<AttributeProof<C>>)> {
+    ensure!(
+        cred_counter <= alist.max_accounts,
+        "Cannot produce proof that cred_counter <= max_accounts."
+    );
+    // The values max_accounts - cred_counter and cred_counter, as in
+    // prove_less_than_or_equal.
+    let mut max_minus_counter_rand = *commitment_rands.max_accounts_rand;
+    max_minus_counter_rand.sub_assign(&commitment_rands.cred_counter_rand);
+    let mut values = vec![
+        C::scalar_from_u64(u64::from(alist.max_accounts - cred_counter)),
+        C::scalar_from_u64(u64::from(cred_counter)),
+    ];
+    let mut randomness = vec![
+        PedersenRandomness::new(max_minus_counter_rand),
+        commitment_rands.cred_counter_rand.clone(),
+    ];
+
+    let mut policy_proofs = Vec::with_capacity(statements.len());
+    for statement in statements.iter() {
+        let (attribute, attribute_randomness) =
+            statement_attribute(alist, commitment_rands, statement)?;
+        let proof = match statement {
+            AttributeStatement::AttributeInRange(statement) => {
+                attribute_in_range_values(statement, attribute, attribute_randomness).map(
+                    |(statement_values, statement_randomness)| {
+                        values.extend_from_slice(&statement_values);
+                        randomness.extend(statement_randomness.iter().cloned());
+                        AttributeProof::AttributeInAggregatedRange
+                    },
+                )
+            }
+            _ => prove_attribute_statement(
+                ro,
+                csprng,
+                gens,
+                cmm_key,
+                statement,
+                attribute,
+                attribute_randomness,
+            ),
+        };
+        match proof {
+            Some(proof) => policy_proofs.push(proof),
+            None => bail!(
+                "Cannot prove the policy statement about attribute {}.",
+                statement.attribute_tag()
+            ),
+        }
+    }
+
+    // The range proof is for a power of two number of values, so it is padded
+    // with 0 values with randomness 0.
+    let num_values = values.len().next_power_of_two();
+    ensure!(
+        num_values <= 128 && gens.G_H.len() >= 64 * num_values,
+        "Not enough bulletproof generators to aggregate {} range proofs.",
+        values.len() / 2
+    );
+    values.resize(num_values, C::Scalar::zero());
+    randomness.resize(num_values, PedersenRandomness::zero());
+    let proof = match bulletprove(
+        ro,
+        csprng,
+        64,
+        num_values as u8,
+        &values,
+        gens,
+        cmm_key,
+        &randomness,
+    ) {
+        Some(proof) => proof,
+        None => bail!("Cannot produce the aggregated range proof."),
+    };
+    Ok((proof, policy_proofs))
+}
+
+/// The attribute that a policy statement is about, and the randomness of the
+/// commitment to it.
+fn statement_attribute<'a, C: Curve, AttributeType: Attribute<C::Scalar>>(
+    alist: &'a AttributeList<C::Scalar, AttributeType>,
+    commitment_rands: &'a CommitmentsRandomness<C>,
+    statement: &AttributeStatement<C, AttributeType>,
+) -> anyhow::Result<(&'a AttributeType, &'a PedersenRandomness<C>)> {
+    let tag = statement.attribute_tag();
+    match (
+        alist.alist.get(&tag),
+        commitment_rands.attributes_rand.get(&tag),
+    ) {
+        (Some(attribute), Some(randomness)) => Ok((attribute, randomness)),
+        _ => bail!(
+            "Cannot prove a statement about attribute {}. It is either revealed or not part of \
+             the identity object.",
+            tag
+        ),
+    }
+}
+
+/// The values `value - lower` and `upper - 1 - value`, and the randomness of
+/// the commitments to them derived from the commitment to the attribute. A
+/// range proof for these values shows that the attribute is in the range of
+/// the statement. Returns `None` if the attribute is not in the range.
+#[allow(clippy::type_complexity)]
+fn attribute_in_range_values<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    statement: &AttributeInRangeStatement<C, AttributeType>,
+    attribute: &AttributeType,
+    randomness: &PedersenRandomness<C>,
+) -> Option<([C::Scalar; 2], [PedersenRandomness<C>; 2])> {
+    let value = attribute.to_field_element();
+    // value - lower
+    let mut above_lower = value;
+    above_lower.sub_assign(&statement.lower.to_field_element());
+    // upper - value - 1
+    let mut below_upper = statement.upper.to_field_element();
+    below_upper.sub_assign(&value);
+    below_upper.sub_assign(&C::Scalar::one());
+    // The range proof only looks at the least significant 64 bits, so we
+    // must check here that the attribute is in range.
+    if !fits_in_u64(&above_lower) || !fits_in_u64(&below_upper) {
+        return None;
+    }
+    let mut minus_randomness = **randomness;
+    minus_randomness.negate();
+    Some(([above_lower, below_upper], [
+        PedersenRandomness::new(**randomness),
+        PedersenRandomness::new(minus_randomness),
+    ]))
+}
+
+/// Prove a statement about an attribute with respect to the commitment to the
+/// attribute, made with the given key and randomness. The proof is added to
+/// the given transcript. Returns `None` if the attribute does not satisfy the
+/// statement.
+pub fn prove_attribute_statement<C: Curve, AttributeType: Attribute<C::Scalar>, R: Rng>(
+    ro: &mut RandomOracle,
+    csprng: &mut R,
+    gens: &Generators<C>,
+    cmm_key: &PedersenKey<C>,
+    statement: &AttributeStatement<C, AttributeType>,
+    attribute: &AttributeType,
+    randomness: &PedersenRandomness<C>,
+) -> Option<AttributeProof<C>> {
+    match statement {
+        AttributeStatement::AttributeInRange(statement) => {
+            let (values, randomness) = attribute_in_range_values(statement, attribute, randomness)?;
+            let proof = bulletprove(ro, csprng, 64, 2, &values, gens, cmm_key, &randomness)?;
+            Some(AttributeProof::AttributeInRange(proof))
+        }
+        AttributeStatement::AttributeInSet(statement) => {
+            let value = Value::<C>::new(attribute.to_field_element());
+            let commitment = cmm_key.hide(&value, randomness);
+            let set = statement.set.iter().map(|a| a.to_field_element()).collect();
+            let proof = com_set::prove_membership(
+                ro, csprng, cmm_key, commitment, set, &value, randomness,
+            )?;
+            Some(AttributeProof::AttributeInSet(proof))
+        }
+        AttributeStatement::AttributeNotInSet(statement) => {
+            let value = Value::<C>::new(attribute.to_field_element());
+            let commitment = cmm_key.hide(&value, randomness);
+            let set = statement.set.iter().map(|a| a.to_field_element()).collect();
+            let proof = com_set::prove_non_membership(
+                ro, csprng, cmm_key, commitment, set, &value, randomness,
+            )?;
+            Some(AttributeProof::AttributeNotInSet(proof))
+        }
+    }
+}
+
+/// Check whether the field element is less than 2^64.
+fn fits_in_u64<F: PrimeField>(x: &F) -> bool { x.into_repr().as_ref()[1..].iter().all(|&l| l == 0) }
+
 /// Computing the commitments for the credential deployment info. We only
 /// compute commitments for values that are not revealed as part of the policy.
 /// For the other values the verifier (the chain) will compute commitments with
@@ -1086,7 +1475,7 @@ mod tests {
     use either::Either::Left;
     use pedersen_scheme::CommitmentKey as PedersenKey;
 
-    type ExampleCurve = pairing::bls12_381::G1;
+    type ExampleCurve = curve_arithmetic::bls12_381::G1;
 
     const EXPIRY: TransactionTime = TransactionTime {
         seconds: 111111111111111111,
@@ -1258,6 +1647,7 @@ mod tests {
                 tree.insert(AttributeTag::from(8u8), AttributeKind::from(31));
                 tree
             },
+            statements: Vec::new(),
             _phantom: Default::default(),
         };
         let mut keys = BTreeMap::new();
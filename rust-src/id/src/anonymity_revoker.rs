@@ -1,7 +1,25 @@
 //! Functionality needed by the anonymity revoker.
-use crate::{secret_sharing::*, types::*};
+//!
+//! Anonymity revocation is done by threshold decryption. Each anonymity
+//! revoker decrypts only its own share, and proves that the decryption is
+//! correct with respect to its public key, see
+//! [decrypt_id_cred_pub_share] and [decrypt_prf_key_share]. The shares are then
+//! combined with [reveal_id_cred_pub_verified] or [reveal_prf_key_verified],
+//! which check every proof and report the anonymity revokers that provided an
+//! invalid share.
+use crate::{
+    secret_sharing::*,
+    sigma_protocols::{common::*, dlog_eq::*},
+    types::*,
+};
 use curve_arithmetic::*;
-use elgamal::Message;
+use elgamal::{
+    decrypt_from_chunks_given_table, value_to_chunks, Cipher, DiscreteLog, Message, SecretKey,
+};
+use rand::*;
+use random_oracle::RandomOracle;
+use std::collections::{BTreeMap, BTreeSet};
+use thiserror::Error;
 
 /// Reveal the `idCredPub` based on the given shares.
 /// It is important to remember that this always succeeds in computing
@@ -23,3 +41,398 @@ pub fn reveal_id_cred_pub<C: Curve>(shares: &[(ArIdentity, Message<C>)]) -> C {
 /// value is meaningful must be ensured by the caller, e.g., by making sure that
 /// the threshold is compatible with the number of shares.
 pub fn reveal_prf_key<C: Curve>(shares: &[(ArIdentity, Value<C>)]) -> C::Scalar { reveal(&shares) }
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+/// Reasons why combining decrypted shares can fail.
+pub enum RevealError {
+    #[error("Anonymity revoker {0} is not an anonymity revoker of the account holder.")]
+    UnknownAnonymityRevoker(ArIdentity),
+    #[error("Anonymity revoker {0} provided more than one share.")]
+    DuplicateShare(ArIdentity),
+    #[error(
+        "Only {valid} valid shares, but the threshold is {threshold}. Anonymity revokers \
+         {invalid:?} provided invalid shares."
+    )]
+    InsufficientShares {
+        valid:     usize,
+        threshold: Threshold,
+        invalid:   Vec<ArIdentity>,
+    },
+}
+
+/// The value revealed from the valid shares, together with the anonymity
+/// revokers whose shares were invalid, and were thus not used.
+#[derive(Debug)]
+pub struct Revealed<T> {
+    pub value:   T,
+    pub invalid: Vec<ArIdentity>,
+}
+
+/// The statement that the `messages` are the decryptions of the respective
+/// `ciphers` with the secret key corresponding to `pk`.
+fn decryption_statement<C: Curve>(
+    pk: &ArPublicKey<C>,
+    ciphers: &[Cipher<C>],
+    messages: impl IntoIterator<Item = C>,
+) -> DlogEq<C> {
+    let mut public = vec![pk.key];
+    let mut coeff = vec![pk.generator];
+    for (cipher, m) in ciphers.iter().zip(messages) {
+        public.push(cipher.1.minus_point(&m));
+        coeff.push(cipher.0);
+    }
+    DlogEq { public, coeff }
+}
+
+/// The encryptions in the exponent of the chunks of `prf_key_share`.
+fn prf_key_share_chunks<C: Curve>(
+    context: &GlobalContext<C>,
+    prf_key_share: &Value<C>,
+) -> impl Iterator<Item = C> {
+    let h = *context.encryption_in_exponent_generator();
+    value_to_chunks::<C>(prf_key_share, CHUNK_SIZE)
+        .into_iter()
+        .map(move |chunk| h.mul_by_scalar(&chunk))
+}
+
+fn id_cred_pub_share_ro(ar_identity: ArIdentity) -> RandomOracle {
+    let mut ro = RandomOracle::domain("IdCredPubShareDecryption");
+    ro.append_message(b"ar_identity", &ar_identity);
+    ro
+}
+
+fn prf_key_share_ro<C: Curve>(context: &GlobalContext<C>, ar_identity: ArIdentity) -> RandomOracle {
+    let mut ro = RandomOracle::domain("PrfKeyShareDecryption");
+    ro.append_message(b"global_context", context);
+    ro.append_message(b"ar_identity", &ar_identity);
+    ro
+}
+
+/// Decrypt the share of `idCredPub` of the anonymity revoker with the given
+/// identity and secret key, and prove that the decryption is correct.
+pub fn decrypt_id_cred_pub_share<C: Curve, R: Rng>(
+    ar_identity: ArIdentity,
+    ar_secret_key: &SecretKey<C>,
+    ar_data: &ChainArData<C>,
+    csprng: &mut R,
+) -> ChainArDecryptedData<C> {
+    let id_cred_pub_share = ar_secret_key.decrypt(&ar_data.enc_id_cred_pub_share);
+    let statement = decryption_statement(
+        &ArPublicKey::from(ar_secret_key),
+        &[ar_data.enc_id_cred_pub_share],
+        vec![id_cred_pub_share.value],
+    );
+    let secret = DlogEqSecret {
+        secret: Value::new(ar_secret_key.scalar),
+    };
+    let proof_of_decryption = prove(
+        &mut id_cred_pub_share_ro(ar_identity),
+        &statement,
+        secret,
+        csprng,
+    )
+    .expect("The statement is consistent.");
+    ChainArDecryptedData {
+        ar_identity,
+        id_cred_pub_share,
+        proof_of_decryption,
+    }
+}
+
+/// Check that the share is the correct decryption of the encrypted share under
+/// the given public key of the anonymity revoker.
+pub fn verify_id_cred_pub_share<C: Curve>(
+    ar_public_key: &ArPublicKey<C>,
+    ar_data: &ChainArData<C>,
+    share: &ChainArDecryptedData<C>,
+) -> bool {
+    let statement = decryption_statement(ar_public_key, &[ar_data.enc_id_cred_pub_share], vec![
+        share.id_cred_pub_share.value,
+    ]);
+    verify(
+        &mut id_cred_pub_share_ro(share.ar_identity),
+        &statement,
+        &share.proof_of_decryption,
+    )
+}
+
+/// Decrypt the share of the PRF key of the anonymity revoker with the given
+/// identity and secret key, and prove that the decryption is correct. The
+/// `table` is used to decrypt the chunks of the share, and must be for the
+/// base [GlobalContext::encryption_in_exponent_generator].
+pub fn decrypt_prf_key_share<C: Curve, D: DiscreteLog<C> + ?Sized, R: Rng>(
+    context: &GlobalContext<C>,
+    ar_identity: ArIdentity,
+    ar_secret_key: &SecretKey<C>,
+    ar_data: &IpArData<C>,
+    table: &D,
+    csprng: &mut R,
+) -> IpArDecryptedData<C> {
+    let prf_key_share = decrypt_from_chunks_given_table(
+        ar_secret_key,
+        &ar_data.enc_prf_key_share,
+        table,
+        CHUNK_SIZE,
+    );
+    let statement = decryption_statement(
+        &ArPublicKey::from(ar_secret_key),
+        &ar_data.enc_prf_key_share,
+        prf_key_share_chunks(context, &prf_key_share),
+    );
+    let secret = DlogEqSecret {
+        secret: Value::new(ar_secret_key.scalar),
+    };
+    let proof_of_decryption = prove(
+        &mut prf_key_share_ro(context, ar_identity),
+        &statement,
+        secret,
+        csprng,
+    )
+    .expect("The statement is consistent.");
+    IpArDecryptedData {
+        ar_identity,
+        prf_key_share,
+        proof_of_decryption,
+    }
+}
+
+/// Check that the share is the correct decryption of the encrypted share under
+/// the given public key of the anonymity revoker.
+pub fn verify_prf_key_share<C: Curve>(
+    context: &GlobalContext<C>,
+    ar_public_key: &ArPublicKey<C>,
+    ar_data: &IpArData<C>,
+    share: &IpArDecryptedData<C>,
+) -> bool {
+    let statement = decryption_statement(
+        ar_public_key,
+        &ar_data.enc_prf_key_share,
+        prf_key_share_chunks(context, &share.prf_key_share),
+    );
+    verify(
+        &mut prf_key_share_ro(context, share.ar_identity),
+        &statement,
+        &share.proof_of_decryption,
+    )
+}
+
+/// Split the shares into the valid ones, and the identities of anonymity
+/// revokers with invalid shares. The function `verify` returns `None` if the
+/// anonymity revoker of the share is not known.
+fn check_shares<'a, S>(
+    threshold: Threshold,
+    shares: &'a [S],
+    ar_identity: impl Fn(&S) -> ArIdentity,
+    verify: impl Fn(&S) -> Option<bool>,
+) -> Result<Revealed<Vec<&'a S>>, RevealError> {
+    let mut seen = BTreeSet::new();
+    let mut valid = Vec::with_capacity(shares.len());
+    let mut invalid = Vec::new();
+    for share in shares {
+        let ar = ar_identity(share);
+        if !seen.insert(ar) {
+            return Err(RevealError::DuplicateShare(ar));
+        }
+        match verify(share) {
+            None => return Err(RevealError::UnknownAnonymityRevoker(ar)),
+            Some(true) => valid.push(share),
+            Some(false) => invalid.push(ar),
+        }
+    }
+    if valid.len() < usize::from(threshold) {
+        return Err(RevealError::InsufficientShares {
+            valid: valid.len(),
+            threshold,
+            invalid,
+        });
+    }
+    Ok(Revealed {
+        value: valid,
+        invalid,
+    })
+}
+
+/// Verify the given shares of `idCredPub`, and reveal it from the valid ones.
+/// The `ar_data` is the anonymity revocation data of the credential, and
+/// `threshold` its revocation threshold. Fails if there are fewer valid shares
+/// than the threshold, or if a share is not from an anonymity revoker of the
+/// credential.
+pub fn reveal_id_cred_pub_verified<C: Curve, A: HasArPublicKey<C>>(
+    known_ars: &BTreeMap<ArIdentity, A>,
+    ar_data: &BTreeMap<ArIdentity, ChainArData<C>>,
+    threshold: Threshold,
+    shares: &[ChainArDecryptedData<C>],
+) -> Result<Revealed<C>, RevealError> {
+    let Revealed { value, invalid } = check_shares(
+        threshold,
+        shares,
+        |share| share.ar_identity,
+        |share| {
+            let ar_public_key = known_ars.get(&share.ar_identity)?.get_public_key();
+            let single_ar_data = ar_data.get(&share.ar_identity)?;
+            Some(verify_id_cred_pub_share(
+                ar_public_key,
+                single_ar_data,
+                share,
+            ))
+        },
+    )?;
+    let valid_shares = value
+        .into_iter()
+        .map(|share| (share.ar_identity, share.id_cred_pub_share.value))
+        .collect::<Vec<_>>();
+    Ok(Revealed {
+        value: reveal_in_group(&valid_shares),
+        invalid,
+    })
+}
+
+/// Verify the given shares of the PRF key, and reveal it from the valid ones.
+/// The `ar_data` is the anonymity revocation data of the identity object, and
+/// `threshold` its revocation threshold. Fails if there are fewer valid shares
+/// than the threshold, or if a share is not from an anonymity revoker of the
+/// identity object.
+pub fn reveal_prf_key_verified<C: Curve, A: HasArPublicKey<C>>(
+    context: &GlobalContext<C>,
+    known_ars: &BTreeMap<ArIdentity, A>,
+    ar_data: &BTreeMap<ArIdentity, IpArData<C>>,
+    threshold: Threshold,
+    shares: &[IpArDecryptedData<C>],
+) -> Result<Revealed<C::Scalar>, RevealError> {
+    let Revealed { value, invalid } = check_shares(
+        threshold,
+        shares,
+        |share| share.ar_identity,
+        |share| {
+            let ar_public_key = known_ars.get(&share.ar_identity)?.get_public_key();
+            let single_ar_data = ar_data.get(&share.ar_identity)?;
+            Some(verify_prf_key_share(
+                context,
+                ar_public_key,
+                single_ar_data,
+                share,
+            ))
+        },
+    )?;
+    let valid_shares = value
+        .into_iter()
+        .map(|share| (share.ar_identity, share.prf_key_share.clone()))
+        .collect::<Vec<_>>();
+    Ok(Revealed {
+        value: reveal_prf_key(&valid_shares),
+        invalid,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::ArCurve, sigma_protocols::com_enc_eq, test::test_create_ars, utils};
+    use crypto_common::*;
+    use elgamal::BabyStepGiantStep;
+
+    /// Encrypt the given share of the PRF key for the anonymity revoker. The
+    /// proof of the identity provider is not used by anonymity revocation, so
+    /// it is left as zeros.
+    fn make_ip_ar_data<R: Rng>(
+        context: &GlobalContext<ArCurve>,
+        ar_public_key: &ArPublicKey<ArCurve>,
+        share: &Value<ArCurve>,
+        csprng: &mut R,
+    ) -> IpArData<ArCurve> {
+        let (enc_prf_key_share, _, _) =
+            utils::encrypt_prf_share(context, ar_public_key, share, csprng);
+        let zero = to_bytes(&ArCurve::scalar_from_u64(0));
+        let proof_com_enc_eq: com_enc_eq::Witness<ArCurve> =
+            from_bytes(&mut std::io::Cursor::new(zero.repeat(3))).expect("Zero scalars are valid.");
+        IpArData {
+            enc_prf_key_share,
+            proof_com_enc_eq,
+        }
+    }
+
+    #[test]
+    fn test_reveal_prf_key_verified() {
+        let mut csprng = thread_rng();
+        let context = GlobalContext::<ArCurve>::generate(String::from("genesis_string"));
+        let (ar_infos, ar_keys) = test_create_ars(context.elgamal_generator(), 3, &mut csprng);
+        // Small shares so that decryption is cheap.
+        let plain_shares = ar_keys
+            .keys()
+            .map(|ar_id| (*ar_id, Value::from(u64::from(csprng.gen::<u16>()))))
+            .collect::<Vec<_>>();
+        let ar_data = plain_shares
+            .iter()
+            .map(|(ar_id, share)| {
+                let pk = &ar_infos[ar_id].ar_public_key;
+                (*ar_id, make_ip_ar_data(&context, pk, share, &mut csprng))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let table = BabyStepGiantStep::new(context.encryption_in_exponent_generator(), 1 << 10);
+        let mut shares = ar_keys
+            .iter()
+            .map(|(ar_id, key)| {
+                decrypt_prf_key_share(&context, *ar_id, key, &ar_data[ar_id], &table, &mut csprng)
+            })
+            .collect::<Vec<_>>();
+        for share in shares.iter() {
+            assert!(verify_prf_key_share(
+                &context,
+                &ar_infos[&share.ar_identity].ar_public_key,
+                &ar_data[&share.ar_identity],
+                share
+            ));
+        }
+
+        let revealed =
+            reveal_prf_key_verified(&context, &ar_infos, &ar_data, Threshold(2), &shares)
+                .expect("All shares are valid.");
+        assert_eq!(revealed.value, reveal_prf_key(&plain_shares));
+        assert!(revealed.invalid.is_empty());
+
+        // A share that does not match the encryption is detected, and the
+        // remaining shares are enough to reveal the key.
+        let cheater = shares[2].ar_identity;
+        shares[2].prf_key_share = Value::from(u64::from(csprng.gen::<u16>()) + (1 << 16));
+        let revealed =
+            reveal_prf_key_verified(&context, &ar_infos, &ar_data, Threshold(2), &shares)
+                .expect("There are enough valid shares.");
+        assert_eq!(revealed.value, reveal_prf_key(&plain_shares[..2]));
+        assert_eq!(revealed.invalid, vec![cheater]);
+        assert_eq!(
+            reveal_prf_key_verified(&context, &ar_infos, &ar_data, Threshold(3), &shares)
+                .unwrap_err(),
+            RevealError::InsufficientShares {
+                valid:     2,
+                threshold: Threshold(3),
+                invalid:   vec![cheater],
+            }
+        );
+
+        // The proof is bound to the identity of the anonymity revoker.
+        shares[2] = decrypt_prf_key_share(
+            &context,
+            shares[0].ar_identity,
+            &ar_keys[&cheater],
+            &ar_data[&cheater],
+            &table,
+            &mut csprng,
+        );
+        shares[2].ar_identity = cheater;
+        assert!(!verify_prf_key_share(
+            &context,
+            &ar_infos[&cheater].ar_public_key,
+            &ar_data[&cheater],
+            &shares[2]
+        ));
+
+        // Shares of anonymity revokers that are not known are rejected.
+        let unknown = ArIdentity::new(4);
+        shares[2].ar_identity = unknown;
+        assert_eq!(
+            reveal_prf_key_verified(&context, &ar_infos, &ar_data, Threshold(2), &shares)
+                .unwrap_err(),
+            RevealError::UnknownAnonymityRevoker(unknown)
+        );
+    }
+}
@@ -1,17 +1,21 @@
 //! Functionality needed by the chain to verify credential deployments.
 use crate::{
     secret_sharing::Threshold,
-    sigma_protocols::{com_enc_eq, com_eq_sig, com_mult, common::*},
+    sigma_protocols::{com_enc_eq, com_eq_sig, com_mult, com_set, common::*},
     types::*,
     utils,
 };
-use bulletproofs::range_proof::verify_less_than_or_equal;
+use bulletproofs::range_proof::{
+    verify_efficient, Generators, RangeProof, RangeProofBatch, RangeProofEquations,
+};
 use core::fmt::{self, Display};
 use crypto_common::{to_bytes, types::TransactionTime};
 use curve_arithmetic::{Curve, Pairing};
 use ed25519_dalek::Verifier;
 use either::Either;
+use ff::Field;
 use pedersen_scheme::{Commitment, CommitmentKey, Randomness, Value};
+use rand::Rng;
 use random_oracle::RandomOracle;
 use sha2::{Digest, Sha256};
 use std::collections::{BTreeMap, BTreeSet};
@@ -43,6 +47,63 @@ impl Display for CdiVerificationError {
         }
     }
 }
+
+/// How the range proofs in a credential are checked. They are either verified
+/// one at a time, or collected in a batch that is verified at the end.
+trait RangeProofVerifier<C: Curve> {
+    fn verify_range_proof(
+        &mut self,
+        ro: &mut RandomOracle,
+        n: u8,
+        commitments: &[Commitment<C>],
+        proof: &RangeProof<C>,
+    ) -> bool;
+}
+
+/// Verify each range proof as it is encountered.
+struct SingleRangeProofs<'a, C: Curve> {
+    gens:           &'a Generators<C>,
+    commitment_key: &'a CommitmentKey<C>,
+}
+
+impl<'a, C: Curve> RangeProofVerifier<C> for SingleRangeProofs<'a, C> {
+    fn verify_range_proof(
+        &mut self,
+        ro: &mut RandomOracle,
+        n: u8,
+        commitments: &[Commitment<C>],
+        proof: &RangeProof<C>,
+    ) -> bool {
+        verify_efficient(ro, n, commitments, proof, self.gens, self.commitment_key).is_ok()
+    }
+}
+
+/// Compute the equations of the range proofs, to be added to a batch later.
+/// Only malformed proofs are rejected immediately, the rest are checked when
+/// the batch is verified.
+struct DeferredRangeProofs<'a, C: Curve> {
+    gens:      &'a Generators<C>,
+    equations: Vec<RangeProofEquations<C>>,
+}
+
+impl<'a, C: Curve> RangeProofVerifier<C> for DeferredRangeProofs<'a, C> {
+    fn verify_range_proof(
+        &mut self,
+        ro: &mut RandomOracle,
+        n: u8,
+        commitments: &[Commitment<C>],
+        proof: &RangeProof<C>,
+    ) -> bool {
+        match RangeProofEquations::new(ro, n, commitments, proof, self.gens) {
+            Ok(equations) => {
+                self.equations.push(equations);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
 /// Verify credential deployment info. This checks that the data is consistent,
 /// and that the credential is signed by the specified identity provider.
 pub fn verify_cdi<
@@ -58,6 +119,114 @@ pub fn verify_cdi<
     known_ars: &BTreeMap<ArIdentity, A>,
     cdi: &CredentialDeploymentInfo<P, C, AttributeType>,
     new_or_existing: &Either<TransactionTime, AccountAddress>,
+) -> Result<(), CdiVerificationError> {
+    let mut range_proofs = SingleRangeProofs {
+        gens:           global_context.bulletproof_generators(),
+        commitment_key: &global_context.on_chain_commitment_key,
+    };
+    verify_cdi_worker(
+        global_context,
+        ip_info,
+        known_ars,
+        cdi,
+        new_or_existing,
+        &mut range_proofs,
+    )
+}
+
+/// Verify a list of credential deployments. The result is the same as calling
+/// [verify_cdi] on each of them, but the range proofs of all the credentials
+/// are combined with random weights and checked with a single
+/// multi-exponentiation. If this combined check fails, the credentials are
+/// verified one by one to find the invalid ones.
+///
+/// The sigma protocols, which include the pairings of the proof of knowledge
+/// of the identity provider's signature, cannot be combined in the same way
+/// since the pairing values are hashed to compute the challenges, and thus
+/// must be computed exactly for each credential. These checks, together with
+/// the account holder's signatures and the transcripts of the range proofs,
+/// are instead done for all the credentials in parallel if the `parallel`
+/// feature is enabled.
+///
+/// If verification fails, the result lists the index of each credential that
+/// is invalid, in increasing order, together with the reason.
+#[allow(clippy::type_complexity)]
+pub fn verify_cdi_batch<
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+    A: HasArPublicKey<C> + Sync,
+    R: Rng,
+>(
+    global_context: &GlobalContext<C>,
+    // NB: The following map only needs to be a superset of the ars
+    // in the cdis.
+    known_ars: &BTreeMap<ArIdentity, A>,
+    cdis: &[(
+        &IpInfo<P>,
+        &CredentialDeploymentInfo<P, C, AttributeType>,
+        Either<TransactionTime, AccountAddress>,
+    )],
+    csprng: &mut R,
+) -> Result<(), Vec<(usize, CdiVerificationError)>> {
+    let gens = global_context.bulletproof_generators();
+    let checked = utils::map_maybe_parallel(cdis, |(ip_info, cdi, new_or_existing)| {
+        let mut range_proofs = DeferredRangeProofs {
+            gens,
+            equations: Vec::new(),
+        };
+        verify_cdi_worker(
+            global_context,
+            ip_info,
+            known_ars,
+            cdi,
+            new_or_existing,
+            &mut range_proofs,
+        )
+        .map(|()| range_proofs.equations)
+    });
+    let mut batch = RangeProofBatch::new(gens, &global_context.on_chain_commitment_key);
+    let mut failed = Vec::new();
+    let mut batched = Vec::with_capacity(cdis.len());
+    for (i, result) in checked.into_iter().enumerate() {
+        match result {
+            Ok(equations) => {
+                for equation in equations {
+                    batch.add_equations(csprng, equation);
+                }
+                batched.push(i);
+            }
+            Err(e) => failed.push((i, e)),
+        }
+    }
+    if !batch.verify() {
+        for i in batched {
+            let (ip_info, cdi, new_or_existing) = &cdis[i];
+            if let Err(e) = verify_cdi(global_context, ip_info, known_ars, cdi, new_or_existing) {
+                failed.push((i, e));
+            }
+        }
+        failed.sort_by_key(|(i, _)| *i);
+    }
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(failed)
+    }
+}
+
+fn verify_cdi_worker<
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+    A: HasArPublicKey<C>,
+>(
+    global_context: &GlobalContext<C>,
+    ip_info: &IpInfo<P>,
+    known_ars: &BTreeMap<ArIdentity, A>,
+    cdi: &CredentialDeploymentInfo<P, C, AttributeType>,
+    new_or_existing: &Either<TransactionTime, AccountAddress>,
+    range_proofs: &mut impl RangeProofVerifier<C>,
 ) -> Result<(), CdiVerificationError> {
     // We need to check that the threshold is actually equal to
     // the number of coefficients in the sharing polynomial
@@ -75,7 +244,6 @@ pub fn verify_cdi<
         return Err(CdiVerificationError::Ar);
     }
     let on_chain_commitment_key = global_context.on_chain_commitment_key;
-    let gens = global_context.bulletproof_generators();
     let ip_verify_key = &ip_info.ip_verify_key;
     // Compute the challenge prefix by hashing the values.
     let mut ro = RandomOracle::domain("credential");
@@ -152,17 +320,71 @@ pub fn verify_cdi<
         return Err(CdiVerificationError::Proof);
     }
 
-    if !verify_less_than_or_equal(
-        &mut ro,
-        8,
-        &cdi.proofs.id_proofs.commitments.cmm_cred_counter,
-        &cdi.proofs.id_proofs.commitments.cmm_max_accounts,
-        &cdi.proofs.id_proofs.cred_counter_less_than_max_accounts,
-        &gens,
-        &on_chain_commitment_key,
-    ) {
+    // The proof that cred_counter <= max_accounts, which is a range proof for
+    // max_accounts - cred_counter and cred_counter.
+    let cmm_max_minus_counter = Commitment(
+        commitments
+            .cmm_max_accounts
+            .minus_point(&commitments.cmm_cred_counter),
+    );
+    let counter_commitments = [cmm_max_minus_counter, commitments.cmm_cred_counter];
+    let id_proofs = &cdi.proofs.id_proofs;
+    if id_proofs.range_proofs_version == ID_RANGE_PROOFS_VERSION_0 {
+        if !range_proofs.verify_range_proof(
+            &mut ro,
+            8,
+            &counter_commitments,
+            &id_proofs.cred_counter_less_than_max_accounts,
+        ) {
+            return Err(CdiVerificationError::Proof);
+        }
+
+        // The proofs of the policy statements continue the same transcript.
+        if !verify_policy(
+            &mut ro,
+            range_proofs,
+            &on_chain_commitment_key,
+            &commitments,
+            &cdi.values.policy,
+            &id_proofs.policy_proofs,
+            None,
+        ) {
+            return Err(CdiVerificationError::Policy);
+        }
+    } else if id_proofs.range_proofs_version == ID_RANGE_PROOFS_VERSION_1 {
+        // The policy statements that are not in-range statements are proved
+        // first, and the in-range statements are proved by the aggregated
+        // range proof together with the credential counter.
+        let mut aggregated_commitments = counter_commitments.to_vec();
+        if !verify_policy(
+            &mut ro,
+            range_proofs,
+            &on_chain_commitment_key,
+            &commitments,
+            &cdi.values.policy,
+            &id_proofs.policy_proofs,
+            Some(&mut aggregated_commitments),
+        ) {
+            return Err(CdiVerificationError::Policy);
+        }
+        // The padding added by the prover, commitments to 0 with randomness 0.
+        let num_values = aggregated_commitments.len().next_power_of_two();
+        if num_values > 128 {
+            return Err(CdiVerificationError::Proof);
+        }
+        aggregated_commitments.resize(num_values, Commitment(C::zero_point()));
+        if !range_proofs.verify_range_proof(
+            &mut ro,
+            64,
+            &aggregated_commitments,
+            &id_proofs.cred_counter_less_than_max_accounts,
+        ) {
+            return Err(CdiVerificationError::Proof);
+        }
+    } else {
         return Err(CdiVerificationError::Proof);
     }
+
     let signed = utils::credential_hash_to_sign(&cdv, &proofs.id_proofs, new_or_existing);
     // Notice that here we provide all the verification keys, and the
     // function `verify_accunt_ownership_proof` assumes that
@@ -176,12 +398,6 @@ pub fn verify_cdi<
         return Err(CdiVerificationError::AccountOwnership);
     }
 
-    let check_policy = verify_policy(&on_chain_commitment_key, &commitments, &cdi.values.policy);
-
-    if !check_policy {
-        return Err(CdiVerificationError::Policy);
-    }
-
     Ok(())
 }
 
@@ -214,7 +430,7 @@ fn id_cred_pub_verifier<C: Curve, A: HasArPublicKey<C>>(
     cmm_sharing_coeff: &[Commitment<C>],
     proof_id_cred_pub: &BTreeMap<ArIdentity, com_enc_eq::Witness<C>>,
 ) -> Result<IdCredPubVerifiers<C>, CdiVerificationError> {
-    let mut provers = Vec::with_capacity(proof_id_cred_pub.len());
+    let mut shares = Vec::with_capacity(proof_id_cred_pub.len());
     let mut witnesses = Vec::with_capacity(proof_id_cred_pub.len());
 
     // The encryptions and the proofs have to match.
@@ -229,38 +445,165 @@ fn id_cred_pub_verifier<C: Curve, A: HasArPublicKey<C>>(
         if ar_id != ar_id_1 {
             return Err(CdiVerificationError::IdCredPub);
         }
-        let cmm_share = utils::commitment_to_share(&ar_id.to_scalar::<C>(), cmm_sharing_coeff);
-
         // finding the correct AR data.
         let ar_info = known_ars
             .get(ar_id)
             .ok_or(CdiVerificationError::IdCredPub)?;
-        let item_prover = com_enc_eq::ComEncEq {
-            cipher: ar_data.enc_id_cred_pub_share,
-            commitment: cmm_share,
-            pub_key: *ar_info.get_public_key(),
-            cmm_key: *commitment_key,
-            encryption_in_exponent_generator: ar_info.get_public_key().generator,
-        };
-        provers.push(item_prover);
+        shares.push((
+            ar_id.to_scalar::<C>(),
+            ar_data.enc_id_cred_pub_share,
+            *ar_info.get_public_key(),
+        ));
         witnesses.push(witness.clone());
     }
+    // Computing the commitments to the shares is independent for each AR.
+    let provers = utils::map_maybe_parallel(&shares, |(share_number, cipher, pub_key)| {
+        com_enc_eq::ComEncEq {
+            cipher: *cipher,
+            commitment: utils::commitment_to_share(share_number, cmm_sharing_coeff),
+            pub_key: *pub_key,
+            cmm_key: *commitment_key,
+            encryption_in_exponent_generator: pub_key.generator,
+        }
+    });
     Ok((ReplicateAdapter { protocols: provers }, ReplicateWitness {
         witnesses,
     }))
 }
 
-/// Verify a policy. This currently does not do anything since
-/// the only check that is done is that the commitments are opened correctly,
-/// and that check is part of the signature check.
+/// Verify a policy. Revealed attributes need no further checks here since
+/// checking that they are opened correctly is part of the signature check.
+/// What remains is to verify the proofs of the statements in the policy.
+/// Statements can only be made about attributes that are not revealed, i.e.,
+/// those that there are commitments to.
+///
+/// If `aggregated` is given, the in-range statements must be proved by the
+/// aggregated range proof, see [ID_RANGE_PROOFS_VERSION_1], and the
+/// commitments to the values of their range proofs are appended to it.
+/// Otherwise all statements must have their own proofs.
 fn verify_policy<C: Curve, AttributeType: Attribute<C::Scalar>>(
-    _commitment_key: &CommitmentKey<C>,
-    _commitments: &CredentialDeploymentCommitments<C>,
-    _policy: &Policy<C, AttributeType>,
+    ro: &mut RandomOracle,
+    range_proofs: &mut impl RangeProofVerifier<C>,
+    commitment_key: &CommitmentKey<C>,
+    commitments: &CredentialDeploymentCommitments<C>,
+    policy: &Policy<C, AttributeType>,
+    policy_proofs: &[AttributeProof<C>],
+    mut aggregated: Option<&mut Vec<Commitment<C>>>,
 ) -> bool {
+    if policy.statements.len() != policy_proofs.len() {
+        return false;
+    }
+    for (statement, proof) in policy.statements.iter().zip(policy_proofs) {
+        let commitment = match commitments.cmm_attributes.get(&statement.attribute_tag()) {
+            Some(commitment) => commitment,
+            None => return false,
+        };
+        if let Some(aggregated) = aggregated.as_mut() {
+            match (statement, proof) {
+                (
+                    AttributeStatement::AttributeInRange(statement),
+                    AttributeProof::AttributeInAggregatedRange,
+                ) => {
+                    aggregated.extend_from_slice(&attribute_in_range_commitments(
+                        commitment_key,
+                        statement,
+                        commitment,
+                    ));
+                    continue;
+                }
+                (AttributeStatement::AttributeInRange(_), _) => return false,
+                _ => {}
+            }
+        }
+        if !verify_attribute_statement_worker(
+            ro,
+            range_proofs,
+            commitment_key,
+            statement,
+            commitment,
+            proof,
+        ) {
+            return false;
+        }
+    }
     true
 }
 
+/// Verify the proof of a statement about an attribute with respect to the
+/// commitment to the attribute. This is the counterpart of
+/// [prove_attribute_statement](crate::account_holder::prove_attribute_statement).
+pub fn verify_attribute_statement<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    ro: &mut RandomOracle,
+    gens: &Generators<C>,
+    commitment_key: &CommitmentKey<C>,
+    statement: &AttributeStatement<C, AttributeType>,
+    commitment: &Commitment<C>,
+    proof: &AttributeProof<C>,
+) -> bool {
+    let mut range_proofs = SingleRangeProofs {
+        gens,
+        commitment_key,
+    };
+    verify_attribute_statement_worker(
+        ro,
+        &mut range_proofs,
+        commitment_key,
+        statement,
+        commitment,
+        proof,
+    )
+}
+
+fn verify_attribute_statement_worker<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    ro: &mut RandomOracle,
+    range_proofs: &mut impl RangeProofVerifier<C>,
+    commitment_key: &CommitmentKey<C>,
+    statement: &AttributeStatement<C, AttributeType>,
+    commitment: &Commitment<C>,
+    proof: &AttributeProof<C>,
+) -> bool {
+    match (statement, proof) {
+        (
+            AttributeStatement::AttributeInRange(statement),
+            AttributeProof::AttributeInRange(proof),
+        ) => {
+            let commitments = attribute_in_range_commitments(commitment_key, statement, commitment);
+            range_proofs.verify_range_proof(ro, 64, &commitments, proof)
+        }
+        (AttributeStatement::AttributeInSet(statement), AttributeProof::AttributeInSet(proof)) => {
+            let set = statement.set.iter().map(|a| a.to_field_element()).collect();
+            com_set::verify_membership(ro, commitment_key, *commitment, set, proof)
+        }
+        (
+            AttributeStatement::AttributeNotInSet(statement),
+            AttributeProof::AttributeNotInSet(proof),
+        ) => {
+            let set = statement.set.iter().map(|a| a.to_field_element()).collect();
+            com_set::verify_non_membership(ro, commitment_key, *commitment, set, proof)
+        }
+        _ => false,
+    }
+}
+
+/// The commitments to `value - lower` and `upper - 1 - value`, respectively,
+/// derived from the commitment to the attribute. Their range proof shows that
+/// the attribute is in the range of the statement.
+fn attribute_in_range_commitments<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    commitment_key: &CommitmentKey<C>,
+    statement: &AttributeInRangeStatement<C, AttributeType>,
+    commitment: &Commitment<C>,
+) -> [Commitment<C>; 2] {
+    let zero = Randomness::<C>::zero();
+    let cmm_lower = commitment_key.hide_worker(&statement.lower.to_field_element(), &zero);
+    let mut upper_minus_one = statement.upper.to_field_element();
+    upper_minus_one.sub_assign(&C::Scalar::one());
+    let cmm_upper = commitment_key.hide_worker(&upper_minus_one, &zero);
+    [
+        Commitment(commitment.minus_point(&cmm_lower)),
+        Commitment(cmm_upper.minus_point(commitment)),
+    ]
+}
+
 /// Verify the proof of knowledge of signature on the attribute list.
 /// A none return value means we cannot construct a verifier, and consequently
 /// it should be interperted as the signature being invalid.
@@ -352,8 +695,11 @@ mod tests {
     use super::*;
 
     use crate::{account_holder::*, constants::*, identity_provider::*, test::*};
-    use crypto_common::types::{KeyIndex, KeyPair};
-    use pairing::bls12_381::G1;
+    use crypto_common::{
+        serialize_deserialize,
+        types::{KeyIndex, KeyPair},
+    };
+    use curve_arithmetic::bls12_381::G1;
     use rand::*;
     use std::collections::btree_map::BTreeMap;
     use Either::{Left, Right};
@@ -425,6 +771,7 @@ mod tests {
                 tree.insert(AttributeTag::from(8u8), AttributeKind::from(31));
                 tree
             },
+            statements: Vec::new(),
             _phantom: Default::default(),
         };
         let cred_data = CredentialData {
@@ -483,6 +830,516 @@ mod tests {
         assert_eq!(cdi_check, Ok(()));
     }
 
+    #[test]
+    fn test_verify_cdi_with_statements() {
+        let mut csprng = thread_rng();
+
+        let max_attrs = 10;
+        let num_ars = 5;
+        let IpData {
+            public_ip_info: ip_info,
+            ip_secret_key,
+            ip_cdi_secret_key,
+        } = test_create_ip_info(&mut csprng, num_ars, max_attrs);
+        let global_ctx = GlobalContext::<G1>::generate(String::from("genesis_string"));
+        let (ars_infos, _) =
+            test_create_ars(&global_ctx.on_chain_commitment_key.g, num_ars, &mut csprng);
+        let aci = test_create_aci(&mut csprng);
+        let initial_acc_data = InitialAccountData {
+            keys:      {
+                let mut keys = BTreeMap::new();
+                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
+                keys
+            },
+            threshold: SignatureThreshold(1),
+        };
+        let (context, pio, randomness) = test_create_pio(
+            &aci,
+            &ip_info,
+            &ars_infos,
+            &global_ctx,
+            num_ars,
+            &initial_acc_data,
+        );
+        let alist = test_create_attributes();
+        let (ip_sig, _) = verify_credentials(
+            &pio,
+            context,
+            &alist,
+            EXPIRY,
+            &ip_secret_key,
+            &ip_cdi_secret_key,
+        )
+        .expect("Signing the identity object should succeed.");
+        let id_object = IdentityObject {
+            pre_identity_object: pio,
+            alist,
+            signature: ip_sig,
+        };
+        let id_use_data = IdObjectUseData { aci, randomness };
+        // Attribute 0 has value 55, attribute 8 (value 31) is revealed.
+        let policy = |statements| Policy {
+            valid_to: YearMonth::new(2022, 5).unwrap(),
+            created_at: YearMonth::new(2020, 5).unwrap(),
+            policy_vec: {
+                let mut tree = BTreeMap::new();
+                tree.insert(AttributeTag::from(8u8), AttributeKind::from(31));
+                tree
+            },
+            statements,
+            _phantom: Default::default(),
+        };
+        let range_policy = |lower: u64, upper: u64| {
+            policy(vec![AttributeStatement::AttributeInRange(
+                AttributeInRangeStatement {
+                    attribute_tag: AttributeTag::from(0u8),
+                    lower:         AttributeKind::from(lower),
+                    upper:         AttributeKind::from(upper),
+                    _phantom:      Default::default(),
+                },
+            )])
+        };
+        let set_statement = |set: &[u64]| AttributeSetStatement {
+            attribute_tag: AttributeTag::from(0u8),
+            set:           set.iter().map(|&x| AttributeKind::from(x)).collect(),
+            _phantom:      Default::default(),
+        };
+        let cred_data = CredentialData {
+            keys:      {
+                let mut keys = BTreeMap::new();
+                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
+                keys
+            },
+            threshold: SignatureThreshold(1),
+        };
+        let (cdi, _) = create_credential(
+            context,
+            &id_object,
+            &id_use_data,
+            0,
+            range_policy(18, 65),
+            &cred_data,
+            &Left(EXPIRY),
+        )
+        .expect("Should generate the credential successfully.");
+        let cdi_check = verify_cdi(&global_ctx, &ip_info, &ars_infos, &cdi, &Left(EXPIRY));
+        assert_eq!(cdi_check, Ok(()));
+
+        // The statement and its proof survive a serialization roundtrip.
+        let des = serialize_deserialize(&cdi).expect("Deserialization must be successful.");
+        assert_eq!(des.values.policy, cdi.values.policy);
+        let cdi_check = verify_cdi(&global_ctx, &ip_info, &ars_infos, &des, &Left(EXPIRY));
+        assert_eq!(cdi_check, Ok(()));
+
+        // Changing the statement invalidates the proofs.
+        let mut wrong_cdi = des;
+        wrong_cdi.values.policy = range_policy(18, 30);
+        let cdi_check = verify_cdi(&global_ctx, &ip_info, &ars_infos, &wrong_cdi, &Left(EXPIRY));
+        assert_ne!(cdi_check, Ok(()));
+
+        // The attribute is not in the range, so no proof can be produced.
+        assert!(create_credential(
+            context,
+            &id_object,
+            &id_use_data,
+            0,
+            range_policy(56, 65),
+            &cred_data,
+            &Left(EXPIRY),
+        )
+        .is_err());
+
+        // Set membership and non-membership, together with a range statement.
+        let set_policy = policy(vec![
+            AttributeStatement::AttributeInSet(set_statement(&[12, 55, 70])),
+            AttributeStatement::AttributeNotInSet(set_statement(&[54, 56])),
+            AttributeStatement::AttributeInRange(AttributeInRangeStatement {
+                attribute_tag: AttributeTag::from(0u8),
+                lower:         AttributeKind::from(18),
+                upper:         AttributeKind::from(65),
+                _phantom:      Default::default(),
+            }),
+        ]);
+        let (cdi, _) = create_credential(
+            context,
+            &id_object,
+            &id_use_data,
+            0,
+            set_policy,
+            &cred_data,
+            &Left(EXPIRY),
+        )
+        .expect("Should generate the credential successfully.");
+        let des = serialize_deserialize(&cdi).expect("Deserialization must be successful.");
+        let cdi_check = verify_cdi(&global_ctx, &ip_info, &ars_infos, &des, &Left(EXPIRY));
+        assert_eq!(cdi_check, Ok(()));
+
+        // Proofs do not verify if they are in the wrong order.
+        let mut wrong_cdi = des;
+        wrong_cdi.proofs.id_proofs.policy_proofs.swap(0, 1);
+        let cdi_check = verify_cdi(&global_ctx, &ip_info, &ars_infos, &wrong_cdi, &Left(EXPIRY));
+        assert_eq!(cdi_check, Err(CdiVerificationError::Policy));
+
+        // False set statements cannot be proved.
+        for statement in [
+            AttributeStatement::AttributeInSet(set_statement(&[12, 54, 70])),
+            AttributeStatement::AttributeNotInSet(set_statement(&[54, 55])),
+        ] {
+            assert!(create_credential(
+                context,
+                &id_object,
+                &id_use_data,
+                0,
+                policy(vec![statement]),
+                &cred_data,
+                &Left(EXPIRY),
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_verify_cdi_aggregated_range_proofs() {
+        let mut csprng = thread_rng();
+
+        let max_attrs = 10;
+        let num_ars = 5;
+        let IpData {
+            public_ip_info: ip_info,
+            ip_secret_key,
+            ip_cdi_secret_key,
+        } = test_create_ip_info(&mut csprng, num_ars, max_attrs);
+        // Enough generators to aggregate the credential counter with two
+        // in-range statements, which needs 8 values of 64 bits.
+        let global_ctx = GlobalContext::<G1>::generate_size(String::from("genesis_string"), 512);
+        let (ars_infos, _) =
+            test_create_ars(&global_ctx.on_chain_commitment_key.g, num_ars, &mut csprng);
+        let aci = test_create_aci(&mut csprng);
+        let initial_acc_data = InitialAccountData {
+            keys:      {
+                let mut keys = BTreeMap::new();
+                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
+                keys
+            },
+            threshold: SignatureThreshold(1),
+        };
+        let (context, pio, randomness) = test_create_pio(
+            &aci,
+            &ip_info,
+            &ars_infos,
+            &global_ctx,
+            num_ars,
+            &initial_acc_data,
+        );
+        let alist = test_create_attributes();
+        let (ip_sig, _) = verify_credentials(
+            &pio,
+            context,
+            &alist,
+            EXPIRY,
+            &ip_secret_key,
+            &ip_cdi_secret_key,
+        )
+        .expect("Signing the identity object should succeed.");
+        let id_object = IdentityObject {
+            pre_identity_object: pio,
+            alist,
+            signature: ip_sig,
+        };
+        let id_use_data = IdObjectUseData { aci, randomness };
+        // Attribute 0 has value 55.
+        let policy = |statements| Policy {
+            valid_to: YearMonth::new(2022, 5).unwrap(),
+            created_at: YearMonth::new(2020, 5).unwrap(),
+            policy_vec: BTreeMap::new(),
+            statements,
+            _phantom: Default::default(),
+        };
+        let range_statement = |lower: u64, upper: u64| {
+            AttributeStatement::AttributeInRange(AttributeInRangeStatement {
+                attribute_tag: AttributeTag::from(0u8),
+                lower:         AttributeKind::from(lower),
+                upper:         AttributeKind::from(upper),
+                _phantom:      Default::default(),
+            })
+        };
+        let cred_data = CredentialData {
+            keys:      {
+                let mut keys = BTreeMap::new();
+                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
+                keys
+            },
+            threshold: SignatureThreshold(1),
+        };
+
+        let (cdi, _) = create_credential_aggregated(
+            context,
+            &id_object,
+            &id_use_data,
+            0,
+            policy(vec![range_statement(18, 65)]),
+            &cred_data,
+            &Left(EXPIRY),
+        )
+        .expect("Should generate the credential successfully.");
+        let des = serialize_deserialize(&cdi).expect("Deserialization must be successful.");
+        assert_eq!(
+            des.proofs.id_proofs.range_proofs_version,
+            ID_RANGE_PROOFS_VERSION_1
+        );
+        assert!(matches!(des.proofs.id_proofs.policy_proofs[..], [
+            AttributeProof::AttributeInAggregatedRange
+        ]));
+        let cdi_check = verify_cdi(&global_ctx, &ip_info, &ars_infos, &des, &Left(EXPIRY));
+        assert_eq!(cdi_check, Ok(()));
+
+        // The identity ownership proofs also round-trip on their own.
+        let id_proofs = serialize_deserialize(&cdi.proofs.id_proofs)
+            .expect("Deserialization must be successful.");
+        assert_eq!(id_proofs.range_proofs_version, ID_RANGE_PROOFS_VERSION_1);
+        assert_eq!(to_bytes(&id_proofs), to_bytes(&cdi.proofs.id_proofs));
+
+        // The aggregated proofs are smaller than the separate ones.
+        let (separate_cdi, _) = create_credential(
+            context,
+            &id_object,
+            &id_use_data,
+            0,
+            policy(vec![range_statement(18, 65)]),
+            &cred_data,
+            &Left(EXPIRY),
+        )
+        .expect("Should generate the credential successfully.");
+        assert!(to_bytes(&cdi.proofs).len() < to_bytes(&separate_cdi.proofs).len());
+
+        // Proofs in one format are not valid in the other.
+        let mut wrong_cdi = des;
+        wrong_cdi.proofs.id_proofs.range_proofs_version = ID_RANGE_PROOFS_VERSION_0;
+        let cdi_check = verify_cdi(&global_ctx, &ip_info, &ars_infos, &wrong_cdi, &Left(EXPIRY));
+        assert_ne!(cdi_check, Ok(()));
+        wrong_cdi.proofs.id_proofs.range_proofs_version = ID_RANGE_PROOFS_VERSION_1;
+        wrong_cdi.proofs.id_proofs.policy_proofs =
+            separate_cdi.proofs.id_proofs.policy_proofs.clone();
+        let cdi_check = verify_cdi(&global_ctx, &ip_info, &ars_infos, &wrong_cdi, &Left(EXPIRY));
+        assert_eq!(cdi_check, Err(CdiVerificationError::Policy));
+
+        // Changing the statement invalidates the aggregated proof.
+        let mut wrong_cdi = serialize_deserialize(&cdi).expect("Deserialization must succeed.");
+        wrong_cdi.values.policy = policy(vec![range_statement(18, 30)]);
+        let cdi_check = verify_cdi(&global_ctx, &ip_info, &ars_infos, &wrong_cdi, &Left(EXPIRY));
+        assert_ne!(cdi_check, Ok(()));
+
+        // Several in-range statements together with a set statement.
+        let (cdi, _) = create_credential_aggregated(
+            context,
+            &id_object,
+            &id_use_data,
+            0,
+            policy(vec![
+                range_statement(18, 65),
+                AttributeStatement::AttributeInSet(AttributeSetStatement {
+                    attribute_tag: AttributeTag::from(0u8),
+                    set:           vec![AttributeKind::from(12), AttributeKind::from(55)],
+                    _phantom:      Default::default(),
+                }),
+                range_statement(50, 60),
+            ]),
+            &cred_data,
+            &Left(EXPIRY),
+        )
+        .expect("Should generate the credential successfully.");
+        let des = serialize_deserialize(&cdi).expect("Deserialization must be successful.");
+        let cdi_check = verify_cdi(&global_ctx, &ip_info, &ars_infos, &des, &Left(EXPIRY));
+        assert_eq!(cdi_check, Ok(()));
+        assert_eq!(
+            verify_cdi_batch(
+                &global_ctx,
+                &ars_infos,
+                &[
+                    (&ip_info, &des, Left(EXPIRY)),
+                    (&ip_info, &separate_cdi, Left(EXPIRY))
+                ],
+                &mut csprng
+            ),
+            Ok(())
+        );
+
+        // The attribute is not in one of the ranges, so no proof can be
+        // produced.
+        assert!(create_credential_aggregated(
+            context,
+            &id_object,
+            &id_use_data,
+            0,
+            policy(vec![range_statement(18, 65), range_statement(56, 65)]),
+            &cred_data,
+            &Left(EXPIRY),
+        )
+        .is_err());
+
+        // Four in-range statements need more generators than the context has.
+        assert!(create_credential_aggregated(
+            context,
+            &id_object,
+            &id_use_data,
+            0,
+            policy(vec![
+                range_statement(18, 65),
+                range_statement(50, 60),
+                range_statement(0, 100),
+                range_statement(55, 56)
+            ]),
+            &cred_data,
+            &Left(EXPIRY),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_verify_cdi_batch() {
+        let mut csprng = thread_rng();
+
+        let max_attrs = 10;
+        let num_ars = 3;
+        let IpData {
+            public_ip_info: ip_info,
+            ip_secret_key,
+            ip_cdi_secret_key,
+        } = test_create_ip_info(&mut csprng, num_ars, max_attrs);
+        let global_ctx = GlobalContext::<G1>::generate(String::from("genesis_string"));
+        let (ars_infos, _) =
+            test_create_ars(&global_ctx.on_chain_commitment_key.g, num_ars, &mut csprng);
+        let aci = test_create_aci(&mut csprng);
+        let initial_acc_data = InitialAccountData {
+            keys:      {
+                let mut keys = BTreeMap::new();
+                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
+                keys
+            },
+            threshold: SignatureThreshold(1),
+        };
+        let (context, pio, randomness) = test_create_pio(
+            &aci,
+            &ip_info,
+            &ars_infos,
+            &global_ctx,
+            num_ars,
+            &initial_acc_data,
+        );
+        let alist = test_create_attributes();
+        let (ip_sig, _) = verify_credentials(
+            &pio,
+            context,
+            &alist,
+            EXPIRY,
+            &ip_secret_key,
+            &ip_cdi_secret_key,
+        )
+        .expect("Signing the identity object should succeed.");
+        let id_object = IdentityObject {
+            pre_identity_object: pio,
+            alist,
+            signature: ip_sig,
+        };
+        let id_use_data = IdObjectUseData { aci, randomness };
+        // Attribute 0 has value 55, attribute 8 (value 31) is revealed.
+        let policy = Policy {
+            valid_to:   YearMonth::new(2022, 5).unwrap(),
+            created_at: YearMonth::new(2020, 5).unwrap(),
+            policy_vec: {
+                let mut tree = BTreeMap::new();
+                tree.insert(AttributeTag::from(8u8), AttributeKind::from(31));
+                tree
+            },
+            statements: vec![AttributeStatement::AttributeInRange(
+                AttributeInRangeStatement {
+                    attribute_tag: AttributeTag::from(0u8),
+                    lower:         AttributeKind::from(18),
+                    upper:         AttributeKind::from(65),
+                    _phantom:      Default::default(),
+                },
+            )],
+            _phantom:   Default::default(),
+        };
+        let cred_data = CredentialData {
+            keys:      {
+                let mut keys = BTreeMap::new();
+                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
+                keys
+            },
+            threshold: SignatureThreshold(1),
+        };
+        let cdis = (0..3)
+            .map(|cred_counter| {
+                create_credential(
+                    context,
+                    &id_object,
+                    &id_use_data,
+                    cred_counter,
+                    policy.clone(),
+                    &cred_data,
+                    &Left(EXPIRY),
+                )
+                .expect("Should generate the credential successfully.")
+                .0
+            })
+            .collect::<Vec<_>>();
+        let batch = cdis
+            .iter()
+            .map(|cdi| (&ip_info, cdi, Left(EXPIRY)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            verify_cdi_batch(&global_ctx, &ars_infos, &batch, &mut csprng),
+            Ok(())
+        );
+        assert_eq!(
+            verify_cdi_batch::<IpPairing, _, AttributeKind, _, _>(
+                &global_ctx,
+                &ars_infos,
+                &[],
+                &mut csprng
+            ),
+            Ok(())
+        );
+
+        // A credential whose only invalid part is the range proof of the credential
+        // counter. This is only detected by the combined check, after which the
+        // credentials are checked individually.
+        let mut bad_cdi = cdis[2].clone();
+        bad_cdi.proofs.id_proofs.cred_counter_less_than_max_accounts = cdis[1]
+            .proofs
+            .id_proofs
+            .cred_counter_less_than_max_accounts
+            .clone();
+        bad_cdi.proofs.proof_acc_sk = AccountOwnershipProof {
+            sigs: cred_data.sign(&Left(EXPIRY), &UnsignedCredentialDeploymentInfo {
+                values: bad_cdi.values.clone(),
+                proofs: bad_cdi.proofs.id_proofs.clone(),
+            }),
+        };
+        assert_eq!(
+            verify_cdi(&global_ctx, &ip_info, &ars_infos, &bad_cdi, &Left(EXPIRY)),
+            Err(CdiVerificationError::Proof)
+        );
+        // A credential that fails before the combined check.
+        let wrong_expiry = TransactionTime {
+            seconds: EXPIRY.seconds + 1,
+        };
+        let batch = vec![
+            (&ip_info, &cdis[0], Left(EXPIRY)),
+            (&ip_info, &bad_cdi, Left(EXPIRY)),
+            (&ip_info, &cdis[1], Left(wrong_expiry)),
+            (&ip_info, &cdis[2], Left(EXPIRY)),
+        ];
+        assert_eq!(
+            verify_cdi_batch(&global_ctx, &ars_infos, &batch, &mut csprng),
+            Err(vec![
+                (1, CdiVerificationError::Proof),
+                (2, CdiVerificationError::AccountOwnership)
+            ])
+        );
+    }
+
     #[test]
     fn test_verify_initial_cdi() {
         let mut csprng = thread_rng();
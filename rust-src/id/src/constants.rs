@@ -6,7 +6,6 @@ use crypto_common::{
     Buffer, Deserial, Get, ParseResult, Put, ReadBytesExt, SerdeDeserialize, SerdeSerialize, Serial,
 };
 use curve_arithmetic::{Curve, Pairing};
-use pairing::bls12_381::G1;
 use serde::{
     de::{self, Visitor},
     Deserializer, Serializer,
@@ -15,12 +14,12 @@ use std::{fmt, io::Cursor, str::FromStr};
 use thiserror::Error;
 
 /// Curve used by the anonymity revoker.
-pub type ArCurve = pairing::bls12_381::G1;
+pub type ArCurve = curve_arithmetic::bls12_381::G1;
 /// Pairing used by the identity provider.
-pub type IpPairing = pairing::bls12_381::Bls12;
+pub type IpPairing = curve_arithmetic::bls12_381::Bls12;
 /// Field used by the identity provider and anonymity revoker.
 /// This isthe base field of both the ArCurve and the IpPairing.
-pub type BaseField = <pairing::bls12_381::Bls12 as Pairing>::ScalarField;
+pub type BaseField = <IpPairing as Pairing>::ScalarField;
 
 /// Index used to create the RegId of the initial credential.
 pub const INITIAL_CREDENTIAL_INDEX: u8 = 0;
@@ -107,13 +106,13 @@ impl From<u64> for AttributeKind {
     fn from(x: u64) -> Self { AttributeKind(x.to_string()) }
 }
 
-impl Attribute<<G1 as Curve>::Scalar> for AttributeKind {
-    fn to_field_element(&self) -> <G1 as Curve>::Scalar {
+impl Attribute<<ArCurve as Curve>::Scalar> for AttributeKind {
+    fn to_field_element(&self) -> <ArCurve as Curve>::Scalar {
         let mut buf = [0u8; 32];
         let len = self.0.as_bytes().len();
         buf[1 + (31 - len)..].copy_from_slice(self.0.as_bytes());
         buf[0] = len as u8; // this should be valid because len <= 31 so the first two bits will be unset
-        <<G1 as Curve>::Scalar as Deserial>::deserial(&mut Cursor::new(&buf))
+        <<ArCurve as Curve>::Scalar as Deserial>::deserial(&mut Cursor::new(&buf))
             .expect("31 bytes + length fits into a scalar.")
     }
 }
@@ -4,9 +4,9 @@ use crate::{
     types::*,
 };
 use crypto_common::{size_t, types::TransactionTime, *};
+use curve_arithmetic::bls12_381::{Bls12, G1};
 use either::Either::{Left, Right};
 use ffi_helpers::*;
-use pairing::bls12_381::{Bls12, G1};
 use pedersen_scheme::CommitmentKey as PedersenKey;
 use rand::thread_rng;
 use std::{collections::BTreeMap, convert::TryInto, io::Cursor};
@@ -293,6 +293,7 @@ mod test {
                 tree.insert(AttributeTag::from(0u8), AttributeKind::from(55));
                 tree
             },
+            statements: Vec::new(),
             _phantom: Default::default(),
         };
 
@@ -304,6 +305,7 @@ mod test {
                 tree.insert(AttributeTag::from(0u8), AttributeKind::from(5));
                 tree
             },
+            statements: Vec::new(),
             _phantom: Default::default(),
         };
 
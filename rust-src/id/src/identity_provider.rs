@@ -278,37 +278,45 @@ fn compute_prf_sharing_verifier<C: Curve>(
     known_ars: &BTreeMap<ArIdentity, ArInfo<C>>,
     encryption_in_exponent_generator: &C,
 ) -> Option<IdCredPubVerifiers<C>> {
-    let mut verifiers = Vec::with_capacity(ip_ar_data.len());
+    let mut shares = Vec::with_capacity(ip_ar_data.len());
     let mut witnesses = Vec::with_capacity(ip_ar_data.len());
 
     for (ar_id, ar_data) in ip_ar_data.iter() {
-        let cmm_share = utils::commitment_to_share(&ar_id.to_scalar::<C>(), cmm_sharing_coeff);
         // finding the right encryption key
+        let ar_info = known_ars.get(ar_id)?;
+        shares.push((
+            ar_id.to_scalar::<C>(),
+            &ar_data.enc_prf_key_share,
+            ar_info.ar_public_key,
+        ));
+        // TODO: Figure out whether we can somehow get rid of this clone.
+        witnesses.push(ar_data.proof_com_enc_eq.clone())
+    }
+
+    // The verifiers for different ARs are independent of each other.
+    let verifiers = utils::map_maybe_parallel(&shares, |(share_number, ciphers, pub_key)| {
+        let cmm_share = utils::commitment_to_share(share_number, cmm_sharing_coeff);
 
         // Take linear combination of ciphers
         let u8_chunk_size = u8::from(CHUNK_SIZE);
         let two_chunksize = C::scalar_from_u64(1 << u8_chunk_size);
         let mut power_of_two = C::Scalar::one();
 
-        let mut scalars = Vec::with_capacity(ar_data.enc_prf_key_share.len());
-        for _ in 0..ar_data.enc_prf_key_share.len() {
+        let mut scalars = Vec::with_capacity(ciphers.len());
+        for _ in 0..ciphers.len() {
             scalars.push(power_of_two);
             power_of_two.mul_assign(&two_chunksize);
         }
-        let combined_ciphers = multicombine(&ar_data.enc_prf_key_share, &scalars);
+        let combined_ciphers = multicombine(&ciphers[..], &scalars);
 
-        let ar_info = known_ars.get(ar_id)?;
-        let verifier = com_enc_eq::ComEncEq {
+        com_enc_eq::ComEncEq {
             cipher: combined_ciphers,
             commitment: cmm_share,
-            pub_key: ar_info.ar_public_key,
+            pub_key: *pub_key,
             cmm_key: *ar_commitment_key,
             encryption_in_exponent_generator: *encryption_in_exponent_generator,
-        };
-        verifiers.push(verifier);
-        // TODO: Figure out whether we can somehow get rid of this clone.
-        witnesses.push(ar_data.proof_com_enc_eq.clone())
-    }
+        }
+    });
     Some((
         ReplicateAdapter {
             protocols: verifiers,
@@ -366,6 +374,7 @@ pub fn create_initial_cdi<
         valid_to:   alist.valid_to,
         created_at: alist.created_at,
         policy_vec: BTreeMap::new(),
+        statements: Vec::new(),
         _phantom:   Default::default(),
     };
     let cred_values = InitialCredentialDeploymentValues {
@@ -8,6 +8,7 @@ pub mod constants;
 #[cfg(feature = "ffi")]
 mod ffi;
 pub mod identity_provider;
+pub mod presentation;
 pub mod secret_sharing;
 pub mod sigma_protocols;
 pub mod types;
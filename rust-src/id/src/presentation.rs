@@ -0,0 +1,409 @@
+//! Presentations of attributes of an existing credential.
+//!
+//! A service (the verifier) sends a [PresentationRequest] to the account
+//! holder, asking them to reveal some attributes and prove some statements
+//! about others. The account holder responds with a [Presentation] which
+//! proves the request with respect to the attribute commitments that were
+//! part of the credential deployment, using the randomness that was used to
+//! make them (see [CommitmentsRandomness]).
+//!
+//! All the proofs are bound to the challenge in the request, the credential
+//! registration id, and the request itself. The verifier must choose a fresh
+//! challenge for each request so that presentations cannot be replayed.
+use crate::{
+    account_holder::prove_attribute_statement,
+    chain::verify_attribute_statement,
+    sigma_protocols::{common::*, dlog},
+    types::*,
+};
+use anyhow::bail;
+use crypto_common::*;
+use crypto_common_derive::*;
+use curve_arithmetic::{Curve, Value};
+use pedersen_scheme::{Commitment, CommitmentKey};
+use rand::*;
+use random_oracle::RandomOracle;
+use std::collections::{BTreeMap, BTreeSet};
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(
+    serialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeSerialize",
+    deserialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeDeserialize<'de>"
+))]
+/// A request for a presentation, made by the verifier.
+pub struct PresentationRequest<C: Curve, AttributeType: Attribute<C::Scalar>> {
+    /// Challenge chosen by the verifier. This should be fresh for each request
+    /// to prevent replay of presentations.
+    #[serde(
+        rename = "challenge",
+        serialize_with = "base16_encode",
+        deserialize_with = "base16_decode"
+    )]
+    pub challenge:           [u8; 32],
+    /// Attributes whose values should be revealed.
+    #[serde(rename = "revealedAttributes")]
+    #[set_size_length = 2]
+    pub revealed_attributes: BTreeSet<AttributeTag>,
+    /// Statements about attributes that should be proved without revealing
+    /// their values.
+    #[serde(rename = "statements")]
+    #[size_length = 2]
+    pub statements:          Vec<AttributeStatement<C, AttributeType>>,
+}
+
+#[derive(Debug, Clone, Serialize, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(
+    serialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeSerialize",
+    deserialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeDeserialize<'de>"
+))]
+/// A revealed attribute value together with a proof that it is the value
+/// committed to in the credential.
+pub struct RevealedAttribute<C: Curve, AttributeType: Attribute<C::Scalar>> {
+    #[serde(rename = "value")]
+    pub value: AttributeType,
+    /// Proof of knowledge of the randomness of the commitment, with the
+    /// revealed value factored out.
+    #[serde(rename = "proof")]
+    pub proof: dlog::Proof<C>,
+}
+
+#[derive(Debug, Clone, Serialize, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(
+    serialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeSerialize",
+    deserialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeDeserialize<'de>"
+))]
+/// The account holder's response to a [PresentationRequest].
+pub struct Presentation<C: Curve, AttributeType: Attribute<C::Scalar>> {
+    /// The revealed attributes, for each attribute in the request.
+    #[serde(rename = "revealedAttributes")]
+    #[map_size_length = 2]
+    pub revealed_attributes: BTreeMap<AttributeTag, RevealedAttribute<C, AttributeType>>,
+    /// Proofs of the statements in the request, in the same order.
+    #[serde(
+        rename = "proofs",
+        serialize_with = "base16_encode",
+        deserialize_with = "base16_decode"
+    )]
+    #[size_length = 2]
+    pub proofs:              Vec<AttributeProof<C>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+/// Reasons why verification of a presentation can fail.
+pub enum PresentationVerificationError {
+    #[error("The presentation does not answer the request.")]
+    RequestMismatch,
+    #[error("The credential has no commitment to attribute {0}.")]
+    MissingCommitment(AttributeTag),
+    #[error("The revealed value of attribute {0} is not valid.")]
+    RevealedAttribute(AttributeTag),
+    #[error("The proof of the statement about attribute {0} is not valid.")]
+    Statement(AttributeTag),
+}
+
+/// Construct the random oracle that all the proofs of a presentation are bound
+/// to.
+fn presentation_ro<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    global_context: &GlobalContext<C>,
+    cred_id: &C,
+    request: &PresentationRequest<C, AttributeType>,
+) -> RandomOracle {
+    let mut ro = RandomOracle::domain("presentation");
+    ro.append_message(b"cred_id", cred_id);
+    ro.append_message(b"global_context", global_context);
+    ro.append_message(b"request", request);
+    ro
+}
+
+/// The statement that the commitment minus the revealed value is a
+/// commitment to 0, i.e., a power of `h`.
+fn reveal_protocol<C: Curve>(
+    cmm_key: &CommitmentKey<C>,
+    commitment: &Commitment<C>,
+    value: &C::Scalar,
+) -> dlog::Dlog<C> {
+    dlog::Dlog {
+        public: commitment.minus_point(&cmm_key.g.mul_by_scalar(value)),
+        coeff:  cmm_key.h,
+    }
+}
+
+/// Produce a presentation for the given request. The attribute list must be
+/// the one from the identity object the credential was created from, and the
+/// randomness must be the one returned when the credential was created. Only
+/// attributes that were not revealed in the credential can be used.
+pub fn prove_presentation<C: Curve, AttributeType: Attribute<C::Scalar>, R: Rng>(
+    global_context: &GlobalContext<C>,
+    request: &PresentationRequest<C, AttributeType>,
+    values: &CredentialDeploymentValues<C, AttributeType>,
+    alist: &AttributeList<C::Scalar, AttributeType>,
+    randomness: &CommitmentsRandomness<C>,
+    csprng: &mut R,
+) -> anyhow::Result<Presentation<C, AttributeType>> {
+    let cmm_key = &global_context.on_chain_commitment_key;
+    let lookup =
+        |tag: &AttributeTag| match (alist.alist.get(tag), randomness.attributes_rand.get(tag)) {
+            (Some(attribute), Some(rand)) => Ok((attribute, rand)),
+            _ => bail!("There is no commitment to attribute {}.", tag),
+        };
+    let mut ro = presentation_ro(global_context, &values.cred_id, request);
+
+    let mut revealed_attributes = BTreeMap::new();
+    for tag in request.revealed_attributes.iter() {
+        let (attribute, rand) = lookup(tag)?;
+        let value = attribute.to_field_element();
+        let commitment = cmm_key.hide_worker(&value, rand);
+        let secret = dlog::DlogSecret {
+            secret: Value::new(**rand),
+        };
+        let proof = match prove(
+            &mut ro,
+            &reveal_protocol(cmm_key, &commitment, &value),
+            secret,
+            csprng,
+        ) {
+            Some(proof) => proof,
+            None => bail!("Cannot prove the value of attribute {}.", tag),
+        };
+        revealed_attributes.insert(*tag, RevealedAttribute {
+            value: attribute.clone(),
+            proof,
+        });
+    }
+
+    let mut proofs = Vec::with_capacity(request.statements.len());
+    for statement in request.statements.iter() {
+        let tag = statement.attribute_tag();
+        let (attribute, rand) = lookup(&tag)?;
+        match prove_attribute_statement(
+            &mut ro,
+            csprng,
+            global_context.bulletproof_generators(),
+            cmm_key,
+            statement,
+            attribute,
+            rand,
+        ) {
+            Some(proof) => proofs.push(proof),
+            None => bail!("Cannot prove the statement about attribute {}.", tag),
+        }
+    }
+    Ok(Presentation {
+        revealed_attributes,
+        proofs,
+    })
+}
+
+/// Verify a presentation with respect to the request, and the values and
+/// commitments of the credential the presentation is about.
+pub fn verify_presentation<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    global_context: &GlobalContext<C>,
+    request: &PresentationRequest<C, AttributeType>,
+    values: &CredentialDeploymentValues<C, AttributeType>,
+    commitments: &CredentialDeploymentCommitments<C>,
+    presentation: &Presentation<C, AttributeType>,
+) -> Result<(), PresentationVerificationError> {
+    if !request
+        .revealed_attributes
+        .iter()
+        .eq(presentation.revealed_attributes.keys())
+        || request.statements.len() != presentation.proofs.len()
+    {
+        return Err(PresentationVerificationError::RequestMismatch);
+    }
+    let cmm_key = &global_context.on_chain_commitment_key;
+    let lookup = |tag: &AttributeTag| {
+        commitments
+            .cmm_attributes
+            .get(tag)
+            .ok_or(PresentationVerificationError::MissingCommitment(*tag))
+    };
+    let mut ro = presentation_ro(global_context, &values.cred_id, request);
+
+    for (tag, revealed) in presentation.revealed_attributes.iter() {
+        let commitment = lookup(tag)?;
+        let value = revealed.value.to_field_element();
+        if !verify(
+            &mut ro,
+            &reveal_protocol(cmm_key, commitment, &value),
+            &revealed.proof,
+        ) {
+            return Err(PresentationVerificationError::RevealedAttribute(*tag));
+        }
+    }
+
+    let gens = global_context.bulletproof_generators();
+    for (statement, proof) in request.statements.iter().zip(presentation.proofs.iter()) {
+        let tag = statement.attribute_tag();
+        let commitment = lookup(&tag)?;
+        if !verify_attribute_statement(&mut ro, gens, cmm_key, statement, commitment, proof) {
+            return Err(PresentationVerificationError::Statement(tag));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{account_holder::*, constants::*, identity_provider::*, test::*};
+    use crypto_common::types::{KeyIndex, KeyPair, TransactionTime};
+    use curve_arithmetic::bls12_381::G1;
+    use either::Either::Left;
+
+    const EXPIRY: TransactionTime = TransactionTime {
+        seconds: 111111111111111111,
+    };
+
+    #[test]
+    fn test_presentation() {
+        let mut csprng = thread_rng();
+
+        let num_ars = 3;
+        let IpData {
+            public_ip_info: ip_info,
+            ip_secret_key,
+            ip_cdi_secret_key,
+        } = test_create_ip_info(&mut csprng, num_ars, 10);
+        let global_ctx = GlobalContext::<G1>::generate(String::from("genesis_string"));
+        let (ars_infos, _) =
+            test_create_ars(&global_ctx.on_chain_commitment_key.g, num_ars, &mut csprng);
+        let aci = test_create_aci(&mut csprng);
+        let acc_data = InitialAccountData {
+            keys:      {
+                let mut keys = BTreeMap::new();
+                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
+                keys
+            },
+            threshold: SignatureThreshold(1),
+        };
+        let (context, pio, randomness) =
+            test_create_pio(&aci, &ip_info, &ars_infos, &global_ctx, num_ars, &acc_data);
+        let alist = test_create_attributes();
+        let (ip_sig, _) = verify_credentials(
+            &pio,
+            context,
+            &alist,
+            EXPIRY,
+            &ip_secret_key,
+            &ip_cdi_secret_key,
+        )
+        .expect("Signing the identity object should succeed.");
+        let id_object = IdentityObject {
+            pre_identity_object: pio,
+            alist,
+            signature: ip_sig,
+        };
+        let id_use_data = IdObjectUseData { aci, randomness };
+        // Nothing is revealed in the credential itself.
+        let policy = Policy {
+            valid_to:   id_object.alist.valid_to,
+            created_at: id_object.alist.created_at,
+            policy_vec: BTreeMap::new(),
+            statements: Vec::new(),
+            _phantom:   Default::default(),
+        };
+        let cred_data = CredentialData {
+            keys:      {
+                let mut keys = BTreeMap::new();
+                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
+                keys
+            },
+            threshold: SignatureThreshold(1),
+        };
+        let (cdi, commitments_randomness) = create_credential(
+            context,
+            &id_object,
+            &id_use_data,
+            0,
+            policy,
+            &cred_data,
+            &Left(EXPIRY),
+        )
+        .expect("Should generate the credential successfully.");
+        let commitments = &cdi.proofs.id_proofs.commitments;
+
+        // Attribute 0 has value 55, attribute 8 has value 31.
+        let request = PresentationRequest {
+            challenge:           csprng.gen(),
+            revealed_attributes: {
+                let mut set = BTreeSet::new();
+                set.insert(AttributeTag::from(8u8));
+                set
+            },
+            statements:          vec![
+                AttributeStatement::AttributeInRange(AttributeInRangeStatement {
+                    attribute_tag: AttributeTag::from(0u8),
+                    lower:         AttributeKind::from(18),
+                    upper:         AttributeKind::from(65),
+                    _phantom:      Default::default(),
+                }),
+                AttributeStatement::AttributeInSet(AttributeSetStatement {
+                    attribute_tag: AttributeTag::from(0u8),
+                    set:           vec![AttributeKind::from(55), AttributeKind::from(56)],
+                    _phantom:      Default::default(),
+                }),
+            ],
+        };
+        let presentation = prove_presentation(
+            &global_ctx,
+            &request,
+            &cdi.values,
+            &id_object.alist,
+            &commitments_randomness,
+            &mut csprng,
+        )
+        .expect("Proving should succeed.");
+        let presentation =
+            serialize_deserialize(&presentation).expect("Deserialization should succeed.");
+        assert_eq!(
+            presentation.revealed_attributes[&AttributeTag::from(8u8)].value,
+            AttributeKind::from(31)
+        );
+        assert_eq!(
+            verify_presentation(
+                &global_ctx,
+                &request,
+                &cdi.values,
+                commitments,
+                &presentation
+            ),
+            Ok(())
+        );
+
+        // The presentation cannot be replayed for a different challenge.
+        let mut other_request = request.clone();
+        other_request.challenge = csprng.gen();
+        assert_ne!(
+            verify_presentation(
+                &global_ctx,
+                &other_request,
+                &cdi.values,
+                commitments,
+                &presentation
+            ),
+            Ok(())
+        );
+
+        // A wrong revealed value is rejected.
+        let mut wrong_presentation = presentation;
+        wrong_presentation
+            .revealed_attributes
+            .get_mut(&AttributeTag::from(8u8))
+            .expect("Attribute 8 is revealed.")
+            .value = AttributeKind::from(32);
+        assert_eq!(
+            verify_presentation(
+                &global_ctx,
+                &request,
+                &cdi.values,
+                commitments,
+                &wrong_presentation
+            ),
+            Err(PresentationVerificationError::RevealedAttribute(
+                AttributeTag::from(8u8)
+            ))
+        );
+    }
+}
@@ -197,7 +197,7 @@ pub fn reveal_in_group<P: Into<u64> + Copy, C: Curve>(shares: &[(P, C)]) -> C {
 #[cfg(test)]
 mod test {
     use super::*;
-    use pairing::bls12_381::{Fr, G1};
+    use curve_arithmetic::bls12_381::{Fr, G1};
     use rand::seq::SliceRandom;
 
     // Test Lagrange interpolation polynomials at x={0,1}
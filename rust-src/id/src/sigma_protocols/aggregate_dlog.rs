@@ -55,7 +55,7 @@ impl<C: Curve> SigmaProtocol for AggregateDlog<C> {
         for g in self.coeff.iter() {
             let rand = C::generate_non_zero_scalar(csprng);
             // FIXME: Multiexponentiation would be useful in this case.
-            point = point.plus_point(&g.mul_by_scalar(&rand));
+            point = point.plus_point(&g.mul_by_secret_scalar(&rand));
             rands.push(rand);
         }
         Some((point, rands))
@@ -121,7 +121,7 @@ impl<C: Curve> SigmaProtocol for AggregateDlog<C> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use pairing::bls12_381::G1;
+    use curve_arithmetic::bls12_381::G1;
     use rand::{thread_rng, Rng};
 
     #[test]
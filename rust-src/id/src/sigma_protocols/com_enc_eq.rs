@@ -196,8 +196,8 @@ impl<C: Curve> SigmaProtocol for ComEncEq<C> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use curve_arithmetic::bls12_381::G1;
     use elgamal::{Message, SecretKey as ElgamalSecretKey};
-    use pairing::bls12_381::G1;
 
     #[test]
     pub fn test_com_enc_eq_correctness() {
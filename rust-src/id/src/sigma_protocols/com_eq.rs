@@ -72,7 +72,7 @@ impl<C: Curve, D: Curve<Scalar = C::Scalar>> SigmaProtocol for ComEq<C, D> {
         let alpha = Value::<D>::generate_non_zero(csprng);
         // This cR_i is R_i from the specification.
         let (v, cR) = self.cmm_key.commit(&alpha, csprng);
-        u = u.plus_point(&self.g.mul_by_scalar(&alpha));
+        u = u.plus_point(&self.g.mul_by_secret_scalar(&alpha));
         Some((CommittedPoints { u, v }, (alpha, cR)))
     }
 
@@ -149,7 +149,7 @@ impl<C: Curve, D: Curve<Scalar = C::Scalar>> SigmaProtocol for ComEq<C, D> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use pairing::bls12_381::{G1, G2};
+    use curve_arithmetic::bls12_381::{G1, G2};
 
     #[test]
     pub fn test_com_eq_correctness() {
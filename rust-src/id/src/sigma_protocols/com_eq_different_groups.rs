@@ -161,7 +161,7 @@ impl<C1: Curve, C2: Curve<Scalar = C1::Scalar>> SigmaProtocol for ComEqDiffGroup
 #[cfg(test)]
 mod tests {
     use super::*;
-    use pairing::bls12_381::{G1, G2};
+    use curve_arithmetic::bls12_381::{G1, G2};
 
     #[test]
     pub fn test_com_eq_diff_grps_correctness() {
@@ -99,7 +99,7 @@ impl<'a, P: Pairing, C: Curve<Scalar = P::ScalarField>> SigmaProtocol for ComEqS
         // challenge. This is using the bilinearity property of pairings and differs
         // from the specification in the way the computation is carried out, but
         // not in the observable outcomes.
-        let mut point = g_tilda.mul_by_scalar(&rho_prime);
+        let mut point = g_tilda.mul_by_secret_scalar(&rho_prime);
 
         let mut commitments = Vec::with_capacity(n);
         for i in 0..n {
@@ -107,7 +107,7 @@ impl<'a, P: Pairing, C: Curve<Scalar = P::ScalarField>> SigmaProtocol for ComEqS
             let mu_i = Value::generate_non_zero(csprng);
 
             // And a point in G2 computed from it.
-            let cU_i = cY_tilda(i).mul_by_scalar(&mu_i);
+            let cU_i = cY_tilda(i).mul_by_secret_scalar(&mu_i);
             // A commitment to the value v_i, and a randomness
             let (c_i, cR_i) = cmm_key.commit(&mu_i, csprng);
 
@@ -288,7 +288,7 @@ impl<'a, P: Pairing, C: Curve<Scalar = P::ScalarField>> SigmaProtocol for ComEqS
 #[cfg(test)]
 mod tests {
     use super::*;
-    use pairing::bls12_381::{Bls12, G1};
+    use curve_arithmetic::bls12_381::{Bls12, G1};
     use ps_sig::{SecretKey as PsSigSecretKey, Signature};
 
     #[test]
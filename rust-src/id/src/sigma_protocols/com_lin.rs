@@ -216,8 +216,8 @@ impl<C: Curve> SigmaProtocol for ComLin<C> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use curve_arithmetic::bls12_381::{Fr, G1};
     use ff::PrimeField;
-    use pairing::bls12_381::{Fr, G1};
     // use pairing::bls12_381::G1;
     use rand::thread_rng;
     // use std::convert::TryInto;
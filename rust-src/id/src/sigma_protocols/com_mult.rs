@@ -172,7 +172,7 @@ impl<'a, C: Curve> SigmaProtocol for ComMult<C> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use pairing::bls12_381::G1;
+    use curve_arithmetic::bls12_381::G1;
     use rand::thread_rng;
 
     #[test]
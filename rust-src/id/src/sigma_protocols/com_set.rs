@@ -0,0 +1,720 @@
+//! This module provides sigma protocols for proving that a committed value is,
+//! or is not, a member of a public set $\{s_1, \ldots, s_k\}$, without
+//! revealing the value.
+//!
+//! Both protocols are based on the polynomial $p(X) = \prod_{j=1}^k (X - s_j)$
+//! which is zero exactly at the members of the set. Given a commitment $C =
+//! g^x h^r$ the prover commits to the partial products $P_j = \prod_{i=1}^j (x
+//! - s_i)$ for $j = 2, \ldots, k$, and proves using the
+//! [com_mult](super::com_mult) protocol that each of them is the product of the
+//! previous one and $x - s_j$. Note that $C g^{-s_j}$ is a commitment to $x -
+//! s_j$ with randomness $r$, so it need not be sent. Then
+//! - membership is proved by proving knowledge of the discrete logarithm of the
+//!   commitment to $P_k$ with respect to $h$, i.e., that it is a commitment to
+//!   $0$.
+//! - non-membership is proved by committing to $w = P_k^{-1}$ and proving using
+//!   [com_mult](super::com_mult) that $P_k w$ is committed to by $g$ with
+//!   randomness $0$, i.e., that $P_k w = 1$, which implies $P_k \neq 0$.
+//!
+//! The size of the proofs is linear in the size of the set.
+use crate::sigma_protocols::{
+    com_mult::{self, ComMult, ComMultSecret},
+    common::*,
+    dlog::{self, Dlog, DlogSecret},
+};
+use crypto_common::*;
+use crypto_common_derive::*;
+use curve_arithmetic::Curve;
+use ff::Field;
+use pedersen_scheme::{Commitment, CommitmentKey, Randomness, Value};
+use random_oracle::{Challenge, RandomOracle};
+
+/// The public data shared by the membership and non-membership protocols.
+pub struct ComSet<C: Curve> {
+    /// Commitment to the value $x$.
+    pub commitment: Commitment<C>,
+    /// The set. The order of elements determines the order of the partial
+    /// products, but not the meaning of the statement.
+    pub set:        Vec<C::Scalar>,
+    /// Commitments to the partial products $P_2, \ldots, P_k$.
+    pub products:   Vec<Commitment<C>>,
+    /// The commitment key with which all the commitments are generated.
+    pub cmm_key:    CommitmentKey<C>,
+}
+
+/// The prover's secret data. The value and randomness of the commitment to
+/// $x$, and the randomness of the commitments to the partial products.
+pub struct ComSetSecret<C: Curve> {
+    pub value:         Value<C>,
+    pub randomness:    Randomness<C>,
+    pub products_rand: Vec<Randomness<C>>,
+}
+
+/// The set membership sigma proof instance.
+pub struct ComSetMembership<C: Curve> {
+    pub com_set: ComSet<C>,
+}
+
+/// The set non-membership sigma proof instance.
+pub struct ComSetNonMembership<C: Curve> {
+    pub com_set:     ComSet<C>,
+    /// Commitment to the inverse of $P_k$.
+    pub cmm_inverse: Commitment<C>,
+}
+
+/// The secret data for the non-membership proof. The randomness used to commit
+/// to the inverse of $P_k$ in addition to the shared secret data.
+pub struct ComSetNonMembershipSecret<C: Curve> {
+    pub com_set_secret: ComSetSecret<C>,
+    pub inverse_rand:   Randomness<C>,
+}
+
+/// The witness consisting of the witnesses for the multiplications of the
+/// partial products, and the witness for the final step, which is different
+/// for membership and non-membership.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Witness<C: Curve, W: Serialize> {
+    #[size_length = 4]
+    products: Vec<com_mult::Witness<C>>,
+    last:     W,
+}
+
+pub type MembershipWitness<C> = Witness<C, dlog::Witness<C>>;
+
+pub type NonMembershipWitness<C> = Witness<C, com_mult::Witness<C>>;
+
+/// A proof that a committed value is in a set. This contains the commitments
+/// to the partial products as well as the sigma proof.
+#[derive(Debug, Clone, Serialize)]
+pub struct MembershipProof<C: Curve> {
+    #[size_length = 4]
+    pub products: Vec<Commitment<C>>,
+    pub proof:    SigmaProof<MembershipWitness<C>>,
+}
+
+/// A proof that a committed value is not in a set. This contains the
+/// commitments to the partial products and to the inverse of the value of the
+/// polynomial, as well as the sigma proof.
+#[derive(Debug, Clone, Serialize)]
+pub struct NonMembershipProof<C: Curve> {
+    #[size_length = 4]
+    pub products:    Vec<Commitment<C>>,
+    pub cmm_inverse: Commitment<C>,
+    pub proof:       SigmaProof<NonMembershipWitness<C>>,
+}
+
+impl<C: Curve> ComSet<C> {
+    /// Commitment to $x - s_j$ derived from the commitment to $x$.
+    fn factor(&self, s_j: &C::Scalar) -> Commitment<C> {
+        Commitment(
+            self.commitment
+                .minus_point(&self.cmm_key.g.mul_by_scalar(s_j)),
+        )
+    }
+
+    /// Commitment to $P_k$, or `None` if the set is empty.
+    fn last_product(&self) -> Option<Commitment<C>> {
+        match self.products.last() {
+            Some(p) => Some(*p),
+            None => self.set.first().map(|s| self.factor(s)),
+        }
+    }
+
+    /// The com_mult instances establishing that $P_{j} = P_{j-1} (x - s_j)$.
+    /// Returns `None` if the set is empty, or if the number of partial
+    /// products is inconsistent with the size of the set.
+    fn mult_protocols(&self) -> Option<Vec<ComMult<C>>> {
+        let first = self.set.first()?;
+        if self.products.len() + 1 != self.set.len() {
+            return None;
+        }
+        let mut protocols = Vec::with_capacity(self.products.len());
+        let mut previous = self.factor(first);
+        for (s_j, product) in izip!(self.set[1..].iter(), self.products.iter()) {
+            protocols.push(ComMult {
+                cmms:    [previous, self.factor(s_j), *product],
+                cmm_key: self.cmm_key,
+            });
+            previous = *product;
+        }
+        Some(protocols)
+    }
+
+    fn public(&self, ro: &mut RandomOracle) {
+        ro.append_message(b"commitment", &self.commitment);
+        ro.extend_from(b"set", self.set.iter());
+        ro.extend_from(b"products", self.products.iter());
+        ro.append_message(b"cmm_key", &self.cmm_key)
+    }
+
+    /// Commit to the partial products of the given value. The value and
+    /// randomness must be the opening of the commitment, which is not checked.
+    /// Returns `None` if the set is empty.
+    pub fn prepare<R: rand::Rng>(
+        cmm_key: &CommitmentKey<C>,
+        commitment: Commitment<C>,
+        set: Vec<C::Scalar>,
+        value: &Value<C>,
+        randomness: &Randomness<C>,
+        csprng: &mut R,
+    ) -> Option<(Self, ComSetSecret<C>)> {
+        let (first, rest) = set.split_first()?;
+        let mut products = Vec::with_capacity(rest.len());
+        let mut products_rand = Vec::with_capacity(rest.len());
+        let mut product = **value;
+        product.sub_assign(first);
+        for s_j in rest {
+            let mut factor = **value;
+            factor.sub_assign(s_j);
+            product.mul_assign(&factor);
+            let (cmm, rand) = cmm_key.commit(&Value::<C>::new(product), csprng);
+            products.push(cmm);
+            products_rand.push(rand);
+        }
+        let com_set = ComSet {
+            commitment,
+            set,
+            products,
+            cmm_key: *cmm_key,
+        };
+        let secret = ComSetSecret {
+            value: value.clone(),
+            randomness: randomness.clone(),
+            products_rand,
+        };
+        Some((com_set, secret))
+    }
+
+    /// The secrets for the com_mult instances, together with the value and
+    /// randomness of $P_k$.
+    fn mult_secrets(
+        &self,
+        secret: ComSetSecret<C>,
+    ) -> Option<(Vec<ComMultSecret<C>>, C::Scalar, Randomness<C>)> {
+        let (first, rest) = self.set.split_first()?;
+        if rest.len() != secret.products_rand.len() {
+            return None;
+        }
+        let mut secrets = Vec::with_capacity(rest.len());
+        let mut previous = *secret.value;
+        previous.sub_assign(first);
+        let mut previous_rand = secret.randomness.clone();
+        for (s_j, product_rand) in izip!(rest.iter(), secret.products_rand) {
+            let mut factor = *secret.value;
+            factor.sub_assign(s_j);
+            let mut product = previous;
+            product.mul_assign(&factor);
+            secrets.push(ComMultSecret {
+                values: [Value::new(previous), Value::new(factor)],
+                rands:  [
+                    previous_rand,
+                    secret.randomness.clone(),
+                    product_rand.clone(),
+                ],
+            });
+            previous = product;
+            previous_rand = product_rand;
+        }
+        Some((secrets, previous, previous_rand))
+    }
+}
+
+/// Run the commit phase of all the given com_mult instances.
+#[allow(clippy::type_complexity)]
+fn commit_products<C: Curve, R: rand::Rng>(
+    protocols: &[ComMult<C>],
+    csprng: &mut R,
+) -> Option<(
+    Vec<<ComMult<C> as SigmaProtocol>::CommitMessage>,
+    Vec<<ComMult<C> as SigmaProtocol>::ProverState>,
+)> {
+    let mut points = Vec::with_capacity(protocols.len());
+    let mut states = Vec::with_capacity(protocols.len());
+    for p in protocols.iter() {
+        let (point, state) = p.commit_point(csprng)?;
+        points.push(point);
+        states.push(state);
+    }
+    Some((points, states))
+}
+
+/// Compute the witnesses of all the given com_mult instances.
+fn products_witness<C: Curve>(
+    protocols: &[ComMult<C>],
+    secrets: Vec<ComMultSecret<C>>,
+    states: Vec<<ComMult<C> as SigmaProtocol>::ProverState>,
+    challenge: &C::Scalar,
+) -> Option<Vec<com_mult::Witness<C>>> {
+    if secrets.len() != protocols.len() || states.len() != protocols.len() {
+        return None;
+    }
+    let mut witnesses = Vec::with_capacity(protocols.len());
+    for (p, secret, state) in izip!(protocols.iter(), secrets, states) {
+        witnesses.push(p.generate_witness(secret, state, challenge)?);
+    }
+    Some(witnesses)
+}
+
+/// Recompute the commit messages of all the given com_mult instances.
+fn extract_products<C: Curve>(
+    protocols: &[ComMult<C>],
+    witnesses: &[com_mult::Witness<C>],
+    challenge: &C::Scalar,
+) -> Option<Vec<<ComMult<C> as SigmaProtocol>::CommitMessage>> {
+    if witnesses.len() != protocols.len() {
+        return None;
+    }
+    let mut points = Vec::with_capacity(protocols.len());
+    for (p, witness) in izip!(protocols.iter(), witnesses.iter()) {
+        points.push(p.extract_point(challenge, witness)?);
+    }
+    Some(points)
+}
+
+impl<C: Curve> ComSetMembership<C> {
+    /// The final step, proving that $P_k$ is committed to with value $0$.
+    fn last_protocol(&self) -> Option<Dlog<C>> {
+        Some(Dlog {
+            public: self.com_set.last_product()?.0,
+            coeff:  self.com_set.cmm_key.h,
+        })
+    }
+}
+
+impl<C: Curve> SigmaProtocol for ComSetMembership<C> {
+    type CommitMessage = (Vec<<ComMult<C> as SigmaProtocol>::CommitMessage>, C);
+    type ProtocolChallenge = C::Scalar;
+    type ProverState = (Vec<<ComMult<C> as SigmaProtocol>::ProverState>, C::Scalar);
+    type ProverWitness = MembershipWitness<C>;
+    type SecretData = ComSetSecret<C>;
+
+    #[inline]
+    fn public(&self, ro: &mut RandomOracle) { self.com_set.public(ro) }
+
+    #[inline]
+    fn get_challenge(&self, challenge: &Challenge) -> Self::ProtocolChallenge {
+        C::scalar_from_bytes(challenge)
+    }
+
+    fn commit_point<R: rand::Rng>(
+        &self,
+        csprng: &mut R,
+    ) -> Option<(Self::CommitMessage, Self::ProverState)> {
+        let (points, states) = commit_products(&self.com_set.mult_protocols()?, csprng)?;
+        let (last_point, last_state) = self.last_protocol()?.commit_point(csprng)?;
+        Some(((points, last_point), (states, last_state)))
+    }
+
+    fn generate_witness(
+        &self,
+        secret: Self::SecretData,
+        state: Self::ProverState,
+        challenge: &Self::ProtocolChallenge,
+    ) -> Option<Self::ProverWitness> {
+        let protocols = self.com_set.mult_protocols()?;
+        let (secrets, last_value, last_rand) = self.com_set.mult_secrets(secret)?;
+        // The value is not in the set, so there is no valid witness.
+        if !last_value.is_zero() {
+            return None;
+        }
+        let products = products_witness(&protocols, secrets, state.0, challenge)?;
+        let last = self.last_protocol()?.generate_witness(
+            DlogSecret {
+                secret: Value::new(*last_rand),
+            },
+            state.1,
+            challenge,
+        )?;
+        Some(Witness { products, last })
+    }
+
+    fn extract_point(
+        &self,
+        challenge: &Self::ProtocolChallenge,
+        witness: &Self::ProverWitness,
+    ) -> Option<Self::CommitMessage> {
+        let points = extract_products(
+            &self.com_set.mult_protocols()?,
+            &witness.products,
+            challenge,
+        )?;
+        let last_point = self
+            .last_protocol()?
+            .extract_point(challenge, &witness.last)?;
+        Some((points, last_point))
+    }
+
+    #[cfg(test)]
+    fn with_valid_data<R: rand::Rng>(
+        data_size: usize,
+        csprng: &mut R,
+        f: impl FnOnce(Self, Self::SecretData, &mut R) -> (),
+    ) {
+        let cmm_key = CommitmentKey::generate(csprng);
+        let set = (0..=data_size)
+            .map(|_| C::generate_scalar(csprng))
+            .collect::<Vec<_>>();
+        let value = Value::<C>::new(set[csprng.gen_range(0, set.len())]);
+        let (commitment, randomness) = cmm_key.commit(&value, csprng);
+        let (com_set, secret) =
+            ComSet::prepare(&cmm_key, commitment, set, &value, &randomness, csprng)
+                .expect("The set is not empty.");
+        f(ComSetMembership { com_set }, secret, csprng)
+    }
+}
+
+impl<C: Curve> ComSetNonMembership<C> {
+    /// The final step, proving that $P_k w = 1$.
+    fn last_protocol(&self) -> Option<ComMult<C>> {
+        Some(ComMult {
+            cmms:    [
+                self.com_set.last_product()?,
+                self.cmm_inverse,
+                Commitment(self.com_set.cmm_key.g),
+            ],
+            cmm_key: self.com_set.cmm_key,
+        })
+    }
+
+    /// Commit to the partial products of the given value, and the inverse of
+    /// the product. The value and randomness must be the opening of the
+    /// commitment, which is not checked. Returns `None` if the set is empty
+    /// or the value is in the set.
+    pub fn prepare<R: rand::Rng>(
+        cmm_key: &CommitmentKey<C>,
+        commitment: Commitment<C>,
+        set: Vec<C::Scalar>,
+        value: &Value<C>,
+        randomness: &Randomness<C>,
+        csprng: &mut R,
+    ) -> Option<(Self, ComSetNonMembershipSecret<C>)> {
+        let mut product = C::Scalar::one();
+        for s_j in set.iter() {
+            let mut factor = **value;
+            factor.sub_assign(s_j);
+            product.mul_assign(&factor);
+        }
+        let inverse = Value::<C>::new(product.inverse()?);
+        let (cmm_inverse, inverse_rand) = cmm_key.commit(&inverse, csprng);
+        let (com_set, com_set_secret) =
+            ComSet::prepare(cmm_key, commitment, set, value, randomness, csprng)?;
+        Some((
+            ComSetNonMembership {
+                com_set,
+                cmm_inverse,
+            },
+            ComSetNonMembershipSecret {
+                com_set_secret,
+                inverse_rand,
+            },
+        ))
+    }
+}
+
+impl<C: Curve> SigmaProtocol for ComSetNonMembership<C> {
+    type CommitMessage = (
+        Vec<<ComMult<C> as SigmaProtocol>::CommitMessage>,
+        <ComMult<C> as SigmaProtocol>::CommitMessage,
+    );
+    type ProtocolChallenge = C::Scalar;
+    type ProverState = (
+        Vec<<ComMult<C> as SigmaProtocol>::ProverState>,
+        <ComMult<C> as SigmaProtocol>::ProverState,
+    );
+    type ProverWitness = NonMembershipWitness<C>;
+    type SecretData = ComSetNonMembershipSecret<C>;
+
+    #[inline]
+    fn public(&self, ro: &mut RandomOracle) {
+        self.com_set.public(ro);
+        ro.append_message(b"cmm_inverse", &self.cmm_inverse)
+    }
+
+    #[inline]
+    fn get_challenge(&self, challenge: &Challenge) -> Self::ProtocolChallenge {
+        C::scalar_from_bytes(challenge)
+    }
+
+    fn commit_point<R: rand::Rng>(
+        &self,
+        csprng: &mut R,
+    ) -> Option<(Self::CommitMessage, Self::ProverState)> {
+        let (points, states) = commit_products(&self.com_set.mult_protocols()?, csprng)?;
+        let (last_point, last_state) = self.last_protocol()?.commit_point(csprng)?;
+        Some(((points, last_point), (states, last_state)))
+    }
+
+    fn generate_witness(
+        &self,
+        secret: Self::SecretData,
+        state: Self::ProverState,
+        challenge: &Self::ProtocolChallenge,
+    ) -> Option<Self::ProverWitness> {
+        let protocols = self.com_set.mult_protocols()?;
+        let (secrets, last_value, last_rand) = self.com_set.mult_secrets(secret.com_set_secret)?;
+        // The value is in the set, so there is no valid witness.
+        let inverse = last_value.inverse()?;
+        let products = products_witness(&protocols, secrets, state.0, challenge)?;
+        let last = self.last_protocol()?.generate_witness(
+            ComMultSecret {
+                values: [Value::new(last_value), Value::new(inverse)],
+                rands:  [last_rand, secret.inverse_rand, Randomness::zero()],
+            },
+            state.1,
+            challenge,
+        )?;
+        Some(Witness { products, last })
+    }
+
+    fn extract_point(
+        &self,
+        challenge: &Self::ProtocolChallenge,
+        witness: &Self::ProverWitness,
+    ) -> Option<Self::CommitMessage> {
+        let points = extract_products(
+            &self.com_set.mult_protocols()?,
+            &witness.products,
+            challenge,
+        )?;
+        let last_point = self
+            .last_protocol()?
+            .extract_point(challenge, &witness.last)?;
+        Some((points, last_point))
+    }
+
+    #[cfg(test)]
+    fn with_valid_data<R: rand::Rng>(
+        data_size: usize,
+        csprng: &mut R,
+        f: impl FnOnce(Self, Self::SecretData, &mut R) -> (),
+    ) {
+        let cmm_key = CommitmentKey::generate(csprng);
+        let set = (0..=data_size)
+            .map(|_| C::generate_scalar(csprng))
+            .collect::<Vec<_>>();
+        let value = Value::<C>::generate(csprng);
+        let (commitment, randomness) = cmm_key.commit(&value, csprng);
+        let (non_membership, secret) =
+            ComSetNonMembership::prepare(&cmm_key, commitment, set, &value, &randomness, csprng)
+                .expect("A random value is not in the set.");
+        f(non_membership, secret, csprng)
+    }
+}
+
+/// Prove that the value committed to in `commitment` with the given randomness
+/// is in the set. Returns `None` if it is not.
+pub fn prove_membership<C: Curve, R: rand::Rng>(
+    ro: &mut RandomOracle,
+    csprng: &mut R,
+    cmm_key: &CommitmentKey<C>,
+    commitment: Commitment<C>,
+    set: Vec<C::Scalar>,
+    value: &Value<C>,
+    randomness: &Randomness<C>,
+) -> Option<MembershipProof<C>> {
+    let (com_set, secret) = ComSet::prepare(cmm_key, commitment, set, value, randomness, csprng)?;
+    let protocol = ComSetMembership { com_set };
+    let proof = prove(ro, &protocol, secret, csprng)?;
+    Some(MembershipProof {
+        products: protocol.com_set.products,
+        proof,
+    })
+}
+
+/// Verify a proof that the value committed to in `commitment` is in the set.
+pub fn verify_membership<C: Curve>(
+    ro: &mut RandomOracle,
+    cmm_key: &CommitmentKey<C>,
+    commitment: Commitment<C>,
+    set: Vec<C::Scalar>,
+    proof: &MembershipProof<C>,
+) -> bool {
+    let protocol = ComSetMembership {
+        com_set: ComSet {
+            commitment,
+            set,
+            products: proof.products.clone(),
+            cmm_key: *cmm_key,
+        },
+    };
+    verify(ro, &protocol, &proof.proof)
+}
+
+/// Prove that the value committed to in `commitment` with the given randomness
+/// is not in the set. Returns `None` if it is.
+pub fn prove_non_membership<C: Curve, R: rand::Rng>(
+    ro: &mut RandomOracle,
+    csprng: &mut R,
+    cmm_key: &CommitmentKey<C>,
+    commitment: Commitment<C>,
+    set: Vec<C::Scalar>,
+    value: &Value<C>,
+    randomness: &Randomness<C>,
+) -> Option<NonMembershipProof<C>> {
+    let (protocol, secret) =
+        ComSetNonMembership::prepare(cmm_key, commitment, set, value, randomness, csprng)?;
+    let proof = prove(ro, &protocol, secret, csprng)?;
+    Some(NonMembershipProof {
+        products: protocol.com_set.products,
+        cmm_inverse: protocol.cmm_inverse,
+        proof,
+    })
+}
+
+/// Verify a proof that the value committed to in `commitment` is not in the
+/// set.
+pub fn verify_non_membership<C: Curve>(
+    ro: &mut RandomOracle,
+    cmm_key: &CommitmentKey<C>,
+    commitment: Commitment<C>,
+    set: Vec<C::Scalar>,
+    proof: &NonMembershipProof<C>,
+) -> bool {
+    let protocol = ComSetNonMembership {
+        com_set:     ComSet {
+            commitment,
+            set,
+            products: proof.products.clone(),
+            cmm_key: *cmm_key,
+        },
+        cmm_inverse: proof.cmm_inverse,
+    };
+    verify(ro, &protocol, &proof.proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve_arithmetic::bls12_381::G1;
+    use rand::thread_rng;
+
+    #[test]
+    pub fn test_com_set_membership_correctness() {
+        let mut csprng = thread_rng();
+        for data_size in 0..20 {
+            ComSetMembership::<G1>::with_valid_data(
+                data_size,
+                &mut csprng,
+                |com_set, secret, csprng| {
+                    let challenge_prefix = generate_challenge_prefix(csprng);
+                    let mut ro = RandomOracle::domain(&challenge_prefix);
+                    let proof = prove(&mut ro.split(), &com_set, secret, csprng)
+                        .expect("Proving should succeed.");
+                    assert!(verify(&mut ro, &com_set, &proof));
+                },
+            )
+        }
+    }
+
+    #[test]
+    pub fn test_com_set_non_membership_correctness() {
+        let mut csprng = thread_rng();
+        for data_size in 0..20 {
+            ComSetNonMembership::<G1>::with_valid_data(
+                data_size,
+                &mut csprng,
+                |com_set, secret, csprng| {
+                    let challenge_prefix = generate_challenge_prefix(csprng);
+                    let mut ro = RandomOracle::domain(&challenge_prefix);
+                    let proof = prove(&mut ro.split(), &com_set, secret, csprng)
+                        .expect("Proving should succeed.");
+                    assert!(verify(&mut ro, &com_set, &proof));
+                },
+            )
+        }
+    }
+
+    #[test]
+    pub fn test_com_set_soundness() {
+        let mut csprng = thread_rng();
+        let cmm_key = CommitmentKey::<G1>::generate(&mut csprng);
+        let set = (0..10)
+            .map(|_| G1::generate_scalar(&mut csprng))
+            .collect::<Vec<_>>();
+        let member = Value::<G1>::new(set[3]);
+        let non_member = Value::<G1>::generate(&mut csprng);
+        let (cmm_member, rand_member) = cmm_key.commit(&member, &mut csprng);
+        let (cmm_non_member, rand_non_member) = cmm_key.commit(&non_member, &mut csprng);
+        let ro = RandomOracle::domain("com_set_test");
+
+        // The prover cannot produce proofs of false statements.
+        assert!(prove_membership(
+            &mut ro.split(),
+            &mut csprng,
+            &cmm_key,
+            cmm_non_member,
+            set.clone(),
+            &non_member,
+            &rand_non_member
+        )
+        .is_none());
+        assert!(prove_non_membership(
+            &mut ro.split(),
+            &mut csprng,
+            &cmm_key,
+            cmm_member,
+            set.clone(),
+            &member,
+            &rand_member
+        )
+        .is_none());
+
+        let proof = prove_membership(
+            &mut ro.split(),
+            &mut csprng,
+            &cmm_key,
+            cmm_member,
+            set.clone(),
+            &member,
+            &rand_member,
+        )
+        .expect("Proving should succeed.");
+        assert!(verify_membership(
+            &mut ro.split(),
+            &cmm_key,
+            cmm_member,
+            set.clone(),
+            &proof
+        ));
+        // The proof does not verify for a different commitment or set.
+        assert!(!verify_membership(
+            &mut ro.split(),
+            &cmm_key,
+            cmm_non_member,
+            set.clone(),
+            &proof
+        ));
+        assert!(!verify_membership(
+            &mut ro.split(),
+            &cmm_key,
+            cmm_member,
+            set[1..].to_vec(),
+            &proof
+        ));
+
+        let proof = prove_non_membership(
+            &mut ro.split(),
+            &mut csprng,
+            &cmm_key,
+            cmm_non_member,
+            set.clone(),
+            &non_member,
+            &rand_non_member,
+        )
+        .expect("Proving should succeed.");
+        assert!(verify_non_membership(
+            &mut ro.split(),
+            &cmm_key,
+            cmm_non_member,
+            set.clone(),
+            &proof
+        ));
+        assert!(!verify_non_membership(
+            &mut ro.split(),
+            &cmm_key,
+            cmm_member,
+            set,
+            &proof
+        ));
+    }
+}
@@ -78,7 +78,7 @@ pub struct SigmaProof<W: Serialize> {
     pub witness:   W,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AndWitness<W1: Serialize, W2: Serialize> {
     pub w1: W1,
     pub w2: W2,
@@ -177,7 +177,7 @@ impl<P1: SigmaProtocol, P2: SigmaProtocol> AndAdapter<P1, P2> {
 
 /// ## This section provides an and-like adapter, but where we combine
 /// multiple proofs of the same kind, only with different parameters.
-#[derive(Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ReplicateWitness<W: Serialize> {
     #[size_length = 4]
     pub witnesses: Vec<W>,
@@ -50,7 +50,7 @@ impl<C: Curve> SigmaProtocol for Dlog<C> {
         csprng: &mut R,
     ) -> Option<(Self::CommitMessage, Self::ProverState)> {
         let rand_scalar = C::generate_non_zero_scalar(csprng);
-        let randomised_point = self.coeff.mul_by_scalar(&rand_scalar);
+        let randomised_point = self.coeff.mul_by_secret_scalar(&rand_scalar);
         Some((randomised_point, rand_scalar))
     }
 
@@ -100,7 +100,7 @@ impl<C: Curve> SigmaProtocol for Dlog<C> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use pairing::bls12_381::G1;
+    use curve_arithmetic::bls12_381::G1;
 
     #[test]
     pub fn test_dlog_correctness() {
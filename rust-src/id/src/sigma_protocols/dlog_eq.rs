@@ -0,0 +1,177 @@
+//! The module provides the implementation of the `dlog_eq` sigma protocol.
+//! This protocol enables one to prove knowledge of a single discrete logarithm
+//! $x$ such that $y_i = G_i^x$ for all $i$, i.e., that the public values $y_i$
+//! have the same discrete logarithm with respect to the respective bases.
+//!
+//! It is used by anonymity revokers to prove that they decrypted a share
+//! correctly. For an ElGamal public key $pk = g^x$ and a cipher $(c_1, c_2)$
+//! decrypting to $m$ the prover shows that $pk = g^x$ and $c_2 m^{-1} = c_1^x$.
+use crate::sigma_protocols::common::*;
+use crypto_common::*;
+use curve_arithmetic::{Curve, Value};
+use ff::Field;
+use random_oracle::{Challenge, RandomOracle};
+
+pub struct DlogEq<C: Curve> {
+    /// The evaluated points y_i referenced in the module description.
+    pub public: Vec<C>,
+    /// The points G_i referenced in the module description, in the same order
+    /// as the public values.
+    pub coeff:  Vec<C>,
+}
+
+pub struct DlogEqSecret<C: Curve> {
+    pub secret: Value<C>,
+}
+
+/// Dlog equality witness. We deliberately make it opaque.
+#[derive(Debug, Serialize, Clone, Copy, Eq, PartialEq)]
+pub struct Witness<C: Curve> {
+    witness: C::Scalar,
+}
+
+/// Convenient alias for the dlog equality proof.
+pub type Proof<C> = SigmaProof<Witness<C>>;
+
+impl<C: Curve> SigmaProtocol for DlogEq<C> {
+    type CommitMessage = Vec<C>;
+    type ProtocolChallenge = C::Scalar;
+    type ProverState = C::Scalar;
+    type ProverWitness = Witness<C>;
+    type SecretData = DlogEqSecret<C>;
+
+    fn public(&self, ro: &mut RandomOracle) {
+        ro.extend_from(b"public", &self.public);
+        ro.extend_from(b"coeff", &self.coeff)
+    }
+
+    fn get_challenge(&self, challenge: &Challenge) -> Self::ProtocolChallenge {
+        C::scalar_from_bytes(challenge)
+    }
+
+    fn commit_point<R: rand::Rng>(
+        &self,
+        csprng: &mut R,
+    ) -> Option<(Self::CommitMessage, Self::ProverState)> {
+        if self.public.len() != self.coeff.len() {
+            return None;
+        }
+        let rand_scalar = C::generate_non_zero_scalar(csprng);
+        let points = self
+            .coeff
+            .iter()
+            .map(|g| g.mul_by_secret_scalar(&rand_scalar))
+            .collect();
+        Some((points, rand_scalar))
+    }
+
+    fn generate_witness(
+        &self,
+        secret: Self::SecretData,
+        state: Self::ProverState,
+        challenge: &Self::ProtocolChallenge,
+    ) -> Option<Self::ProverWitness> {
+        let mut witness = *challenge;
+        witness.mul_assign(&secret.secret);
+        witness.negate();
+        witness.add_assign(&state);
+        Some(Witness { witness })
+    }
+
+    fn extract_point(
+        &self,
+        challenge: &Self::ProtocolChallenge,
+        witness: &Self::ProverWitness,
+    ) -> Option<Self::CommitMessage> {
+        if self.public.len() != self.coeff.len() {
+            return None;
+        }
+        let points = izip!(self.public.iter(), self.coeff.iter())
+            .map(|(y, g)| {
+                y.mul_by_scalar(challenge)
+                    .plus_point(&g.mul_by_scalar(&witness.witness))
+            })
+            .collect();
+        Some(points)
+    }
+
+    #[cfg(test)]
+    fn with_valid_data<R: rand::Rng>(
+        data_size: usize,
+        csprng: &mut R,
+        f: impl FnOnce(Self, Self::SecretData, &mut R) -> (),
+    ) {
+        let secret = Value::generate(csprng);
+        let coeff: Vec<C> = (0..data_size).map(|_| C::generate(csprng)).collect();
+        let public = coeff.iter().map(|g| g.mul_by_scalar(&secret)).collect();
+        f(DlogEq { public, coeff }, DlogEqSecret { secret }, csprng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve_arithmetic::bls12_381::G1;
+
+    #[test]
+    pub fn test_dlog_eq_correctness() {
+        let mut csprng = rand::thread_rng();
+        for i in 1..20 {
+            DlogEq::with_valid_data(i, &mut csprng, |dlog_eq: DlogEq<G1>, secret, csprng| {
+                let challenge_prefix = generate_challenge_prefix(csprng);
+                let mut ro = RandomOracle::domain(&challenge_prefix);
+                let proof = prove(&mut ro.split(), &dlog_eq, secret, csprng)
+                    .expect("Proving should succeed.");
+                assert!(verify(&mut ro, &dlog_eq, &proof));
+            })
+        }
+    }
+
+    #[test]
+    pub fn test_dlog_eq_soundness() {
+        let mut csprng = rand::thread_rng();
+        for i in 2..20 {
+            DlogEq::with_valid_data(i, &mut csprng, |dlog_eq: DlogEq<G1>, secret, csprng| {
+                let challenge_prefix = generate_challenge_prefix(csprng);
+                let mut ro = RandomOracle::domain(&challenge_prefix);
+                let proof = prove(&mut ro.split(), &dlog_eq, secret, csprng)
+                    .expect("Proving should succeed.");
+
+                let mut wrong_ro = RandomOracle::domain(generate_challenge_prefix(csprng));
+                assert!(!verify(&mut wrong_ro, &dlog_eq, &proof));
+
+                // A public value with a different discrete logarithm.
+                let mut wrong_public = dlog_eq.public.clone();
+                wrong_public[i - 1] = G1::generate(csprng);
+                let dlog_eq_wrong_public = DlogEq {
+                    public: wrong_public,
+                    coeff:  dlog_eq.coeff.clone(),
+                };
+                assert!(!verify(&mut ro.split(), &dlog_eq_wrong_public, &proof));
+
+                let mut wrong_coeff = dlog_eq.coeff.clone();
+                wrong_coeff[0] = G1::generate(csprng);
+                let dlog_eq_wrong_coeff = DlogEq {
+                    public: dlog_eq.public.clone(),
+                    coeff:  wrong_coeff,
+                };
+                assert!(!verify(&mut ro.split(), &dlog_eq_wrong_coeff, &proof));
+
+                // The proof is not valid for only a part of the statement.
+                let dlog_eq_short = DlogEq {
+                    public: dlog_eq.public[1..].to_vec(),
+                    coeff:  dlog_eq.coeff[1..].to_vec(),
+                };
+                assert!(!verify(&mut ro.split(), &dlog_eq_short, &proof));
+
+                let wrong_proof_witness = SigmaProof {
+                    witness: Witness {
+                        witness: G1::generate_scalar(csprng),
+                    },
+                    ..proof
+                };
+                assert!(!verify(&mut ro, &dlog_eq, &wrong_proof_witness));
+            })
+        }
+    }
+}
@@ -7,8 +7,10 @@ pub mod com_eq_different_groups;
 pub mod com_eq_sig;
 pub mod com_lin;
 pub mod com_mult;
+pub mod com_set;
 pub mod common;
 pub mod dlog;
+pub mod dlog_eq;
 
 #[cfg(test)]
 pub mod sigma_test;
@@ -1,5 +1,5 @@
 use crate::sigma_protocols::{com_enc_eq, com_eq_sig, common::*, dlog};
-use pairing::bls12_381::{Bls12, G1, G2};
+use curve_arithmetic::bls12_381::{Bls12, G1, G2};
 use random_oracle::RandomOracle;
 
 #[test]
@@ -15,7 +15,7 @@ use curve_arithmetic::Curve;
 use dodis_yampolskiy_prf as prf;
 use ed25519_dalek as ed25519;
 use either::Either::Left;
-use elgamal::{PublicKey, SecretKey};
+use elgamal::{Message, PublicKey, SecretKey};
 use rand::*;
 use std::{collections::BTreeMap, convert::TryFrom};
 
@@ -225,6 +225,7 @@ pub fn test_pipeline() {
             tree.insert(AttributeTag::from(8u8), AttributeKind::from(31));
             tree
         },
+        statements: Vec::new(),
         _phantom: Default::default(),
     };
     let acc_data = CredentialData {
@@ -301,6 +302,45 @@ pub fn test_pipeline() {
             .mul_by_scalar(&id_use_data.aci.cred_holder_info.id_cred.id_cred_sec)
     );
 
+    // Revoking anonymity with proofs of correct decryption, where one of the
+    // anonymity revokers cheats.
+    let mut shares = ars_secret
+        .iter()
+        .map(|(ar_id, key)| {
+            let ar = cdi.values.ar_data.get(ar_id).expect("AR is present.");
+            decrypt_id_cred_pub_share(*ar_id, key, ar, &mut csprng)
+        })
+        .collect::<Vec<_>>();
+    for share in shares.iter() {
+        let ar = cdi.values.ar_data.get(&share.ar_identity).unwrap();
+        let ar_public_key = &ars_infos.get(&share.ar_identity).unwrap().ar_public_key;
+        assert!(verify_id_cred_pub_share(ar_public_key, ar, share));
+    }
+    let cheater = shares[1].ar_identity;
+    shares[1].id_cred_pub_share = Message::generate(&mut csprng);
+    let revealed = reveal_id_cred_pub_verified(
+        &ars_infos,
+        &cdi.values.ar_data,
+        cdi.values.threshold,
+        &shares,
+    )
+    .expect("There are enough valid shares.");
+    assert_eq!(revealed.value, revealed_id_cred_pub);
+    assert_eq!(revealed.invalid, vec![cheater]);
+    let (ar_id, key) = ars_secret.iter().next().unwrap();
+    let ar = cdi.values.ar_data.get(ar_id).unwrap();
+    shares.push(decrypt_id_cred_pub_share(*ar_id, key, ar, &mut csprng));
+    assert_eq!(
+        reveal_id_cred_pub_verified(
+            &ars_infos,
+            &cdi.values.ar_data,
+            cdi.values.threshold,
+            &shares
+        )
+        .unwrap_err(),
+        RevealError::DuplicateShare(*ar_id)
+    );
+
     // generate a new cdi from a modified pre-identity object in which we swapped
     // two anonymity revokers. Verification of this credential should fail the
     // signature at the very least.
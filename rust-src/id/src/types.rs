@@ -3,9 +3,9 @@
 use crate::{
     secret_sharing::Threshold,
     sigma_protocols::{
-        com_enc_eq, com_eq, com_eq_different_groups, com_eq_sig, com_mult,
+        com_enc_eq, com_eq, com_eq_different_groups, com_eq_sig, com_mult, com_set,
         common::{ReplicateAdapter, ReplicateWitness},
-        dlog,
+        dlog, dlog_eq,
     },
 };
 use anyhow::{anyhow, bail};
@@ -717,10 +717,14 @@ pub struct IpArData<C: Curve> {
 pub struct IpArDecryptedData<C: Curve> {
     /// identity of the anonymity revoker
     #[serde(rename = "arIdentity")]
-    pub ar_identity:   ArIdentity,
+    pub ar_identity:         ArIdentity,
     /// share of prf key
     #[serde(rename = "prfKeyShare")]
-    pub prf_key_share: Value<C>,
+    pub prf_key_share:       Value<C>,
+    /// proof that the share is the decryption of the encrypted share under the
+    /// public key of the anonymity revoker
+    #[serde(rename = "proofOfDecryption")]
+    pub proof_of_decryption: dlog_eq::Proof<C>,
 }
 
 /// Data relating to a single anonymity revoker sent by the account holder to
@@ -747,10 +751,14 @@ pub struct ChainArData<C: Curve> {
 pub struct ChainArDecryptedData<C: Curve> {
     /// identity of the anonymity revoker
     #[serde(rename = "arIdentity")]
-    pub ar_identity:       ArIdentity,
+    pub ar_identity:         ArIdentity,
     /// share of id cred pub
     #[serde(rename = "idCredPubShare")]
-    pub id_cred_pub_share: Message<C>,
+    pub id_cred_pub_share:   Message<C>,
+    /// proof that the share is the decryption of the encrypted share under the
+    /// public key of the anonymity revoker
+    #[serde(rename = "proofOfDecryption")]
+    pub proof_of_decryption: dlog_eq::Proof<C>,
 }
 
 // NOTE: This struct is redundant, but we will
@@ -1076,6 +1084,7 @@ impl<P: Pairing, C: Curve<Scalar = P::ScalarField>> Serial for CredDeploymentPro
         tmp_out.put(&self.id_proofs.proof_reg_id);
         tmp_out.put(&self.proof_acc_sk);
         tmp_out.put(&self.id_proofs.cred_counter_less_than_max_accounts);
+        serial_policy_proofs(&self.id_proofs, &mut tmp_out);
         let len: u32 = tmp_out.len() as u32; // safe
         out.put(&len);
         out.write_all(&tmp_out).expect("Writing to buffer is safe.");
@@ -1097,6 +1106,7 @@ impl<P: Pairing, C: Curve<Scalar = P::ScalarField>> Deserial for CredDeploymentP
         let proof_reg_id = limited.get()?;
         let proof_acc_sk = limited.get()?;
         let cred_counter_less_than_max_accounts = limited.get()?;
+        let (range_proofs_version, policy_proofs) = deserial_policy_proofs(&mut limited)?;
         if limited.limit() == 0 {
             Ok(CredDeploymentProofs {
                 id_proofs: IdOwnershipProofs {
@@ -1107,6 +1117,8 @@ impl<P: Pairing, C: Curve<Scalar = P::ScalarField>> Deserial for CredDeploymentP
                     proof_ip_sig,
                     proof_reg_id,
                     cred_counter_less_than_max_accounts,
+                    policy_proofs,
+                    range_proofs_version,
                 },
                 proof_acc_sk,
             })
@@ -1118,7 +1130,7 @@ impl<P: Pairing, C: Curve<Scalar = P::ScalarField>> Deserial for CredDeploymentP
 
 /// This structure contains all proofs, which are required to prove ownership of
 /// an identity, in a credential deployment.
-#[derive(Debug, Serialize, SerdeSerialize, SerdeDeserialize, Clone)]
+#[derive(Debug, SerdeSerialize, SerdeDeserialize, Clone)]
 #[serde(bound(
     serialize = "P: Pairing, C: Curve<Scalar=P::ScalarField>",
     deserialize = "P: Pairing, C: Curve<Scalar=P::ScalarField>"
@@ -1171,13 +1183,151 @@ pub struct IdOwnershipProofs<P: Pairing, C: Curve<Scalar = P::ScalarField>> {
         deserialize_with = "base16_decode"
     )]
     pub proof_reg_id: com_mult::Witness<C>,
-    /// Proof that cred_counter is less than or equal to max_accounts
+    /// Proof that cred_counter is less than or equal to max_accounts. If the
+    /// range proofs are in [ID_RANGE_PROOFS_VERSION_1] this is the aggregated
+    /// range proof, which also covers the in-range policy statements.
     #[serde(
         rename = "credCounterLessThanMaxAccounts",
         serialize_with = "base16_encode",
         deserialize_with = "base16_decode"
     )]
     pub cred_counter_less_than_max_accounts: RangeProof<C>,
+    /// Proofs of the statements in the policy, in the order of the
+    /// statements.
+    #[serde(
+        rename = "policyProofs",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "base16_encode",
+        deserialize_with = "base16_decode"
+    )]
+    pub policy_proofs: Vec<AttributeProof<C>>,
+    /// The format of the range proofs, either [ID_RANGE_PROOFS_VERSION_0] or
+    /// [ID_RANGE_PROOFS_VERSION_1].
+    #[serde(
+        rename = "rangeProofsVersion",
+        default = "default_range_proofs_version",
+        skip_serializing_if = "is_default_range_proofs_version"
+    )]
+    pub range_proofs_version: Version,
+}
+
+/// The version of the range proofs of a credential where the credential
+/// counter and each in-range policy statement have their own range proof.
+pub const ID_RANGE_PROOFS_VERSION_0: Version = VERSION_0;
+
+/// The version of the range proofs of a credential where a single range proof
+/// of 64 bit values covers the credential counter and all the in-range policy
+/// statements. The proof is stored in place of the credential counter proof,
+/// and the in-range statements have an
+/// [AttributeProof::AttributeInAggregatedRange] proof. The values are, in
+/// order, `max_accounts - cred_counter`, `cred_counter`, and `value - lower`
+/// and `upper - 1 - value` for each in-range statement, padded with 0 values
+/// with randomness 0 to a power of two.
+pub const ID_RANGE_PROOFS_VERSION_1: Version = Version { value: 1 };
+
+/// Flag set in the serialized number of policy proofs to indicate that a
+/// version of the range proofs follows. There are far fewer policy statements
+/// than this, so version 0 proofs are serialized exactly as before versioning
+/// was introduced.
+const ID_PROOFS_VERSIONED_FLAG: u16 = 0x8000;
+
+fn default_range_proofs_version() -> Version { ID_RANGE_PROOFS_VERSION_0 }
+
+fn is_default_range_proofs_version(version: &Version) -> bool {
+    *version == ID_RANGE_PROOFS_VERSION_0
+}
+
+/// Serialize the policy proofs, and the range proofs version if it is not 0.
+/// The proofs of policy statements are only serialized if there are any, or if
+/// the version is not 0. This keeps the serialization, and thus the hash signed
+/// by the account keys, of credentials without statements unchanged.
+fn serial_policy_proofs<P: Pairing, C: Curve<Scalar = P::ScalarField>, B: Buffer>(
+    id_proofs: &IdOwnershipProofs<P, C>,
+    out: &mut B,
+) {
+    if id_proofs.range_proofs_version != ID_RANGE_PROOFS_VERSION_0 {
+        out.put(&(ID_PROOFS_VERSIONED_FLAG | id_proofs.policy_proofs.len() as u16));
+        out.put(&id_proofs.range_proofs_version);
+        serial_vector_no_length(&id_proofs.policy_proofs, out)
+    } else if !id_proofs.policy_proofs.is_empty() {
+        out.put(&(id_proofs.policy_proofs.len() as u16));
+        serial_vector_no_length(&id_proofs.policy_proofs, out)
+    }
+}
+
+/// Deserialize the range proofs version and the policy proofs, the inverse of
+/// [serial_policy_proofs]. The proofs of policy statements are only present if
+/// there is input left.
+fn deserial_policy_proofs<C: Curve, R: Read>(
+    source: &mut std::io::Take<R>,
+) -> ParseResult<(Version, Vec<AttributeProof<C>>)> {
+    if source.limit() == 0 {
+        return Ok((ID_RANGE_PROOFS_VERSION_0, Vec::new()));
+    }
+    let num_proofs: u16 = source.get()?;
+    let version = if num_proofs & ID_PROOFS_VERSIONED_FLAG == 0 {
+        ID_RANGE_PROOFS_VERSION_0
+    } else {
+        let version: Version = source.get()?;
+        if version != ID_RANGE_PROOFS_VERSION_1 {
+            bail!("Unsupported range proofs version {}.", version)
+        }
+        version
+    };
+    let num_proofs = usize::from(num_proofs & !ID_PROOFS_VERSIONED_FLAG);
+    Ok((version, deserial_vector_no_length(source, num_proofs)?))
+}
+
+impl<P: Pairing, C: Curve<Scalar = P::ScalarField>> Serial for IdOwnershipProofs<P, C> {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        out.put(&self.sig);
+        out.put(&self.commitments);
+        out.put(&self.challenge);
+        out.put(&(self.proof_id_cred_pub.len() as u32));
+        serial_map_no_length(&self.proof_id_cred_pub, out);
+        out.put(&self.proof_ip_sig);
+        out.put(&self.proof_reg_id);
+        out.put(&self.cred_counter_less_than_max_accounts);
+        serial_policy_proofs(self, out)
+    }
+}
+
+/// The serialization of the proofs does not include their length, so the
+/// proofs of policy statements extend to the end of the input. This reads all
+/// of the input.
+impl<P: Pairing, C: Curve<Scalar = P::ScalarField>> Deserial for IdOwnershipProofs<P, C> {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let mut bytes = Vec::new();
+        source.read_to_end(&mut bytes)?;
+        let len = bytes.len() as u64;
+        let mut limited = Cursor::new(bytes).take(len);
+        let sig = limited.get()?;
+        let commitments = limited.get()?;
+        let challenge = limited.get()?;
+        let proof_id_cred_pub_len: u32 = limited.get()?;
+        let proof_id_cred_pub =
+            deserial_map_no_length(&mut limited, proof_id_cred_pub_len as usize)?;
+        let proof_ip_sig = limited.get()?;
+        let proof_reg_id = limited.get()?;
+        let cred_counter_less_than_max_accounts = limited.get()?;
+        let (range_proofs_version, policy_proofs) = deserial_policy_proofs(&mut limited)?;
+        if limited.limit() == 0 {
+            Ok(IdOwnershipProofs {
+                sig,
+                commitments,
+                challenge,
+                proof_id_cred_pub,
+                proof_ip_sig,
+                proof_reg_id,
+                cred_counter_less_than_max_accounts,
+                policy_proofs,
+                range_proofs_version,
+            })
+        } else {
+            bail!("Trailing data after the proofs of policy statements.")
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, SerdeSerialize, SerdeDeserialize)]
@@ -1185,27 +1335,50 @@ pub struct IdOwnershipProofs<P: Pairing, C: Curve<Scalar = P::ScalarField>> {
     serialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeSerialize",
     deserialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeDeserialize<'de>"
 ))]
-/// A policy is (currently) revealed values of attributes that are part of the
-/// identity object. Policies are part of credentials.
+/// A policy consists of revealed values of attributes that are part of the
+/// identity object, together with statements about attributes that are not
+/// revealed, but are instead proved about the attribute commitments.
+/// Policies are part of credentials.
 pub struct Policy<C: Curve, AttributeType: Attribute<C::Scalar>> {
     #[serde(rename = "validTo")]
     pub valid_to:   YearMonth,
     #[serde(rename = "createdAt")]
     pub created_at: YearMonth,
-    /// Revealed attributes for now. In the future we might have
-    /// additional items with (Tag, Property, Proof).
+    /// Revealed attributes.
     #[serde(rename = "revealedAttributes")]
     pub policy_vec: BTreeMap<AttributeTag, AttributeType>,
+    /// Statements about attributes that are not revealed. Each statement is
+    /// accompanied by a proof in the credential, in the same order.
+    #[serde(rename = "statements", default, skip_serializing_if = "Vec::is_empty")]
+    pub statements: Vec<AttributeStatement<C, AttributeType>>,
     #[serde(skip)]
     pub _phantom:   std::marker::PhantomData<C>,
 }
 
+/// The version of the policy serialization that supports attribute
+/// statements.
+pub const POLICY_VERSION_1: Version = Version { value: 1 };
+
+/// Flag set in the serialized number of revealed attributes to indicate that a
+/// version follows. Version 0 policies (only revealed attributes) never have
+/// this bit set since there are fewer than 256 attribute tags, so they are
+/// serialized exactly as before versioning was introduced.
+const POLICY_VERSIONED_FLAG: u16 = 0x8000;
+
 impl<C: Curve, AttributeType: Attribute<C::Scalar>> Serial for Policy<C, AttributeType> {
     fn serial<B: Buffer>(&self, out: &mut B) {
         out.put(&self.valid_to);
         out.put(&self.created_at);
-        out.put(&(self.policy_vec.len() as u16));
-        serial_map_no_length(&self.policy_vec, out)
+        if self.statements.is_empty() {
+            out.put(&(self.policy_vec.len() as u16));
+            serial_map_no_length(&self.policy_vec, out)
+        } else {
+            out.put(&(POLICY_VERSIONED_FLAG | self.policy_vec.len() as u16));
+            out.put(&POLICY_VERSION_1);
+            serial_map_no_length(&self.policy_vec, out);
+            out.put(&(self.statements.len() as u16));
+            serial_vector_no_length(&self.statements, out)
+        }
     }
 }
 
@@ -1214,16 +1387,186 @@ impl<C: Curve, AttributeType: Attribute<C::Scalar>> Deserial for Policy<C, Attri
         let valid_to = source.get()?;
         let created_at = source.get()?;
         let len: u16 = source.get()?;
-        let policy_vec = deserial_map_no_length(source, usize::from(len))?;
+        let (policy_vec, statements) = if len & POLICY_VERSIONED_FLAG == 0 {
+            (
+                deserial_map_no_length(source, usize::from(len))?,
+                Vec::new(),
+            )
+        } else {
+            let version: Version = source.get()?;
+            if version != POLICY_VERSION_1 {
+                bail!("Unsupported policy version {}.", version)
+            }
+            let policy_vec =
+                deserial_map_no_length(source, usize::from(len & !POLICY_VERSIONED_FLAG))?;
+            let num_statements: u16 = source.get()?;
+            let statements = deserial_vector_no_length(source, usize::from(num_statements))?;
+            (policy_vec, statements)
+        };
         Ok(Policy {
             valid_to,
             created_at,
             policy_vec,
+            statements,
             _phantom: Default::default(),
         })
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(
+    serialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeSerialize",
+    deserialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeDeserialize<'de>"
+))]
+#[serde(tag = "type")]
+/// A statement about the value of an attribute that is proved with respect to
+/// the commitment to the attribute, without revealing the value.
+pub enum AttributeStatement<C: Curve, AttributeType: Attribute<C::Scalar>> {
+    /// The attribute value lies in a given interval.
+    #[serde(rename = "AttributeInRange")]
+    AttributeInRange(AttributeInRangeStatement<C, AttributeType>),
+    /// The attribute value is a member of a given set.
+    #[serde(rename = "AttributeInSet")]
+    AttributeInSet(AttributeSetStatement<C, AttributeType>),
+    /// The attribute value is not a member of a given set.
+    #[serde(rename = "AttributeNotInSet")]
+    AttributeNotInSet(AttributeSetStatement<C, AttributeType>),
+}
+
+impl<C: Curve, AttributeType: Attribute<C::Scalar>> AttributeStatement<C, AttributeType> {
+    /// The attribute the statement is about.
+    pub fn attribute_tag(&self) -> AttributeTag {
+        match self {
+            AttributeStatement::AttributeInRange(statement) => statement.attribute_tag,
+            AttributeStatement::AttributeInSet(statement) => statement.attribute_tag,
+            AttributeStatement::AttributeNotInSet(statement) => statement.attribute_tag,
+        }
+    }
+}
+
+impl<C: Curve, AttributeType: Attribute<C::Scalar>> Serial
+    for AttributeStatement<C, AttributeType>
+{
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        match self {
+            AttributeStatement::AttributeInRange(statement) => {
+                0u8.serial(out);
+                statement.serial(out)
+            }
+            AttributeStatement::AttributeInSet(statement) => {
+                1u8.serial(out);
+                statement.serial(out)
+            }
+            AttributeStatement::AttributeNotInSet(statement) => {
+                2u8.serial(out);
+                statement.serial(out)
+            }
+        }
+    }
+}
+
+impl<C: Curve, AttributeType: Attribute<C::Scalar>> Deserial
+    for AttributeStatement<C, AttributeType>
+{
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        match source.get()? {
+            0u8 => Ok(AttributeStatement::AttributeInRange(source.get()?)),
+            1u8 => Ok(AttributeStatement::AttributeInSet(source.get()?)),
+            2u8 => Ok(AttributeStatement::AttributeNotInSet(source.get()?)),
+            n => bail!("AttributeStatement::deserial: Unsupported tag {}.", n),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(
+    serialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeSerialize",
+    deserialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeDeserialize<'de>"
+))]
+/// The statement that the value of an attribute lies in the interval
+/// `[lower, upper)`. The comparison is on the field element encodings of the
+/// attributes (see [Attribute::to_field_element]), so the bounds should be
+/// chosen so that this agrees with the intended meaning, e.g., for dates in the
+/// format YYYYMMDD all three values must have the same length.
+///
+/// The proof is a bulletproof that both `value - lower` and `upper - value - 1`
+/// are in the range `[0, 2^64)`.
+pub struct AttributeInRangeStatement<C: Curve, AttributeType: Attribute<C::Scalar>> {
+    #[serde(rename = "attributeTag")]
+    pub attribute_tag: AttributeTag,
+    /// Lower bound, inclusive.
+    #[serde(rename = "lower")]
+    pub lower:         AttributeType,
+    /// Upper bound, exclusive.
+    #[serde(rename = "upper")]
+    pub upper:         AttributeType,
+    #[serde(skip)]
+    pub _phantom:      std::marker::PhantomData<C>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(
+    serialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeSerialize",
+    deserialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeDeserialize<'de>"
+))]
+/// The statement that the value of an attribute is, or is not, a member of a
+/// set. Membership is in terms of the field element encodings of the
+/// attributes (see [Attribute::to_field_element]).
+pub struct AttributeSetStatement<C: Curve, AttributeType: Attribute<C::Scalar>> {
+    #[serde(rename = "attributeTag")]
+    pub attribute_tag: AttributeTag,
+    #[serde(rename = "set")]
+    #[size_length = 2]
+    pub set:           Vec<AttributeType>,
+    #[serde(skip)]
+    pub _phantom:      std::marker::PhantomData<C>,
+}
+
+#[derive(Debug, Clone)]
+/// A proof of an [AttributeStatement]. The variants correspond to the variants
+/// of the statement.
+pub enum AttributeProof<C: Curve> {
+    AttributeInRange(RangeProof<C>),
+    AttributeInSet(com_set::MembershipProof<C>),
+    AttributeNotInSet(com_set::NonMembershipProof<C>),
+    /// The statement that the attribute is in a range is proved by the
+    /// aggregated range proof of the credential, see
+    /// [ID_RANGE_PROOFS_VERSION_1].
+    AttributeInAggregatedRange,
+}
+
+impl<C: Curve> Serial for AttributeProof<C> {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        match self {
+            AttributeProof::AttributeInRange(proof) => {
+                0u8.serial(out);
+                proof.serial(out)
+            }
+            AttributeProof::AttributeInSet(proof) => {
+                1u8.serial(out);
+                proof.serial(out)
+            }
+            AttributeProof::AttributeNotInSet(proof) => {
+                2u8.serial(out);
+                proof.serial(out)
+            }
+            AttributeProof::AttributeInAggregatedRange => 3u8.serial(out),
+        }
+    }
+}
+
+impl<C: Curve> Deserial for AttributeProof<C> {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        match source.get()? {
+            0u8 => Ok(AttributeProof::AttributeInRange(source.get()?)),
+            1u8 => Ok(AttributeProof::AttributeInSet(source.get()?)),
+            2u8 => Ok(AttributeProof::AttributeNotInSet(source.get()?)),
+            3u8 => Ok(AttributeProof::AttributeInAggregatedRange),
+            n => bail!("AttributeProof::deserial: Unsupported tag {}.", n),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 /// Which signature scheme is being used. Currently only one is supported.
 pub enum SchemeId {
@@ -1519,7 +1862,7 @@ pub enum AccountCredentialWithoutProofs<C: Curve, AttributeType: Attribute<C::Sc
 /// This is the CredentialDeploymentInfo structure, that instead of containing
 /// CredDeploymentProofs, it contains UnsignedCredDeploymentProofs, and
 /// the reg_id that also has to be signed.
-#[derive(Debug, Serialize, SerdeSerialize, SerdeDeserialize)]
+#[derive(Debug, Serial, SerdeSerialize, SerdeDeserialize)]
 #[serde(bound(
     serialize = "P: Pairing, C: Curve<Scalar = P::ScalarField>, AttributeType: \
                  Attribute<C::Scalar> + SerdeSerialize",
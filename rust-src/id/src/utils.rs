@@ -39,6 +39,25 @@ pub fn commitment_to_share<C: Curve>(
     Commitment(multiexp(coeff_commitments, &exponents))
 }
 
+/// Apply the function to each of the elements, returning the results in the
+/// same order. With the `parallel` feature the elements are processed in
+/// parallel. This is intended for the independent per-anonymity revoker
+/// computations when verifying the sharing of a value.
+pub(crate) fn map_maybe_parallel<T: Sync, U: Send, F: Fn(&T) -> U + Send + Sync>(
+    xs: &[T],
+    f: F,
+) -> Vec<U> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        xs.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        xs.iter().map(f).collect()
+    }
+}
+
 /// Interpret the array as coefficients of a polynomial starting at 0,
 /// and evaluate the polynomial at the given point.
 pub fn evaluate_poly<F: Field, R: AsRef<F>>(coeffs: &[R], point: &F) -> F {
@@ -321,7 +340,7 @@ where
 mod tests {
     use super::*;
     use crypto_common::to_bytes;
-    use pairing::bls12_381::Fr;
+    use curve_arithmetic::bls12_381::Fr;
     use rand::{thread_rng, Rng};
     use std::collections::BTreeMap;
 
@@ -0,0 +1,241 @@
+//! The BIP39 word list, and conversion between entropy and BIP39 sentences.
+//! This is shared by the key generation tools and the wallets, which derive
+//! their keys from a BIP39 sentence via
+//! [ConcordiumHdWallet](crate::ConcordiumHdWallet).
+use bitvec::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+const BIP39_ENGLISH: &str = include_str!("../data/BIP39English.txt");
+
+/// List of BIP39 words. There is a test that checks that this list has correct
+/// length, so there is no need to check when using it.
+pub fn bip39_words() -> impl Iterator<Item = &'static str> { BIP39_ENGLISH.split_whitespace() }
+
+/// Inverse mapping to the implicit mapping in bip39_words. Maps word to its
+/// index in the list. This allows to quickly test membership and convert words
+/// to their index.
+pub fn bip39_map() -> HashMap<&'static str, usize> { bip39_words().zip(0..).collect() }
+
+/// Verify whether the given vector of words constitutes a valid BIP39 sentence.
+pub fn verify_bip39(word_vec: &[String], bip_word_map: &HashMap<&str, usize>) -> bool {
+    // check that word_vec contains allowed number of words
+    match word_vec.len() {
+        12 | 15 | 18 | 21 | 24 => (),
+        _ => return false,
+    };
+
+    // convert word vector to bits
+    let mut bit_vec = BitVec::<Msb0, u8>::new();
+    for word in word_vec {
+        match bip_word_map.get(word.as_str()) {
+            Some(idx) => {
+                let word_bits = BitVec::<Msb0, u16>::from_element(*idx as u16);
+                // There are 2048 words in the BIP39 list, which can be represented using 11
+                // bits. Thus, the first 5 bits of word_bits are 0. Remove those leading zeros
+                // and add the remaining ones to bit_bec.
+                bit_vec.extend_from_bitslice(&word_bits[5..]);
+            }
+            None => return false, // not valid if it contains invalid word
+        };
+    }
+
+    // Valid sentence consists of initial entropy of length ent_len plus
+    // checksum of length ent_len/32. Hence, ent_len * 33/32 = bit_vec.len().
+    // Note that bit_vec.len() is always a multiple of 33 because 11 bits
+    // are added for each word and all allowed word counts are multiples of 3.
+    let ent_len = 32 * bit_vec.len() / 33;
+
+    // split bits after ent_len off. These correspond to the checksum.
+    let checksum = bit_vec.split_off(ent_len);
+
+    // checksum is supposed to be first cs_len bits of SHA256(entropy)
+    let hash = Sha256::digest(&bit_vec.into_vec());
+
+    // convert hash from byte vector to bit vector
+    let hash_bits = BitVec::<Msb0, u8>::from_slice(&hash).unwrap();
+
+    // sentence is valid if checksum equals fist ent_len/32 bits of hash
+    checksum == hash_bits[0..ent_len / 32]
+}
+
+/// Convert given byte array to valid BIP39 sentence.
+/// Bytes must contain {16, 20, 24, 28, 32} bytes corresponding to
+/// {128, 160, 192, 224, 256} bits.
+/// This uses the method described at https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki
+pub fn bytes_to_bip39(bytes: &[u8], bip_word_list: &[&str]) -> Result<Vec<String>, String> {
+    let ent_len = 8 * bytes.len(); // input is called entropy in BIP39
+    match ent_len {
+        128 | 160 | 192 | 224 | 256 => (),
+        _ => {
+            return Err(
+                "The number of bytes to be converted to a BIP39 sentence must be in {16, 20, 24, \
+                 28, 32}."
+                    .to_string(),
+            )
+        }
+    };
+
+    // checksum length is ent_len / 32
+    let cs_len = ent_len / 32;
+
+    // checksum is first cs_len bits of SHA256(bytes)
+    // first compute hash of bytes
+    let hash = Sha256::digest(bytes);
+
+    // convert hash from byte vector to bit vector
+    let hash_bits = BitVec::<Msb0, u8>::from_slice(&hash)
+        .map_err(|e| format!("Failed to convert hash to bit vector because {}", e))?;
+
+    // convert input bytes from byte vector to bit vector
+    let mut random_bits = BitVec::<Msb0, u8>::from_slice(&bytes)
+        .map_err(|e| format!("Failed to convert hash to bit vector because {}", e))?;
+
+    // append the first cs_len bits of hash_bits to the end of random_bits
+    for i in 0..cs_len {
+        random_bits.push(hash_bits[i]);
+    }
+
+    // go over random_bits in chunks of 11 bits and convert those to words
+    let mut vec = Vec::<String>::new();
+    let random_iter = random_bits.chunks(11);
+    for chunk in random_iter {
+        let idx = chunk.iter().fold(0, |acc, b| acc << 1 | *b as usize); // convert chunk to integer
+        vec.push(bip_word_list[idx].to_string());
+    }
+
+    Ok(vec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test correct generation of BIP39 sentences.
+    /// Values are taken from https://github.com/trezor/python-mnemonic/blob/master/vectors.json
+    #[test]
+    pub fn test_bip39_generation() {
+        let bip39_vec: Vec<_> = bip39_words().collect();
+        assert_eq!(bip39_vec.len(), 2048);
+
+        assert_eq!(
+            bytes_to_bip39(
+                &hex::decode("00000000000000000000000000000000").unwrap(),
+                &bip39_vec
+            )
+            .unwrap()
+            .join(" "),
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon about",
+        );
+        assert_eq!(
+            bytes_to_bip39(
+                &hex::decode("7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f").unwrap(),
+                &bip39_vec
+            )
+            .unwrap()
+            .join(" "),
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+        );
+        assert_eq!(
+            bytes_to_bip39(
+                &hex::decode("000000000000000000000000000000000000000000000000").unwrap(),
+                &bip39_vec
+            )
+            .unwrap()
+            .join(" "),
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon abandon abandon abandon abandon abandon abandon agent",
+        );
+        assert_eq!(
+            bytes_to_bip39(
+                &hex::decode("0000000000000000000000000000000000000000000000000000000000000000")
+                    .unwrap(),
+                &bip39_vec
+            )
+            .unwrap()
+            .join(" "),
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon abandon abandon art",
+        );
+        assert_eq!(
+            bytes_to_bip39(
+                &hex::decode("f585c11aec520db57dd353c69554b21a89b20fb0650966fa0a9d6f74fd989d8f")
+                    .unwrap(),
+                &bip39_vec
+            )
+            .unwrap()
+            .join(" "),
+            "void come effort suffer camp survey warrior heavy shoot primary clutch crush open \
+             amazing screen patrol group space point ten exist slush involve unfold",
+        );
+    }
+
+    /// Test BIP39 verification.
+    #[test]
+    pub fn test_bip39_verification() {
+        let bip39_vec: Vec<_> = bip39_words().collect();
+        assert_eq!(bip39_vec.len(), 2048);
+        let mut bip39_map = HashMap::new();
+        for (i, word) in bip39_vec.iter().enumerate() {
+            bip39_map.insert(*word, i);
+        }
+
+        let valid_list = vec![
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "art".to_string(),
+        ];
+        let invalid_list = vec![
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+            "abandon".to_string(),
+        ];
+
+        assert!(verify_bip39(&valid_list, &bip39_map));
+        assert!(!verify_bip39(&invalid_list, &bip39_map));
+    }
+}
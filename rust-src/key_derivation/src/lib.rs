@@ -0,0 +1,337 @@
+//! Deterministic derivation of all the secrets of a wallet from a single BIP39
+//! seed.
+//!
+//! Keys are derived with [SLIP-0010](https://github.com/satoshilabs/slips/blob/master/slip-0010.md)
+//! for ed25519, which only supports hardened derivation. All indices below are
+//! therefore hardened. With `i` the identity of the identity provider, `j` the
+//! index of the identity obtained from that identity provider, and `k` the
+//! credential counter, the paths are
+//!
+//! - `m/44'/919'/i'/j'/0'/k'` for the signing key of the account of credential
+//!   `k`,
+//! - `m/44'/919'/i'/j'/2'` for `idCredSec` of the identity,
+//! - `m/44'/919'/i'/j'/3'` for the PRF key of the identity,
+//! - `m/44'/919'/i'/j'/4'/k'/l'` for the randomness of the commitments in
+//!   credential `k`, where `l` is 0 for `idCredSec`, 1 for the PRF key, 2 for
+//!   the credential counter, and 3 for the maximum number of accounts,
+//! - `m/44'/919'/i'/j'/5'/k'/t'` for the randomness of the commitment to the
+//!   attribute with tag `t` in credential `k`.
+//!
+//! Here 919 is the SLIP-0044 coin type of Concordium. Scalars are obtained
+//! from the derived 32 byte keys with [keygen_bls](keygen_bls::keygen_bls).
+use crypto_common::types::KeyPair;
+use curve_arithmetic::Curve;
+use dodis_yampolskiy_prf as prf;
+use ed25519_dalek as ed25519;
+use hmac::{Hmac, Mac, NewMac};
+use id::{
+    constants::ArCurve,
+    types::{AttributeTag, CommitmentsRandomness, IdCredentials, IpIdentity},
+};
+use pedersen_scheme::{Randomness as PedersenRandomness, Value as PedersenValue};
+use sha2::Sha512;
+use std::collections::HashMap;
+use thiserror::Error;
+
+pub mod bip39;
+pub mod recovery;
+
+/// Indices at or above this are hardened. In SLIP-0010 for ed25519 every
+/// index is hardened.
+pub const HARDENED_OFFSET: u32 = 1 << 31;
+
+/// Purpose of the paths, as in BIP44.
+pub const PURPOSE: u32 = 44;
+
+/// The SLIP-0044 coin type of Concordium.
+pub const CONCORDIUM_COIN_TYPE: u32 = 919;
+
+/// Number of PBKDF2 iterations used to turn a BIP39 mnemonic into a seed.
+const BIP39_PBKDF2_ROUNDS: u32 = 2048;
+
+#[derive(Debug, Error, PartialEq, Eq, Clone, Copy)]
+pub enum DerivationError {
+    #[error("Index {0} is too large. Indices must be less than 2^31.")]
+    InvalidIndex(u32),
+}
+
+/// A node in the derivation tree: a 32 byte key together with the chain code
+/// used to derive its children.
+#[derive(Clone)]
+pub struct ExtendedKey {
+    pub key:        [u8; 32],
+    pub chain_code: [u8; 32],
+}
+
+impl ExtendedKey {
+    /// The root of the tree for the given seed.
+    pub fn master(seed: &[u8]) -> Self { hmac_split(b"ed25519 seed", &[seed]) }
+
+    /// Derive the hardened child with the given index. The index is given
+    /// without the hardening offset, and so must be less than 2^31.
+    pub fn derive_child(&self, index: u32) -> Result<Self, DerivationError> {
+        if index >= HARDENED_OFFSET {
+            return Err(DerivationError::InvalidIndex(index));
+        }
+        let index_bytes = (index | HARDENED_OFFSET).to_be_bytes();
+        Ok(hmac_split(&self.chain_code, &[
+            &[0],
+            &self.key,
+            &index_bytes,
+        ]))
+    }
+
+    /// Derive the node at the given path below this one.
+    pub fn derive_path(&self, path: &[u32]) -> Result<Self, DerivationError> {
+        path.iter()
+            .try_fold(self.clone(), |node, &index| node.derive_child(index))
+    }
+}
+
+/// Compute HMAC-SHA512 with the given key over the concatenation of the
+/// inputs, and split the result into a key and a chain code.
+fn hmac_split(key: &[u8], inputs: &[&[u8]]) -> ExtendedKey {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length.");
+    for input in inputs {
+        mac.update(input);
+    }
+    let out = mac.finalize().into_bytes();
+    let mut key = [0u8; 32];
+    let mut chain_code = [0u8; 32];
+    key.copy_from_slice(&out[..32]);
+    chain_code.copy_from_slice(&out[32..]);
+    ExtendedKey { key, chain_code }
+}
+
+/// Compute the BIP39 seed of a mnemonic sentence and an optional passphrase
+/// (use the empty string if there is none). The mnemonic is not checked
+/// against the word list.
+pub fn bip39_seed(mnemonic: &str, passphrase: &str) -> [u8; 64] {
+    let salt = format!("mnemonic{}", passphrase);
+    let mut seed = [0u8; 64];
+    pbkdf2::pbkdf2::<Hmac<Sha512>>(
+        mnemonic.as_bytes(),
+        salt.as_bytes(),
+        BIP39_PBKDF2_ROUNDS,
+        &mut seed,
+    );
+    seed
+}
+
+/// A wallet whose keys are all derived from a single seed.
+pub struct ConcordiumHdWallet {
+    pub seed: [u8; 64],
+}
+
+impl ConcordiumHdWallet {
+    /// Construct the wallet from a BIP39 mnemonic sentence and passphrase.
+    pub fn from_mnemonic(mnemonic: &str, passphrase: &str) -> Self {
+        ConcordiumHdWallet {
+            seed: bip39_seed(mnemonic, passphrase),
+        }
+    }
+
+    /// The node `m/44'/919'/i'/j'` below which all secrets of identity `j` from
+    /// identity provider `i` are derived.
+    fn identity_node(
+        &self,
+        identity_provider: IpIdentity,
+        identity_index: u32,
+    ) -> Result<ExtendedKey, DerivationError> {
+        ExtendedKey::master(&self.seed).derive_path(&[
+            PURPOSE,
+            CONCORDIUM_COIN_TYPE,
+            identity_provider.0,
+            identity_index,
+        ])
+    }
+
+    fn derive_scalar(
+        &self,
+        identity_provider: IpIdentity,
+        identity_index: u32,
+        path: &[u32],
+    ) -> Result<<ArCurve as Curve>::Scalar, DerivationError> {
+        let node = self
+            .identity_node(identity_provider, identity_index)?
+            .derive_path(path)?;
+        // keygen_bls only fails if asked for more output than HKDF can produce,
+        // and it asks for a fixed 48 bytes.
+        Ok(keygen_bls::keygen_bls(&node.key, b"").expect("48 bytes is a valid HKDF length."))
+    }
+
+    /// The signing key of the account created from credential number
+    /// `credential_counter` of the identity.
+    pub fn get_account_signing_key(
+        &self,
+        identity_provider: IpIdentity,
+        identity_index: u32,
+        credential_counter: u8,
+    ) -> Result<KeyPair, DerivationError> {
+        let node = self
+            .identity_node(identity_provider, identity_index)?
+            .derive_path(&[0, credential_counter.into()])?;
+        // Any 32 bytes are a valid ed25519 secret key.
+        let secret = ed25519::SecretKey::from_bytes(&node.key)
+            .expect("The derived key has the correct length.");
+        let public = ed25519::PublicKey::from(&secret);
+        Ok(KeyPair { secret, public })
+    }
+
+    /// The secret credentials of the identity.
+    pub fn get_id_cred_sec(
+        &self,
+        identity_provider: IpIdentity,
+        identity_index: u32,
+    ) -> Result<IdCredentials<ArCurve>, DerivationError> {
+        let scalar = self.derive_scalar(identity_provider, identity_index, &[2])?;
+        Ok(IdCredentials {
+            id_cred_sec: PedersenValue::new(scalar),
+        })
+    }
+
+    /// The PRF key of the identity.
+    pub fn get_prf_key(
+        &self,
+        identity_provider: IpIdentity,
+        identity_index: u32,
+    ) -> Result<prf::SecretKey<ArCurve>, DerivationError> {
+        let scalar = self.derive_scalar(identity_provider, identity_index, &[3])?;
+        Ok(prf::SecretKey::new(scalar))
+    }
+
+    /// The randomness of the commitments in credential number
+    /// `credential_counter` of the identity, including the randomness of the
+    /// commitments to the given attributes.
+    pub fn get_commitments_randomness(
+        &self,
+        identity_provider: IpIdentity,
+        identity_index: u32,
+        credential_counter: u8,
+        attributes: impl IntoIterator<Item = AttributeTag>,
+    ) -> Result<CommitmentsRandomness<ArCurve>, DerivationError> {
+        let k = u32::from(credential_counter);
+        let randomness = |path: &[u32]| -> Result<PedersenRandomness<ArCurve>, DerivationError> {
+            let scalar = self.derive_scalar(identity_provider, identity_index, path)?;
+            Ok(PedersenRandomness::new(scalar))
+        };
+        let mut attributes_rand = HashMap::new();
+        for tag in attributes {
+            attributes_rand.insert(tag, randomness(&[5, k, tag.0.into()])?);
+        }
+        Ok(CommitmentsRandomness {
+            id_cred_sec_rand: randomness(&[4, k, 0])?,
+            prf_rand: randomness(&[4, k, 1])?,
+            cred_counter_rand: randomness(&[4, k, 2])?,
+            max_accounts_rand: randomness(&[4, k, 3])?,
+            attributes_rand,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_node(node: &ExtendedKey, key: &str, chain_code: &str) {
+        assert_eq!(hex::encode(node.key), key);
+        assert_eq!(hex::encode(node.chain_code), chain_code);
+    }
+
+    #[test]
+    // Test vector 1 for ed25519 from SLIP-0010.
+    fn test_slip10_vector_1() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let m = ExtendedKey::master(&seed);
+        check_node(
+            &m,
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+            "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb",
+        );
+        check_node(
+            &m.derive_child(0).unwrap(),
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+            "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69",
+        );
+        let node = m.derive_path(&[0, 1]).unwrap();
+        check_node(
+            &node,
+            "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+            "a320425f77d1b5c2505a6b1b27382b37368ee640e3557c315416801243552f14",
+        );
+        let sk = ed25519::SecretKey::from_bytes(&node.key).unwrap();
+        assert_eq!(
+            hex::encode(ed25519::PublicKey::from(&sk).as_bytes()),
+            "1932a5270f335bed617d5b935c80aedb1a35bd9fc1e31acafd5372c30f5c1187"
+        );
+    }
+
+    #[test]
+    fn test_invalid_index() {
+        let m = ExtendedKey::master(&[0u8; 64]);
+        assert_eq!(
+            m.derive_child(HARDENED_OFFSET).err(),
+            Some(DerivationError::InvalidIndex(HARDENED_OFFSET))
+        );
+    }
+
+    #[test]
+    // Test vector from the reference implementation of BIP39.
+    fn test_bip39_seed() {
+        let seed = bip39_seed(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon about",
+            "TREZOR",
+        );
+        assert_eq!(
+            hex::encode(&seed[..]),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+    }
+
+    #[test]
+    fn test_wallet_derivation() {
+        let wallet = ConcordiumHdWallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon about",
+            "",
+        );
+        let ip = IpIdentity(0);
+        let key = |id, k| wallet.get_account_signing_key(ip, id, k).unwrap().public;
+        // Derivation is deterministic, and different paths give different keys.
+        assert_eq!(key(0, 0), key(0, 0));
+        assert_ne!(key(0, 0), key(0, 1));
+        assert_ne!(key(0, 0), key(1, 0));
+        assert_ne!(
+            key(0, 0),
+            wallet
+                .get_account_signing_key(IpIdentity(1), 0, 0)
+                .unwrap()
+                .public
+        );
+
+        let prf_key = wallet.get_prf_key(ip, 0).unwrap();
+        let id_cred_sec = wallet.get_id_cred_sec(ip, 0).unwrap().id_cred_sec;
+        assert_eq!(prf_key, wallet.get_prf_key(ip, 0).unwrap());
+        assert_ne!(*prf_key, *id_cred_sec);
+        assert_ne!(prf_key, wallet.get_prf_key(ip, 1).unwrap());
+
+        let tags = vec![AttributeTag(1), AttributeTag(3)];
+        let rand = wallet
+            .get_commitments_randomness(ip, 0, 0, tags.clone())
+            .unwrap();
+        assert_eq!(rand.attributes_rand.len(), 2);
+        assert_ne!(rand.id_cred_sec_rand, rand.prf_rand);
+        assert_ne!(rand.cred_counter_rand, rand.max_accounts_rand);
+        assert_ne!(
+            rand.attributes_rand[&tags[0]],
+            rand.attributes_rand[&tags[1]]
+        );
+        let rand_1 = wallet.get_commitments_randomness(ip, 0, 1, tags).unwrap();
+        assert_ne!(rand.prf_rand, rand_1.prf_rand);
+
+        assert!(wallet
+            .get_account_signing_key(IpIdentity(HARDENED_OFFSET), 0, 0)
+            .is_err());
+    }
+}
@@ -0,0 +1,229 @@
+//! Recovery of the accounts of a [ConcordiumHdWallet] from its seed.
+//!
+//! The credential registration ID of credential `k` of an identity is
+//! `PRF(prf_key, k)`, and the PRF key is derived from the seed. Recovery thus
+//! consists of computing the registration IDs of all credentials the wallet
+//! could have created, and asking an [AccountLookup] which of them exist. Since
+//! there are infinitely many identities the scan stops after a gap of unused
+//! identities, and similarly the scan of the credentials of an identity stops
+//! after a gap of unused credential counters.
+use crate::*;
+use crypto_common::{base16_encode, to_bytes};
+use curve_arithmetic::Curve;
+use id::types::{AccountAddress, GlobalContext};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+use std::{collections::BTreeSet, convert::Infallible};
+
+/// A source of information about which credentials exist on the chain.
+pub trait AccountLookup {
+    type Error;
+
+    /// Whether a credential with the given registration ID has been deployed.
+    fn reg_id_exists(&mut self, reg_id: &ArCurve) -> Result<bool, Self::Error>;
+}
+
+/// A lookup in a fixed set of registration IDs. In JSON it is a list of base16
+/// encoded registration IDs, so it can be read from a file exported from a
+/// node.
+#[derive(Debug, Default, SerdeSerialize, SerdeDeserialize)]
+#[serde(transparent)]
+pub struct KnownRegIds {
+    reg_ids: BTreeSet<String>,
+}
+
+impl KnownRegIds {
+    pub fn insert(&mut self, reg_id: &ArCurve) {
+        self.reg_ids.insert(hex::encode(to_bytes(reg_id)));
+    }
+}
+
+impl AccountLookup for KnownRegIds {
+    type Error = Infallible;
+
+    fn reg_id_exists(&mut self, reg_id: &ArCurve) -> Result<bool, Infallible> {
+        Ok(self.reg_ids.contains(&hex::encode(to_bytes(reg_id))))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RecoveryError<E> {
+    #[error("Could not derive keys: {0}")]
+    Derivation(#[from] DerivationError),
+    #[error("Account lookup failed: {0}")]
+    Lookup(E),
+}
+
+/// An account found during recovery, together with the keys needed to use it.
+#[derive(SerdeSerialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveredAccount {
+    pub identity_provider:     IpIdentity,
+    pub identity_index:        u32,
+    pub credential_counter:    u8,
+    #[serde(serialize_with = "base16_encode")]
+    pub reg_id:                ArCurve,
+    pub address:               AccountAddress,
+    pub signing_key:           KeyPair,
+    pub encryption_secret_key: elgamal::SecretKey<ArCurve>,
+}
+
+/// Find all the accounts created by the wallet with identities from the given
+/// identity providers. For each identity provider, identities are scanned in
+/// order until `gap_limit` consecutive identities have no credentials, and the
+/// credential counters of each identity are scanned until `gap_limit`
+/// consecutive ones are unused.
+///
+/// The account address and signing key are those of an account created by the
+/// credential. If a credential was instead deployed onto an existing account,
+/// the key is that of the credential, but the address is not that of the
+/// account.
+pub fn recover_accounts<L: AccountLookup>(
+    wallet: &ConcordiumHdWallet,
+    global_context: &GlobalContext<ArCurve>,
+    identity_providers: &[IpIdentity],
+    gap_limit: u32,
+    lookup: &mut L,
+) -> Result<Vec<RecoveredAccount>, RecoveryError<L::Error>> {
+    let mut accounts = Vec::new();
+    for &identity_provider in identity_providers {
+        let mut unused = 0;
+        let mut identity_index = 0;
+        while unused < gap_limit && identity_index < HARDENED_OFFSET {
+            let found = recover_identity_accounts(
+                wallet,
+                global_context,
+                identity_provider,
+                identity_index,
+                gap_limit,
+                lookup,
+            )?;
+            if found.is_empty() {
+                unused += 1;
+            } else {
+                unused = 0;
+                accounts.extend(found);
+            }
+            identity_index += 1;
+        }
+    }
+    Ok(accounts)
+}
+
+/// Find the accounts created from a single identity.
+fn recover_identity_accounts<L: AccountLookup>(
+    wallet: &ConcordiumHdWallet,
+    global_context: &GlobalContext<ArCurve>,
+    identity_provider: IpIdentity,
+    identity_index: u32,
+    gap_limit: u32,
+    lookup: &mut L,
+) -> Result<Vec<RecoveredAccount>, RecoveryError<L::Error>> {
+    let prf_key = wallet.get_prf_key(identity_provider, identity_index)?;
+    let generator = global_context.elgamal_generator();
+    let mut accounts = Vec::new();
+    let mut unused = 0;
+    for credential_counter in 0..=u8::MAX {
+        if unused >= gap_limit {
+            break;
+        }
+        // The exponent only fails to exist with negligible probability, in
+        // which case no credential could have been created.
+        let exponent = match prf_key.prf_exponent(credential_counter) {
+            Ok(exponent) => exponent,
+            Err(_) => {
+                unused += 1;
+                continue;
+            }
+        };
+        let reg_id = generator.mul_by_secret_scalar(&exponent);
+        if !lookup
+            .reg_id_exists(&reg_id)
+            .map_err(RecoveryError::Lookup)?
+        {
+            unused += 1;
+            continue;
+        }
+        unused = 0;
+        accounts.push(RecoveredAccount {
+            identity_provider,
+            identity_index,
+            credential_counter,
+            reg_id,
+            address: AccountAddress::new(&reg_id),
+            signing_key: wallet.get_account_signing_key(
+                identity_provider,
+                identity_index,
+                credential_counter,
+            )?,
+            encryption_secret_key: elgamal::SecretKey {
+                generator: *generator,
+                scalar:    exponent,
+            },
+        });
+    }
+    Ok(accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recover_accounts() {
+        let wallet = ConcordiumHdWallet { seed: [17u8; 64] };
+        let global_context = GlobalContext::<ArCurve>::generate_size(String::from("genesis"), 1);
+        let reg_id = |ip, identity_index, k| {
+            wallet
+                .get_prf_key(IpIdentity(ip), identity_index)
+                .unwrap()
+                .prf(global_context.elgamal_generator(), k)
+                .unwrap()
+        };
+        let mut lookup = KnownRegIds::default();
+        // Credential counter 2 is unused, which is within the gap limit.
+        lookup.insert(&reg_id(0, 0, 0));
+        lookup.insert(&reg_id(0, 0, 1));
+        lookup.insert(&reg_id(0, 0, 3));
+        // Identity 1 is unused, which is also within the gap limit.
+        lookup.insert(&reg_id(0, 2, 0));
+        // These are beyond the gap limit.
+        lookup.insert(&reg_id(0, 2, 4));
+        lookup.insert(&reg_id(0, 5, 0));
+        lookup.insert(&reg_id(1, 0, 2));
+
+        let accounts = recover_accounts(
+            &wallet,
+            &global_context,
+            &[IpIdentity(0), IpIdentity(1)],
+            2,
+            &mut lookup,
+        )
+        .unwrap();
+        let found: Vec<_> = accounts
+            .iter()
+            .map(|acc| {
+                (
+                    acc.identity_provider.0,
+                    acc.identity_index,
+                    acc.credential_counter,
+                )
+            })
+            .collect();
+        assert_eq!(found, vec![(0, 0, 0), (0, 0, 1), (0, 0, 3), (0, 2, 0)]);
+
+        let account = &accounts[1];
+        assert_eq!(account.reg_id, reg_id(0, 0, 1));
+        assert_eq!(account.address, AccountAddress::new(&account.reg_id));
+        assert_eq!(
+            account.signing_key.public,
+            wallet
+                .get_account_signing_key(IpIdentity(0), 0, 1)
+                .unwrap()
+                .public
+        );
+        assert_eq!(
+            elgamal::PublicKey::from(&account.encryption_secret_key).key,
+            account.reg_id
+        );
+    }
+}
@@ -1,9 +1,11 @@
 //! Generate a private key in a deterministic way from a secret seed and key
 //! description.
-use curve_arithmetic::Curve;
-use ff::{Field, PrimeField};
+use curve_arithmetic::{
+    bls12_381::{Fr, G1},
+    Curve,
+};
+use ff::Field;
 use hkdf::Hkdf;
-use pairing::bls12_381::{Fr, FrRepr, G1};
 use sha2::{Digest, Sha256};
 
 /// This function is an implementation of the procedure described in <https://tools.ietf.org/html/draft-irtf-cfrg-bls-signature-04#section-2.3>
@@ -24,7 +26,13 @@ pub fn keygen_bls(ikm: &[u8], key_info: &[u8]) -> Result<Fr, hkdf::InvalidLength
     // shift with
     // 452312848583266388373324160190187140051835877600158453279131187530910662656 =
     // 2^248
-    let shift = Fr::from_repr(FrRepr([0, 0, 0, 72057594037927936])).unwrap();
+    let shift = {
+        // (2^62)^4 = 2^248
+        let mut shift = G1::scalar_from_u64(1 << 62);
+        shift.square();
+        shift.square();
+        shift
+    };
     let mut salt = Sha256::digest(&salt[..]);
     while sk.is_zero() {
         let (_, h) = Hkdf::<Sha256>::extract(Some(&salt), &ikm);
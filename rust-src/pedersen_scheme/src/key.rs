@@ -36,7 +36,8 @@ impl<C: Curve> CommitmentKey<C> {
 
     /// The low-level worker function that actually does the commitment.
     /// The interface is not very type-safe, hence the availability of other
-    /// functions.
+    /// functions. This is not constant-time, so it should only be used when the
+    /// value and randomness are public.
     pub fn hide_worker(&self, value: &C::Scalar, randomness: &C::Scalar) -> Commitment<C> {
         let h = self.h;
         let g = self.g;
@@ -47,9 +48,14 @@ impl<C: Curve> CommitmentKey<C> {
     }
 
     #[inline(always)]
-    /// Hide the value inside a commitment using the given randomness.
+    /// Hide the value inside a commitment using the given randomness. The
+    /// value and randomness are treated as secret, see
+    /// [Curve::mul_by_secret_scalar].
     pub fn hide<V: AsRef<C::Scalar>>(&self, s: &V, r: &Randomness<C>) -> Commitment<C> {
-        self.hide_worker(s.as_ref(), r.as_ref())
+        Commitment(multiexp_secret(&[self.g, self.h], &[
+            *s.as_ref(),
+            *r.as_ref(),
+        ]))
     }
 
     /// Prove that the commitment `self` contains the given value and
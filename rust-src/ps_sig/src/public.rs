@@ -91,9 +91,17 @@ impl<C: Pairing> PublicKey<C> {
 impl<'a, C: Pairing> From<&'a SecretKey<C>> for PublicKey<C> {
     /// Derive this public key from its corresponding `SecretKey`.
     fn from(sk: &SecretKey<C>) -> PublicKey<C> {
-        let ys = sk.ys.iter().map(|r| sk.g.mul_by_scalar(&r)).collect();
-        let y_tildas = sk.ys.iter().map(|r| sk.g_tilda.mul_by_scalar(&r)).collect();
-        let x_tilda = sk.g_tilda.mul_by_scalar(&sk.x);
+        let ys = sk
+            .ys
+            .iter()
+            .map(|r| sk.g.mul_by_secret_scalar(&r))
+            .collect();
+        let y_tildas = sk
+            .ys
+            .iter()
+            .map(|r| sk.g_tilda.mul_by_secret_scalar(&r))
+            .collect();
+        let x_tilda = sk.g_tilda.mul_by_secret_scalar(&sk.x);
         PublicKey {
             g: sk.g,
             g_tilda: sk.g_tilda,
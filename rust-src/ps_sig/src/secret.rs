@@ -14,9 +14,10 @@ use curve_arithmetic::*;
 use ff::Field;
 
 use rand::*;
+use std::fmt;
 
 /// A secret key
-#[derive(Debug, Serialize)]
+#[derive(Serialize)]
 pub struct SecretKey<C: Pairing> {
     /// Generator of the first pairing group. Not secret, but needed for various
     /// operations.
@@ -35,6 +36,28 @@ impl<C: Pairing> PartialEq for SecretKey<C> {
 
 impl<C: Pairing> Eq for SecretKey<C> {}
 
+/// Overwrite the secret scalars with zeros when the key goes out of scope.
+impl<C: Pairing> Drop for SecretKey<C> {
+    fn drop(&mut self) {
+        for y in self.ys.iter_mut() {
+            clear_field_element(y);
+        }
+        clear_field_element(&mut self.x);
+    }
+}
+
+/// Only the generators and the number of scalars are shown.
+impl<C: Pairing> fmt::Debug for SecretKey<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SecretKey")
+            .field("g", &self.g)
+            .field("g_tilda", &self.g_tilda)
+            .field("ys", &format_args!("<{} redacted scalars>", self.ys.len()))
+            .field("x", &"<redacted>")
+            .finish()
+    }
+}
+
 impl<C: Pairing> SecretKey<C> {
     /// Generate a secret key from a `csprng`. NB: This fixes the generators to
     /// be those defined by the library.
@@ -79,7 +102,7 @@ impl<C: Pairing> SecretKey<C> {
         z.add_assign(&self.x);
         let h = self.g.mul_by_scalar(&C::generate_scalar(csprng));
 
-        Ok(Signature(h, h.mul_by_scalar(&z)))
+        Ok(Signature(h, h.mul_by_secret_scalar(&z)))
     }
 
     // FIXME: Should this not require also a AggregateDLog proof that the user knows
@@ -91,10 +114,10 @@ impl<C: Pairing> SecretKey<C> {
     ) -> Signature<C>
     where
         T: Rng, {
-        let sk = self.g.mul_by_scalar(&self.x);
+        let sk = self.g.mul_by_secret_scalar(&self.x);
         let r = C::generate_non_zero_scalar(csprng);
-        let a = self.g.mul_by_scalar(&r);
-        let xmr = sk.plus_point(&message).mul_by_scalar(&r);
+        let a = self.g.mul_by_secret_scalar(&r);
+        let xmr = sk.plus_point(&message).mul_by_secret_scalar(&r);
         Signature(a, xmr)
     }
 }
@@ -121,4 +144,17 @@ mod tests {
     }
 
     macro_test_secret_key_to_byte_conversion!(secret_key_to_byte_conversion_bls12_381, Bls12);
+
+    #[test]
+    fn secret_key_cleared_on_drop() {
+        let mut csprng = thread_rng();
+        let mut sk = std::mem::ManuallyDrop::new(SecretKey::<Bls12>::generate(5, &mut csprng));
+        let printed = format!("{:?}", *sk);
+        assert!(!printed.contains(&format!("{:?}", sk.x)));
+        assert!(sk.ys.iter().all(|y| !printed.contains(&format!("{:?}", y))));
+        // Run the destructor, but keep the memory of the key so it can be
+        // inspected. The scalars in `ys` are freed with the vector.
+        unsafe { std::ptr::drop_in_place(&mut *sk) };
+        assert!(sk.x.is_zero(), "Secret scalar x was not cleared.");
+    }
 }
@@ -44,7 +44,7 @@ impl<C: Pairing> Signature<C> {
     /// commitment, and the randomness used in the commitment.
     pub fn retrieve(&self, r: &SigRetrievalRandomness<C>) -> Self {
         let h = self.0;
-        let hr = h.mul_by_scalar(&r);
+        let hr = h.mul_by_secret_scalar(&r);
         let b = self.1;
         Signature(h, b.minus_point(&hr))
     }
@@ -54,8 +54,10 @@ impl<C: Pairing> Signature<C> {
         let r = C::generate_non_zero_scalar(csprng);
         let t = C::generate_non_zero_scalar(csprng);
         let Signature(a, b) = self;
-        let a_hid = a.mul_by_scalar(&r);
-        let b_hid = b.plus_point(&a.mul_by_scalar(&t)).mul_by_scalar(&r);
+        let a_hid = a.mul_by_secret_scalar(&r);
+        let b_hid = b
+            .plus_point(&a.mul_by_secret_scalar(&t))
+            .mul_by_secret_scalar(&r);
         let sig = Signature(a_hid, b_hid);
         let randomness = BlindingRandomness(Secret::new(r), Secret::new(t));
         (BlindedSignature { sig }, randomness)